@@ -1 +1,55 @@
-fn main() {}
+//! Records the git commit, the pop-launcher revision, and the enabled features of the build,
+//! shown by `--version --json` and the About view.
+
+use std::path::Path;
+use std::process::Command;
+
+fn main() {
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map_or_else(|| "unknown".to_string(), |commit| commit.trim().to_string());
+    println!("cargo:rustc-env=GIT_COMMIT={commit}");
+    // Tarballs have no repository, and cargo would run this again for every build.
+    if Path::new(".git").exists() {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        println!("cargo:rerun-if-changed=.git/refs/heads");
+    }
+
+    let pop_launcher = std::fs::read_to_string("Cargo.lock")
+        .ok()
+        .and_then(|lock| pop_launcher(&lock))
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=POP_LAUNCHER_VERSION={pop_launcher}");
+    println!("cargo:rerun-if-changed=Cargo.lock");
+
+    let mut features = std::env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(str::to_lowercase))
+        .filter(|feature| feature != "default")
+        .map(|feature| feature.replace('_', "-"))
+        .collect::<Vec<_>>();
+    features.sort();
+    println!("cargo:rustc-env=FEATURES={}", features.join(","));
+}
+
+/// The version and revision of pop-launcher in the lock file, such as `1.2.2 (091581c)`.
+fn pop_launcher(lock: &str) -> Option<String> {
+    let package = lock
+        .split("[[package]]")
+        .find(|package| package.contains("name = \"pop-launcher\"\n"))?;
+    let field = |key: &str| {
+        package.lines().find_map(|line| {
+            line.strip_prefix(key)?
+                .strip_prefix(" = \"")?
+                .strip_suffix('"')
+        })
+    };
+    let version = field("version")?;
+    match field("source").and_then(|source| source.split_once('#')) {
+        Some((_, rev)) => Some(format!("{version} ({})", rev.get(..7).unwrap_or(rev))),
+        None => Some(version.to_string()),
+    }
+}
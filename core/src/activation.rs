@@ -0,0 +1,40 @@
+//! Feedback between activating a result and the backend answering.
+
+use std::time::{Duration, Instant};
+
+/// Time the backend has to answer an activation before the row shows an error.
+pub const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// An activation sent to the backend which has not been carried out yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Activation {
+    /// Row of the activated result, marked as pending.
+    pub row: usize,
+    /// ID of the activated result.
+    pub id: u32,
+    /// The backend did not answer within [`TIMEOUT`], so the result may be activated again.
+    pub timed_out: bool,
+    /// Index of the GPU picked from the "Launch using" submenu, if the result was
+    /// activated from it.
+    pub gpu: Option<u32>,
+    /// When the activation times out, or `None` once the backend answered with an entry
+    /// to launch.
+    pub(crate) deadline: Option<Instant>,
+}
+
+impl Activation {
+    pub(crate) fn new(row: usize, id: u32, now: Instant) -> Self {
+        Self {
+            row,
+            id,
+            timed_out: false,
+            gpu: None,
+            deadline: Some(now + TIMEOUT),
+        }
+    }
+
+    /// Whether activating a result again is held back.
+    pub fn is_pending(&self) -> bool {
+        !self.timed_out
+    }
+}
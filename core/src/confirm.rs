@@ -0,0 +1,29 @@
+//! Two-step activation for destructive actions such as powering off.
+
+use pop_launcher::SearchResult;
+
+use crate::Request;
+
+/// An activation held back until the user confirms it.
+#[derive(Debug, Clone)]
+pub struct Confirmation {
+    /// Row replaced by the confirmation prompt.
+    pub row: usize,
+    /// ID of the result the action belongs to.
+    pub id: u32,
+    /// The result the action belongs to, as it was shown when the action was picked.
+    pub result: SearchResult,
+    /// Name of the action, shown in the prompt.
+    pub action: String,
+    /// Request sent once confirmed, unless the result is a local one, which the UI activates.
+    pub request: Request,
+}
+
+/// Whether `name` starts with one of the configured destructive action names,
+/// ignoring case.
+pub fn is_destructive(actions: &[String], name: &str) -> bool {
+    let name = name.to_lowercase();
+    actions
+        .iter()
+        .any(|action| name.starts_with(&action.to_lowercase()))
+}
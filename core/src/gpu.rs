@@ -0,0 +1,53 @@
+//! The "Launch using" submenu of app results on systems with more than one GPU.
+//!
+//! pop-launcher adds a context option of its own for launching on the other GPU. The
+//! launcher replaces it with a submenu listing every GPU, whose options activate the
+//! result like Enter does, asking for the picked GPU when the backend answers with the
+//! desktop entry to launch.
+
+use pop_launcher::ContextOption;
+
+use crate::menu::SEPARATOR;
+
+/// Context options from here on launch the result on the GPU at their offset.
+pub const OPTION_BASE: u32 = u32::MAX - 0xff;
+
+/// The GPUs to pick from, as named in the submenu.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Gpus {
+    /// Name of the submenu.
+    pub submenu: String,
+    /// Names of the GPUs, in the order switcheroo-control lists them.
+    pub names: Vec<String>,
+}
+
+impl Gpus {
+    /// Replaces the GPU options of pop-launcher among `options` with the submenu, when
+    /// there is more than one GPU to pick from.
+    pub fn apply(&self, options: &mut Vec<ContextOption>) {
+        if self.names.len() < 2 {
+            return;
+        }
+
+        options.retain(|option| !is_backend_option(&option.name));
+        options.extend(
+            self.names
+                .iter()
+                .zip(OPTION_BASE..=u32::MAX)
+                .map(|(name, id)| ContextOption {
+                    id,
+                    name: format!("{}{SEPARATOR}{name}", self.submenu),
+                }),
+        );
+    }
+}
+
+/// Index of the GPU the context option `context` launches on, if it is one of the submenu.
+pub fn index(context: u32) -> Option<u32> {
+    context.checked_sub(OPTION_BASE)
+}
+
+/// pop-launcher names its options like "Launch Using Discrete Graphics Card".
+fn is_backend_option(name: &str) -> bool {
+    name.to_lowercase().starts_with("launch using ")
+}
@@ -0,0 +1,100 @@
+//! Focus within the grid of applications shown for an empty query, which is laid out in
+//! pages of `rows` rows of `columns` tiles.
+
+use std::ops::Range;
+
+/// A way the arrow and page keys move the focus through the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Move {
+    Left,
+    Right,
+    Up,
+    Down,
+    PagePrevious,
+    PageNext,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Grid {
+    pub columns: usize,
+    pub rows: usize,
+    len: usize,
+    focused: usize,
+}
+
+impl Grid {
+    /// A grid of `len` tiles, focused on the first.
+    pub fn new(columns: usize, rows: usize, len: usize) -> Self {
+        Self {
+            columns: columns.max(1),
+            rows: rows.max(1),
+            len,
+            focused: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn focused(&self) -> usize {
+        self.focused
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    /// The page holding the focused tile, counted from 0.
+    pub fn page(&self) -> usize {
+        self.focused / self.page_size()
+    }
+
+    pub fn pages(&self) -> usize {
+        self.len.div_ceil(self.page_size())
+    }
+
+    /// Tiles on the page holding the focused one.
+    pub fn visible(&self) -> Range<usize> {
+        let start = self.page() * self.page_size();
+        start..(start + self.page_size()).min(self.len)
+    }
+
+    pub fn focus(&mut self, i: usize) {
+        if i < self.len {
+            self.focused = i;
+        }
+    }
+
+    /// Moves the focus. Left and Right go on to the neighbouring pages past the ends of
+    /// rows, Up and Down past their top and bottom rows, landing on the last tile when the
+    /// row below is shorter.
+    pub fn step(&mut self, step: Move) {
+        if self.len == 0 {
+            return;
+        }
+
+        let last = self.len - 1;
+        self.focused = match step {
+            Move::Left => self.focused.saturating_sub(1),
+            Move::Right => (self.focused + 1).min(last),
+            Move::Up => self
+                .focused
+                .checked_sub(self.columns)
+                .unwrap_or(self.focused),
+            Move::Down if self.focused / self.columns < last / self.columns => {
+                (self.focused + self.columns).min(last)
+            }
+            Move::Down => self.focused,
+            Move::PagePrevious => self.focused.saturating_sub(self.page_size()),
+            Move::PageNext if self.page() + 1 < self.pages() => {
+                (self.focused + self.page_size()).min(last)
+            }
+            Move::PageNext => self.focused,
+        };
+    }
+}
@@ -0,0 +1,13 @@
+//! Inputs which arrive before the results they act on, such as alt-tab pressed again before
+//! the backend listed the windows.
+
+/// Something to do once the first update after opening arrives, in the order it was asked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PendingIntent {
+    /// Map the surface.
+    Show,
+    /// Focus the next result, for alt-tab pressed again.
+    FocusNext,
+    /// Activate the focused result, for alt released.
+    Activate,
+}
@@ -0,0 +1,85 @@
+//! Restrictions applied when the launcher runs on shared or locked-down machines.
+
+use std::path::Path;
+
+use pop_launcher::SearchResult;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Kiosk {
+    pub enabled: bool,
+    /// Applications which may be shown and launched, matched against the result name
+    /// or the desktop entry ID.
+    pub allowed_apps: Vec<String>,
+    /// Query prefixes of the run, terminal, and file plugins, which are never forwarded.
+    pub blocked_prefixes: Vec<String>,
+    /// The desktop entry IDs of the apps allowed by name, and the names of those allowed
+    /// by ID, as found by [`Kiosk::resolve`].
+    #[serde(skip)]
+    pub aliases: Vec<String>,
+}
+
+impl Default for Kiosk {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allowed_apps: Vec::new(),
+            blocked_prefixes: [":", "t:", "~", "/", "find "]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            aliases: Vec::new(),
+        }
+    }
+}
+
+impl Kiosk {
+    pub fn allows_query(&self, query: &str) -> bool {
+        !self.enabled
+            || !self
+                .blocked_prefixes
+                .iter()
+                .any(|prefix| query.trim_start().starts_with(prefix.as_str()))
+    }
+
+    /// Relates the allowed apps to the installed desktop entries, given as their IDs and
+    /// names, so that an app listed in either form is both shown and launched.
+    pub fn resolve<'a>(&mut self, entries: impl IntoIterator<Item = (&'a str, &'a str)>) {
+        self.aliases.clear();
+        for (id, name) in entries {
+            if self.allowed_apps.iter().any(|app| app == id) {
+                self.aliases.push(name.to_owned());
+            } else if self.allowed_apps.iter().any(|app| app == name) {
+                self.aliases.push(id.to_owned());
+            }
+        }
+    }
+
+    pub fn allows_result(&self, item: &SearchResult) -> bool {
+        !self.enabled || self.allows(&item.name)
+    }
+
+    pub fn allows_entry(&self, path: &Path) -> bool {
+        if !self.enabled {
+            return true;
+        }
+
+        path.file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|id| self.allows(id))
+    }
+
+    /// Whether `app`, a name or a desktop entry ID, is allowed.
+    fn allows(&self, app: &str) -> bool {
+        self.allowed_apps
+            .iter()
+            .chain(&self.aliases)
+            .any(|allowed| allowed == app)
+    }
+
+    /// Context menus and completion expose plugin internals, such as file paths.
+    pub fn allows_context(&self) -> bool {
+        !self.enabled
+    }
+}
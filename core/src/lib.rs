@@ -0,0 +1,34 @@
+//! Toolkit-independent state machine behind cosmic-launcher.
+//!
+//! The UI feeds [`Input`]s into a [`Launcher`] and carries out the [`Effect`]s it
+//! returns, which keeps all query, result, and visibility logic free of iced.
+
+pub mod activation;
+pub mod confirm;
+pub mod gpu;
+pub mod grid;
+pub mod intent;
+pub mod kiosk;
+pub mod menu;
+pub mod pin;
+pub mod pipeline;
+pub mod ranking;
+mod request;
+pub mod routing;
+pub mod scope;
+mod state;
+pub mod vi;
+pub mod visibility;
+
+pub use request::Request;
+pub use state::{Effect, Input, Launcher};
+
+/// Maximum number of results kept from a single backend update.
+pub const MAX_RESULTS: usize = 10;
+
+/// Results PageUp and PageDown move the focus by.
+pub const PAGE_SIZE: usize = 5;
+
+/// Results provided by the UI rather than pop-launcher have IDs starting here, so that
+/// their activation is handed back to the UI instead of sent to the backend.
+pub const LOCAL_ID_BASE: u32 = 1 << 31;
@@ -0,0 +1,184 @@
+//! Context menus, whose options may be grouped into submenus.
+//!
+//! pop-launcher and the providers list context options flat. An option named like
+//! "Open with ▸ Firefox" is shown as "Firefox" in a submenu named "Open with", which opens
+//! when it is hovered or focused with the arrow keys.
+
+use pop_launcher::ContextOption;
+
+/// Separates the name of a submenu from the names of the options in it.
+pub const SEPARATOR: &str = " ▸ ";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entry {
+    /// A context option of the result, activated by its ID.
+    Option {
+        id: u32,
+        name: String,
+    },
+    Submenu {
+        name: String,
+        entries: Vec<Entry>,
+    },
+}
+
+impl Entry {
+    pub fn name(&self) -> &str {
+        match self {
+            Entry::Option { name, .. } | Entry::Submenu { name, .. } => name,
+        }
+    }
+}
+
+/// The context menu of a result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Menu {
+    /// ID of the result the options belong to.
+    pub id: u32,
+    pub entries: Vec<Entry>,
+    /// Indices leading from the top level to the focused entry, empty while none is.
+    focus: Vec<usize>,
+}
+
+impl Menu {
+    pub fn new(id: u32, options: Vec<ContextOption>) -> Self {
+        let mut entries = Vec::new();
+        for option in options {
+            let mut path = option.name.split(SEPARATOR).collect::<Vec<_>>();
+            let name = path.pop().unwrap_or_default().to_string();
+            insert(
+                &mut entries,
+                &path,
+                Entry::Option {
+                    id: option.id,
+                    name,
+                },
+            );
+        }
+
+        Self {
+            id,
+            entries,
+            focus: Vec::new(),
+        }
+    }
+
+    /// Indices leading from the top level to the focused entry.
+    pub fn focus(&self) -> &[usize] {
+        &self.focus
+    }
+
+    pub fn focused(&self) -> Option<&Entry> {
+        self.entry(&self.focus)
+    }
+
+    pub fn entry(&self, path: &[usize]) -> Option<&Entry> {
+        let (last, parents) = path.split_last()?;
+        self.level(parents)?.get(*last)
+    }
+
+    /// The entries shown side by side: the top level, then every submenu on the way to the
+    /// focused entry, including the focused entry itself if it is one.
+    pub fn levels(&self) -> Vec<&[Entry]> {
+        let mut levels = vec![self.entries.as_slice()];
+        for depth in 1..=self.focus.len() {
+            if let Some(Entry::Submenu { entries, .. }) = self.entry(&self.focus[..depth]) {
+                levels.push(entries);
+            }
+        }
+        levels
+    }
+
+    /// Name of the option `id`, without the submenus it is in.
+    pub fn option(&self, id: u32) -> Option<&str> {
+        fn find(entries: &[Entry], id: u32) -> Option<&str> {
+            entries.iter().find_map(|entry| match entry {
+                Entry::Option { id: option, name } if *option == id => Some(name.as_str()),
+                Entry::Option { .. } => None,
+                Entry::Submenu { entries, .. } => find(entries, id),
+            })
+        }
+        find(&self.entries, id)
+    }
+
+    pub(crate) fn set_focus(&mut self, path: Vec<usize>) {
+        if self.entry(&path).is_some() {
+            self.focus = path;
+        }
+    }
+
+    /// Focuses the next entry of the focused entry's submenu, wrapping around.
+    pub(crate) fn focus_next(&mut self) {
+        self.step(|i, len| (i + 1) % len, 0);
+    }
+
+    pub(crate) fn focus_previous(&mut self) {
+        self.step(|i, len| (i + len - 1) % len, usize::MAX);
+    }
+
+    /// Moves the focus into the focused submenu.
+    pub(crate) fn expand(&mut self) {
+        if let Some(Entry::Submenu { entries, .. }) = self.focused() {
+            if !entries.is_empty() {
+                self.focus.push(0);
+            }
+        }
+    }
+
+    /// Moves the focus back to the submenu the focused entry is in.
+    pub(crate) fn collapse(&mut self) {
+        if self.focus.len() > 1 {
+            self.focus.pop();
+        }
+    }
+
+    fn level(&self, path: &[usize]) -> Option<&[Entry]> {
+        path.iter().try_fold(self.entries.as_slice(), |entries, i| {
+            match entries.get(*i)? {
+                Entry::Submenu { entries, .. } => Some(entries.as_slice()),
+                Entry::Option { .. } => None,
+            }
+        })
+    }
+
+    /// Moves the focus within its level, or to `first` of the top level if there is none.
+    fn step(&mut self, next: impl Fn(usize, usize) -> usize, first: usize) {
+        if self.focus.is_empty() {
+            if !self.entries.is_empty() {
+                self.focus.push(first.min(self.entries.len() - 1));
+            }
+            return;
+        }
+
+        let (last, parents) = self.focus.split_last().unwrap_or((&0, &[]));
+        let len = self.level(parents).map_or(0, <[Entry]>::len);
+        if len > 0 {
+            let i = next(*last, len);
+            if let Some(last) = self.focus.last_mut() {
+                *last = i;
+            }
+        }
+    }
+}
+
+/// Adds `entry` below the submenus named `path`, creating those missing.
+fn insert(entries: &mut Vec<Entry>, path: &[&str], entry: Entry) {
+    let Some((name, path)) = path.split_first() else {
+        entries.push(entry);
+        return;
+    };
+
+    let i = entries
+        .iter()
+        .position(|existing| matches!(existing, Entry::Submenu { .. } if existing.name() == *name))
+        .unwrap_or_else(|| {
+            entries.push(Entry::Submenu {
+                name: (*name).to_string(),
+                entries: Vec::new(),
+            });
+            entries.len() - 1
+        });
+    if let Entry::Submenu { entries, .. } = &mut entries[i] {
+        insert(entries, path, entry);
+    }
+}
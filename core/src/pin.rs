@@ -0,0 +1,14 @@
+//! Results pinned above the others until the launcher hides, such as to compare them with
+//! the results of other searches.
+
+use pop_launcher::SearchResult;
+
+/// A pinned result, with the query it was found for.
+#[derive(Debug, Clone)]
+pub struct Pin {
+    pub result: SearchResult,
+    pub query: String,
+    /// The result is also among the current results, whose ID it carries. Otherwise its ID
+    /// belongs to an earlier search, and activating it searches for `query` again.
+    pub live: bool,
+}
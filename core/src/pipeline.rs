@@ -0,0 +1,70 @@
+//! Compound queries such as `clip | define`, where each stage's output is the input of
+//! the next, and the last stage is searched for with the final output as its argument.
+//! Only queries whose stages before the last are all known sources are pipelines, so that
+//! `|` keeps its meaning in queries such as `: ls | grep foo` or `= 5|3`.
+
+/// Stages which produce a payload: the clipboard, the primary selection, and a file path.
+pub const SOURCES: [&str; 3] = ["clip", "sel", "file"];
+
+/// One `name argument` segment of a pipeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stage {
+    pub name: String,
+    pub arg: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pipeline {
+    /// Stages producing the payload, in order.
+    pub sources: Vec<Stage>,
+    /// Stage which receives the payload.
+    pub target: Stage,
+}
+
+impl Pipeline {
+    /// The query to search for once the sources produced `payload`.
+    pub fn query(&self, payload: &str) -> String {
+        let mut query = self.target.name.clone();
+        for part in [self.target.arg.as_str(), payload.trim()] {
+            if !part.is_empty() {
+                query.push(' ');
+                query.push_str(part);
+            }
+        }
+        query
+    }
+}
+
+/// Parses a query with at least one `|` into a pipeline. Queries without a pipe, with an
+/// empty stage, or with a stage before the last which is not one of the [`SOURCES`] are
+/// not pipelines.
+pub fn parse(query: &str) -> Option<Pipeline> {
+    let mut stages = query
+        .split('|')
+        .map(|segment| {
+            let segment = segment.trim();
+            let (name, arg) = segment.split_once(' ').unwrap_or((segment, ""));
+            Stage {
+                name: name.to_owned(),
+                arg: arg.trim().to_owned(),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if stages.len() < 2 || stages.iter().any(|stage| stage.name.is_empty()) {
+        return None;
+    }
+
+    let target = stages.pop()?;
+    if !stages
+        .iter()
+        .all(|stage| SOURCES.contains(&stage.name.as_str()))
+    {
+        return None;
+    }
+
+    Some(Pipeline {
+        sources: stages,
+        target,
+    })
+}
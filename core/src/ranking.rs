@@ -0,0 +1,100 @@
+//! Ordering applied to result lists received from the backend.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use pop_launcher::SearchResult;
+use serde::{Deserialize, Serialize};
+
+use crate::MAX_RESULTS;
+
+/// Bonuses added to a result's score. Results with equal scores keep the order the
+/// backend sent them in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Weights {
+    pub window: i32,
+    pub favorite: i32,
+    /// Added once per step of [`Ranking::frequent`].
+    pub frequent: i32,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self {
+            window: 1,
+            favorite: 2,
+            frequent: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ranking {
+    pub weights: Weights,
+    /// Names of results which receive the favorite bonus.
+    pub favorites: Vec<String>,
+    /// Steps of the frequent bonus for results used often and recently, by name.
+    pub frequent: HashMap<String, i32>,
+    /// Sources whose results are kept; empty keeps everything.
+    pub sources: Vec<String>,
+    /// Results kept at most from each of these sources, so that a plugin with many
+    /// matches cannot push the others out of the list.
+    pub quotas: HashMap<String, usize>,
+    /// Sort by score for every query. Otherwise only the results for an empty query are
+    /// sorted, and searches keep the backend's relevance order.
+    pub windows_first: bool,
+}
+
+impl Ranking {
+    pub fn score(&self, item: &SearchResult) -> i32 {
+        let mut score = 0;
+        if item.window.is_some() {
+            score += self.weights.window;
+        }
+        if self.favorites.contains(&item.name) {
+            score += self.weights.favorite;
+        }
+        if let Some(steps) = self.frequent.get(&item.name) {
+            score += self.weights.frequent * steps;
+        }
+        score
+    }
+
+    /// Filters and sorts the results for `query`, then drops results past the quota of
+    /// their source and past [`MAX_RESULTS`].
+    pub fn rank(&self, list: &mut Vec<SearchResult>, query: &str) {
+        if !self.sources.is_empty() {
+            list.retain(|item| match source(item) {
+                Some(source) => self.sources.iter().any(|s| s == source),
+                None => true,
+            });
+        }
+        if self.windows_first || query.trim().is_empty() {
+            list.sort_by_key(|item| Reverse(self.score(item)));
+        }
+        if !self.quotas.is_empty() {
+            let mut counts = HashMap::<&str, usize>::new();
+            list.retain(|item| {
+                let quota = source(item).and_then(|s| self.quotas.get_key_value(s));
+                let Some((source, quota)) = quota else {
+                    return true;
+                };
+                let count = counts.entry(source.as_str()).or_default();
+                *count += 1;
+                *count <= *quota
+            });
+        }
+        list.truncate(MAX_RESULTS);
+    }
+}
+
+/// Identifies the plugin a result came from by the category icon pop-launcher assigns
+/// to every result of that plugin.
+pub fn source(item: &SearchResult) -> Option<&str> {
+    item.category_icon.as_ref().map(|icon| match icon {
+        pop_launcher::IconSource::Name(name) | pop_launcher::IconSource::Mime(name) => {
+            name.as_ref()
+        }
+    })
+}
@@ -0,0 +1,12 @@
+/// Requests forwarded to the pop-launcher service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    Search(String),
+    Activate(u32),
+    Context(u32),
+    Complete(u32),
+    ActivateContext(u32, u32),
+    /// Close the result, such as a window.
+    Quit(u32),
+    Close,
+}
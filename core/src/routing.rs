@@ -0,0 +1,68 @@
+//! User-defined query prefixes, rewritten to the prefixes backends understand before a
+//! query is sent.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Maps prefixes typed by the user, such as `g `, to the prefix they stand for, such as
+/// `google `.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Routes(pub BTreeMap<String, String>);
+
+/// Two prefixes which both match some queries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub prefix: String,
+    /// The prefix `prefix` shadows or is shadowed by.
+    pub other: String,
+    /// Where `other` comes from, such as a plugin name.
+    pub owner: String,
+}
+
+impl Routes {
+    /// Rewrites the longest matching prefix of `query`.
+    pub fn apply(&self, query: &str) -> String {
+        self.0
+            .iter()
+            .filter(|(prefix, _)| !prefix.is_empty() && query.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or_else(
+                || query.to_owned(),
+                |(prefix, target)| format!("{target}{}", &query[prefix.len()..]),
+            )
+    }
+
+    /// Prefixes which overlap each other, or overlap a prefix claimed by a plugin.
+    /// `claimed` lists each plugin's prefix with the plugin's name.
+    pub fn conflicts(&self, claimed: &[(String, String)]) -> Vec<Conflict> {
+        let mut conflicts = Vec::new();
+        let overlaps = |a: &str, b: &str| a.starts_with(b) || b.starts_with(a);
+
+        for (i, prefix) in self.0.keys().enumerate() {
+            for other in self.0.keys().skip(i + 1) {
+                if overlaps(prefix, other) {
+                    conflicts.push(Conflict {
+                        prefix: prefix.clone(),
+                        other: other.clone(),
+                        owner: "routes".into(),
+                    });
+                }
+            }
+
+            for (other, owner) in claimed {
+                // Routing to the prefix a plugin claims is the point of the table.
+                if overlaps(prefix, other) && !self.0[prefix].starts_with(other.as_str()) {
+                    conflicts.push(Conflict {
+                        prefix: prefix.clone(),
+                        other: other.clone(),
+                        owner: owner.clone(),
+                    });
+                }
+            }
+        }
+
+        conflicts
+    }
+}
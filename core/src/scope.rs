@@ -0,0 +1,40 @@
+//! Scopes narrowing the search to one kind of result, such as applications or windows,
+//! picked from the chips under the search field.
+//!
+//! Results of pop-launcher are told apart by the category icon of their plugin, like the
+//! plugins of a profile, and windows by the window they carry. Local providers outside the
+//! scope are not queried at all.
+
+use pop_launcher::SearchResult;
+use serde::{Deserialize, Serialize};
+
+use crate::ranking;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scope {
+    /// Name of the chip, or for the default scopes a key which the chip translates, such
+    /// as `apps`.
+    pub name: String,
+    /// Category icons of the pop-launcher plugins whose results are shown, which the
+    /// configuration gives as plugin names.
+    pub plugins: Vec<String>,
+    /// Names of the local providers queried, with `commands` for the custom commands.
+    pub providers: Vec<String>,
+    pub windows: bool,
+}
+
+impl Scope {
+    /// Whether a result of pop-launcher is shown in the scope.
+    pub fn allows(&self, item: &SearchResult) -> bool {
+        if item.window.is_some() {
+            return self.windows;
+        }
+        ranking::source(item).is_some_and(|source| self.plugins.iter().any(|p| p == source))
+    }
+
+    /// Whether the local provider named `provider` is queried in the scope.
+    pub fn queries(&self, provider: &str) -> bool {
+        self.providers.iter().any(|p| p == provider)
+    }
+}
@@ -0,0 +1,1180 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use pop_launcher::{ContextOption, GpuPreference, SearchResult};
+
+use crate::{
+    activation::Activation,
+    confirm::{self, Confirmation},
+    gpu::{self, Gpus},
+    intent::PendingIntent,
+    kiosk::Kiosk,
+    menu::{Entry, Menu},
+    pin::Pin,
+    pipeline::{self, Pipeline},
+    ranking::Ranking,
+    routing::Routes,
+    scope::Scope,
+    vi::{self, Mode},
+    visibility::{Debounce, Visibility},
+    Request, LOCAL_ID_BASE, MAX_RESULTS, PAGE_SIZE,
+};
+
+/// Everything the UI can tell the launcher about.
+#[derive(Debug, Clone)]
+pub enum Input {
+    InputChanged(String),
+    Backspace,
+    /// Complete the focused result into the search field.
+    Complete,
+    /// Undo the last completion, or focus the previous result if the query was typed.
+    CompletePrevious,
+    Activate(Option<usize>),
+    /// Activate the result in this row, starting another instance of an application even
+    /// if it is running.
+    ActivateNew(usize),
+    Context(usize),
+    /// Close the result in this row, such as a window, then search again without it.
+    Quit(usize),
+    /// Pin the focused result above the others until the launcher hides, or unpin it.
+    Pin,
+    MenuOption(u32, u32),
+    /// Focus the context menu entry at this path, such as one the pointer moved onto,
+    /// opening it if it is a submenu.
+    MenuFocus(Vec<usize>),
+    /// Move the focus into the focused submenu.
+    ExpandSubmenu,
+    /// Move the focus back out of the submenu it is in.
+    CollapseSubmenu,
+    CloseContextMenu,
+    Hide,
+    /// Esc, which leaves insert mode for normal mode with modal navigation, and otherwise
+    /// hides like [`Input::Hide`].
+    Escape,
+    /// The surface lost the keyboard focus.
+    Unfocused,
+    /// The surface got the keyboard focus back.
+    Focused,
+    /// Hide the launcher if it is still without the focus once
+    /// [`crate::visibility::Debounce::unfocus_delay`] is over.
+    CheckHide,
+    /// The session was locked, or unlocked with `false`.
+    Locked(bool),
+    FocusNext,
+    FocusPrevious,
+    /// Move the focus a page of [`PAGE_SIZE`] results down, stopping at the last result.
+    FocusPageNext,
+    /// Move the focus a page of [`PAGE_SIZE`] results up, stopping at the first result.
+    FocusPagePrevious,
+    FocusFirst,
+    FocusLast,
+    /// Focus the result in this row, such as one the pointer moved onto.
+    Focus(usize),
+    ClearQuery,
+    /// Narrow the search to the scope at this position of [`Launcher::scopes`], counted
+    /// from 1, or search everything with 0.
+    Scope(usize),
+    /// Pick the next scope, wrapping around to searching everything.
+    ScopeNext,
+    ScopePrevious,
+    AltTab,
+    AltRelease,
+    AltGrav,
+    /// The pop-launcher service became available.
+    Started,
+    Response(pop_launcher::Response),
+    /// The launcher was activated, usually through its DBus interface.
+    Toggle,
+    /// The launcher was activated with a command-line action, such as alt-tab.
+    Action(String),
+    /// Carry out the activation awaiting confirmation.
+    Confirm,
+    CancelConfirmation,
+    /// Mark the pending activation as failed if the backend has not answered it within
+    /// [`crate::activation::TIMEOUT`].
+    CheckActivation,
+    /// Results for the current query from providers outside pop-launcher, with IDs
+    /// starting at [`LOCAL_ID_BASE`].
+    LocalResults(Vec<SearchResult>),
+    /// Results of providers outside pop-launcher kept below the others while the query is
+    /// empty, such as what is playing, with IDs starting at [`LOCAL_ID_BASE`].
+    FooterResults(Vec<SearchResult>),
+    /// Results kept below the others while the query is not empty, such as a web search
+    /// for it, which become the first ones when nothing else matches. Their IDs start at
+    /// [`LOCAL_ID_BASE`].
+    FallbackResults(Vec<SearchResult>),
+    /// The sources of the pipeline typed as `query` produced `payload`.
+    PipeResolved {
+        query: String,
+        payload: String,
+    },
+    /// Context options of a result given through [`Input::LocalResults`].
+    LocalContext {
+        id: u32,
+        options: Vec<ContextOption>,
+    },
+}
+
+/// Side effects the UI must carry out after an update.
+#[derive(Debug, Clone)]
+pub enum Effect {
+    Request(Request),
+    ShowSurface,
+    HideSurface,
+    OpenContextMenu,
+    CloseContextMenu,
+    Launch {
+        path: std::path::PathBuf,
+        gpu_preference: GpuPreference,
+        action_name: Option<String>,
+    },
+    /// Activate a result given through [`Input::LocalResults`].
+    ActivateLocal(u32),
+    /// Look up the context options of a local result, answering with
+    /// [`Input::LocalContext`].
+    LocalContext(u32),
+    /// Activate a context option of a local result.
+    ActivateLocalContext {
+        id: u32,
+        context: u32,
+    },
+    /// Run the sources of a pipeline, answering with [`Input::PipeResolved`].
+    ResolvePipe(Pipeline),
+    /// A result was activated, identified by its name, so the UI can rank the results used
+    /// most often first.
+    Used(String),
+    /// An activation was sent to the backend, to be followed by [`Input::CheckActivation`]
+    /// once [`crate::activation::TIMEOUT`] is over.
+    AwaitActivation,
+    /// The launcher lost the focus, to be followed by [`Input::CheckHide`] after this long.
+    AwaitHide(Duration),
+    /// A result was hidden with `dd`, identified by its name, to be passed back through
+    /// [`Launcher::set_hidden`] from now on.
+    HideResult(String),
+    /// Copy this text to the clipboard.
+    Copy(String),
+    /// Raise a window of the running application with this name, rather than launch it
+    /// again.
+    Raise(String),
+}
+
+/// Query, result, and visibility state of the launcher.
+#[derive(Debug, Clone)]
+pub struct Launcher {
+    input_value: String,
+    visibility: Visibility,
+    debounce: Debounce,
+    items: Vec<SearchResult>,
+    backend_items: Vec<SearchResult>,
+    local_items: Vec<SearchResult>,
+    footer_items: Vec<SearchResult>,
+    fallback_items: Vec<SearchResult>,
+    /// What to do once the first update after opening arrives, which is nothing once it
+    /// has.
+    intents: Vec<PendingIntent>,
+    menu: Option<Menu>,
+    focused: usize,
+    alt_tab: bool,
+    alt_grav: bool,
+    ranking: Ranking,
+    kiosk: Kiosk,
+    destructive_actions: Vec<String>,
+    confirmation: Option<Confirmation>,
+    activation: Option<Activation>,
+    payload: Option<String>,
+    routes: Routes,
+    /// Queries replaced by completions since the query was last typed, the latest last.
+    completed: Vec<String>,
+    /// The session is locked, so the launcher must not show.
+    locked: bool,
+    gpus: Gpus,
+    /// Digits typed into the empty search field activate the result in their row.
+    number_keys: bool,
+    /// Mode modal navigation starts in each time the launcher opens, if it is enabled.
+    vi: Option<Mode>,
+    mode: Mode,
+    keys: vi::Keys,
+    /// Names of the results never shown.
+    hidden: HashSet<String>,
+    scopes: Vec<Scope>,
+    /// Position of the picked scope in `scopes`, counted from 1, or 0 for everything.
+    scope: usize,
+    /// The scope is kept when the launcher hides, rather than reset to everything.
+    remember_scope: bool,
+    /// Results listed first, in the order they were pinned.
+    pins: Vec<Pin>,
+    /// Names of the applications with a window open, which activating raises instead.
+    running: Vec<String>,
+}
+
+impl Launcher {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            input_value: String::new(),
+            // Opening is guarded from the start, as after losing the focus.
+            visibility: Visibility::Hidden {
+                reopen_after: now + Debounce::default().reopen_guard(),
+            },
+            debounce: Debounce::default(),
+            items: Vec::new(),
+            backend_items: Vec::new(),
+            local_items: Vec::new(),
+            footer_items: Vec::new(),
+            fallback_items: Vec::new(),
+            intents: Vec::new(),
+            menu: None,
+            focused: 0,
+            alt_tab: false,
+            alt_grav: false,
+            ranking: Ranking::default(),
+            kiosk: Kiosk::default(),
+            destructive_actions: Vec::new(),
+            confirmation: None,
+            activation: None,
+            payload: None,
+            routes: Routes::default(),
+            completed: Vec::new(),
+            locked: false,
+            gpus: Gpus::default(),
+            number_keys: false,
+            vi: None,
+            mode: Mode::Insert,
+            keys: vi::Keys::default(),
+            hidden: HashSet::new(),
+            scopes: Vec::new(),
+            scope: 0,
+            remember_scope: false,
+            pins: Vec::new(),
+            running: Vec::new(),
+        }
+    }
+
+    pub fn set_debounce(&mut self, debounce: Debounce) {
+        self.debounce = debounce;
+    }
+
+    /// Sets the names of results and context options which require confirmation.
+    pub fn set_destructive_actions(&mut self, actions: Vec<String>) {
+        self.destructive_actions = actions;
+    }
+
+    pub fn confirmation(&self) -> Option<&Confirmation> {
+        self.confirmation.as_ref()
+    }
+
+    /// The activation sent to the backend which has not been carried out yet.
+    pub fn activation(&self) -> Option<&Activation> {
+        self.activation.as_ref()
+    }
+
+    /// Sets the prefixes rewritten before queries are sent.
+    pub fn set_routes(&mut self, routes: Routes) {
+        self.routes = routes;
+    }
+
+    /// Sets the GPUs listed in the "Launch using" submenu of app results.
+    pub fn set_gpus(&mut self, gpus: Gpus) {
+        self.gpus = gpus;
+    }
+
+    /// Sets whether 1 to 9, typed into the empty search field, activate the result in
+    /// that row rather than start a query.
+    pub fn set_number_keys(&mut self, enabled: bool) {
+        self.number_keys = enabled;
+    }
+
+    /// Enables modal navigation, starting in `start` each time the launcher opens, or
+    /// disables it with `None`. The current mode is kept unless the setting changes.
+    pub fn set_vi_mode(&mut self, start: Option<Mode>) {
+        if self.vi != start {
+            self.vi = start;
+            self.mode = start.unwrap_or_default();
+            self.keys.clear();
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Sets the names of the applications with a window open, as their results name them.
+    pub fn set_running(&mut self, names: Vec<String>) {
+        self.running = names;
+    }
+
+    /// Sets the names of the results never shown, such as those hidden with `dd`.
+    pub fn set_hidden(&mut self, hidden: impl IntoIterator<Item = String>) {
+        self.hidden = hidden.into_iter().collect();
+        self.refresh_items();
+    }
+
+    /// Sets the scopes the search can be narrowed to, and whether the picked one is kept
+    /// when the launcher hides. Everything is searched again if the scopes changed.
+    pub fn set_scopes(&mut self, scopes: Vec<Scope>, remember: bool) {
+        if self.scopes != scopes {
+            self.scopes = scopes;
+            self.scope = 0;
+        }
+        self.remember_scope = remember;
+    }
+
+    pub fn scopes(&self) -> &[Scope] {
+        &self.scopes
+    }
+
+    /// Position of the picked scope in [`Launcher::scopes`], counted from 1, or 0 while
+    /// everything is searched.
+    pub fn scope_index(&self) -> usize {
+        self.scope
+    }
+
+    pub fn scope(&self) -> Option<&Scope> {
+        self.scope.checked_sub(1).and_then(|i| self.scopes.get(i))
+    }
+
+    pub fn set_kiosk(&mut self, kiosk: Kiosk) {
+        self.kiosk = kiosk;
+    }
+
+    pub fn kiosk(&self) -> &Kiosk {
+        &self.kiosk
+    }
+
+    /// Replaces the ranking applied to subsequent backend updates.
+    pub fn set_ranking(&mut self, ranking: Ranking) {
+        self.ranking = ranking;
+    }
+
+    /// Replaces the steps of the frequent bonus, leaving the rest of the ranking as it is.
+    pub fn set_frequent(&mut self, frequent: HashMap<String, i32>) {
+        self.ranking.frequent = frequent;
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input_value
+    }
+
+    /// Output of the pipeline sources when the input is a pipeline, such as the clipboard
+    /// contents for `clip | define`.
+    pub fn payload(&self) -> Option<&str> {
+        self.payload.as_deref()
+    }
+
+    pub fn items(&self) -> &[SearchResult] {
+        &self.items
+    }
+
+    pub fn focused(&self) -> usize {
+        self.focused
+    }
+
+    /// Pinned results, which are the first rows of [`Launcher::items`].
+    pub fn pins(&self) -> &[Pin] {
+        &self.pins
+    }
+
+    pub fn menu(&self) -> Option<&Menu> {
+        self.menu.as_ref()
+    }
+
+    pub fn is_alt_tab(&self) -> bool {
+        self.alt_tab
+    }
+
+    /// Whether the surface is mapped, or about to be once results arrive.
+    pub fn is_active(&self) -> bool {
+        !matches!(self.visibility, Visibility::Hidden { .. })
+    }
+
+    pub fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    pub fn is_waiting_for_result(&self) -> bool {
+        self.intents.contains(&PendingIntent::Show)
+    }
+
+    pub fn intents(&self) -> &[PendingIntent] {
+        &self.intents
+    }
+
+    #[allow(clippy::too_many_lines)]
+    pub fn update(&mut self, input: Input, now: Instant) -> Vec<Effect> {
+        let mut effects = Vec::new();
+
+        if let Some(confirmation) = self.confirmation.take() {
+            match input {
+                Input::Confirm | Input::Activate(None) => {
+                    // The action of a confirmed activation is the name of its result.
+                    if matches!(confirmation.request, Request::Activate(_)) {
+                        effects.push(Effect::Used(confirmation.action));
+                    }
+                    if confirmation.id >= LOCAL_ID_BASE {
+                        effects.push(Effect::ActivateLocal(confirmation.id));
+                    } else {
+                        if matches!(confirmation.request, Request::Activate(_)) {
+                            self.activation =
+                                Some(Activation::new(confirmation.row, confirmation.id, now));
+                            effects.push(Effect::AwaitActivation);
+                        }
+                        effects.push(Effect::Request(confirmation.request));
+                    }
+                    return effects;
+                }
+                Input::CancelConfirmation | Input::Hide | Input::Escape => return effects,
+                // Updates which keep the confirmed result in place leave the prompt open.
+                Input::Response(pop_launcher::Response::Update(_))
+                | Input::LocalResults(_)
+                | Input::FooterResults(_)
+                | Input::FallbackResults(_)
+                | Input::CheckActivation
+                | Input::Started => self.confirmation = Some(confirmation),
+                _ => {}
+            }
+        }
+
+        match input {
+            // In normal mode, a key typed at the end of the query is a command instead.
+            Input::InputChanged(value) if self.mode == Mode::Normal => {
+                let mut typed = value
+                    .strip_prefix(self.input_value.as_str())
+                    .unwrap_or_default()
+                    .chars();
+                if let (Some(key), None) = (typed.next(), typed.next()) {
+                    if let Some(command) = self.keys.push(key) {
+                        self.vi_command(command, &mut effects);
+                    }
+                }
+            }
+            Input::InputChanged(value) if self.number_key_row(&value).is_some() => {
+                let row = self.number_key_row(&value);
+                self.activate(row, true, now, &mut effects);
+            }
+            Input::InputChanged(value) => {
+                self.input_value = value;
+                self.completed.clear();
+                self.search(&mut effects);
+            }
+            Input::Backspace => {
+                self.input_value.pop();
+                self.completed.clear();
+                self.search(&mut effects);
+            }
+            Input::Complete if self.is_stale_pin(self.focused) => {}
+            Input::Complete if !self.alt_tab && self.kiosk.allows_context() => {
+                let focused = self.focused;
+                self.focused = 0;
+                if let Some(item) = self.items.get(focused) {
+                    self.completed.push(self.input_value.clone());
+                    if item.id >= LOCAL_ID_BASE {
+                        self.input_value = item.name.clone();
+                        self.search(&mut effects);
+                    } else {
+                        effects.push(Effect::Request(Request::Complete(item.id)));
+                    }
+                }
+            }
+            Input::Complete => {}
+            Input::CompletePrevious => match self.completed.pop() {
+                Some(query) => {
+                    self.input_value = query;
+                    self.search(&mut effects);
+                }
+                None => self.focus_previous(),
+            },
+            // Enter carries out the focused entry of an open context menu.
+            Input::Activate(None) if self.menu.is_some() => {
+                let focused = self.menu.as_ref().and_then(Menu::focused).cloned();
+                match focused {
+                    Some(Entry::Option { id, .. }) => {
+                        let result = self.menu.as_ref().map_or(0, |menu| menu.id);
+                        self.activate_menu_option(result, id, now, &mut effects);
+                    }
+                    Some(Entry::Submenu { .. }) => self.with_menu(Menu::expand),
+                    None => {}
+                }
+            }
+            Input::Activate(i) => self.activate(i, true, now, &mut effects),
+            Input::ActivateNew(row) => self.activate(Some(row), false, now, &mut effects),
+            Input::Context(_) if !self.kiosk.allows_context() => {}
+            Input::Context(i) if self.is_stale_pin(i) => {}
+            Input::Context(i) => {
+                if self.menu.take().is_some() {
+                    effects.push(Effect::CloseContextMenu);
+                } else if let Some(item) = self.items.get(i) {
+                    if item.id >= LOCAL_ID_BASE {
+                        effects.push(Effect::LocalContext(item.id));
+                    } else {
+                        effects.push(Effect::Request(Request::Context(item.id)));
+                    }
+                }
+            }
+            Input::Quit(i) if self.is_stale_pin(i) => {}
+            Input::Quit(i) => {
+                if let Some(item) = self.items.get(i).filter(|item| item.id < LOCAL_ID_BASE) {
+                    effects.push(Effect::Request(Request::Quit(item.id)));
+                    self.search(&mut effects);
+                }
+            }
+            Input::Pin if !self.alt_tab => {
+                if self.focused < self.pins.len() {
+                    self.pins.remove(self.focused);
+                } else if let Some(item) = self
+                    .items
+                    .get(self.focused)
+                    .filter(|_| self.pins.len() < MAX_RESULTS)
+                {
+                    self.pins.push(Pin {
+                        result: item.clone(),
+                        query: self.input_value.clone(),
+                        live: true,
+                    });
+                }
+                self.refresh_items();
+            }
+            Input::Pin => {}
+            Input::MenuOption(i, context) => {
+                self.activate_menu_option(i, context, now, &mut effects);
+            }
+            Input::MenuFocus(path) => self.with_menu(|menu| menu.set_focus(path)),
+            Input::ExpandSubmenu => self.with_menu(Menu::expand),
+            Input::CollapseSubmenu => self.with_menu(Menu::collapse),
+            Input::CloseContextMenu => {
+                if self.menu.take().is_some() {
+                    effects.push(Effect::CloseContextMenu);
+                }
+            }
+            Input::Escape if self.vi.is_some() && self.mode == Mode::Insert && !self.alt_tab => {
+                if self.menu.take().is_some() {
+                    effects.push(Effect::CloseContextMenu);
+                } else {
+                    self.mode = Mode::Normal;
+                }
+            }
+            Input::Hide | Input::Escape => {
+                if self.menu.take().is_some() {
+                    effects.push(Effect::CloseContextMenu);
+                } else {
+                    self.hide(now, &mut effects);
+                }
+            }
+            Input::Unfocused => match self.visibility {
+                Visibility::Shown if !self.debounce.unfocus_delay().is_zero() => {
+                    let delay = self.debounce.unfocus_delay();
+                    self.visibility = Visibility::Unfocused {
+                        hide_at: now + delay,
+                    };
+                    effects.push(Effect::AwaitHide(delay));
+                }
+                Visibility::Shown | Visibility::Hidden { .. } => {
+                    self.hide_unfocused(now, &mut effects);
+                }
+                Visibility::Unfocused { .. } => {}
+            },
+            Input::Focused => {
+                if let Visibility::Unfocused { .. } = self.visibility {
+                    self.visibility = Visibility::Shown;
+                }
+            }
+            Input::Locked(locked) => {
+                self.locked = locked;
+                if locked && self.is_active() {
+                    self.hide(now, &mut effects);
+                }
+            }
+            // Nothing opens the launcher over the lock screen.
+            Input::Toggle | Input::Action(_) if self.locked => {}
+            Input::CheckHide => {
+                if let Visibility::Unfocused { hide_at } = self.visibility {
+                    if now >= hide_at {
+                        self.hide_unfocused(now, &mut effects);
+                    }
+                }
+            }
+            Input::FocusNext if self.menu.is_some() => self.with_menu(Menu::focus_next),
+            Input::FocusPrevious if self.menu.is_some() => self.with_menu(Menu::focus_previous),
+            Input::FocusNext => self.focus_next(),
+            Input::FocusPrevious => self.focus_previous(),
+            Input::FocusPageNext => {
+                let last = self.items.len().saturating_sub(1);
+                self.focused = (self.focused + PAGE_SIZE).min(last);
+            }
+            Input::FocusPagePrevious => self.focused = self.focused.saturating_sub(PAGE_SIZE),
+            Input::FocusFirst => self.focused = 0,
+            Input::FocusLast => self.focused = self.items.len().saturating_sub(1),
+            Input::Focus(row) => {
+                if row < self.items.len() {
+                    self.focused = row;
+                }
+            }
+            Input::ClearQuery => {
+                self.input_value.clear();
+                self.completed.clear();
+                effects.push(Effect::Request(Request::Search(String::new())));
+            }
+            Input::Scope(scope) => {
+                if scope != self.scope && scope <= self.scopes.len() {
+                    self.scope = scope;
+                    self.refresh_items();
+                    self.search(&mut effects);
+                }
+            }
+            Input::ScopeNext if !self.scopes.is_empty() => {
+                let next = (self.scope + 1) % (self.scopes.len() + 1);
+                effects.extend(self.update(Input::Scope(next), now));
+            }
+            Input::ScopePrevious if !self.scopes.is_empty() => {
+                let previous = (self.scope + self.scopes.len()) % (self.scopes.len() + 1);
+                effects.extend(self.update(Input::Scope(previous), now));
+            }
+            Input::ScopeNext | Input::ScopePrevious => {}
+            Input::AltTab => self.alt_tab(),
+            Input::AltRelease => {
+                if self.is_waiting_for_result() && self.alt_tab {
+                    self.intents.push(PendingIntent::Activate);
+                } else if self.alt_tab || self.alt_grav {
+                    self.activate(None, true, now, &mut effects);
+                }
+            }
+            Input::AltGrav => {
+                if self.alt_grav {
+                    self.focus_next_current();
+                } else {
+                    self.alt_grav = true;
+                }
+            }
+            Input::Started => {
+                effects.push(Effect::Request(Request::Search(String::new())));
+            }
+            Input::Response(response) => self.handle_response(response, now, &mut effects),
+            // A toggle while the launcher waits to hide for the lost focus most likely comes
+            // from the panel button whose click took the focus, so it hides at once.
+            Input::Toggle => match self.visibility {
+                Visibility::Shown | Visibility::Unfocused { .. } => self.hide(now, &mut effects),
+                Visibility::Hidden { reopen_after } => {
+                    if now >= reopen_after {
+                        effects.push(Effect::Request(Request::Search(String::new())));
+                        self.input_value.clear();
+                        self.visibility = Visibility::Shown;
+                        self.intents.push(PendingIntent::Show);
+                    }
+                }
+            },
+            Input::Confirm | Input::CancelConfirmation => {}
+            Input::CheckActivation => {
+                if let Some(activation) = self.activation.as_mut() {
+                    if activation.deadline.is_some_and(|deadline| now >= deadline) {
+                        activation.timed_out = true;
+                    }
+                }
+            }
+            Input::PipeResolved { query, payload } => {
+                if query != self.input_value {
+                    return effects;
+                }
+
+                let Some(pipeline) = pipeline::parse(&query) else {
+                    return effects;
+                };
+
+                let search = self.routes.apply(&pipeline.query(&payload));
+                if self.kiosk.allows_query(&search) {
+                    self.payload = Some(payload);
+                    effects.push(Effect::Request(Request::Search(search)));
+                }
+            }
+            Input::LocalResults(list) => {
+                self.local_items = list;
+                self.refresh_items();
+            }
+            Input::FooterResults(list) => {
+                self.footer_items = list;
+                self.refresh_items();
+            }
+            Input::FallbackResults(list) => {
+                self.fallback_items = list;
+                self.refresh_items();
+            }
+            Input::LocalContext { id, options } => {
+                self.handle_response(
+                    pop_launcher::Response::Context { id, options },
+                    now,
+                    &mut effects,
+                );
+            }
+            Input::Action(action) => {
+                effects.push(Effect::Request(Request::Search(String::new())));
+                if self.is_active() {
+                    // Alt-tab pressed again brings the focus back, if it left.
+                    self.visibility = Visibility::Shown;
+                    if self.items.is_empty() && !self.is_waiting_for_result() {
+                        effects.extend(self.update(Input::Hide, now));
+                    } else {
+                        self.alt_tab();
+                    }
+                } else {
+                    self.input_value = action;
+                    self.visibility = Visibility::Shown;
+                    self.intents.push(PendingIntent::Show);
+                    self.alt_tab();
+                }
+            }
+        }
+
+        effects
+    }
+
+    fn handle_response(
+        &mut self,
+        response: pop_launcher::Response,
+        now: Instant,
+        effects: &mut Vec<Effect>,
+    ) {
+        match response {
+            pop_launcher::Response::Close => self.hide(now, effects),
+            pop_launcher::Response::Context { id, mut options } => {
+                // Windows are raised rather than launched, on whichever GPU they run on.
+                let app = self
+                    .items
+                    .iter()
+                    .any(|item| item.id == id && item.window.is_none());
+                if app && id < LOCAL_ID_BASE {
+                    self.gpus.apply(&mut options);
+                }
+                if options.is_empty() {
+                    return;
+                }
+
+                self.menu = Some(Menu::new(id, options));
+                effects.push(Effect::OpenContextMenu);
+            }
+            pop_launcher::Response::DesktopEntry {
+                path,
+                mut gpu_preference,
+                action_name,
+            } => {
+                if !self.kiosk.allows_entry(&path) {
+                    return;
+                }
+
+                // The row stays pending until the launch hides the launcher.
+                if let Some(activation) = self.activation.as_mut() {
+                    activation.deadline = None;
+                    if let Some(gpu) = activation.gpu {
+                        gpu_preference = GpuPreference::SpecificIdx(gpu);
+                    }
+                }
+
+                effects.push(Effect::Launch {
+                    path,
+                    gpu_preference,
+                    action_name,
+                });
+            }
+            pop_launcher::Response::Update(list) => {
+                if self.alt_tab && self.is_waiting_for_result() && list.is_empty() {
+                    self.hide(now, effects);
+                    return;
+                }
+
+                self.activation = None;
+                self.backend_items = list;
+                self.refresh_items();
+
+                // Inputs which came before the results act on them now, in their order.
+                for intent in std::mem::take(&mut self.intents) {
+                    match intent {
+                        PendingIntent::Show => effects.push(Effect::ShowSurface),
+                        PendingIntent::FocusNext => self.focus_next(),
+                        PendingIntent::Activate => self.activate(None, true, now, effects),
+                    }
+                }
+            }
+            pop_launcher::Response::Fill(s) => {
+                self.activation = None;
+                self.input_value = s;
+                self.search(effects);
+            }
+        }
+    }
+
+    /// Rebuilds the visible results from the latest backend and local results.
+    fn refresh_items(&mut self) {
+        // A focused fallback gives way to the first result as soon as there is one.
+        let focused = self
+            .items
+            .get(self.focused)
+            .filter(|item| {
+                !self
+                    .fallback_items
+                    .iter()
+                    .any(|fallback| fallback.id == item.id)
+            })
+            .cloned();
+        let activated = self
+            .activation
+            .as_ref()
+            .and_then(|activation| self.items.get(activation.row).cloned());
+        let mut list = self
+            .local_items
+            .iter()
+            .chain(&self.backend_items)
+            .filter(|item| self.shows(item))
+            .cloned()
+            .collect();
+        // Alt-tab always lists windows first, whatever the action put in the query.
+        let query = if self.alt_tab { "" } else { &self.input_value };
+        self.ranking.rank(&mut list, query);
+
+        // Pins come first, with the IDs of the current results they stand for, and leave
+        // less room to the others.
+        let mut items = Vec::with_capacity(MAX_RESULTS);
+        for pin in &mut self.pins {
+            let live = list.iter().position(|item| same(item, &pin.result));
+            pin.live = live.is_some();
+            if let Some(i) = live {
+                pin.result = list.remove(i);
+            }
+            items.push(pin.result.clone());
+        }
+        let room = MAX_RESULTS.saturating_sub(items.len());
+
+        if query.trim().is_empty() && !self.alt_tab {
+            let footer = self
+                .footer_items
+                .iter()
+                .filter(|item| self.shows(item))
+                .cloned()
+                .collect::<Vec<_>>();
+            list.truncate(room.saturating_sub(footer.len()));
+            list.extend(footer);
+        } else if !query.trim().is_empty() {
+            let fallbacks = self
+                .fallback_items
+                .iter()
+                .filter(|item| self.shows(item))
+                .cloned()
+                .collect::<Vec<_>>();
+            list.truncate(room.saturating_sub(fallbacks.len()));
+            list.extend(fallbacks);
+        }
+        list.truncate(room);
+        items.extend(list);
+        self.items = items;
+
+        // The focus stays on its result when it survives the update, so that it does not
+        // move to another one under the user's cursor while they type. IDs are only
+        // positions in the backend's answer, so results are told apart by what they show.
+        self.focused = focused
+            .and_then(|focused| self.items.iter().position(|item| same(item, &focused)))
+            .unwrap_or_default();
+        if let Some((activation, activated)) = self.activation.as_mut().zip(activated) {
+            match self.items.iter().position(|item| same(item, &activated)) {
+                Some(row) => activation.row = row,
+                None => self.activation = None,
+            }
+        }
+
+        // The confirmation only stays on its row while that row still shows the result it
+        // was asked for, under the ID its request names.
+        if let Some(confirmation) = self.confirmation.as_ref() {
+            let kept = self
+                .items
+                .get(confirmation.row)
+                .is_some_and(|item| item.id == confirmation.id && same(item, &confirmation.result));
+            if !kept {
+                self.confirmation = None;
+            }
+        }
+    }
+
+    /// Searches for the current input, unless the kiosk policy forbids the query.
+    /// Pipelines are first handed to the UI to resolve their sources.
+    fn search(&mut self, effects: &mut Vec<Effect>) {
+        self.payload = None;
+        self.activation = None;
+        let query = self.routes.apply(&self.input_value);
+        if !self.kiosk.allows_query(&query) {
+            self.items.clear();
+            self.focused = 0;
+        } else if let Some(pipeline) = pipeline::parse(&self.input_value) {
+            // The target stage is routed once the sources are resolved.
+            effects.push(Effect::ResolvePipe(pipeline));
+        } else {
+            effects.push(Effect::Request(Request::Search(query)));
+        }
+    }
+
+    /// Row of the result activated by typing `value` into the empty search field, which
+    /// must be a single digit from 1 to as many results as there are.
+    fn number_key_row(&self, value: &str) -> Option<usize> {
+        if !self.number_keys || !self.input_value.is_empty() || value.len() != 1 {
+            return None;
+        }
+
+        let digit = value.parse::<usize>().ok()?;
+        (1..=self.items.len().min(9))
+            .contains(&digit)
+            .then(|| digit - 1)
+    }
+
+    fn vi_command(&mut self, command: vi::Command, effects: &mut Vec<Effect>) {
+        match command {
+            vi::Command::FocusNext => self.focus_next(),
+            vi::Command::FocusPrevious => self.focus_previous(),
+            vi::Command::FocusFirst => self.focused = 0,
+            vi::Command::FocusLast => self.focused = self.items.len().saturating_sub(1),
+            vi::Command::Hide => {
+                if let Some(item) = self.items.get(self.focused) {
+                    let name = item.name.clone();
+                    self.hidden.insert(name.clone());
+                    effects.push(Effect::HideResult(name));
+                    // The focus stays in its row, on the result which moved up into it.
+                    let row = self.focused;
+                    self.refresh_items();
+                    self.focused = row.min(self.items.len().saturating_sub(1));
+                }
+            }
+            vi::Command::Copy => {
+                if let Some(item) = self.items.get(self.focused) {
+                    effects.push(Effect::Copy(item.name.clone()));
+                }
+            }
+            vi::Command::Insert => self.mode = Mode::Insert,
+        }
+    }
+
+    /// Whether the result in `row` is pinned from an earlier search, so that its ID is not
+    /// the backend's for it anymore.
+    fn is_stale_pin(&self, row: usize) -> bool {
+        self.pins.get(row).is_some_and(|pin| !pin.live)
+    }
+
+    /// The row of the current result with the backend ID `id`, passing over stale pins,
+    /// which keep the IDs of the search they were pinned from.
+    fn row_of(&self, id: u32) -> Option<usize> {
+        (0..self.items.len()).find(|&row| self.items[row].id == id && !self.is_stale_pin(row))
+    }
+
+    /// Whether `item` may be listed. Local results are narrowed to the scope by only
+    /// querying its providers, and alt-tab lists windows whatever the scope.
+    fn shows(&self, item: &SearchResult) -> bool {
+        let in_scope = match self.scope() {
+            Some(scope) if !self.alt_tab && item.id < LOCAL_ID_BASE => scope.allows(item),
+            _ => true,
+        };
+        in_scope && self.kiosk.allows_result(item) && !self.hidden.contains(&item.name)
+    }
+
+    fn with_menu(&mut self, f: impl FnOnce(&mut Menu)) {
+        if let Some(menu) = self.menu.as_mut() {
+            f(menu);
+        }
+    }
+
+    /// Activates the context option `context` of the result `i`, closing the menu.
+    fn activate_menu_option(
+        &mut self,
+        i: u32,
+        context: u32,
+        now: Instant,
+        effects: &mut Vec<Effect>,
+    ) {
+        if let (true, Some(gpu)) = (i < LOCAL_ID_BASE, gpu::index(context)) {
+            if let Some(row) = self.row_of(i) {
+                self.activate(Some(row), true, now, effects);
+                if let Some(activation) = self.activation.as_mut().filter(|a| a.id == i) {
+                    activation.gpu = Some(gpu);
+                }
+            }
+        } else if i >= LOCAL_ID_BASE {
+            effects.push(Effect::ActivateLocalContext { id: i, context });
+        } else {
+            let request = Request::ActivateContext(i, context);
+            let option = self
+                .menu
+                .as_ref()
+                .and_then(|menu| menu.option(context))
+                .filter(|name| confirm::is_destructive(&self.destructive_actions, name));
+            let row = self.row_of(i);
+
+            if let (Some(option), Some(row)) = (option, row) {
+                self.confirmation = Some(Confirmation {
+                    row,
+                    id: i,
+                    result: self.items[row].clone(),
+                    action: option.to_string(),
+                    request,
+                });
+                self.focused = row;
+            } else {
+                effects.push(Effect::Request(request));
+            }
+        }
+
+        if self.menu.take().is_some() {
+            effects.push(Effect::CloseContextMenu);
+        }
+    }
+
+    /// Activates the result in row `i`, or the focused one. An application with a window
+    /// open is raised unless `raise` is false, which starts another instance.
+    fn activate(&mut self, i: Option<usize>, raise: bool, now: Instant, effects: &mut Vec<Effect>) {
+        // A result is activated once until the backend answers.
+        if self.activation.as_ref().is_some_and(Activation::is_pending) {
+            return;
+        }
+
+        let row = i.unwrap_or(self.focused);
+        if let Some(pin) = self.pins.get(row).filter(|pin| !pin.live) {
+            // Searching again for the pin brings back an ID which activates it.
+            self.input_value = pin.query.clone();
+            self.completed.clear();
+            self.focused = row;
+            self.search(effects);
+            return;
+        }
+        if let Some(item) = self.items.get(row) {
+            let request = Request::Activate(item.id);
+            if confirm::is_destructive(&self.destructive_actions, &item.name) {
+                self.confirmation = Some(Confirmation {
+                    row,
+                    id: item.id,
+                    result: item.clone(),
+                    action: item.name.clone(),
+                    request,
+                });
+                self.focused = row;
+            } else if item.id >= LOCAL_ID_BASE {
+                // Fallbacks are named after the query, so their use is not worth ranking by.
+                if !self
+                    .fallback_items
+                    .iter()
+                    .any(|fallback| fallback.id == item.id)
+                {
+                    effects.push(Effect::Used(item.name.clone()));
+                }
+                effects.push(Effect::ActivateLocal(item.id));
+            } else if raise && item.window.is_none() && self.is_running(&item.name) {
+                effects.push(Effect::Used(item.name.clone()));
+                effects.push(Effect::Raise(item.name.clone()));
+                self.hide(now, effects);
+            } else {
+                effects.push(Effect::Used(item.name.clone()));
+                effects.push(Effect::Request(request));
+                effects.push(Effect::AwaitActivation);
+                self.activation = Some(Activation::new(row, item.id, now));
+            }
+        } else {
+            self.hide(now, effects);
+        }
+    }
+
+    fn is_running(&self, name: &str) -> bool {
+        self.running
+            .iter()
+            .any(|running| running.eq_ignore_ascii_case(name))
+    }
+
+    fn alt_tab(&mut self) {
+        if !self.alt_tab {
+            self.alt_tab = true;
+        } else if self.is_waiting_for_result() {
+            self.intents.push(PendingIntent::FocusNext);
+        } else {
+            self.focus_next();
+        }
+    }
+
+    /// Hides the launcher for the lost focus, ignoring attempts to reopen it for a moment.
+    fn hide_unfocused(&mut self, now: Instant, effects: &mut Vec<Effect>) {
+        self.hide(now, effects);
+        self.visibility = Visibility::Hidden {
+            reopen_after: now + self.debounce.reopen_guard(),
+        };
+    }
+
+    fn hide(&mut self, now: Instant, effects: &mut Vec<Effect>) {
+        self.input_value.clear();
+        self.completed.clear();
+        self.activation = None;
+        // Results of the next opening are focused from the top.
+        self.items.clear();
+        self.focused = 0;
+        self.alt_tab = false;
+        self.alt_grav = false;
+        self.intents.clear();
+        self.mode = self.vi.unwrap_or_default();
+        self.keys.clear();
+        if !self.remember_scope {
+            self.scope = 0;
+        }
+        self.pins.clear();
+
+        // XXX The close will reset the launcher, but the search will restart it so it's ready
+        // for the next time it's opened.
+        effects.push(Effect::Request(Request::Close));
+        effects.push(Effect::Request(Request::Search(String::new())));
+
+        if self.is_active() {
+            self.visibility = Visibility::Hidden { reopen_after: now };
+
+            effects.push(Effect::HideSurface);
+            if self.menu.take().is_some() {
+                effects.push(Effect::CloseContextMenu);
+            }
+        }
+    }
+
+    pub fn focus_next(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        self.focused = (self.focused + 1) % self.items.len();
+    }
+
+    pub fn focus_previous(&mut self) {
+        if self.items.is_empty() {
+            return;
+        }
+        self.focused = (self.focused + self.items.len() - 1) % self.items.len();
+    }
+
+    pub fn focus_next_current(&mut self) {
+        let curwins = self.current_app_windows();
+        if curwins.is_empty() {
+            return;
+        }
+        let cur = curwins
+            .iter()
+            .position(|&i| i > self.focused)
+            .unwrap_or_default();
+        self.focused = curwins[cur];
+    }
+
+    pub fn focus_previous_current(&mut self) {
+        let curwins = self.current_app_windows();
+        if let Some(&last) = curwins.last() {
+            self.focused = last;
+        }
+    }
+
+    /// Indices of other results sharing the focused result's name.
+    fn current_app_windows(&self) -> Vec<usize> {
+        let Some(focused) = self.items.get(self.focused) else {
+            return Vec::new();
+        };
+
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(i, item)| item.name == focused.name && *i != self.focused)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+/// Whether two results of different updates show the same thing.
+fn same(a: &SearchResult, b: &SearchResult) -> bool {
+    a.name == b.name && a.description == b.description && a.window == b.window
+}
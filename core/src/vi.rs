@@ -0,0 +1,61 @@
+//! Modal navigation of the results, after vi.
+//!
+//! Esc leaves insert mode, where keys are typed into the search field as usual, for
+//! normal mode, where they are commands instead: j and k move the focus, gg and G go to
+//! the first and last result, dd hides the focused result for good, y copies it, and i
+//! goes back to insert mode. Esc in normal mode hides the launcher.
+
+use serde::{Deserialize, Serialize};
+
+/// Whether keys are typed into the search field or taken as commands.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mode {
+    #[default]
+    Insert,
+    Normal,
+}
+
+/// What a key does in normal mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    FocusNext,
+    FocusPrevious,
+    FocusFirst,
+    FocusLast,
+    /// Hide the focused result from now on.
+    Hide,
+    /// Copy the name of the focused result.
+    Copy,
+    Insert,
+}
+
+/// Normal mode commands spelled with two keys, such as `dd`, wait here for the second.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Keys {
+    pending: Option<char>,
+}
+
+impl Keys {
+    /// The command completed by `key`, if any. Keys which complete nothing are dropped
+    /// along with the one pending before them.
+    pub fn push(&mut self, key: char) -> Option<Command> {
+        match (self.pending.take(), key) {
+            (None, 'j') => Some(Command::FocusNext),
+            (None, 'k') => Some(Command::FocusPrevious),
+            (None, 'G') => Some(Command::FocusLast),
+            (None, 'y') => Some(Command::Copy),
+            (None, 'i' | 'a') => Some(Command::Insert),
+            (Some('g'), 'g') => Some(Command::FocusFirst),
+            (Some('d'), 'd') => Some(Command::Hide),
+            (None, 'g' | 'd') => {
+                self.pending = Some(key);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.pending = None;
+    }
+}
@@ -0,0 +1,53 @@
+//! Showing and hiding the launcher, debounced against panel buttons and focus flickers.
+//!
+//! Clicking the panel button which opened the launcher first takes the keyboard focus
+//! from it, then toggles it. The launcher must neither reopen right after hiding for the
+//! lost focus, nor hide for good when the focus only left for a moment, such as while a
+//! panel applet grabs it.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Thresholds of the show and hide state machine, in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Debounce {
+    /// Time after hiding for the lost focus during which opening the launcher again is
+    /// ignored, so that the click on the panel button which took the focus does not
+    /// reopen it.
+    pub reopen_guard: u64,
+    /// Time the launcher stays open after losing the focus, hiding only if it does not
+    /// come back meanwhile. With 0, it hides at once.
+    pub unfocus_delay: u64,
+}
+
+impl Default for Debounce {
+    fn default() -> Self {
+        Self {
+            reopen_guard: 100,
+            unfocus_delay: 0,
+        }
+    }
+}
+
+impl Debounce {
+    pub fn reopen_guard(&self) -> Duration {
+        Duration::from_millis(self.reopen_guard)
+    }
+
+    pub fn unfocus_delay(&self) -> Duration {
+        Duration::from_millis(self.unfocus_delay)
+    }
+}
+
+/// Whether the launcher is shown, as far as opening and hiding it is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    /// Opening the launcher is ignored until `reopen_after`.
+    Hidden { reopen_after: Instant },
+    /// Shown, or about to be once the first results arrive.
+    Shown,
+    /// Shown without the keyboard focus, and hidden at `hide_at` unless it comes back.
+    Unfocused { hide_at: Instant },
+}
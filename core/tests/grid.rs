@@ -0,0 +1,47 @@
+use cosmic_launcher_core::grid::{Grid, Move};
+
+#[test]
+fn arrows_move_through_rows_and_on_to_the_next_page() {
+    // Two pages of 2 × 3 tiles, the second holding a row of 3 and a row of 1.
+    let mut grid = Grid::new(3, 2, 10);
+    assert_eq!(grid.pages(), 2);
+
+    grid.step(Move::Right);
+    grid.step(Move::Down);
+    assert_eq!(grid.focused(), 4);
+    assert_eq!(grid.visible(), 0..6);
+
+    grid.step(Move::Down);
+    assert_eq!((grid.focused(), grid.page()), (7, 1));
+    assert_eq!(grid.visible(), 6..10);
+
+    // The row below is shorter, so Down lands on its last tile, and stops there.
+    grid.step(Move::Down);
+    assert_eq!(grid.focused(), 9);
+    grid.step(Move::Down);
+    grid.step(Move::Right);
+    assert_eq!(grid.focused(), 9);
+
+    grid.step(Move::Up);
+    grid.step(Move::Left);
+    assert_eq!(grid.focused(), 5);
+}
+
+#[test]
+fn page_keys_keep_the_position_on_the_page() {
+    let mut grid = Grid::new(3, 2, 10);
+    grid.focus(4);
+    grid.step(Move::PageNext);
+    assert_eq!(grid.focused(), 9);
+    grid.step(Move::PageNext);
+    assert_eq!(grid.focused(), 9);
+
+    grid.step(Move::PagePrevious);
+    assert_eq!(grid.focused(), 3);
+    grid.step(Move::PagePrevious);
+    assert_eq!(grid.focused(), 0);
+
+    // Tiles past the end are not focused.
+    grid.focus(12);
+    assert_eq!(grid.focused(), 0);
+}
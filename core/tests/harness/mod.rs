@@ -0,0 +1,90 @@
+//! Headless driver for [`Launcher`] that replays scripted inputs against a virtual clock.
+
+#![allow(dead_code)]
+
+use std::time::{Duration, Instant};
+
+use cosmic_launcher_core::{Effect, Input, Launcher, Request};
+use pop_launcher::SearchResult;
+
+pub struct Harness {
+    pub launcher: Launcher,
+    now: Instant,
+    effects: Vec<Effect>,
+}
+
+impl Harness {
+    /// Creates a launcher whose clock has already moved past the reopen guard.
+    pub fn new() -> Self {
+        let start = Instant::now();
+        Self {
+            launcher: Launcher::new(start),
+            now: start + Duration::from_secs(1),
+            effects: Vec::new(),
+        }
+    }
+
+    pub fn advance(&mut self, millis: u64) {
+        self.now += Duration::from_millis(millis);
+    }
+
+    /// Feeds a single input, returning the effects it produced.
+    pub fn send(&mut self, input: Input) -> Vec<Effect> {
+        let effects = self.launcher.update(input, self.now);
+        self.effects.extend(effects.iter().cloned());
+        effects
+    }
+
+    pub fn script(&mut self, inputs: impl IntoIterator<Item = Input>) {
+        for input in inputs {
+            self.send(input);
+        }
+    }
+
+    /// Delivers a result list as the backend would.
+    pub fn respond(&mut self, results: Vec<SearchResult>) -> Vec<Effect> {
+        self.send(Input::Response(pop_launcher::Response::Update(results)))
+    }
+
+    /// Takes every effect recorded since the last drain.
+    pub fn drain(&mut self) -> Vec<Effect> {
+        std::mem::take(&mut self.effects)
+    }
+
+    /// Takes the backend requests recorded since the last drain.
+    pub fn requests(&mut self) -> Vec<Request> {
+        self.drain()
+            .into_iter()
+            .filter_map(|effect| match effect {
+                Effect::Request(request) => Some(request),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+pub fn count(effects: &[Effect], predicate: impl Fn(&Effect) -> bool) -> usize {
+    effects.iter().filter(|effect| predicate(effect)).count()
+}
+
+pub fn app(id: u32, name: &str) -> SearchResult {
+    SearchResult {
+        id,
+        name: name.to_owned(),
+        description: String::new(),
+        icon: None,
+        category_icon: None,
+        window: None,
+    }
+}
+
+pub fn window(id: u32, app_name: &str, title: &str) -> SearchResult {
+    SearchResult {
+        id,
+        name: app_name.to_owned(),
+        description: title.to_owned(),
+        icon: None,
+        category_icon: None,
+        window: Some((0, id)),
+    }
+}
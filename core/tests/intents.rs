@@ -0,0 +1,97 @@
+mod harness;
+
+use cosmic_launcher_core::intent::PendingIntent;
+use cosmic_launcher_core::{Effect, Input, Request};
+use harness::{app, count, window, Harness};
+
+const ALT_TAB: &str = "\"AltTab\"";
+
+fn windows() -> Vec<pop_launcher::SearchResult> {
+    vec![
+        window(1, "Firefox", "Inbox"),
+        window(2, "Terminal", "~"),
+        window(3, "Files", "Home"),
+    ]
+}
+
+#[test]
+fn alt_tab_pressed_before_the_results_focuses_once_they_arrive() {
+    let mut sim = Harness::new();
+    sim.send(Input::Action(ALT_TAB.into()));
+    sim.send(Input::Action(ALT_TAB.into()));
+    sim.send(Input::AltTab);
+    assert_eq!(
+        sim.launcher.intents(),
+        [
+            PendingIntent::Show,
+            PendingIntent::FocusNext,
+            PendingIntent::FocusNext
+        ]
+    );
+
+    let effects = sim.respond(windows());
+    assert_eq!(
+        count(&effects, |effect| matches!(effect, Effect::ShowSurface)),
+        1
+    );
+    assert_eq!(sim.launcher.focused(), 2);
+    assert!(sim.launcher.intents().is_empty());
+
+    // Later updates do not replay them.
+    sim.respond(windows());
+    assert_eq!(sim.launcher.focused(), 2);
+}
+
+#[test]
+fn alt_released_before_the_results_activates_once_they_arrive() {
+    let mut sim = Harness::new();
+    sim.script([
+        Input::Action(ALT_TAB.into()),
+        Input::AltTab,
+        Input::AltRelease,
+    ]);
+    assert!(sim.launcher.is_active());
+    assert!(sim
+        .requests()
+        .iter()
+        .all(|r| !matches!(r, Request::Activate(_))));
+
+    sim.respond(windows());
+    assert_eq!(sim.requests(), vec![Request::Activate(2)]);
+}
+
+#[test]
+fn hiding_drops_the_intents_so_they_are_not_replayed_later() {
+    let mut sim = Harness::new();
+    sim.script([
+        Input::Action(ALT_TAB.into()),
+        Input::AltTab,
+        Input::AltRelease,
+        Input::Hide,
+    ]);
+    assert!(sim.launcher.intents().is_empty());
+    sim.drain();
+
+    // An update for the old search arriving after the launcher reopened only shows it.
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Files"), app(2, "Firefox")]);
+    assert_eq!(sim.launcher.focused(), 0);
+    assert!(sim
+        .requests()
+        .iter()
+        .all(|r| !matches!(r, Request::Activate(_))));
+}
+
+#[test]
+fn toggling_while_waiting_cancels_the_pending_show() {
+    let mut sim = Harness::new();
+    sim.send(Input::Toggle);
+    sim.send(Input::Toggle);
+    assert!(!sim.launcher.is_waiting_for_result());
+
+    let effects = sim.respond(vec![app(1, "Files")]);
+    assert_eq!(
+        count(&effects, |effect| matches!(effect, Effect::ShowSurface)),
+        0
+    );
+}
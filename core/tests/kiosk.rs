@@ -0,0 +1,108 @@
+mod harness;
+
+use cosmic_launcher_core::{kiosk::Kiosk, Effect, Input, Request};
+use harness::{app, count, Harness};
+
+fn kiosk() -> Harness {
+    let mut sim = Harness::new();
+    sim.launcher.set_kiosk(Kiosk {
+        enabled: true,
+        allowed_apps: vec!["Firefox".into(), "firefox".into()],
+        ..Kiosk::default()
+    });
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Firefox"), app(2, "Terminal")]);
+    sim.drain();
+    sim
+}
+
+fn launches(sim: &mut Harness, path: &str) -> usize {
+    let effects = sim.send(Input::Response(pop_launcher::Response::DesktopEntry {
+        path: path.into(),
+        gpu_preference: pop_launcher::GpuPreference::Default,
+        action_name: None,
+    }));
+    count(&effects, |effect| matches!(effect, Effect::Launch { .. }))
+}
+
+#[test]
+fn only_allowed_apps_are_listed() {
+    let sim = kiosk();
+    let names: Vec<_> = sim.launcher.items().iter().map(|i| &i.name).collect();
+    assert_eq!(names, vec!["Firefox"]);
+}
+
+#[test]
+fn blocked_prefixes_are_not_forwarded() {
+    let mut sim = kiosk();
+    sim.send(Input::InputChanged(":rm -rf".into()));
+    assert!(sim.requests().is_empty());
+    assert!(sim.launcher.items().is_empty());
+
+    sim.send(Input::InputChanged("fire".into()));
+    assert_eq!(sim.requests(), vec![Request::Search("fire".into())]);
+}
+
+#[test]
+fn context_menus_and_completion_are_disabled() {
+    let mut sim = kiosk();
+    sim.script([Input::Context(0), Input::Complete]);
+    assert!(sim.requests().is_empty());
+}
+
+#[test]
+fn only_allowed_desktop_entries_launch() {
+    let mut sim = kiosk();
+    assert_eq!(
+        launches(&mut sim, "/usr/share/applications/firefox.desktop"),
+        1
+    );
+    assert_eq!(
+        launches(&mut sim, "/usr/share/applications/xterm.desktop"),
+        0
+    );
+}
+
+fn resolved(allowed: &str) -> Harness {
+    let mut kiosk = Kiosk {
+        enabled: true,
+        allowed_apps: vec![allowed.into()],
+        ..Kiosk::default()
+    };
+    kiosk.resolve([("org.gnome.Terminal", "Terminal"), ("firefox", "Firefox")]);
+
+    let mut sim = Harness::new();
+    sim.launcher.set_kiosk(kiosk);
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Firefox"), app(2, "Terminal")]);
+    sim.drain();
+    sim
+}
+
+#[test]
+fn apps_allowed_by_name_are_listed_and_launch() {
+    let mut sim = resolved("Terminal");
+    let names: Vec<_> = sim.launcher.items().iter().map(|i| &i.name).collect();
+    assert_eq!(names, vec!["Terminal"]);
+    assert_eq!(
+        launches(
+            &mut sim,
+            "/usr/share/applications/org.gnome.Terminal.desktop"
+        ),
+        1
+    );
+}
+
+#[test]
+fn apps_allowed_by_id_are_listed_and_launch() {
+    let mut sim = resolved("org.gnome.Terminal");
+    let names: Vec<_> = sim.launcher.items().iter().map(|i| &i.name).collect();
+    assert_eq!(names, vec!["Terminal"]);
+    assert_eq!(
+        launches(
+            &mut sim,
+            "/usr/share/applications/org.gnome.Terminal.desktop"
+        ),
+        1
+    );
+}
@@ -0,0 +1,144 @@
+mod harness;
+
+use cosmic_launcher_core::gpu::{self, Gpus};
+use cosmic_launcher_core::menu::{Entry, Menu};
+use cosmic_launcher_core::{Effect, Input, Request};
+use harness::{app, Harness};
+use pop_launcher::{ContextOption, GpuPreference};
+
+fn options(names: &[&str]) -> Vec<ContextOption> {
+    names
+        .iter()
+        .zip(0..)
+        .map(|(name, id)| ContextOption {
+            id,
+            name: (*name).into(),
+        })
+        .collect()
+}
+
+fn names(entries: &[Entry]) -> Vec<&str> {
+    entries.iter().map(Entry::name).collect()
+}
+
+fn with_menu() -> Harness {
+    let mut sim = Harness::new();
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Files")]);
+    sim.send(Input::Response(pop_launcher::Response::Context {
+        id: 1,
+        options: options(&[
+            "New Window",
+            "Open with ▸ Firefox",
+            "Open with ▸ Files",
+            "Remove",
+        ]),
+    }));
+    sim.drain();
+    sim
+}
+
+#[test]
+fn options_are_grouped_into_submenus_by_name() {
+    let menu = Menu::new(
+        1,
+        options(&["Open with ▸ Firefox", "New Window", "Open with ▸ Files"]),
+    );
+
+    assert_eq!(names(&menu.entries), ["Open with", "New Window"]);
+    assert_eq!(menu.levels().len(), 1);
+    assert_eq!(menu.option(2), Some("Files"));
+}
+
+#[test]
+fn arrow_keys_open_submenus_and_activate_their_options() {
+    let mut sim = with_menu();
+    sim.script([Input::FocusNext, Input::FocusNext]);
+
+    // The focused submenu is shown next to the top level.
+    let menu = sim.launcher.menu().unwrap();
+    assert_eq!(menu.focus(), [1]);
+    assert_eq!(names(menu.levels()[1]), ["Firefox", "Files"]);
+    // The results keep their focus while the menu takes the arrow keys.
+    assert_eq!(sim.launcher.focused(), 0);
+
+    sim.script([Input::ExpandSubmenu, Input::FocusNext]);
+    assert_eq!(sim.launcher.menu().unwrap().focus(), [1, 1]);
+    sim.send(Input::CollapseSubmenu);
+    assert_eq!(sim.launcher.menu().unwrap().focus(), [1]);
+
+    sim.script([Input::Activate(None), Input::FocusPrevious]);
+    sim.send(Input::Activate(None));
+    assert!(sim.launcher.menu().is_none());
+    assert_eq!(sim.requests(), [Request::ActivateContext(1, 2)]);
+}
+
+#[test]
+fn hovering_focuses_entries_in_range_only() {
+    let mut sim = with_menu();
+    sim.send(Input::MenuFocus(vec![1, 0]));
+    assert_eq!(sim.launcher.menu().unwrap().focus(), [1, 0]);
+
+    // Options have no entries of their own.
+    sim.send(Input::MenuFocus(vec![0, 0]));
+    sim.send(Input::MenuFocus(vec![7]));
+    assert_eq!(sim.launcher.menu().unwrap().focus(), [1, 0]);
+}
+
+#[test]
+fn gpus_replace_the_backend_options_with_a_submenu() {
+    let mut sim = Harness::new();
+    sim.launcher.set_gpus(Gpus {
+        submenu: "Launch using".into(),
+        names: vec!["iGPU".into(), "dGPU".into()],
+    });
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Steam")]);
+    sim.send(Input::Response(pop_launcher::Response::Context {
+        id: 1,
+        options: options(&["New Window", "Launch Using Discrete Graphics Card"]),
+    }));
+
+    let menu = sim.launcher.menu().unwrap();
+    assert_eq!(names(&menu.entries), ["New Window", "Launch using"]);
+    assert_eq!(menu.option(gpu::OPTION_BASE + 1), Some("dGPU"));
+    sim.drain();
+
+    sim.script([Input::FocusNext, Input::FocusNext, Input::ExpandSubmenu]);
+    sim.script([Input::FocusNext, Input::Activate(None)]);
+    assert_eq!(sim.requests(), [Request::Activate(1)]);
+
+    let effects = sim.send(Input::Response(pop_launcher::Response::DesktopEntry {
+        path: "steam.desktop".into(),
+        gpu_preference: GpuPreference::Default,
+        action_name: None,
+    }));
+    assert!(effects.iter().any(|effect| matches!(
+        effect,
+        Effect::Launch {
+            gpu_preference: GpuPreference::SpecificIdx(1),
+            ..
+        }
+    )));
+}
+
+#[test]
+fn options_of_a_result_skip_stale_pins_with_its_id() {
+    let mut sim = Harness::new();
+    sim.launcher.set_gpus(Gpus {
+        submenu: "Launch using".into(),
+        names: vec!["iGPU".into(), "dGPU".into()],
+    });
+    sim.send(Input::Toggle);
+    sim.send(Input::InputChanged("fi".into()));
+    sim.respond(vec![app(1, "Firefox")]);
+    sim.send(Input::Pin);
+    sim.send(Input::InputChanged("st".into()));
+    sim.respond(vec![app(1, "Steam")]);
+    sim.drain();
+
+    // The stale pin of Firefox comes first with the same ID as Steam.
+    sim.send(Input::MenuOption(1, gpu::OPTION_BASE + 1));
+    assert_eq!(sim.requests(), [Request::Activate(1)]);
+    assert_eq!(sim.launcher.input(), "st");
+}
@@ -0,0 +1,60 @@
+mod harness;
+
+use cosmic_launcher_core::{pipeline, Effect, Input, Request};
+use harness::Harness;
+
+#[test]
+fn parses_stages_and_builds_target_query() {
+    let pipeline = pipeline::parse("file notes.md | open-with  gedit").unwrap();
+    assert_eq!(pipeline.sources.len(), 1);
+    assert_eq!(pipeline.sources[0].name, "file");
+    assert_eq!(pipeline.sources[0].arg, "notes.md");
+    assert_eq!(
+        pipeline.query("/home/me/notes.md"),
+        "open-with gedit /home/me/notes.md"
+    );
+
+    assert!(pipeline::parse("firefox").is_none());
+    assert!(pipeline::parse("clip |").is_none());
+}
+
+#[test]
+fn queries_without_a_source_are_not_pipelines() {
+    assert!(pipeline::parse(": a | b").is_none());
+    assert!(pipeline::parse("= 5|3").is_none());
+    assert!(pipeline::parse("clip | words | ddg").is_none());
+
+    let mut sim = Harness::new();
+    sim.send(Input::Toggle);
+    sim.drain();
+    sim.send(Input::InputChanged(": a | b".into()));
+    assert_eq!(sim.requests(), vec![Request::Search(": a | b".into())]);
+}
+
+#[test]
+fn pipeline_searches_once_resolved() {
+    let mut sim = Harness::new();
+    sim.send(Input::Toggle);
+    sim.drain();
+
+    let effects = sim.send(Input::InputChanged("clip | ddg".into()));
+    assert!(matches!(effects.as_slice(), [Effect::ResolvePipe(_)]));
+    sim.drain();
+
+    // Payloads for a query which was since edited are dropped.
+    sim.send(Input::PipeResolved {
+        query: "clip".into(),
+        payload: "stale".into(),
+    });
+    assert!(sim.requests().is_empty());
+
+    sim.send(Input::PipeResolved {
+        query: "clip | ddg".into(),
+        payload: "ferris\n".into(),
+    });
+    assert_eq!(sim.requests(), vec![Request::Search("ddg ferris".into())]);
+    assert_eq!(sim.launcher.payload(), Some("ferris\n"));
+
+    sim.send(Input::InputChanged("files".into()));
+    assert!(sim.launcher.payload().is_none());
+}
@@ -0,0 +1,103 @@
+mod harness;
+
+use cosmic_launcher_core::ranking::{Ranking, Weights};
+use harness::{app, window};
+
+fn ids(list: &[pop_launcher::SearchResult]) -> Vec<u32> {
+    list.iter().map(|item| item.id).collect()
+}
+
+#[test]
+fn default_ranking_puts_windows_first() {
+    let mut list = vec![
+        app(1, "Files"),
+        window(2, "Firefox", "Inbox"),
+        app(3, "Terminal"),
+    ];
+    Ranking::default().rank(&mut list, "");
+    assert_eq!(ids(&list), vec![2, 1, 3]);
+}
+
+#[test]
+fn favorites_outrank_windows() {
+    let ranking = Ranking {
+        favorites: vec!["Terminal".into()],
+        ..Ranking::default()
+    };
+    let mut list = vec![
+        app(1, "Files"),
+        window(2, "Firefox", "Inbox"),
+        app(3, "Terminal"),
+    ];
+    ranking.rank(&mut list, "");
+    assert_eq!(ids(&list), vec![3, 2, 1]);
+}
+
+#[test]
+fn zero_weights_keep_backend_order() {
+    let ranking = Ranking {
+        weights: Weights {
+            window: 0,
+            favorite: 0,
+            frequent: 0,
+        },
+        ..Ranking::default()
+    };
+    let mut list = vec![app(1, "Files"), window(2, "Firefox", "Inbox")];
+    ranking.rank(&mut list, "");
+    assert_eq!(ids(&list), vec![1, 2]);
+}
+
+#[test]
+fn searches_keep_relevance_order_unless_windows_first() {
+    let list = vec![app(1, "Firefox"), window(2, "Firefox", "Inbox")];
+
+    let mut relevance = list.clone();
+    Ranking::default().rank(&mut relevance, "firefox");
+    assert_eq!(ids(&relevance), vec![1, 2]);
+
+    let ranking = Ranking {
+        windows_first: true,
+        ..Ranking::default()
+    };
+    let mut windows_first = list;
+    ranking.rank(&mut windows_first, "firefox");
+    assert_eq!(ids(&windows_first), vec![2, 1]);
+}
+
+#[test]
+fn frequent_results_rank_ahead_of_windows() {
+    let ranking = Ranking {
+        frequent: [("Terminal".to_string(), 2)].into_iter().collect(),
+        ..Ranking::default()
+    };
+    let mut list = vec![
+        app(1, "Files"),
+        window(2, "Firefox", "Inbox"),
+        app(3, "Terminal"),
+    ];
+    ranking.rank(&mut list, "");
+    assert_eq!(ids(&list), vec![3, 2, 1]);
+}
+
+#[test]
+fn quotas_limit_the_results_of_their_source() {
+    let file = |id, name| pop_launcher::SearchResult {
+        category_icon: Some(pop_launcher::IconSource::Name("system-file-manager".into())),
+        ..app(id, name)
+    };
+    let ranking = Ranking {
+        quotas: [("system-file-manager".to_string(), 2)]
+            .into_iter()
+            .collect(),
+        ..Ranking::default()
+    };
+    let mut list = vec![
+        file(1, "notes.txt"),
+        file(2, "notes.md"),
+        file(3, "notes.pdf"),
+        app(4, "Notes"),
+    ];
+    ranking.rank(&mut list, "notes");
+    assert_eq!(ids(&list), vec![1, 2, 4]);
+}
@@ -0,0 +1,50 @@
+mod harness;
+
+use std::collections::BTreeMap;
+
+use cosmic_launcher_core::routing::Routes;
+use cosmic_launcher_core::{Input, Request};
+use harness::Harness;
+
+fn routes(pairs: &[(&str, &str)]) -> Routes {
+    Routes(
+        pairs
+            .iter()
+            .map(|(from, to)| ((*from).to_owned(), (*to).to_owned()))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+#[test]
+fn longest_prefix_is_rewritten() {
+    let routes = routes(&[("g ", "google "), ("gh ", "github ")]);
+    assert_eq!(routes.apply("g rust"), "google rust");
+    assert_eq!(routes.apply("gh launcher"), "github launcher");
+    assert_eq!(routes.apply("gimp"), "gimp");
+}
+
+#[test]
+fn queries_are_routed_before_sending() {
+    let mut sim = Harness::new();
+    sim.launcher.set_routes(routes(&[("f ", "~/")]));
+    sim.send(Input::Toggle);
+    sim.drain();
+
+    sim.send(Input::InputChanged("f notes".into()));
+    assert_eq!(sim.requests(), vec![Request::Search("~/notes".into())]);
+    // The search field keeps what the user typed.
+    assert_eq!(sim.launcher.input(), "f notes");
+}
+
+#[test]
+fn overlapping_prefixes_conflict() {
+    let routes = routes(&[("f", "~/"), ("fi ", "find ")]);
+    let claimed = vec![("find ".to_owned(), "find".to_owned())];
+
+    let conflicts = routes.conflicts(&claimed);
+    let pairs = conflicts
+        .iter()
+        .map(|c| (c.prefix.as_str(), c.other.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(pairs, vec![("f", "fi "), ("f", "find ")]);
+}
@@ -0,0 +1,852 @@
+mod harness;
+
+use cosmic_launcher_core::scope::Scope;
+use cosmic_launcher_core::vi::Mode;
+use cosmic_launcher_core::visibility::{Debounce, Visibility};
+use cosmic_launcher_core::{Effect, Input, Request, LOCAL_ID_BASE};
+use harness::{app, count, window, Harness};
+
+fn shows(effects: &[Effect]) -> usize {
+    count(effects, |effect| matches!(effect, Effect::ShowSurface))
+}
+
+fn hides(effects: &[Effect]) -> usize {
+    count(effects, |effect| matches!(effect, Effect::HideSurface))
+}
+
+#[test]
+fn toggle_maps_surface_after_first_update() {
+    let mut sim = Harness::new();
+
+    assert_eq!(shows(&sim.send(Input::Toggle)), 0);
+    assert!(sim.launcher.is_active());
+    assert!(sim.launcher.is_waiting_for_result());
+
+    assert_eq!(shows(&sim.respond(vec![app(1, "Files")])), 1);
+    assert!(!sim.launcher.is_waiting_for_result());
+
+    // Later updates must not map the surface a second time.
+    assert_eq!(shows(&sim.respond(vec![app(2, "Firefox")])), 0);
+}
+
+#[test]
+fn toggle_while_waiting_hides() {
+    let mut sim = Harness::new();
+    sim.send(Input::Toggle);
+
+    let effects = sim.send(Input::Toggle);
+    assert_eq!(hides(&effects), 1);
+    assert!(!sim.launcher.is_active());
+
+    // A stale update arriving after the hide must not map the surface.
+    assert_eq!(shows(&sim.respond(vec![app(1, "Files")])), 0);
+}
+
+#[test]
+fn reopen_is_ignored_right_after_unfocus() {
+    let mut sim = Harness::new();
+    sim.script([Input::Toggle]);
+    sim.respond(vec![app(1, "Files")]);
+
+    sim.send(Input::Unfocused);
+    sim.advance(50);
+    sim.send(Input::Toggle);
+    assert!(!sim.launcher.is_active());
+
+    sim.advance(100);
+    sim.send(Input::Toggle);
+    assert!(sim.launcher.is_active());
+}
+
+#[test]
+fn focus_coming_back_within_the_unfocus_delay_keeps_the_launcher_open() {
+    let mut sim = Harness::new();
+    sim.launcher.set_debounce(Debounce {
+        reopen_guard: 0,
+        unfocus_delay: 200,
+    });
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Files")]);
+
+    let effects = sim.send(Input::Unfocused);
+    assert_eq!(
+        count(&effects, |effect| matches!(effect, Effect::AwaitHide(_))),
+        1
+    );
+    sim.advance(100);
+    sim.send(Input::Focused);
+    sim.advance(100);
+    assert_eq!(hides(&sim.send(Input::CheckHide)), 0);
+    assert_eq!(sim.launcher.visibility(), Visibility::Shown);
+
+    sim.send(Input::Unfocused);
+    sim.advance(200);
+    assert_eq!(hides(&sim.send(Input::CheckHide)), 1);
+
+    // Without a reopen guard, the launcher opens again at once.
+    sim.send(Input::Toggle);
+    assert!(sim.launcher.is_active());
+}
+
+#[test]
+fn toggle_while_waiting_to_hide_hides_at_once() {
+    let mut sim = Harness::new();
+    sim.launcher.set_debounce(Debounce {
+        reopen_guard: 100,
+        unfocus_delay: 200,
+    });
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Files")]);
+
+    // The click on the panel button takes the focus, then toggles the launcher.
+    sim.send(Input::Unfocused);
+    assert_eq!(hides(&sim.send(Input::Toggle)), 1);
+    assert!(!sim.launcher.is_active());
+
+    // The pending check no longer applies.
+    sim.advance(200);
+    assert_eq!(hides(&sim.send(Input::CheckHide)), 0);
+    assert!(!sim.launcher.is_active());
+}
+
+#[test]
+fn locking_the_session_hides_the_launcher_until_unlocked() {
+    let mut sim = Harness::new();
+    sim.script([Input::Toggle, Input::InputChanged("pw".into())]);
+    sim.respond(vec![app(1, "Password")]);
+
+    assert_eq!(hides(&sim.send(Input::Locked(true))), 1);
+    assert_eq!(sim.launcher.input(), "");
+
+    sim.script([Input::Toggle, Input::Action("\"AltTab\"".into())]);
+    assert!(!sim.launcher.is_active());
+
+    sim.send(Input::Locked(false));
+    sim.send(Input::Toggle);
+    assert!(sim.launcher.is_active());
+}
+
+#[test]
+fn hide_clears_query_and_restarts_backend() {
+    let mut sim = Harness::new();
+    sim.script([Input::Toggle, Input::InputChanged("fir".into())]);
+    sim.respond(vec![app(1, "Firefox")]);
+    sim.drain();
+
+    sim.send(Input::Hide);
+    assert_eq!(sim.launcher.input(), "");
+    assert_eq!(
+        sim.requests(),
+        vec![Request::Close, Request::Search(String::new())]
+    );
+}
+
+#[test]
+fn alt_tab_action_waits_then_cycles() {
+    let mut sim = Harness::new();
+    sim.send(Input::Action("\"AltTab\"".into()));
+    assert!(sim.launcher.is_alt_tab());
+    assert!(sim.launcher.is_waiting_for_result());
+
+    let effects = sim.respond(vec![
+        app(3, "Files"),
+        window(1, "Firefox", "Inbox"),
+        window(2, "Terminal", "~"),
+    ]);
+    assert_eq!(shows(&effects), 1);
+    // Windows are ranked ahead of applications.
+    assert_eq!(sim.launcher.items()[0].id, 1);
+    assert_eq!(sim.launcher.focused(), 0);
+
+    sim.send(Input::Action("\"AltTab\"".into()));
+    assert_eq!(sim.launcher.focused(), 1);
+
+    sim.drain();
+    sim.send(Input::AltRelease);
+    assert_eq!(sim.requests(), vec![Request::Activate(2)]);
+}
+
+#[test]
+fn alt_tab_without_windows_hides() {
+    let mut sim = Harness::new();
+    sim.send(Input::Action("\"AltTab\"".into()));
+
+    let effects = sim.respond(Vec::new());
+    assert_eq!(shows(&effects), 0);
+    assert_eq!(hides(&effects), 1);
+    assert!(!sim.launcher.is_alt_tab());
+}
+
+#[test]
+fn alt_release_outside_alt_tab_does_nothing() {
+    let mut sim = Harness::new();
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Files")]);
+    sim.drain();
+
+    sim.send(Input::AltRelease);
+    assert!(sim.requests().is_empty());
+}
+
+#[test]
+fn hide_closes_context_menu_first() {
+    let mut sim = Harness::new();
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Files")]);
+    sim.send(Input::Response(pop_launcher::Response::Context {
+        id: 1,
+        options: vec![pop_launcher::ContextOption {
+            id: 0,
+            name: "New Window".into(),
+        }],
+    }));
+    assert!(sim.launcher.menu().is_some());
+
+    let effects = sim.send(Input::Hide);
+    assert_eq!(
+        count(&effects, |e| matches!(e, Effect::CloseContextMenu)),
+        1
+    );
+    assert_eq!(hides(&effects), 0);
+    assert!(sim.launcher.is_active());
+
+    assert_eq!(hides(&sim.send(Input::Hide)), 1);
+}
+
+#[test]
+fn complete_is_ignored_during_alt_tab() {
+    let mut sim = Harness::new();
+    sim.send(Input::Action("\"AltTab\"".into()));
+    sim.respond(vec![window(1, "Firefox", "Inbox")]);
+    sim.drain();
+
+    sim.send(Input::Complete);
+    assert!(sim.requests().is_empty());
+}
+
+#[test]
+fn destructive_actions_wait_for_confirmation() {
+    let mut sim = Harness::new();
+    sim.launcher
+        .set_destructive_actions(vec!["shut down".into()]);
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Files"), app(2, "Shut Down")]);
+    sim.drain();
+
+    sim.send(Input::Activate(Some(1)));
+    assert!(sim.requests().is_empty());
+    assert_eq!(sim.launcher.confirmation().map(|c| c.row), Some(1));
+
+    // Escape dismisses the prompt without hiding the launcher.
+    let effects = sim.send(Input::Hide);
+    assert_eq!(hides(&effects), 0);
+    assert!(sim.launcher.confirmation().is_none());
+
+    sim.script([Input::Activate(Some(1)), Input::Activate(None)]);
+    assert_eq!(sim.requests(), vec![Request::Activate(2)]);
+}
+
+#[test]
+fn destructive_local_results_wait_for_confirmation() {
+    let mut sim = Harness::new();
+    sim.launcher
+        .set_destructive_actions(vec!["Empty Trash".into()]);
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Files")]);
+    sim.send(Input::LocalResults(vec![app(
+        LOCAL_ID_BASE,
+        "Empty Trash (3 items, 12 KiB)",
+    )]));
+    sim.drain();
+
+    let effects = sim.send(Input::Activate(Some(0)));
+    assert!(!effects
+        .iter()
+        .any(|effect| matches!(effect, Effect::ActivateLocal(_))));
+    assert_eq!(sim.launcher.confirmation().map(|c| c.row), Some(0));
+
+    let effects = sim.send(Input::Confirm);
+    assert_eq!(
+        count(
+            &effects,
+            |effect| matches!(effect, Effect::ActivateLocal(id) if *id == LOCAL_ID_BASE)
+        ),
+        1
+    );
+    assert!(sim.requests().is_empty());
+}
+
+#[test]
+fn typing_cancels_confirmation() {
+    let mut sim = Harness::new();
+    sim.launcher.set_destructive_actions(vec!["Restart".into()]);
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Restart")]);
+
+    sim.send(Input::Activate(None));
+    sim.send(Input::InputChanged("r".into()));
+    assert!(sim.launcher.confirmation().is_none());
+}
+
+#[test]
+fn an_update_that_reuses_the_id_cancels_confirmation() {
+    let mut sim = Harness::new();
+    sim.launcher.set_destructive_actions(vec!["Restart".into()]);
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Restart")]);
+
+    sim.send(Input::Activate(None));
+    assert!(sim.launcher.confirmation().is_some());
+
+    // A late update puts another result under the same ID.
+    sim.respond(vec![app(1, "Files")]);
+    assert!(sim.launcher.confirmation().is_none());
+
+    sim.drain();
+    sim.send(Input::Confirm);
+    assert!(sim.requests().is_empty());
+}
+
+#[test]
+fn local_results_are_activated_by_the_ui() {
+    let mut sim = Harness::new();
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Files")]);
+    sim.send(Input::LocalResults(vec![app(
+        LOCAL_ID_BASE,
+        "Grep project",
+    )]));
+    assert_eq!(sim.launcher.items().len(), 2);
+
+    // A later backend update keeps the local results.
+    sim.respond(vec![app(2, "Firefox")]);
+    assert_eq!(sim.launcher.items()[0].id, LOCAL_ID_BASE);
+    sim.drain();
+
+    let effects = sim.send(Input::Activate(Some(0)));
+    assert_eq!(
+        count(
+            &effects,
+            |effect| matches!(effect, Effect::ActivateLocal(id) if *id == LOCAL_ID_BASE)
+        ),
+        1
+    );
+    assert!(sim.requests().is_empty());
+}
+
+#[test]
+fn local_context_options_are_handled_by_the_ui() {
+    let mut sim = Harness::new();
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Files")]);
+    sim.send(Input::LocalResults(vec![app(LOCAL_ID_BASE, "Ada")]));
+    sim.drain();
+
+    let effects = sim.send(Input::Context(0));
+    assert_eq!(
+        count(
+            &effects,
+            |effect| matches!(effect, Effect::LocalContext(id) if *id == LOCAL_ID_BASE)
+        ),
+        1
+    );
+
+    let effects = sim.send(Input::LocalContext {
+        id: LOCAL_ID_BASE,
+        options: vec![pop_launcher::ContextOption {
+            id: 0,
+            name: "Copy ada@example.com".into(),
+        }],
+    });
+    assert_eq!(
+        count(&effects, |effect| matches!(effect, Effect::OpenContextMenu)),
+        1
+    );
+
+    let effects = sim.send(Input::MenuOption(LOCAL_ID_BASE, 0));
+    assert_eq!(
+        count(&effects, |effect| matches!(
+            effect,
+            Effect::ActivateLocalContext { id, context: 0 } if *id == LOCAL_ID_BASE
+        )),
+        1
+    );
+    assert_eq!(
+        count(&effects, |effect| matches!(
+            effect,
+            Effect::CloseContextMenu
+        )),
+        1
+    );
+    assert!(sim.requests().is_empty());
+}
+
+#[test]
+fn footer_results_stay_below_the_results_of_an_empty_query() {
+    let mut sim = Harness::new();
+    sim.send(Input::Toggle);
+    sim.send(Input::FooterResults(vec![app(
+        LOCAL_ID_BASE,
+        "Now playing",
+    )]));
+    sim.respond((1..=12).map(|id| app(id, "Window")).collect());
+
+    // The footer keeps its place however many results come before it.
+    let items = sim.launcher.items();
+    assert_eq!(items.len(), cosmic_launcher_core::MAX_RESULTS);
+    assert_eq!(items.last().map(|item| item.id), Some(LOCAL_ID_BASE));
+
+    sim.send(Input::InputChanged("fi".into()));
+    sim.respond(vec![app(1, "Files")]);
+    assert_eq!(sim.launcher.items().len(), 1);
+
+    sim.send(Input::InputChanged(String::new()));
+    sim.respond(vec![app(1, "Files")]);
+    assert_eq!(sim.launcher.items()[1].id, LOCAL_ID_BASE);
+}
+
+#[test]
+fn fallbacks_stay_last_and_are_focused_only_when_nothing_matches() {
+    let mut sim = Harness::new();
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Files")]);
+    sim.send(Input::FallbackResults(vec![app(
+        LOCAL_ID_BASE,
+        "Search the web",
+    )]));
+    assert_eq!(sim.launcher.items().len(), 1);
+
+    sim.send(Input::InputChanged("zz".into()));
+    sim.respond(Vec::new());
+    assert_eq!(sim.launcher.items()[0].id, LOCAL_ID_BASE);
+    assert_eq!(sim.launcher.focused(), 0);
+
+    // Results arriving take the focus from the fallback, which moves below them.
+    sim.send(Input::InputChanged("fi".into()));
+    sim.respond(vec![app(1, "Files")]);
+    assert_eq!(sim.launcher.items()[1].id, LOCAL_ID_BASE);
+    assert_eq!(sim.launcher.focused(), 0);
+    sim.drain();
+
+    let effects = sim.send(Input::Activate(Some(1)));
+    assert_eq!(
+        count(
+            &effects,
+            |effect| matches!(effect, Effect::ActivateLocal(id) if *id == LOCAL_ID_BASE)
+        ),
+        1
+    );
+    assert_eq!(
+        count(&effects, |effect| matches!(effect, Effect::Used(_))),
+        0
+    );
+}
+
+#[test]
+fn activations_report_the_used_result_once_confirmed() {
+    let mut sim = Harness::new();
+    sim.launcher.set_destructive_actions(vec!["Restart".into()]);
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Files"), app(2, "Restart")]);
+    let used = |effects: &[Effect], name: &str| {
+        count(
+            effects,
+            |effect| matches!(effect, Effect::Used(used) if used == name),
+        )
+    };
+
+    let effects = sim.send(Input::Activate(Some(0)));
+    assert_eq!(used(&effects, "Files"), 1);
+    // The backend answers the activation before the next one is taken.
+    sim.respond(vec![app(1, "Files"), app(2, "Restart")]);
+
+    let effects = sim.send(Input::Activate(Some(1)));
+    assert_eq!(used(&effects, "Restart"), 0);
+    let effects = sim.send(Input::Confirm);
+    assert_eq!(used(&effects, "Restart"), 1);
+}
+
+#[test]
+fn paging_and_hovering_move_the_focus_within_the_results() {
+    let mut sim = Harness::new();
+    sim.send(Input::Toggle);
+    sim.respond((1..=8).map(|id| app(id, "Window")).collect());
+
+    sim.send(Input::FocusPageNext);
+    assert_eq!(sim.launcher.focused(), 5);
+    sim.send(Input::FocusPageNext);
+    assert_eq!(sim.launcher.focused(), 7);
+    sim.send(Input::FocusPagePrevious);
+    assert_eq!(sim.launcher.focused(), 2);
+    sim.send(Input::FocusPagePrevious);
+    assert_eq!(sim.launcher.focused(), 0);
+
+    sim.send(Input::FocusLast);
+    assert_eq!(sim.launcher.focused(), 7);
+    sim.send(Input::FocusFirst);
+    assert_eq!(sim.launcher.focused(), 0);
+
+    sim.send(Input::Focus(3));
+    assert_eq!(sim.launcher.focused(), 3);
+    sim.send(Input::Focus(8));
+    assert_eq!(sim.launcher.focused(), 3);
+}
+
+#[test]
+fn shift_tab_undoes_completions_before_moving_the_focus_back() {
+    let mut sim = Harness::new();
+    sim.send(Input::Toggle);
+    sim.send(Input::InputChanged("fi".into()));
+    sim.respond(vec![app(1, "Files"), app(2, "Firefox")]);
+    sim.drain();
+
+    sim.send(Input::Complete);
+    assert_eq!(sim.requests(), vec![Request::Complete(1)]);
+    sim.send(Input::Response(pop_launcher::Response::Fill(
+        "Files".into(),
+    )));
+    sim.respond(vec![app(1, "Files")]);
+    sim.drain();
+
+    sim.send(Input::CompletePrevious);
+    assert_eq!(sim.launcher.input(), "fi");
+    assert_eq!(sim.requests(), vec![Request::Search("fi".into())]);
+    sim.respond(vec![app(1, "Files"), app(2, "Firefox")]);
+
+    // With no completion left, it moves the focus back like ArrowUp.
+    sim.send(Input::CompletePrevious);
+    assert_eq!(sim.launcher.focused(), 1);
+    assert_eq!(sim.launcher.input(), "fi");
+}
+
+#[test]
+fn focus_follows_its_result_across_updates() {
+    let mut sim = Harness::new();
+    sim.send(Input::Toggle);
+    sim.send(Input::InputChanged("f".into()));
+    sim.respond(vec![app(1, "Files"), app(2, "Firefox"), app(3, "Fonts")]);
+    sim.send(Input::FocusNext);
+    assert_eq!(sim.launcher.focused(), 1);
+
+    // The backend numbers its results afresh, and Firefox moved down a row.
+    sim.send(Input::InputChanged("fi".into()));
+    sim.respond(vec![
+        app(1, "Files"),
+        app(2, "File Roller"),
+        app(3, "Firefox"),
+    ]);
+    assert_eq!(sim.launcher.focused(), 2);
+    assert_eq!(sim.launcher.items()[2].name, "Firefox");
+
+    // Once it is gone, the focus goes back to the top.
+    sim.send(Input::InputChanged("fil".into()));
+    sim.respond(vec![app(1, "Files"), app(2, "File Roller")]);
+    assert_eq!(sim.launcher.focused(), 0);
+
+    // Reopening the launcher starts from the top as well.
+    sim.send(Input::FocusNext);
+    sim.send(Input::Hide);
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Terminal"), app(2, "File Roller")]);
+    assert_eq!(sim.launcher.focused(), 0);
+}
+
+#[test]
+fn activations_stay_pending_until_the_backend_answers() {
+    let mut sim = Harness::new();
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Files"), app(2, "Firefox")]);
+    sim.drain();
+
+    let effects = sim.send(Input::Activate(None));
+    assert_eq!(
+        count(&effects, |effect| matches!(effect, Effect::AwaitActivation)),
+        1
+    );
+    assert_eq!(
+        sim.launcher.activation().map(|a| (a.row, a.id)),
+        Some((0, 1))
+    );
+
+    // Pressing Enter again does not activate anything until the backend answers.
+    sim.send(Input::Activate(None));
+    sim.send(Input::Activate(Some(1)));
+    assert_eq!(sim.requests(), vec![Request::Activate(1)]);
+
+    // Checks before the timeout leave it pending.
+    sim.advance(500);
+    sim.send(Input::CheckActivation);
+    assert!(!sim.launcher.activation().unwrap().timed_out);
+
+    // Without an answer within a second, the row shows an error and can be retried.
+    sim.advance(500);
+    sim.send(Input::CheckActivation);
+    assert!(sim.launcher.activation().unwrap().timed_out);
+    sim.send(Input::Activate(None));
+    assert_eq!(sim.requests(), vec![Request::Activate(1)]);
+
+    // An entry to launch keeps the row pending without timing out, until the launch hides
+    // the launcher.
+    sim.send(Input::Response(pop_launcher::Response::DesktopEntry {
+        path: "/usr/share/applications/org.gnome.Nautilus.desktop".into(),
+        gpu_preference: pop_launcher::GpuPreference::Default,
+        action_name: None,
+    }));
+    sim.advance(2000);
+    sim.send(Input::CheckActivation);
+    assert!(!sim.launcher.activation().unwrap().timed_out);
+    sim.send(Input::Hide);
+    assert!(sim.launcher.activation().is_none());
+}
+
+#[test]
+fn digits_typed_into_the_empty_field_activate_their_row() {
+    let mut sim = Harness::new();
+    sim.launcher.set_number_keys(true);
+    sim.send(Input::Toggle);
+    sim.respond(vec![
+        window(1, "Files", "Home"),
+        window(2, "Firefox", "News"),
+    ]);
+    sim.drain();
+
+    sim.send(Input::InputChanged("2".into()));
+    assert_eq!(sim.launcher.input(), "");
+    assert_eq!(sim.requests(), [Request::Activate(2)]);
+    sim.respond(vec![
+        window(1, "Files", "Home"),
+        window(2, "Firefox", "News"),
+    ]);
+    sim.drain();
+
+    // Without a result in its row, or after anything else, a digit is typed.
+    sim.send(Input::InputChanged("3".into()));
+    assert_eq!(sim.launcher.input(), "3");
+    sim.script([
+        Input::InputChanged(String::new()),
+        Input::InputChanged("f".into()),
+    ]);
+    sim.send(Input::InputChanged("f1".into()));
+    assert_eq!(sim.launcher.input(), "f1");
+    assert_eq!(
+        sim.requests(),
+        [
+            Request::Search("3".into()),
+            Request::Search(String::new()),
+            Request::Search("f".into()),
+            Request::Search("f1".into()),
+        ]
+    );
+}
+
+#[test]
+fn normal_mode_takes_keys_as_commands() {
+    let mut sim = Harness::new();
+    sim.launcher.set_vi_mode(Some(Mode::Insert));
+    sim.send(Input::Toggle);
+    let results = vec![app(1, "Files"), app(2, "Firefox"), app(3, "Terminal")];
+    sim.respond(results.clone());
+    sim.drain();
+
+    sim.send(Input::Escape);
+    assert_eq!(sim.launcher.mode(), Mode::Normal);
+    assert!(sim.launcher.is_active());
+
+    sim.script([
+        Input::InputChanged("j".into()),
+        Input::InputChanged("G".into()),
+    ]);
+    assert_eq!(sim.launcher.focused(), 2);
+    sim.script([
+        Input::InputChanged("g".into()),
+        Input::InputChanged("g".into()),
+        Input::InputChanged("j".into()),
+    ]);
+    assert_eq!(sim.launcher.focused(), 1);
+    assert_eq!(sim.launcher.input(), "");
+    assert!(sim.requests().is_empty());
+
+    let effects = sim.send(Input::InputChanged("y".into()));
+    assert_eq!(
+        count(
+            &effects,
+            |e| matches!(e, Effect::Copy(name) if name == "Firefox")
+        ),
+        1
+    );
+
+    // dd hides the result for good, leaving the focus in its row.
+    sim.script([
+        Input::InputChanged("d".into()),
+        Input::InputChanged("d".into()),
+    ]);
+    let names = |sim: &Harness| {
+        sim.launcher
+            .items()
+            .iter()
+            .map(|item| item.name.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(&sim), ["Files", "Terminal"]);
+    assert_eq!(sim.launcher.focused(), 1);
+    assert_eq!(
+        count(&sim.drain(), |e| {
+            matches!(e, Effect::HideResult(name) if name == "Firefox")
+        }),
+        1
+    );
+    sim.respond(results);
+    assert_eq!(names(&sim), ["Files", "Terminal"]);
+
+    sim.script([
+        Input::InputChanged("i".into()),
+        Input::InputChanged("f".into()),
+    ]);
+    assert_eq!(sim.launcher.mode(), Mode::Insert);
+    assert_eq!(sim.launcher.input(), "f");
+
+    // Esc in normal mode hides, and the launcher opens in insert mode again.
+    sim.script([Input::Escape, Input::Escape]);
+    assert!(!sim.launcher.is_active());
+    assert_eq!(sim.launcher.mode(), Mode::Insert);
+}
+
+#[test]
+fn scopes_narrow_the_results_until_hidden() {
+    let mut sim = Harness::new();
+    let file = |id, name| pop_launcher::SearchResult {
+        category_icon: Some(pop_launcher::IconSource::Name("system-file-manager".into())),
+        ..app(id, name)
+    };
+    sim.launcher.set_scopes(
+        vec![
+            Scope {
+                name: "Files".into(),
+                plugins: vec!["system-file-manager".into()],
+                ..Scope::default()
+            },
+            Scope {
+                name: "Windows".into(),
+                windows: true,
+                ..Scope::default()
+            },
+        ],
+        false,
+    );
+    sim.send(Input::Toggle);
+    sim.send(Input::InputChanged("notes".into()));
+    let results = vec![
+        app(1, "Notes"),
+        file(2, "notes.txt"),
+        window(3, "Editor", "notes.txt"),
+    ];
+    sim.respond(results.clone());
+    assert_eq!(sim.launcher.items().len(), 3);
+    sim.drain();
+
+    // Picking a scope searches again, and only its results are listed meanwhile.
+    sim.send(Input::ScopeNext);
+    assert_eq!(sim.launcher.scope().unwrap().name, "Files");
+    assert_eq!(sim.requests(), [Request::Search("notes".into())]);
+    sim.respond(results.clone());
+    let ids = |sim: &Harness| {
+        sim.launcher
+            .items()
+            .iter()
+            .map(|item| item.id)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(&sim), [2]);
+
+    sim.script([Input::ScopePrevious, Input::ScopePrevious]);
+    assert_eq!(sim.launcher.scope_index(), 2);
+    sim.respond(results);
+    assert_eq!(ids(&sim), [3]);
+
+    sim.send(Input::Hide);
+    assert_eq!(sim.launcher.scope_index(), 0);
+}
+
+#[test]
+fn quitting_a_window_closes_it_and_searches_again() {
+    let mut sim = Harness::new();
+    sim.send(Input::Toggle);
+    sim.send(Input::InputChanged("fire".into()));
+    sim.respond(vec![app(1, "Firefox"), window(2, "Firefox", "News")]);
+    sim.drain();
+
+    sim.send(Input::Quit(1));
+    assert_eq!(
+        sim.requests(),
+        [Request::Quit(2), Request::Search("fire".into())]
+    );
+    assert!(sim.launcher.is_active());
+}
+
+#[test]
+fn pins_stay_first_across_searches_until_hidden() {
+    let mut sim = Harness::new();
+    sim.send(Input::Toggle);
+    sim.send(Input::InputChanged("fi".into()));
+    sim.respond(vec![app(1, "Files"), app(2, "Firefox")]);
+    sim.send(Input::FocusNext);
+    sim.send(Input::Pin);
+    let names = |sim: &Harness| {
+        sim.launcher
+            .items()
+            .iter()
+            .map(|item| item.name.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(&sim), ["Firefox", "Files"]);
+    assert_eq!(sim.launcher.focused(), 0);
+
+    sim.send(Input::InputChanged("term".into()));
+    sim.respond(vec![app(1, "Terminal")]);
+    assert_eq!(names(&sim), ["Firefox", "Terminal"]);
+    sim.drain();
+
+    // The pin's ID is the earlier search's, which is repeated before activating it.
+    sim.send(Input::Activate(Some(0)));
+    assert_eq!(sim.launcher.input(), "fi");
+    assert_eq!(sim.requests(), [Request::Search("fi".into())]);
+    sim.respond(vec![app(1, "Files"), app(2, "Firefox")]);
+    sim.send(Input::Activate(Some(0)));
+    assert_eq!(sim.requests(), [Request::Activate(2)]);
+
+    sim.send(Input::Hide);
+    assert!(sim.launcher.pins().is_empty());
+}
+
+#[test]
+fn activating_a_running_application_raises_it() {
+    let mut sim = Harness::new();
+    sim.launcher.set_running(vec!["firefox".into()]);
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Firefox"), app(2, "Files")]);
+    sim.drain();
+
+    let effects = sim.send(Input::Activate(Some(0)));
+    assert_eq!(
+        count(
+            &effects,
+            |effect| matches!(effect, Effect::Raise(name) if name == "Firefox")
+        ),
+        1
+    );
+    assert_eq!(
+        count(
+            &effects,
+            |effect| matches!(effect, Effect::Used(name) if name == "Firefox")
+        ),
+        1
+    );
+    assert_eq!(hides(&effects), 1);
+
+    // Another instance is started through the backend instead.
+    sim.send(Input::Toggle);
+    sim.respond(vec![app(1, "Firefox"), app(2, "Files")]);
+    sim.drain();
+    sim.send(Input::ActivateNew(0));
+    assert_eq!(sim.requests(), [Request::Activate(1)]);
+}
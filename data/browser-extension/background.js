@@ -0,0 +1,34 @@
+// Reports open tabs to the native messaging host, and focuses the tabs it asks for.
+
+const port = chrome.runtime.connectNative("com.system76.cosmic_launcher.tabs");
+
+async function report() {
+  const tabs = await chrome.tabs.query({});
+  port.postMessage({
+    type: "tabs",
+    tabs: tabs.map((tab) => ({
+      id: tab.id,
+      windowId: tab.windowId,
+      title: tab.title ?? "",
+      url: tab.url ?? "",
+    })),
+  });
+}
+
+port.onMessage.addListener(async (message) => {
+  if (message.type === "activate") {
+    await chrome.tabs.update(message.tabId, { active: true });
+    await chrome.windows.update(message.windowId, { focused: true });
+  }
+});
+
+for (const event of [
+  chrome.tabs.onCreated,
+  chrome.tabs.onRemoved,
+  chrome.tabs.onUpdated,
+  chrome.tabs.onActivated,
+]) {
+  event.addListener(report);
+}
+
+report();
@@ -1 +1,211 @@
 app-name = Cosmic Launcher
+confirm = Confirm
+cancel = Cancel
+confirm-action = {$action}?
+launch-failed = {$name} did not start
+starting = Starting…
+not-responding = Not responding, press Enter to retry
+edit-launch-env = Edit launch environment…
+launch-using = Launch using
+gpu-integrated = Integrated GPU
+gpu-discrete = Discrete GPU
+gpu-numbered = GPU #{$number}
+show-qr-code = Show as QR code
+preferences = Preferences…
+reload-plugins = Reload plugins
+about = About
+copy-details = Copy details
+no-results = No results for “{$query}”
+fallback-web = Search {$engine} for “{$query}”
+fallback-run = Run “{$query}”
+fallback-files = Find files named “{$query}”
+result-count = { $count ->
+    [one] 1 result
+   *[other] { $count } results
+}
+insert-mode = Type to search apps or type “?” for more options...
+normal-mode = Normal mode, press i to type
+scope-all = All
+scope-apps = Apps
+scope-files = Files
+scope-windows = Windows
+scope-web = Web
+secondary-hint = Shift + Enter: {$action}
+secondary-new-instance = New window
+secondary-close-window = Close window
+secondary-open-folder = Open folder
+secondary-copy-url = Copy link
+pinned = Pinned
+container-logs = Show logs
+container-start = Start
+container-stop = Stop
+container-copy-name = Copy name
+container-image = Image · {$size}
+vm-start = Start
+vm-shut-down = Shut down
+vm-open-viewer = Open viewer
+vm-system = system
+vm-session = session
+power-performance = Performance
+power-balanced = Balanced
+power-saver = Power saver
+power-profile = Power profile
+power-profile-active = Power profile · ✓ Active
+night-light = Night light
+toggle-on = ✓ On
+toggle-off = Off
+keep-awake = Keep screen awake
+do-not-disturb = Do not disturb
+clear-notifications = Clear notifications
+notification-count = { $count ->
+    [0] No notifications
+    [one] 1 notification
+   *[other] { $count } notifications
+}
+note-add = Add note: {$note}
+note-save-in = Save as a new note in {$path}
+note-append-to = Append to {$path}
+note-open-folder = Open notes folder
+note-open-file = Open notes file
+task-add = Add task: {$summary}
+task-add-to = Add to {$list}
+task-complete = Complete
+task-delete = Delete
+task-priority-high = High priority
+task-priority-medium = Medium priority
+task-priority-low = Low priority
+password-description = {$length} random characters
+password-description-cleared = {$length} random characters, cleared from the clipboard after {$seconds} seconds
+password-show = Show password
+password-hide = Hide password
+password-generate-another = Generate another
+unicode-unnamed = Unnamed character
+unicode-copy-character = Copy character
+unicode-copy-code = Copy {$code}
+shell-history-run-again = Run again in {$shell}
+shell-history-run = Run in terminal
+shell-history-copy = Copy command
+settings-app = COSMIC Settings
+settings-wireless = Wi-Fi
+settings-wired = Wired
+settings-vpn = VPN
+settings-bluetooth = Bluetooth
+settings-appearance = Appearance
+settings-wallpaper = Wallpaper
+settings-panel = Panel
+settings-dock = Dock
+settings-window-management = Window Management
+settings-workspaces = Workspaces
+settings-displays = Displays
+settings-notifications = Notifications
+settings-power = Power & Battery
+settings-sound = Sound
+settings-keyboard = Keyboard
+settings-mouse = Mouse
+settings-touchpad = Touchpad
+settings-users = Users
+settings-date-time = Date & Time
+settings-region-language = Region & Language
+settings-default-apps = Default Applications
+settings-firmware = Firmware
+settings-about = About
+trash-open = Open Trash
+trash-open-description = Show deleted files in the file manager
+trash-empty = Empty Trash ({ $items ->
+    [one] 1 item
+   *[other] { $items } items
+}, {$size})
+trash-empty-description = Permanently delete the files in the trash
+drive-mounted = Mounted at {$path}
+drive-not-mounted = Not mounted · {$device}
+drive-mount-and-open = Mount and open
+drive-open = Open
+drive-unmount = Unmount
+drive-eject = Eject
+drive-safely-remove = Safely remove {$name}
+drive-safely-remove-description = Every filesystem is unmounted, so the drive can be powered off and unplugged
+ocr-copy-text = Copy text from screen
+ocr-copy-text-description = Select a region of the screen to copy the text in it
+media-play = Play
+media-pause = Pause
+media-next = Next track
+media-previous = Previous track
+media-playing = ▶ Playing
+media-paused = Paused
+media-stopped = Stopped
+media-now-playing = Now playing · {$player}
+wallpaper-current = {$dir} · ✓ Current
+wallpaper-set-on-all = Set on all displays
+wallpaper-set-on = Set on {$output}
+appearance-dark-mode = Switch to dark mode
+appearance-light-mode = Switch to light mode
+appearance-preset = Theme preset
+crash-report = Report issue
+crash-description = The launcher crashed at {$time} · {$path}
+crash-open-report = Open report
+crash-open-issues = Open issue tracker
+crash-dismiss = Dismiss
+weather-fetch = Weather in {$place}
+weather-fetch-hint = Press Enter to fetch the forecast
+weather-today = Today
+weather-tomorrow = Tomorrow
+weather-day = {$day}: {$summary}
+weather-range = {$min}° to {$max}°
+weather-clear = Clear
+weather-partly-cloudy = Partly cloudy
+weather-overcast = Overcast
+weather-fog = Fog
+weather-drizzle = Drizzle
+weather-rain = Rain
+weather-snow = Snow
+weather-thunderstorm = Thunderstorm
+weather-unknown = Unknown
+repo-open-terminal = Open in terminal
+repo-open-editor = Open in editor
+repo-open-remote = Open remote URL
+project-open = Open {$project} in {$editor}
+session-tmux = tmux session, { $windows ->
+    [one] 1 window
+   *[other] { $windows } windows
+}
+session-tmux-attached = tmux session, { $windows ->
+    [one] 1 window
+   *[other] { $windows } windows
+}, attached
+session-tmux-window = tmux window
+session-toolbox = Toolbox container
+session-distrobox = Distrobox container
+network-wifi-connected = Wi-Fi · Connected
+network-wifi-connecting = Wi-Fi · Connecting
+network-wifi-disconnected = Wi-Fi · Disconnected
+network-vpn-connected = VPN · Connected
+network-vpn-connecting = VPN · Connecting
+network-vpn-disconnected = VPN · Disconnected
+bluetooth-connected = Connected
+bluetooth-battery = Connected · {$battery}% battery
+bluetooth-disconnected = Disconnected
+audio-output = Output
+audio-output-default = Output · Default
+audio-input = Input
+audio-input-default = Input · Default
+audio-make-default = Make default
+audio-make-default-move = Make default and move streams
+snippet-copy = Copy to clipboard
+snippet-type = Type into the focused window
+kube-context = Context · {$cluster}
+kube-context-active = Context · {$cluster} · ✓ Active
+kube-namespace = Namespace
+kube-namespace-active = Namespace · ✓ Active
+dev-nix = Nix dev shell
+dev-toolbox = Toolbox container
+dev-distrobox = Distrobox container
+dev-devcontainer = Devcontainer
+contact-write = Write to {$email}
+contact-call = Call {$phone}
+contact-copy = Copy {$text}
+game-description = {$store} game
+appimage-description = AppImage · {$path}
+appimage-integrate = Integrate (create desktop entry)
+clock-in-zone = {$time} in {$zone}
+clock-source = {$time} {$zone}, UTC{$offset}
+clock-local-time = local time
@@ -0,0 +1,60 @@
+//! What the running launcher was built from, printed by `--version --json` and shown in the
+//! About view, so that support requests name the exact build.
+
+use serde::Serialize;
+
+use crate::config::{self, VERSION};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct About {
+    pub version: &'static str,
+    pub commit: &'static str,
+    /// Cargo profile of the build, such as `release`.
+    pub profile: &'static str,
+    /// Version and revision of the pop-launcher crate, which speaks the launcher protocol.
+    pub pop_launcher: &'static str,
+    pub features: Vec<&'static str>,
+}
+
+impl About {
+    pub fn current() -> Self {
+        Self {
+            version: VERSION,
+            commit: env!("GIT_COMMIT"),
+            profile: config::profile(),
+            pop_launcher: env!("POP_LAUNCHER_VERSION"),
+            features: env!("FEATURES")
+                .split(',')
+                .filter(|feature| !feature.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let features = if self.features.is_empty() {
+            "none".to_string()
+        } else {
+            self.features.join(", ")
+        };
+        vec![
+            format!("cosmic-launcher {} ({})", self.version, self.profile),
+            format!("Commit: {}", self.commit),
+            format!("pop-launcher: {}", self.pop_launcher),
+            format!("Features: {features}"),
+        ]
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Prints the version for `--version`, with the details of the build as JSON for `--json`.
+pub fn print(json: bool) {
+    let about = About::current();
+    if json {
+        println!("{}", about.to_json());
+    } else {
+        println!("cosmic-launcher {}", about.version);
+    }
+}
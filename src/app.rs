@@ -1,22 +1,53 @@
+use crate::about::About;
 use crate::app::iced::event::listen_raw;
-use crate::components;
-use crate::subscriptions::launcher;
+use crate::backends::{budget, Action, Registry};
+use crate::blur::Blur;
+use crate::components::{
+    self,
+    title::{self, TitleOverflow},
+};
+use crate::config::{self, Config, Density, KeyboardHints};
+use crate::crash;
+use crate::fallback;
+use crate::fl;
+use crate::icons::{App, IconResolver};
+use crate::launch::{self, LaunchRequest, StderrTail};
+use crate::logging::{LogLevel, RequestSpans};
+use crate::matcher;
+use crate::panel;
+use crate::pipe;
+use crate::plugins;
+use crate::qr;
+use crate::secondary::Secondary;
+use crate::snapshot::Snapshot;
+use crate::style::{Highlight, Interaction, BAR_WIDTH};
+#[cfg(feature = "control-socket")]
+use crate::subscriptions::control;
+use crate::subscriptions::launcher::{self, Request};
+use crate::subscriptions::session;
+use crate::subscriptions::toplevel;
+use crate::surface::{self, SurfaceMode};
 use clap::Parser;
-use cosmic::app::{command, Command, Core, CosmicFlags, DbusActivationDetails, Settings};
-use cosmic::cctk::sctk;
+use cosmic::app::{Command, Core, CosmicFlags, DbusActivationDetails, Settings};
+use cosmic::cctk::sctk::reexports::protocols::xdg::shell::client::xdg_positioner;
+use cosmic::cctk::sctk::{self, reexports::calloop};
+use cosmic::cctk::wayland_client::protocol::wl_surface::WlSurface;
+use cosmic::cosmic_config::{self, CosmicConfigEntry};
 use cosmic::iced::alignment::{Horizontal, Vertical};
 use cosmic::iced::event::Status;
 use cosmic::iced::id::Id;
-use cosmic::iced::wayland::actions::layer_surface::SctkLayerSurfaceSettings;
+use cosmic::iced::wayland::actions::layer_surface::{IcedMargin, SctkLayerSurfaceSettings};
 use cosmic::iced::wayland::actions::popup::{SctkPopupSettings, SctkPositioner};
+#[cfg(feature = "window-fallback")]
+use cosmic::iced::wayland::actions::window::SctkWindowSettings;
 use cosmic::iced::wayland::layer_surface::{
-    destroy_layer_surface, get_layer_surface, Anchor, KeyboardInteractivity,
+    destroy_layer_surface, get_layer_surface, set_size, Anchor, KeyboardInteractivity,
 };
-use cosmic::iced::widget::{column, container, Column};
+use cosmic::iced::widget::{column, container, image, Column, Row};
 use cosmic::iced::{self, Length, Subscription};
 use cosmic::iced_core::keyboard::key::Named;
 use cosmic::iced_core::{Border, Padding, Point, Rectangle, Shadow};
-use cosmic::iced_runtime::core::event::wayland::LayerEvent;
+use cosmic::iced_runtime::core::event::wayland::{LayerEvent, PopupEvent};
 use cosmic::iced_runtime::core::event::{wayland, PlatformSpecific};
 use cosmic::iced_runtime::core::layout::Limits;
 use cosmic::iced_runtime::core::window::Id as SurfaceId;
@@ -25,63 +56,113 @@ use cosmic::iced_sctk::commands::activation::request_token;
 use cosmic::iced_style::{application, container::Appearance as ContainerAppearance};
 use cosmic::iced_widget::row;
 use cosmic::theme::{self, Button, Container};
-use cosmic::widget::icon::{from_name, IconFallback};
+use cosmic::widget::icon::from_name;
 use cosmic::widget::{
-    button, divider, horizontal_space, icon, mouse_area, scrollable, text,
+    button, divider, horizontal_space, icon, mouse_area, popover, scrollable, text,
     text_input::{self, StyleSheet as TextInputStyleSheet},
 };
 use cosmic::{keyboard_nav, Element, Theme};
-use iced::keyboard::Key;
+use cosmic_launcher::client;
+use cosmic_launcher::commands::LauncherCommands;
+use cosmic_launcher_core::gpu::Gpus;
+use cosmic_launcher_core::grid::{Grid, Move as GridMove};
+use cosmic_launcher_core::kiosk::Kiosk;
+use cosmic_launcher_core::menu::{Entry as MenuEntry, Menu};
+use cosmic_launcher_core::scope::Scope;
+use cosmic_launcher_core::vi::Mode;
+use cosmic_launcher_core::{
+    activation, Effect, Input, Launcher, LOCAL_ID_BASE, MAX_RESULTS, PAGE_SIZE,
+};
+use iced::keyboard::{Key, Modifiers};
 use iced::widget::vertical_space;
 use iced::{Alignment, Color};
 use once_cell::sync::Lazy;
 use pop_launcher::{ContextOption, GpuPreference, IconSource, SearchResult};
 use serde::{Deserialize, Serialize};
+use std::any::TypeId;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::str::FromStr;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use unicode_truncate::UnicodeTruncateStr;
 use unicode_width::UnicodeWidthStr;
 
 static INPUT_ID: Lazy<Id> = Lazy::new(|| Id::new("input_id"));
-static RESULT_IDS: Lazy<[Id; 10]> = Lazy::new(|| {
-    (0..10)
+static RESULTS_ID: Lazy<Id> = Lazy::new(|| Id::new("results"));
+static RESULT_IDS: Lazy<[Id; MAX_RESULTS]> = Lazy::new(|| {
+    (0..MAX_RESULTS)
         .map(|id| Id::new(id.to_string()))
         .collect::<Vec<_>>()
         .try_into()
         .unwrap()
 });
+/// The rows holding each result, whose bounds anchor context menus opened with the keyboard.
+static RESULT_ROW_IDS: Lazy<[Id; MAX_RESULTS]> = Lazy::new(|| {
+    (0..MAX_RESULTS)
+        .map(|id| Id::new(format!("row-{id}")))
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+});
+/// Columns of a result's title before it overflows.
+const TITLE_WIDTH: usize = 45;
+/// Time the "Ctrl + N" hints take to fade in once Ctrl is held, with
+/// [`KeyboardHints::WhileCtrl`].
+const HINT_FADE: Duration = Duration::from_millis(150);
+/// Tiles in each row and rows on each page of the app grid.
+const GRID_COLUMNS: usize = 5;
+const GRID_ROWS: usize = 3;
+/// Applications in the app grid, filling three pages.
+const GRID_APPS: usize = GRID_COLUMNS * GRID_ROWS * 3;
+/// Height of a result row with [`Density::Compact`].
+const COMPACT_ROW_HEIGHT: f32 = 28.0;
+const MARQUEE_INTERVAL: Duration = Duration::from_millis(200);
+/// Width of the context menu and of each of its open submenus.
+const MENU_WIDTH: f32 = 240.0;
+const MENU_MAX_HEIGHT: f32 = 800.0;
+/// Space kept clear between menus and the edges of the output, for the panels.
+const MENU_OUTPUT_MARGIN: f32 = 48.0;
+/// Time the compositor has to show the menu popup before menus are drawn inside the
+/// launcher instead.
+const POPUP_TIMEOUT: Duration = Duration::from_millis(500);
+
 pub(crate) static WINDOW_ID: Lazy<SurfaceId> = Lazy::new(SurfaceId::unique);
 pub(crate) static MENU_ID: Lazy<SurfaceId> = Lazy::new(SurfaceId::unique);
+pub(crate) static QR_ID: Lazy<SurfaceId> = Lazy::new(SurfaceId::unique);
 
 #[derive(Parser, Debug, Serialize, Deserialize, Clone)]
 #[command(author, version, about, long_about = None)]
-#[command(propagate_version = true)]
+#[command(disable_version_flag = true)]
 pub struct Args {
     #[clap(subcommand)]
     pub subcommand: Option<LauncherCommands>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone, clap::Subcommand)]
-pub enum LauncherCommands {
-    #[clap(about = "Toggle the launcher and switch to the alt-tab view")]
-    AltTab,
-}
-
-impl ToString for LauncherCommands {
-    fn to_string(&self) -> String {
-        serde_json::ser::to_string(self).unwrap()
-    }
-}
-
-impl FromStr for LauncherCommands {
-    type Err = serde_json::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        serde_json::de::from_str(s)
-    }
+    /// Print version
+    #[arg(short = 'V', long)]
+    pub version: bool,
+    /// With `--version`, print the commit, pop-launcher version, and features of the build as JSON
+    #[arg(long, requires = "version")]
+    pub json: bool,
+    /// Maximum level of log messages to record, overriding `RUST_LOG`
+    #[arg(long, value_enum, global = true)]
+    pub log_level: Option<LogLevel>,
+    /// Write logs to this file instead of journald
+    #[arg(long, global = true)]
+    pub log_file: Option<PathBuf>,
+    /// Configuration profile to start with
+    #[arg(long)]
+    pub profile: Option<String>,
+    /// Only show the applications allowed by the kiosk config
+    #[arg(long)]
+    pub kiosk: bool,
+    /// Show search latency, frame time, and result count in a corner of the launcher
+    #[arg(long)]
+    pub perf: bool,
+    /// Serve a JSON API for tests and automation on this Unix socket
+    #[cfg(feature = "control-socket")]
+    #[arg(long)]
+    pub control_socket: Option<PathBuf>,
 }
 
 impl CosmicFlags for Args {
@@ -93,8 +174,7 @@ impl CosmicFlags for Args {
     }
 }
 
-pub fn run() -> cosmic::iced::Result {
-    let args = Args::parse();
+pub fn run(args: Args) -> cosmic::iced::Result {
     cosmic::app::run_single_instance::<CosmicLauncher>(
         Settings::default()
             .antialiasing(true)
@@ -117,6 +197,97 @@ pub fn menu_button<'a, Message>(
         .width(Length::Fill)
 }
 
+/// The entries of the context menu `menu` at `depth`, which is 0 for the top level and one
+/// more for each submenu.
+fn menu_level<'a>(menu: &Menu, depth: usize, entries: &'a [MenuEntry]) -> Element<'a, Message> {
+    let focus = menu.focus();
+    let options = entries.iter().enumerate().map(|(n, entry)| {
+        let mut path = focus[..depth].to_vec();
+        path.push(n);
+        let button = match entry {
+            MenuEntry::Option { id, name } => {
+                menu_button(text::body(name)).on_press(Message::MenuButton(menu.id, *id))
+            }
+            MenuEntry::Submenu { name, .. } => menu_button(
+                row![
+                    text::body(name),
+                    horizontal_space(Length::Fill),
+                    icon::from_name("go-next-symbolic").size(16),
+                ]
+                .align_items(Alignment::Center),
+            )
+            .on_press(Message::MenuFocus(path.clone())),
+        };
+        mouse_area(button.selected(focus.starts_with(&path)))
+            .on_enter(Message::MenuFocus(path))
+            .into()
+    });
+
+    // Only desktop entries have a launch environment.
+    let launch_env = (depth == 0 && menu.id < LOCAL_ID_BASE)
+        .then(|| {
+            [
+                divider::horizontal::light().into(),
+                menu_button(text::body(fl!("edit-launch-env")))
+                    .on_press(Message::EditLaunchEnv)
+                    .into(),
+            ]
+        })
+        .into_iter()
+        .flatten();
+
+    // Each level scrolls on its own, keeping the others in place.
+    scrollable(
+        Column::with_children(options.chain(launch_env))
+            .width(Length::Fixed(MENU_WIDTH))
+            .padding([8, 0]),
+    )
+    .id(menu_level_id(depth))
+    .into()
+}
+
+/// Names of the GPUs in the "Launch using" submenu, given whether each is the default.
+/// Of two GPUs, the default one is the integrated one.
+fn gpu_names(defaults: &[bool]) -> Vec<String> {
+    let (integrated, discrete) = (fl!("gpu-integrated"), fl!("gpu-discrete"));
+    match defaults {
+        [true, _] => vec![integrated, discrete],
+        [_, true] => vec![discrete, integrated],
+        _ => (1..=defaults.len())
+            .map(|number| fl!("gpu-numbered", number = number))
+            .collect(),
+    }
+}
+
+/// ID of the scrollable holding the context menu level at `depth`.
+fn menu_level_id(depth: usize) -> Id {
+    Id::new(format!("menu-level-{depth}"))
+}
+
+/// A popup menu of `entries`, styled like the launcher.
+fn menu_popup<'a>(entries: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
+    container(container(entries).style(theme::Container::custom(|theme| {
+        let cosmic = theme.cosmic();
+        let corners = cosmic.corner_radii;
+        ContainerAppearance {
+            text_color: Some(cosmic.background.on.into()),
+            background: Some(Color::from(cosmic.background.base).into()),
+            border: Border {
+                radius: corners.radius_m.into(),
+                width: 1.0,
+                color: cosmic.background.divider.into(),
+            },
+            shadow: Shadow::default(),
+            icon_color: Some(cosmic.background.on.into()),
+        }
+    })))
+    .width(Length::Shrink)
+    .height(Length::Shrink)
+    .align_x(Horizontal::Center)
+    .align_y(Vertical::Top)
+    .into()
+}
+
 pub fn menu_control_padding() -> Padding {
     let theme = cosmic::theme::active();
     let cosmic = theme.cosmic();
@@ -126,17 +297,80 @@ pub fn menu_control_padding() -> Padding {
 #[derive(Clone)]
 pub struct CosmicLauncher {
     core: Core,
-    input_value: String,
-    active_surface: bool,
-    launcher_items: Vec<SearchResult>,
+    state: Launcher,
     tx: Option<mpsc::Sender<launcher::Request>>,
-    wait_for_result: bool,
-    menu: Option<(u32, Vec<ContextOption>)>,
     cursor_position: Option<Point<f32>>,
-    focused: usize,
-    last_hide: Instant,
-    alt_tab: bool,
-    alt_grav: bool,
+    /// The context menu was asked for with the keyboard rather than the pointer.
+    keyboard_menu: bool,
+    /// The compositor has shown the menu popup opened last.
+    menu_shown: bool,
+    /// The compositor failed to show a menu popup, so menus are drawn inside the launcher.
+    inline_menu: bool,
+    surface_mode: SurfaceMode,
+    spans: RequestSpans,
+    config: Config,
+    profile: Option<String>,
+    force_kiosk: bool,
+    /// Where to serve the control API, if anywhere.
+    #[cfg(feature = "control-socket")]
+    control_socket: Option<PathBuf>,
+    #[cfg(feature = "control-socket")]
+    control_tx: Option<tokio::sync::mpsc::Sender<control::Response>>,
+    /// App IDs of open toplevels, keyed by their protocol ID.
+    toplevels: HashMap<u32, String>,
+    /// Titles of open toplevels, keyed by their protocol ID, to find the window of a result.
+    titles: HashMap<u32, String>,
+    /// Toplevel raised while cycling through alt-tab with `alt_tab_preview`.
+    previewed: Option<u32>,
+    /// Name of the output of the focused window, which the launcher opens on.
+    output: Option<String>,
+    /// Size of that output in logical pixels, if known.
+    output_size: Option<(i32, i32)>,
+    toplevel_tx: Option<calloop::channel::Sender<toplevel::Request>>,
+    modifiers: Modifiers,
+    /// When Ctrl was pressed, if it is held.
+    ctrl_since: Option<Instant>,
+    /// Launches still waiting for a window to appear.
+    pending_launches: Vec<(LaunchRequest, StderrTail)>,
+    backends: Registry,
+    icons: IconResolver,
+    /// Scroll position of the focused title, and the row it belongs to.
+    marquee: usize,
+    marquee_row: usize,
+    /// The last query sent to the backend, and the custom commands matching it.
+    search: String,
+    commands: Vec<SearchResult>,
+    /// Results of the providers which answered the last query, in the order they answered.
+    provider_results: Vec<SearchResult>,
+    /// Text shown as a QR code in a popup, with its rendered code.
+    qr_code: Option<(String, image::Handle)>,
+    /// The secret last put on the clipboard, cleared from it when the session locks.
+    secret: Option<String>,
+    /// State kept across restarts.
+    snapshot: Snapshot,
+    /// Whether the snapshot changed since it was last written, which happens on hiding.
+    unsaved: bool,
+    /// Performance overlay, if shown.
+    perf: Option<components::perf::Hud>,
+    /// Whether the menu popup shows the preferences rather than the options of a result.
+    preferences: bool,
+    /// Whether the About view takes the place of the results.
+    about: bool,
+    /// Blur behind the launcher surface, while it is shown.
+    blur: Option<Blur>,
+    /// Category icons of the pop-launcher plugins, by plugin name.
+    plugin_icons: HashMap<String, String>,
+    /// Applications of the app grid, and the focus within it.
+    grid_apps: Vec<App>,
+    grid: Grid,
+}
+
+/// Entries of the menu opened by right-clicking the launcher outside of the results.
+#[derive(Debug, Clone, Copy)]
+pub enum Preference {
+    Open,
+    ReloadPlugins,
+    About,
 }
 
 #[derive(Debug, Clone)]
@@ -144,152 +378,1027 @@ pub enum Message {
     InputChanged(String),
     Backspace,
     TabPress,
-    CompleteFocusedId(Id),
+    ShiftTabPress,
     Activate(Option<usize>),
     Context(usize),
+    /// The Menu key or Shift + F10 asked for the focused result's context menu.
+    ContextKey,
+    /// Where the focused row is, to open its context menu below it.
+    MenuBounds(Option<Rectangle>),
     MenuButton(u32, u32),
+    /// The pointer moved onto the context menu entry at this path.
+    MenuFocus(Vec<usize>),
+    ExpandSubmenu,
+    CollapseSubmenu,
+    /// The compositor configured the menu popup, or dismissed it if `false`.
+    MenuPopup(bool),
+    /// The compositor had its time to show the menu popup.
+    MenuPopupTimeout,
     CloseContextMenu,
+    CloseQrCode,
+    OpenPreferences,
+    ClosePreferences,
+    Preference(Preference),
+    CopyAbout,
     CursorMoved(Point<f32>),
+    /// The pointer moved onto the result in this row.
+    Hovered(usize),
     Hide,
     LauncherEvent(launcher::Event),
-    Layer(LayerEvent),
+    /// An event of the launcher surface, with its `wl_surface` unless it is a window.
+    Layer(LayerEvent, Option<WlSurface>),
     KeyboardNav(keyboard_nav::Message),
-    ActivationToken(Option<String>, String, String, GpuPreference),
+    /// PageUp, PageDown, Home, or End moved the focus through the results.
+    Page(Input),
+    /// A scope chip was clicked, or Ctrl + Left or Right moved to another one.
+    Scope(Input),
+    ActivationToken(Option<String>, LaunchRequest),
+    Launched(LaunchRequest, Option<StderrTail>),
+    LaunchDeadline(String),
+    Toplevel(toplevel::Event),
+    Session(session::Event),
+    #[cfg(feature = "control-socket")]
+    Control(control::Event),
     AltTab,
     AltRelease,
     AltGrav,
+    /// A character key pressed while Ctrl is held.
+    ControlKey(String),
+    ConfigUpdated(Config),
+    Confirm,
+    CancelConfirmation,
+    EditLaunchEnv,
+    PipeResolved(String, String),
+    ProviderResults(String, Vec<SearchResult>),
+    FooterResults(Vec<SearchResult>),
+    ProviderAction(Option<Action>),
+    ProviderContext(u32, Vec<ContextOption>),
+    MarqueeTick,
+    /// The backend had [`activation::TIMEOUT`] to answer the pending activation.
+    CheckActivation,
+    /// The launcher lost the focus long enough ago to hide, unless it got it back.
+    CheckHide,
+    Modifiers(Modifiers),
+    /// A frame was drawn, timed while the performance overlay is shown.
+    Frame(Instant),
+    /// Whether each GPU is the default one, as switcheroo-control listed them at startup.
+    Gpus(Vec<bool>),
+    /// An arrow or page key moved the focus through the app grid.
+    Grid(GridMove),
+    /// The app grid tile with this index was clicked.
+    GridActivate(usize),
 }
 
 impl CosmicLauncher {
-    fn hide(&mut self) -> Command<Message> {
-        self.input_value.clear();
-        self.focused = 0;
-        self.alt_tab = false;
-        self.alt_grav = false;
-        self.wait_for_result = false;
+    /// Feeds an input to the launcher state and carries out the resulting effects.
+    fn apply(&mut self, input: Input) -> Command<Message> {
+        let effects = self.state.update(input, Instant::now());
+        let command = Command::batch(
+            effects
+                .into_iter()
+                .map(|effect| self.run_effect(effect))
+                .collect::<Vec<_>>(),
+        );
+        self.preview();
+        command
+    }
+
+    /// Moves the focus with the keyboard, scrolling the focused context menu option into view.
+    fn move_focus(&mut self, input: Input) -> Command<Message> {
+        Command::batch([self.apply(input), self.scroll_menu_to_focused()])
+    }
 
-        // XXX The close will reset the launcher, but the search will restart it so it's ready
-        // for the next time it's opened.
-        if let Some(ref sender) = &self.tx {
-            let _res = sender.blocking_send(launcher::Request::Close);
+    /// Raises the window focused in alt-tab, unless it already is, when previews are on.
+    fn preview(&mut self) {
+        if !self.config.alt_tab_preview || !self.state.is_alt_tab() {
+            self.previewed = None;
+            return;
         }
 
-        if let Some(tx) = &self.tx {
-            let _res = tx.blocking_send(launcher::Request::Search(String::new()));
-        } else {
-            tracing::info!("NOT FOUND");
+        let window = self.focused_window();
+        if window.is_none() || window == self.previewed {
+            return;
+        }
+        if let (Some(handle), Some(tx)) = (window, self.toplevel_tx.as_ref()) {
+            let _res = tx.send(toplevel::Request::Activate(handle));
+            self.previewed = window;
         }
+    }
+
+    /// The toplevel of the focused result, if it is a window.
+    fn focused_window(&self) -> Option<u32> {
+        let item = self.state.items().get(self.state.focused())?;
+        item.window?;
+        self.titles
+            .iter()
+            .find(|(handle, title)| {
+                **title == item.description
+                    && self
+                        .toplevels
+                        .get(handle)
+                        .is_some_and(|app_id| self.icons.is_app(&item.name, app_id))
+            })
+            .map(|(handle, _)| *handle)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn run_effect(&mut self, effect: Effect) -> Command<Message> {
+        match effect {
+            Effect::Request(request) => {
+                self.spans.sent(&request);
+                let search = match &request {
+                    Request::Search(query) => Some(query.clone()),
+                    _ => None,
+                };
 
-        if self.active_surface {
-            self.active_surface = false;
+                if let Some(tx) = &self.tx {
+                    let _res = tx.blocking_send(request);
+                } else {
+                    tracing::info!("NOT FOUND");
+                }
 
-            let mut commands = vec![destroy_layer_surface(*WINDOW_ID)];
-            if self.menu.take().is_some() {
-                commands.push(commands::popup::destroy_popup(*MENU_ID));
+                if let Some(query) = search {
+                    return self.search_local(query);
+                }
+            }
+            Effect::ShowSurface => return self.create_surface(),
+            Effect::HideSurface => {
+                self.grid.focus(0);
+                return Command::batch([
+                    self.save_snapshot(),
+                    self.close_qr_code(),
+                    self.destroy_surface(),
+                ]);
+            }
+            Effect::CloseContextMenu => return commands::popup::destroy_popup(*MENU_ID),
+            Effect::OpenContextMenu => return self.open_context_menu(),
+            Effect::Launch {
+                path,
+                gpu_preference,
+                action_name,
+            } => {
+                let Some(entry) = cosmic::desktop::load_desktop_file(None, path) else {
+                    return Command::none();
+                };
+
+                let exec = if let Some(action_name) = action_name {
+                    entry
+                        .desktop_actions
+                        .into_iter()
+                        .find(|action| action.name == action_name)
+                        .map(|action| action.exec)
+                } else {
+                    entry.exec
+                };
+
+                let Some(exec) = exec else {
+                    return Command::none();
+                };
+
+                return request_launch(LaunchRequest {
+                    env: self.config.launch_env(&entry.id),
+                    app_id: entry.id.to_string(),
+                    name: entry.name,
+                    wm_class: entry.wm_class,
+                    exec,
+                    gpu: gpu_preference,
+                    working_dir: None,
+                    query: None,
+                    watch: true,
+                });
+            }
+            Effect::AwaitActivation => {
+                return Command::perform(tokio::time::sleep(activation::TIMEOUT), |()| {
+                    cosmic::app::message::app(Message::CheckActivation)
+                });
+            }
+            Effect::AwaitHide(delay) => {
+                return Command::perform(tokio::time::sleep(delay), |()| {
+                    cosmic::app::message::app(Message::CheckHide)
+                });
+            }
+            Effect::Used(name) => {
+                self.snapshot.used(&name);
+                self.state.set_frequent(self.snapshot.frecency());
+                self.unsaved = true;
+            }
+            Effect::HideResult(name) => {
+                self.snapshot.hidden.insert(name);
+                self.unsaved = true;
+            }
+            Effect::Copy(text) => {
+                return Command::perform(launch::copy(text), |()| cosmic::app::message::none());
+            }
+            Effect::Raise(name) => {
+                let window = self
+                    .toplevels
+                    .iter()
+                    .find(|(_, app_id)| self.icons.is_app(&name, app_id))
+                    .map(|(handle, _)| *handle);
+                if let (Some(window), Some(tx)) = (window, self.toplevel_tx.as_ref()) {
+                    let _res = tx.send(toplevel::Request::Activate(window));
+                }
+            }
+            Effect::ResolvePipe(pipeline) => {
+                let query = self.state.input().to_string();
+                return Command::perform(pipe::resolve(pipeline), move |payload| {
+                    cosmic::app::message::app(Message::PipeResolved(query, payload))
+                });
+            }
+            Effect::LocalContext(id) => {
+                if let Some(options) = self.backends.context(id, self.search.clone()) {
+                    return Command::perform(options, move |options| {
+                        cosmic::app::message::app(Message::ProviderContext(id, options))
+                    });
+                }
+            }
+            Effect::ActivateLocalContext { id, context } => {
+                if let Some(activate) =
+                    self.backends
+                        .activate_context(id, context, self.search.clone())
+                {
+                    return Command::perform(activate, |action| {
+                        cosmic::app::message::app(Message::ProviderAction(action))
+                    });
+                }
+            }
+            Effect::ActivateLocal(id) if id >= fallback::ID_BASE => {
+                let action = fallback::activate(&self.config.fallbacks, id, self.state.input());
+                return self.update(Message::ProviderAction(action));
+            }
+            Effect::ActivateLocal(id) => {
+                if let Some(activate) = self.backends.activate(id, self.search.clone()) {
+                    return Command::perform(activate, |action| {
+                        cosmic::app::message::app(Message::ProviderAction(action))
+                    });
+                }
+
+                let Some(command) = id
+                    .checked_sub(LOCAL_ID_BASE)
+                    .and_then(|i| self.config.commands.get(i as usize))
+                else {
+                    return Command::none();
+                };
+
+                return request_launch(LaunchRequest {
+                    env: self.config.launch_env(&command.name),
+                    app_id: command.name.clone(),
+                    name: command.name.clone(),
+                    wm_class: None,
+                    exec: command.exec.clone(),
+                    gpu: GpuPreference::Default,
+                    working_dir: command.working_dir(),
+                    query: Some(
+                        self.state
+                            .payload()
+                            .unwrap_or(self.state.input())
+                            .to_string(),
+                    ),
+                    watch: false,
+                });
             }
-            return Command::batch(commands);
         }
 
         Command::none()
     }
 
-    fn focus_next(&mut self) {
-        if self.launcher_items.is_empty() {
-            return;
+    /// A window of the application an application result launches, if one is open.
+    fn running_window(&self, item: &SearchResult) -> Option<u32> {
+        if item.window.is_some() || item.id >= LOCAL_ID_BASE {
+            return None;
         }
-        self.focused = (self.focused + 1) % self.launcher_items.len();
+
+        self.toplevels
+            .iter()
+            .find(|(_, app_id)| self.icons.is_app(&item.name, app_id))
+            .map(|(handle, _)| *handle)
     }
 
-    fn focus_previous(&mut self) {
-        if self.launcher_items.is_empty() {
-            return;
+    /// Tells the launcher state which applications have a window open, so that activating
+    /// one raises the window.
+    fn update_running(&mut self) {
+        let names = self
+            .toplevels
+            .values()
+            .flat_map(|app_id| self.icons.names(app_id))
+            .collect();
+        self.state.set_running(names);
+    }
+
+    /// What Shift + Enter does to `item`, if anything else than activating it.
+    fn secondary(&self, item: &SearchResult) -> Option<Secondary> {
+        Secondary::of(item, self.running_window(item).is_some())
+    }
+
+    fn run_secondary(&mut self, row: usize, secondary: Secondary) -> Command<Message> {
+        match secondary {
+            Secondary::NewInstance => self.apply(Input::ActivateNew(row)),
+            Secondary::CloseWindow => self.apply(Input::Quit(row)),
+            Secondary::OpenFolder(path) => {
+                let folder = if path.is_dir() {
+                    path
+                } else {
+                    path.parent()
+                        .map_or_else(|| path.clone(), Path::to_path_buf)
+                };
+                Command::batch([
+                    Command::perform(launch::open(folder.into()), |()| {
+                        cosmic::app::message::none()
+                    }),
+                    self.apply(Input::Hide),
+                ])
+            }
+            Secondary::CopyUrl(url) => Command::batch([
+                Command::perform(launch::copy(url), |()| cosmic::app::message::none()),
+                self.apply(Input::Hide),
+            ]),
         }
-        self.focused = (self.focused + self.launcher_items.len() - 1) % self.launcher_items.len();
     }
 
-    fn focus_next_current(&mut self) {
-        if self.launcher_items.is_empty() {
-            return;
+    /// Whether the focused result is a window whose title is scrolled through.
+    fn scrolls_title(&self) -> bool {
+        self.config.title_overflow == TitleOverflow::Marquee
+            && self.state.is_active()
+            && self
+                .state
+                .items()
+                .get(self.state.focused())
+                .filter(|item| item.window.is_some())
+                .is_some_and(|item| {
+                    item.description
+                        .lines()
+                        .any(|line| title::overflows(line, TITLE_WIDTH))
+                })
+    }
+
+    /// Shows the custom commands matching a search at once, then queries the providers.
+    fn search_local(&mut self, query: String) -> Command<Message> {
+        // Custom commands at the end of a pipeline are matched by name alone.
+        let name = match self.state.payload() {
+            Some(_) => query.split_whitespace().next().unwrap_or_default(),
+            None => &query,
+        };
+        self.commands = match self.state.scope() {
+            Some(scope) if !scope.queries("commands") => Vec::new(),
+            _ => self.config.command_results(name),
+        };
+        self.provider_results.clear();
+        self.search.clone_from(&query);
+
+        let commands = Command::batch([
+            self.apply(Input::FallbackResults(fallback::results(
+                &self.config.fallbacks,
+                self.state.input(),
+            ))),
+            self.apply(Input::LocalResults(self.commands.clone())),
+        ]);
+        if self.backends.is_empty() {
+            return commands;
+        }
+        if query.is_empty() {
+            return Command::batch([
+                commands,
+                Command::perform(self.backends.footer(), |results| {
+                    cosmic::app::message::app(Message::FooterResults(results))
+                }),
+            ]);
+        }
+
+        // Each provider's results are merged as soon as it answers, rather than once the
+        // slowest one has.
+        let queries = self
+            .backends
+            .query(query.clone(), self.state.scope())
+            .into_iter()
+            .map(|results| {
+                let query = query.clone();
+                Command::perform(results, move |results| {
+                    cosmic::app::message::app(Message::ProviderResults(query, results))
+                })
+            });
+        Command::batch(std::iter::once(commands).chain(queries))
+    }
+
+    /// Launches a program given as its arguments, such as one run by a provider. As it
+    /// has no desktop entry, none of the `launch_env` variables, keyed by desktop entry
+    /// ID, apply to it.
+    fn run_program(
+        &self,
+        args: Vec<String>,
+        working_dir: Option<PathBuf>,
+        gpu: GpuPreference,
+    ) -> Command<Message> {
+        let (Some(program), Ok(exec)) = (
+            args.first().cloned(),
+            shlex::try_join(args.iter().map(String::as_str)),
+        ) else {
+            return Command::none();
+        };
+
+        request_launch(LaunchRequest {
+            env: Vec::new(),
+            app_id: program.clone(),
+            name: program,
+            wm_class: None,
+            exec,
+            gpu,
+            working_dir,
+            query: None,
+            watch: false,
+        })
+    }
+
+    /// Shows text as a QR code in a popup below the cursor, over the results.
+    #[allow(clippy::cast_possible_truncation)]
+    fn open_qr_code(&mut self, text: String) -> Command<Message> {
+        let Some(handle) = qr::render(&text) else {
+            tracing::warn!("{} bytes are too many for a QR code", text.len());
+            return Command::none();
+        };
+        let close = self.close_qr_code();
+        self.qr_code = Some((text, handle));
+
+        let pos = self.cursor_position.unwrap_or(Point::ORIGIN);
+        let rect = Rectangle {
+            x: pos.x.round() as i32,
+            y: pos.y.round() as i32,
+            width: 1,
+            height: 1,
+        };
+        Command::batch([
+            close,
+            commands::popup::get_popup(SctkPopupSettings {
+                parent: *WINDOW_ID,
+                id: *QR_ID,
+                positioner: SctkPositioner {
+                    size: None,
+                    size_limits: Limits::NONE.min_width(1.0).min_height(1.0).max_width(400.0).max_height(800.0),
+                    anchor_rect: rect,
+                    anchor:
+                        sctk::reexports::protocols::xdg::shell::client::xdg_positioner::Anchor::Bottom,
+                    gravity: sctk::reexports::protocols::xdg::shell::client::xdg_positioner::Gravity::Bottom,
+                    reactive: true,
+                    ..Default::default()
+                },
+                grab: true,
+                parent_size: None,
+            }),
+        ])
+    }
+
+    /// Opens the menu popup at the pointer.
+    #[allow(clippy::cast_possible_truncation)]
+    fn open_menu(&mut self) -> Command<Message> {
+        let Some(pos) = self.cursor_position.as_ref() else {
+            return Command::none();
+        };
+        let rect = Rectangle {
+            x: pos.x.round() as i32,
+            y: pos.y.round() as i32,
+            width: 1,
+            height: 1,
+        };
+        self.open_menu_at(
+            rect,
+            xdg_positioner::Anchor::Right,
+            xdg_positioner::Gravity::Right,
+        )
+    }
+
+    /// Opens the context menu below the focused row if it was asked for with the keyboard,
+    /// since the pointer may be anywhere then, or else at the pointer.
+    fn open_context_menu(&mut self) -> Command<Message> {
+        let row = RESULT_ROW_IDS.get(self.state.focused());
+        let (true, Some(row)) = (self.keyboard_menu, row) else {
+            return self.open_menu();
+        };
+        container::visible_bounds(row.clone())
+            .map(|bounds| cosmic::app::message::app(Message::MenuBounds(bounds)))
+    }
+
+    /// Opens the menu popup at `rect`, unless menus are drawn inside the launcher.
+    #[allow(clippy::cast_precision_loss)]
+    fn open_menu_at(
+        &mut self,
+        rect: Rectangle<i32>,
+        anchor: xdg_positioner::Anchor,
+        gravity: xdg_positioner::Gravity,
+    ) -> Command<Message> {
+        if self.inline_menu {
+            return Command::none();
+        }
+        self.menu_shown = false;
+
+        // Leave room for two levels of submenus next to the context menu.
+        let mut max_width = if self.state.menu().is_some() {
+            MENU_WIDTH * 3.0
+        } else {
+            300.0
+        };
+        // Menus taller than the output scroll instead.
+        let mut max_height = MENU_MAX_HEIGHT;
+        if let Some((width, height)) = self.output_size {
+            max_width = max_width.min(width as f32).max(MENU_WIDTH);
+            max_height = max_height
+                .min(height as f32 - 2.0 * MENU_OUTPUT_MARGIN)
+                .max(MENU_OUTPUT_MARGIN);
+        }
+
+        Command::batch([
+            commands::popup::get_popup(SctkPopupSettings {
+                parent: *WINDOW_ID,
+                id: *MENU_ID,
+                positioner: SctkPositioner {
+                    size: None,
+                    size_limits: Limits::NONE
+                        .min_width(1.0)
+                        .min_height(1.0)
+                        .max_width(max_width)
+                        .max_height(max_height),
+                    anchor_rect: rect,
+                    anchor,
+                    gravity,
+                    reactive: true,
+                    ..Default::default()
+                },
+                grab: true,
+                parent_size: None,
+            }),
+            Command::perform(tokio::time::sleep(POPUP_TIMEOUT), |()| {
+                cosmic::app::message::app(Message::MenuPopupTimeout)
+            }),
+        ])
+    }
+
+    /// Draws menus inside the launcher from now on, if the menu popup opened last was not
+    /// shown. Some compositors do not support popups of layer surfaces.
+    fn fall_back_to_inline_menu(&mut self) -> Command<Message> {
+        let open = self.state.menu().is_some() || self.preferences;
+        if self.menu_shown || self.inline_menu || !open {
+            return Command::none();
+        }
+
+        tracing::warn!("the compositor did not show the menu popup; drawing menus inline");
+        self.inline_menu = true;
+        commands::popup::destroy_popup(*MENU_ID)
+    }
+
+    /// The preferences or the context options of a result, whichever menu is open.
+    fn menu_view(&self) -> Option<Element<Message>> {
+        if self.preferences {
+            return Some(menu_popup(scrollable(
+                Column::with_children([
+                    menu_button(text::body(fl!("preferences")))
+                        .on_press(Message::Preference(Preference::Open))
+                        .into(),
+                    menu_button(text::body(fl!("reload-plugins")))
+                        .on_press(Message::Preference(Preference::ReloadPlugins))
+                        .into(),
+                    divider::horizontal::light().into(),
+                    menu_button(text::body(fl!("about")))
+                        .on_press(Message::Preference(Preference::About))
+                        .into(),
+                ])
+                .padding([8, 0]),
+            )));
+        }
+
+        // The top level and each open submenu are shown side by side.
+        let menu = self.state.menu()?;
+        let levels = menu.levels().into_iter().enumerate();
+        Some(menu_popup(Row::with_children(
+            levels.map(|(depth, entries)| menu_level(menu, depth, entries)),
+        )))
+    }
+
+    fn close_preferences(&mut self) -> Command<Message> {
+        if std::mem::take(&mut self.preferences) {
+            commands::popup::destroy_popup(*MENU_ID)
+        } else {
+            Command::none()
+        }
+    }
+
+    fn close_qr_code(&mut self) -> Command<Message> {
+        if self.qr_code.take().is_some() {
+            commands::popup::destroy_popup(*QR_ID)
+        } else {
+            Command::none()
+        }
+    }
+
+    fn create_surface(&self) -> Command<Message> {
+        let output = self.output.as_deref();
+        let margin = self.config.top_margin(output);
+        let (anchor, margin) = match self.config.anchor(output) {
+            config::Anchor::Top => (
+                Anchor::TOP,
+                IcedMargin {
+                    top: margin + panel::top_clearance(),
+                    ..Default::default()
+                },
+            ),
+            // Without an edge to anchor to, the compositor centers the surface.
+            config::Anchor::Center => (Anchor::empty(), IcedMargin::default()),
+            config::Anchor::Bottom => (
+                Anchor::BOTTOM,
+                IcedMargin {
+                    bottom: margin,
+                    ..Default::default()
+                },
+            ),
+        };
+        match self.surface_mode {
+            SurfaceMode::LayerShell => get_layer_surface(SctkLayerSurfaceSettings {
+                id: *WINDOW_ID,
+                keyboard_interactivity: KeyboardInteractivity::Exclusive,
+                anchor,
+                namespace: "launcher".into(),
+                size: None,
+                // Kept clear of the space panels and docks reserve along the edges.
+                exclusive_zone: 0,
+                margin,
+                size_limits: Limits::NONE
+                    .min_width(1.0)
+                    .min_height(1.0)
+                    .max_width(self.max_width()),
+                ..Default::default()
+            }),
+            #[cfg(feature = "window-fallback")]
+            SurfaceMode::Window => commands::window::get_window(SctkWindowSettings {
+                window_id: *WINDOW_ID,
+                app_id: Some(<Self as cosmic::Application>::APP_ID.to_string()),
+                title: Some(fl!("app-name")),
+                autosize: true,
+                size_limits: Limits::NONE
+                    .min_width(1.0)
+                    .min_height(1.0)
+                    .max_width(self.max_width()),
+                resizable: None,
+                client_decorations: false,
+                transparent: true,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Scrolls the results so that the focused one is in view.
+    #[allow(clippy::cast_precision_loss)]
+    fn scroll_to_focused(&self) -> Command<Message> {
+        let last = self.state.items().len().saturating_sub(1).max(1);
+        let y = self.state.focused() as f32 / last as f32;
+        iced::widget::scrollable::snap_to(
+            RESULTS_ID.clone(),
+            iced::widget::scrollable::RelativeOffset { x: 0.0, y },
+        )
+    }
+
+    /// Scrolls the focused level of the context menu so that its focused entry is in view.
+    #[allow(clippy::cast_precision_loss)]
+    fn scroll_menu_to_focused(&self) -> Command<Message> {
+        let Some(menu) = self.state.menu() else {
+            return Command::none();
+        };
+        let Some((last, parents)) = menu.focus().split_last() else {
+            return Command::none();
+        };
+        let len = menu
+            .levels()
+            .get(parents.len())
+            .map_or(0, |entries| entries.len());
+
+        let y = *last as f32 / len.saturating_sub(1).max(1) as f32;
+        iced::widget::scrollable::snap_to(
+            menu_level_id(parents.len()),
+            iced::widget::scrollable::RelativeOffset { x: 0.0, y },
+        )
+    }
+
+    /// Hands the active profile's ranking and the kiosk policy to the launcher state, and
+    /// the matcher settings to the providers.
+    fn apply_config(&mut self) {
+        crash::set_config(&self.config);
+        if !self.config.blur {
+            self.blur = None;
+        }
+        matcher::set(self.config.matcher);
+        let mut ranking = self.config.profile(self.profile.as_deref()).ranking();
+        ranking.windows_first = self.config.windows_first;
+        ranking.frequent = self.snapshot.frecency();
+        ranking.sources = self.plugin_icons(&ranking.sources);
+        ranking.quotas = ranking
+            .quotas
+            .into_iter()
+            .map(|(plugin, quota)| (self.plugin_icon(&plugin), quota))
+            .collect();
+        self.state.set_ranking(ranking);
+
+        self.state.set_routes(self.config.routes.clone());
+
+        let mut kiosk = self.config.kiosk.clone();
+        kiosk.enabled |= self.force_kiosk;
+        kiosk.resolve(self.icons.entries());
+        self.state.set_kiosk(kiosk);
+        self.update_grid();
+
+        self.state
+            .set_destructive_actions(self.config.destructive_actions.clone());
+        self.state.set_debounce(self.config.debounce);
+        self.state.set_number_keys(self.config.number_keys);
+        self.state.set_vi_mode(self.config.vi_mode);
+        self.state.set_hidden(self.snapshot.hidden.iter().cloned());
+        let scopes = self
+            .config
+            .scopes
+            .iter()
+            .map(|scope| Scope {
+                plugins: self.plugin_icons(&scope.plugins),
+                ..scope.clone()
+            })
+            .collect();
+        self.state.set_scopes(scopes, self.config.remember_scope);
+    }
+
+    /// Blurs what is behind `surface` if the config asks for it, once per surface.
+    fn blur(&mut self, surface: &WlSurface) {
+        if !self.config.blur {
+            self.blur = None;
+        } else if self.blur.as_ref().map(Blur::surface) != Some(surface) {
+            self.blur = Blur::new(surface);
         }
-        let mut curwins = vec![];
-        let mut cur = 0;
-        for i in 0..self.launcher_items.len() {
-            if self.launcher_items[i].name == self.launcher_items[self.focused].name && i != self.focused {
-                curwins.push(i);
-            } 
-            if i == self.focused {
-                cur = i;
+    }
+
+    /// The category icon by which the results of the pop-launcher plugin named `plugin`
+    /// are told apart, or `plugin` itself if no such plugin is installed.
+    fn plugin_icon(&self, plugin: &str) -> String {
+        self.plugin_icons
+            .get(plugin)
+            .cloned()
+            .unwrap_or_else(|| plugin.to_owned())
+    }
+
+    fn plugin_icons(&self, plugins: &[String]) -> Vec<String> {
+        plugins
+            .iter()
+            .map(|plugin| self.plugin_icon(plugin))
+            .collect()
+    }
+
+    /// Writes the snapshot on a blocking thread if it changed, and lays out the app grid
+    /// again for the applications used since.
+    fn save_snapshot(&mut self) -> Command<Message> {
+        if !std::mem::take(&mut self.unsaved) {
+            return Command::none();
+        }
+        self.update_grid();
+        let snapshot = self.snapshot.clone();
+        Command::perform(tokio::task::spawn_blocking(move || snapshot.save()), |_| {
+            cosmic::app::message::none()
+        })
+    }
+
+    fn update_grid(&mut self) {
+        let grid_apps = if self.config.app_grid {
+            let favorites = &self.config.profile(self.profile.as_deref()).favorites;
+            self.find_grid_apps(favorites, self.state.kiosk())
+        } else {
+            Vec::new()
+        };
+        self.grid_apps = grid_apps;
+        self.grid = Grid::new(GRID_COLUMNS, GRID_ROWS, self.grid_apps.len());
+    }
+
+    /// The favorites, then the applications used most often lately, of those the kiosk
+    /// policy allows.
+    fn find_grid_apps(&self, favorites: &[String], kiosk: &Kiosk) -> Vec<App> {
+        let mut frequent = self.snapshot.frecency().into_iter().collect::<Vec<_>>();
+        frequent.sort_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.cmp(b)));
+
+        let mut apps: Vec<App> = Vec::new();
+        for name in favorites
+            .iter()
+            .chain(frequent.iter().map(|(name, _)| name))
+        {
+            if apps.len() == GRID_APPS {
+                break;
+            }
+            if let Some(app) = self.icons.app(name) {
+                if kiosk.allows_entry(&app.path) && !apps.contains(&app) {
+                    apps.push(app);
+                }
             }
         }
-        self.focused = curwins[(cur + 1) % curwins.len()];
+        apps
     }
-    fn focus_previous_current(&mut self) {
-        if self.launcher_items.is_empty() {
-            return;
+
+    /// Opacity of the "Ctrl + N" hints, or `None` while they are hidden.
+    fn hint_alpha(&self) -> Option<f32> {
+        match self.config.keyboard_hints {
+            KeyboardHints::Always => Some(1.0),
+            KeyboardHints::WhileCtrl => self
+                .ctrl_since
+                .map(|since| (since.elapsed().as_secs_f32() / HINT_FADE.as_secs_f32()).min(1.0)),
+            KeyboardHints::Never => None,
         }
-        let mut curwins = vec![];
-        for i in 0..self.launcher_items.len() {
-            if self.launcher_items[i].name == self.launcher_items[self.focused].name && i != self.focused {
-                curwins.push(i);
+    }
+
+    /// Whether the hints are fading in, and must be redrawn every frame.
+    fn hints_fading(&self) -> bool {
+        self.config.keyboard_hints == KeyboardHints::WhileCtrl
+            && self
+                .ctrl_since
+                .is_some_and(|since| since.elapsed() < HINT_FADE)
+    }
+
+    /// Whether the app grid takes the place of the results.
+    fn grid_shown(&self) -> bool {
+        !self.grid.is_empty()
+            && self.state.input().is_empty()
+            && !self.state.is_alt_tab()
+            && !self.about
+    }
+
+    /// Grid tile launched by typing `value` into the empty search field, which must be a
+    /// single digit from 1 to as many tiles as the page shows.
+    fn number_key_tile(&self, value: &str) -> Option<usize> {
+        if !self.config.number_keys || !self.grid_shown() || value.len() != 1 {
+            return None;
+        }
+
+        let digit = value.parse::<usize>().ok()?;
+        self.grid.visible().take(9).nth(digit.checked_sub(1)?)
+    }
+
+    /// Launches the application of the grid tile `i`.
+    fn activate_tile(&mut self, i: usize) -> Command<Message> {
+        let Some(app) = self.grid_apps.get(i).cloned() else {
+            return Command::none();
+        };
+        let used = self.run_effect(Effect::Used(app.name));
+        let launch = self.run_effect(Effect::Launch {
+            path: app.path,
+            gpu_preference: GpuPreference::Default,
+            action_name: None,
+        });
+        Command::batch([used, launch, self.apply(Input::Hide)])
+    }
+
+    /// Carries out a request of the control API, and answers it.
+    #[cfg(feature = "control-socket")]
+    fn control(&mut self, request: control::Request) -> Command<Message> {
+        let (response, command) = match request {
+            control::Request::Show if self.state.is_active() => {
+                (control::Response::Ok, Command::none())
             }
+            control::Request::Show => (control::Response::Ok, self.apply(Input::Toggle)),
+            control::Request::Hide => (control::Response::Ok, self.apply(Input::Hide)),
+            control::Request::Type { text } => (
+                control::Response::Ok,
+                self.update(Message::InputChanged(text)),
+            ),
+            control::Request::Results => (
+                control::Response::Results {
+                    visible: self.state.is_active(),
+                    focused: self.state.focused(),
+                    results: self
+                        .state
+                        .items()
+                        .iter()
+                        .map(|item| control::Row {
+                            name: item.name.clone(),
+                            description: item.description.clone(),
+                        })
+                        .collect(),
+                },
+                Command::none(),
+            ),
+            control::Request::Activate { index } if index < self.state.items().len() => (
+                control::Response::Ok,
+                self.update(Message::Activate(Some(index))),
+            ),
+            control::Request::Activate { index } => (
+                control::Response::Error(format!("there is no result in row {index}")),
+                Command::none(),
+            ),
+        };
+
+        if let Some(tx) = self.control_tx.as_ref() {
+            let _res = tx.try_send(response);
+        }
+        command
+    }
+
+    /// Saves the active profile as the one to start with next time.
+    fn remember_profile(&mut self) {
+        if self.snapshot.profile != self.profile {
+            self.snapshot.profile.clone_from(&self.profile);
+            self.snapshot.save();
         }
-        self.focused = curwins[(curwins.len() - 1) % curwins.len()];
     }
-}
 
-async fn launch(token: Option<String>, app_id: String, exec: String, gpu: GpuPreference) {
-    let mut envs = Vec::new();
-    if let Some(token) = token {
-        envs.push(("XDG_ACTIVATION_TOKEN".to_string(), token.clone()));
-        envs.push(("DESKTOP_STARTUP_ID".to_string(), token));
+    /// Maximum width of the launcher on the output it opens on.
+    fn max_width(&self) -> f32 {
+        self.config.max_width(self.output.as_deref())
     }
 
-    if let Some(gpu_envs) = try_get_gpu_envs(gpu).await {
-        envs.extend(gpu_envs);
+    /// Applies a changed maximum width to the mapped surface.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn resize_surface(&self) -> Command<Message> {
+        match self.surface_mode {
+            SurfaceMode::LayerShell => {
+                set_size(*WINDOW_ID, Some(self.max_width().round() as u32), None)
+            }
+            #[cfg(feature = "window-fallback")]
+            SurfaceMode::Window => Command::none(),
+        }
     }
 
-    cosmic::desktop::spawn_desktop_exec(exec, envs, Some(&app_id)).await;
+    fn destroy_surface(&self) -> Command<Message> {
+        match self.surface_mode {
+            SurfaceMode::LayerShell => destroy_layer_surface(*WINDOW_ID),
+            #[cfg(feature = "window-fallback")]
+            SurfaceMode::Window => commands::window::close_window(*WINDOW_ID),
+        }
+    }
 }
 
-async fn try_get_gpu_envs(gpu: GpuPreference) -> Option<HashMap<String, String>> {
-    let connection = zbus::Connection::system().await.ok()?;
-    let proxy = switcheroo_control::SwitcherooControlProxy::new(&connection)
-        .await
-        .ok()?;
-    let gpus = proxy.get_gpus().await.ok()?;
-    match gpu {
-        GpuPreference::Default => gpus.into_iter().find(|gpu| gpu.default),
-        GpuPreference::NonDefault => gpus.into_iter().find(|gpu| !gpu.default),
-        GpuPreference::SpecificIdx(idx) => gpus.into_iter().nth(idx as usize),
-    }
-    .map(|gpu| gpu.environment)
+/// Requests an activation token for the launched application, then launches it.
+fn request_launch(request: LaunchRequest) -> Command<Message> {
+    request_token(
+        Some(String::from(
+            <CosmicLauncher as cosmic::Application>::APP_ID,
+        )),
+        Some(*WINDOW_ID),
+        move |token| cosmic::app::Message::App(Message::ActivationToken(token, request)),
+    )
 }
 
 impl cosmic::Application for CosmicLauncher {
     type Message = Message;
     type Executor = cosmic::executor::single::Executor;
     type Flags = Args;
-    const APP_ID: &'static str = "com.system76.CosmicLauncher";
+    const APP_ID: &'static str = client::APP_ID;
 
-    fn init(mut core: Core, _flags: Args) -> (Self, Command<Message>) {
+    fn init(mut core: Core, flags: Args) -> (Self, Command<Message>) {
         core.set_keyboard_nav(false);
-        (
-            CosmicLauncher {
-                core,
-                input_value: String::new(),
-                active_surface: false,
-                launcher_items: Vec::new(),
-                tx: None,
-                wait_for_result: false,
-                menu: None,
-                cursor_position: None,
-                focused: 0,
-                last_hide: Instant::now(),
-                alt_tab: false,
-                alt_grav: false
+        let config = Config::load(Self::APP_ID);
+        let snapshot = Snapshot::load();
+        budget::forget();
+        let mut launcher = CosmicLauncher {
+            core,
+            state: Launcher::new(Instant::now()),
+            tx: None,
+            cursor_position: None,
+            keyboard_menu: false,
+            menu_shown: false,
+            inline_menu: false,
+            surface_mode: surface::detect(),
+            spans: RequestSpans::default(),
+            backends: Registry::load(&config),
+            icons: IconResolver::load(),
+            marquee: 0,
+            marquee_row: 0,
+            config,
+            profile: match flags.subcommand {
+                Some(LauncherCommands::Profile { name }) => name,
+                _ => flags.profile.or_else(|| snapshot.profile.clone()),
             },
-            Command::none(),
-        )
+            force_kiosk: flags.kiosk,
+            #[cfg(feature = "control-socket")]
+            control_socket: flags.control_socket,
+            #[cfg(feature = "control-socket")]
+            control_tx: None,
+            toplevels: HashMap::new(),
+            titles: HashMap::new(),
+            previewed: None,
+            output: None,
+            output_size: None,
+            toplevel_tx: None,
+            modifiers: Modifiers::empty(),
+            ctrl_since: None,
+            pending_launches: Vec::new(),
+            search: String::new(),
+            commands: Vec::new(),
+            provider_results: Vec::new(),
+            qr_code: None,
+            secret: None,
+            snapshot,
+            unsaved: false,
+            perf: flags.perf.then(components::perf::Hud::default),
+            preferences: false,
+            about: false,
+            blur: None,
+            plugin_icons: plugins::icons(),
+            grid_apps: Vec::new(),
+            grid: Grid::new(GRID_COLUMNS, GRID_ROWS, 0),
+        };
+        launcher.remember_profile();
+        launcher.apply_config();
+        // GPUs rarely come and go, so they are only listed once.
+        let gpus = Command::perform(launch::gpus(), |gpus| {
+            cosmic::app::message::app(Message::Gpus(gpus))
+        });
+        (launcher, gpus)
     }
 
     fn core(&self) -> &Core {
@@ -310,254 +1419,438 @@ impl cosmic::Application for CosmicLauncher {
         )))
     }
 
-    #[allow(clippy::too_many_lines)]
     fn update(&mut self, message: Message) -> Command<Self::Message> {
+        crash::record(&message);
         match message {
-            Message::InputChanged(value) => {
-                self.input_value = value.clone();
-                if let Some(tx) = &self.tx {
-                    let _res = tx.blocking_send(launcher::Request::Search(value));
+            // The rows of the number keys are tiles while the grid takes the place of the results.
+            Message::InputChanged(value) if self.number_key_tile(&value).is_some() => {
+                if let Some(i) = self.number_key_tile(&value) {
+                    return self.activate_tile(i);
                 }
             }
-            Message::Backspace => {
-                let len = self.input_value.len();
-                if len > 0 {
-                    self.input_value.remove(len - 1);
-                }
-                if let Some(tx) = &self.tx {
-                    let _res =
-                        tx.blocking_send(launcher::Request::Search(self.input_value.clone()));
-                }
+            Message::InputChanged(value) => {
+                self.about = false;
+                return self.apply(Input::InputChanged(value));
             }
-            Message::TabPress if !self.alt_tab => {
-                let focused = self.focused;
-                self.focused = 0;
-                return command::message(cosmic::app::Message::App(
-                    Self::Message::CompleteFocusedId(RESULT_IDS[focused].clone()),
-                ));
+            Message::Backspace => return self.apply(Input::Backspace),
+            Message::TabPress => return self.apply(Input::Complete),
+            Message::ShiftTabPress => return self.apply(Input::CompletePrevious),
+            // Enter activates the focused entry of an open context menu instead.
+            Message::Activate(None) if self.state.menu().is_some() => {
+                return self.apply(Input::Activate(None));
             }
-            Message::TabPress => {}
-            Message::CompleteFocusedId(id) => {
-                let i = RESULT_IDS
-                    .iter()
-                    .position(|res_id| res_id == &id)
-                    .unwrap_or_default();
-
-                if let Some(id) = self.launcher_items.get(i).map(|res| res.id) {
-                    if let Some(tx) = &self.tx {
-                        let _res = tx.blocking_send(launcher::Request::Complete(id));
-                    }
-                }
+            Message::Activate(None) if self.grid_shown() => {
+                return self.activate_tile(self.grid.focused());
             }
+            Message::GridActivate(i) => return self.activate_tile(i),
+            Message::Grid(step) if self.grid_shown() => self.grid.step(step),
+            Message::Grid(_) => {}
             Message::Activate(i) => {
-                if let (Some(tx), Some(item)) =
-                    (&self.tx, self.launcher_items.get(i.unwrap_or(self.focused)))
+                // Enter raises a running application, Shift + Enter starts another instance.
+                let row = i.unwrap_or(self.state.focused());
+                let item = self.state.items().get(row);
+                let secondary = item.and_then(|item| self.secondary(item));
+                if let (Some(secondary), true, None) =
+                    (secondary, self.modifiers.shift(), self.state.confirmation())
                 {
-                    let _res = tx.blocking_send(launcher::Request::Activate(item.id));
-                } else {
-                    return self.hide();
-                }
-            }
-            #[allow(clippy::cast_possible_wrap)]
-            Message::Context(i) => {
-                if self.menu.take().is_some() {
-                    return commands::popup::destroy_popup(*MENU_ID);
+                    return self.run_secondary(row, secondary);
                 }
 
-                if let (Some(tx), Some(item)) = (&self.tx, self.launcher_items.get(i)) {
-                    let _res = tx.blocking_send(launcher::Request::Context(item.id));
+                return self.apply(Input::Activate(i));
+            }
+            Message::Modifiers(modifiers) => {
+                if modifiers.control() != self.modifiers.control() {
+                    self.ctrl_since = modifiers.control().then(Instant::now);
                 }
+                self.modifiers = modifiers;
             }
+            Message::Context(i) => {
+                self.keyboard_menu = false;
+                return self.apply(Input::Context(i));
+            }
+            Message::ContextKey => {
+                self.keyboard_menu = true;
+                return self.apply(Input::Context(self.state.focused()));
+            }
+            Message::MenuBounds(Some(bounds)) if self.state.menu().is_some() => {
+                #[allow(clippy::cast_possible_truncation)]
+                let rect = Rectangle {
+                    x: bounds.x.round() as i32,
+                    y: bounds.y.round() as i32,
+                    width: bounds.width.round() as i32,
+                    height: bounds.height.round() as i32,
+                };
+                return self.open_menu_at(
+                    rect,
+                    xdg_positioner::Anchor::BottomLeft,
+                    xdg_positioner::Gravity::BottomRight,
+                );
+            }
+            // The focused row is scrolled out of view.
+            Message::MenuBounds(_) => return self.open_menu(),
+            Message::Hovered(i) => return self.apply(Input::Focus(i)),
             Message::CursorMoved(pos) => {
                 self.cursor_position = Some(pos);
             }
-            Message::MenuButton(i, context) => {
-                if let Some(tx) = &self.tx {
-                    let _res = tx.blocking_send(launcher::Request::ActivateContext(i, context));
-                }
-
-                if self.menu.take().is_some() {
-                    return commands::popup::destroy_popup(*MENU_ID);
-                }
+            Message::MenuButton(i, context) => return self.apply(Input::MenuOption(i, context)),
+            Message::MenuFocus(path) => return self.apply(Input::MenuFocus(path)),
+            Message::ExpandSubmenu if self.state.menu().is_none() => {
+                return self.update(Message::Grid(GridMove::Right));
+            }
+            Message::CollapseSubmenu if self.state.menu().is_none() => {
+                return self.update(Message::Grid(GridMove::Left));
+            }
+            Message::ExpandSubmenu => return self.move_focus(Input::ExpandSubmenu),
+            Message::CollapseSubmenu => return self.move_focus(Input::CollapseSubmenu),
+            Message::MenuPopup(true) => self.menu_shown = true,
+            Message::MenuPopup(false) | Message::MenuPopupTimeout => {
+                return self.fall_back_to_inline_menu();
             }
             Message::LauncherEvent(e) => match e {
                 launcher::Event::Started(tx) => {
-                    _ = tx.blocking_send(launcher::Request::Search(String::new()));
                     self.tx.replace(tx);
+                    return self.apply(Input::Started);
                 }
-                launcher::Event::Response(response) => match response {
-                    pop_launcher::Response::Close => return self.hide(),
-                    #[allow(clippy::cast_possible_truncation)]
-                    pop_launcher::Response::Context { id, options } => {
-                        if options.is_empty() {
-                            return Command::none();
-                        }
-
-                        self.menu = Some((id, options));
-                        let Some(pos) = self.cursor_position.as_ref() else {
-                            return Command::none();
-                        };
-                        let rect = Rectangle {
-                            x: pos.x.round() as i32,
-                            y: pos.y.round() as i32,
-                            width: 1,
-                            height: 1,
-                        };
-
-                        return commands::popup::get_popup(SctkPopupSettings {
-                            parent: *WINDOW_ID,
-                            id: *MENU_ID,
-                            positioner: SctkPositioner {
-                                size: None,
-                                size_limits: Limits::NONE.min_width(1.0).min_height(1.0).max_width(300.0).max_height(800.0),
-                                anchor_rect: rect,
-                                anchor:
-                                    sctk::reexports::protocols::xdg::shell::client::xdg_positioner::Anchor::Right,
-                                gravity: sctk::reexports::protocols::xdg::shell::client::xdg_positioner::Gravity::Right,
-                                reactive: true,
-                                ..Default::default()
-                            },
-                            grab: true,
-                            parent_size: None,
-                        });
-                    }
-                    pop_launcher::Response::DesktopEntry {
-                        path,
-                        gpu_preference,
-                        action_name,
-                    } => {
-                        if let Some(entry) = cosmic::desktop::load_desktop_file(None, path) {
-                            let exec = if let Some(action_name) = action_name {
-                                entry
-                                    .desktop_actions
-                                    .into_iter()
-                                    .find(|action| action.name == action_name)
-                                    .map(|action| action.exec)
-                            } else {
-                                entry.exec
-                            };
-
-                            let Some(exec) = exec else {
-                                return Command::none();
-                            };
-
-                            return request_token(
-                                Some(String::from(Self::APP_ID)),
-                                Some(*WINDOW_ID),
-                                move |token| {
-                                    cosmic::app::Message::App(Message::ActivationToken(
-                                        token,
-                                        entry.id.to_string(),
-                                        exec,
-                                        gpu_preference,
-                                    ))
-                                },
-                            );
+                launcher::Event::Response(response) => {
+                    self.spans.received(&response);
+                    if let pop_launcher::Response::Update(list) = &response {
+                        for item in list.iter().filter(|item| item.window.is_some()) {
+                            if let Some(IconSource::Name(name) | IconSource::Mime(name)) =
+                                item.icon.as_ref()
+                            {
+                                self.icons.resolve(name);
+                            }
                         }
                     }
-                    pop_launcher::Response::Update(mut list) => {
-                        if self.alt_tab && self.wait_for_result && list.is_empty() {
-                            return self.hide();
-                        }
-                        list.sort_by(|a, b| {
-                            let a = i32::from(a.window.is_none());
-                            let b = i32::from(b.window.is_none());
-                            a.cmp(&b)
-                        });
-                        list.truncate(10);
-                        self.launcher_items.splice(.., list);
-
-                        if self.wait_for_result {
-                            self.wait_for_result = false;
-                            return Command::batch(vec![get_layer_surface(
-                                SctkLayerSurfaceSettings {
-                                    id: *WINDOW_ID,
-                                    keyboard_interactivity: KeyboardInteractivity::Exclusive,
-                                    anchor: Anchor::TOP,
-                                    namespace: "launcher".into(),
-                                    size: None,
-                                    margin: iced::wayland::actions::layer_surface::IcedMargin {
-                                        top: 16,
-                                        ..Default::default()
-                                    },
-                                    size_limits: Limits::NONE
-                                        .min_width(1.0)
-                                        .min_height(1.0)
-                                        .max_width(600.0),
-                                    ..Default::default()
-                                },
-                            )]);
-                        }
+                    return self.apply(Input::Response(response));
+                }
+            },
+            Message::Layer(e, surface) => match e {
+                LayerEvent::Focused => {
+                    if let Some(surface) = surface {
+                        self.blur(&surface);
                     }
-                    pop_launcher::Response::Fill(s) => {
-                        self.input_value = s;
-                        if let Some(tx) = &self.tx {
-                            let _res = tx
-                                .blocking_send(launcher::Request::Search(self.input_value.clone()));
-                        }
+                    return self.apply(Input::Focused);
+                }
+                LayerEvent::Done => {
+                    if self.blur.as_ref().map(Blur::surface) == surface.as_ref() {
+                        self.blur = None;
                     }
-                },
+                }
+                LayerEvent::Unfocused => return self.apply(Input::Unfocused),
             },
-            Message::Layer(e) => match e {
-                LayerEvent::Focused => {}
-                LayerEvent::Unfocused => {
-                    self.last_hide = Instant::now();
-                    return self.hide();
+            Message::CloseContextMenu => return self.apply(Input::CloseContextMenu),
+            Message::CloseQrCode => return self.close_qr_code(),
+            Message::Hide => {
+                if self.qr_code.is_some() {
+                    return self.close_qr_code();
+                }
+                if self.preferences {
+                    return self.close_preferences();
+                }
+                if std::mem::take(&mut self.about) {
+                    return Command::none();
                 }
-                LayerEvent::Done => {}
+                return self.apply(Input::Escape);
+            }
+            Message::KeyboardNav(keyboard_nav::Message::FocusNext) if self.grid_shown() => {
+                self.grid.step(GridMove::Down);
+            }
+            Message::KeyboardNav(keyboard_nav::Message::FocusPrevious) if self.grid_shown() => {
+                self.grid.step(GridMove::Up);
+            }
+            Message::KeyboardNav(e) => match e {
+                keyboard_nav::Message::FocusNext => return self.move_focus(Input::FocusNext),
+                keyboard_nav::Message::FocusPrevious => {
+                    return self.move_focus(Input::FocusPrevious)
+                }
+                keyboard_nav::Message::Escape => return self.apply(Input::ClearQuery),
+                _ => {}
             },
-            Message::CloseContextMenu => {
-                if self.menu.take().is_some() {
-                    return commands::popup::destroy_popup(*MENU_ID);
+            Message::Page(Input::FocusPageNext) if self.grid_shown() => {
+                self.grid.step(GridMove::PageNext);
+            }
+            Message::Page(Input::FocusPagePrevious) if self.grid_shown() => {
+                self.grid.step(GridMove::PagePrevious);
+            }
+            Message::Page(input) => {
+                return Command::batch([self.apply(input), self.scroll_to_focused()]);
+            }
+            Message::Scope(input) => return self.apply(input),
+            Message::ActivationToken(token, request) => {
+                return Command::perform(launch::launch(token, request.clone()), move |stderr| {
+                    cosmic::app::message::app(Message::Launched(request, stderr))
+                });
+            }
+            Message::Launched(request, stderr) => {
+                let hide = self.apply(Input::Hide);
+                let Some(stderr) = stderr.filter(|_| request.watch) else {
+                    return hide;
+                };
+
+                let app_id = request.app_id.clone();
+                self.pending_launches.push((request, stderr));
+                return Command::batch([
+                    hide,
+                    Command::perform(tokio::time::sleep(launch::LAUNCH_TIMEOUT), move |()| {
+                        cosmic::app::message::app(Message::LaunchDeadline(app_id))
+                    }),
+                ]);
+            }
+            Message::LaunchDeadline(app_id) => {
+                let Some(i) = self
+                    .pending_launches
+                    .iter()
+                    .position(|(request, _)| request.app_id == app_id)
+                else {
+                    return Command::none();
+                };
+
+                let (request, stderr) = self.pending_launches.remove(i);
+                // Single-instance applications may only raise a window which is already open.
+                if self.toplevels.values().any(|id| request.matches(id)) {
+                    return Command::none();
                 }
+
+                tracing::warn!("{} did not map a window after launching", request.app_id);
+                let summary = fl!("launch-failed", name = request.name);
+                return Command::perform(launch::notify(summary, stderr.text()), |()| {
+                    cosmic::app::message::none()
+                });
             }
-            Message::Hide => {
-                if self.menu.take().is_some() {
-                    return commands::popup::destroy_popup(*MENU_ID);
+            Message::CheckActivation => return self.apply(Input::CheckActivation),
+            Message::CheckHide => return self.apply(Input::CheckHide),
+            #[cfg(feature = "control-socket")]
+            Message::Control(control::Event::Started(tx)) => self.control_tx = Some(tx),
+            #[cfg(feature = "control-socket")]
+            Message::Control(control::Event::Request(request)) => return self.control(request),
+            Message::Session(session::Event::Locked(locked)) => {
+                let hide = self.apply(Input::Locked(locked));
+                if !locked {
+                    return hide;
+                }
+
+                // Nothing sensitive is left behind for whoever sits down at the locked
+                // session, be it on the clipboard, in a QR code, or in a provider.
+                self.backends.forget();
+                crash::forget();
+                let clear = match self.secret.take() {
+                    Some(secret) => Command::perform(launch::clear_secret(secret), |()| {
+                        cosmic::app::message::none()
+                    }),
+                    None => Command::none(),
+                };
+                return Command::batch([hide, self.close_qr_code(), clear]);
+            }
+            Message::MarqueeTick => {
+                if self.marquee_row == self.state.focused() {
+                    self.marquee += 1;
+                } else {
+                    self.marquee_row = self.state.focused();
+                    self.marquee = 0;
                 }
-                return self.hide();
             }
-            Message::KeyboardNav(e) => {
-                match e {
-                    keyboard_nav::Message::FocusNext => {
-                        self.focus_next();
+            Message::ProviderResults(query, results) => {
+                // Providers may answer after the query changed.
+                if query != self.search || results.is_empty() {
+                    return Command::none();
+                }
+
+                // Results are kept in the order of their providers, whose rank their IDs
+                // follow, but never go above the focused row, which the user may be about
+                // to activate.
+                let pinned = (self.state.focused() + 1).saturating_sub(self.commands.len());
+                let at = self
+                    .provider_results
+                    .iter()
+                    .position(|result| result.id > results[0].id)
+                    .unwrap_or(self.provider_results.len())
+                    .max(pinned.min(self.provider_results.len()));
+                self.provider_results.splice(at..at, results);
+                let mut list = self.commands.clone();
+                list.extend(self.provider_results.iter().cloned());
+                return self.apply(Input::LocalResults(list));
+            }
+            Message::FooterResults(results) => {
+                return self.apply(Input::FooterResults(results));
+            }
+            Message::ProviderAction(Some(Action::QrCode(text))) => {
+                return self.open_qr_code(text);
+            }
+            Message::ProviderAction(Some(Action::Refresh)) => {
+                return self.search_local(self.search.clone());
+            }
+            Message::ProviderAction(Some(Action::Query(query))) => {
+                return self.update(Message::InputChanged(query));
+            }
+            Message::ProviderAction(action) => {
+                let hide = self.apply(Input::Hide);
+                return match action {
+                    Some(Action::Open(uri)) => Command::batch([
+                        hide,
+                        Command::perform(launch::open(uri.into()), |()| {
+                            cosmic::app::message::none()
+                        }),
+                    ]),
+                    Some(Action::Copy(text)) => Command::batch([
+                        hide,
+                        Command::perform(launch::copy(text), |()| cosmic::app::message::none()),
+                    ]),
+                    Some(Action::Type(text)) => Command::batch([
+                        hide,
+                        Command::perform(launch::type_text(text), |()| {
+                            cosmic::app::message::none()
+                        }),
+                    ]),
+                    Some(Action::CopySecret { text, clear_after }) => {
+                        self.secret = Some(text.clone());
+                        Command::batch([
+                            hide,
+                            Command::perform(launch::copy_secret(text, clear_after), |()| {
+                                cosmic::app::message::none()
+                            }),
+                        ])
                     }
-                    keyboard_nav::Message::FocusPrevious => {
-                        self.focus_previous();
+                    // Handled above, without hiding the launcher.
+                    Some(Action::QrCode(_) | Action::Refresh | Action::Query(_)) => Command::none(),
+                    Some(Action::Run(args)) => {
+                        Command::batch([hide, self.run_program(args, None, GpuPreference::Default)])
                     }
-                    keyboard_nav::Message::Escape => {
-                        self.input_value.clear();
-                        if let Some(tx) = &self.tx {
-                            let _res = tx.blocking_send(launcher::Request::Search(String::new()));
-                        }
+                    Some(Action::RunOnDiscreteGpu(args)) => Command::batch([
+                        hide,
+                        self.run_program(args, None, GpuPreference::NonDefault),
+                    ]),
+                    Some(Action::Terminal { dir, args }) => {
+                        let args = self.config.terminal.command_line(args);
+                        Command::batch([hide, self.run_program(args, dir, GpuPreference::Default)])
+                    }
+                    None => hide,
+                };
+            }
+            Message::ProviderContext(id, options) => {
+                return self.apply(Input::LocalContext { id, options });
+            }
+            Message::PipeResolved(query, payload) => {
+                return self.apply(Input::PipeResolved { query, payload });
+            }
+            Message::OpenPreferences => {
+                self.preferences = true;
+                return self.open_menu();
+            }
+            Message::ClosePreferences => return self.close_preferences(),
+            Message::Preference(preference) => {
+                let close = self.close_preferences();
+                let open = |target: std::ffi::OsString| {
+                    Command::perform(launch::open(target), |()| cosmic::app::message::none())
+                };
+                return match preference {
+                    Preference::Open => match Config::dir(Self::APP_ID) {
+                        Some(dir) => Command::batch([close, open(dir.into_os_string())]),
+                        None => close,
+                    },
+                    Preference::ReloadPlugins => {
+                        self.backends = Registry::load(&self.config);
+                        let search = self.search_local(self.search.clone());
+                        Command::batch([close, search])
+                    }
+                    Preference::About => {
+                        self.about = true;
+                        close
                     }
-                    _ => {}
                 };
             }
-            Message::ActivationToken(token, app_id, exec, dgpu) => {
-                return Command::perform(launch(token, app_id, exec, dgpu), |()| {
-                    cosmic::app::message::app(Message::Hide)
+            Message::CopyAbout => {
+                return Command::perform(launch::copy(About::current().to_json()), |()| {
+                    cosmic::app::message::none()
                 });
             }
-            Message::AltTab => {
-                if self.alt_tab {
-                    self.focus_next();
-                } else {
-                    self.alt_tab = true;
-                }
+            Message::EditLaunchEnv => {
+                let close = self.apply(Input::CloseContextMenu);
+                let Some(path) = self.config.launch_env_path(Self::APP_ID) else {
+                    return close;
+                };
+
+                return Command::batch([
+                    close,
+                    Command::perform(launch::open(path.into_os_string()), |()| {
+                        cosmic::app::message::none()
+                    }),
+                ]);
             }
+            Message::Toplevel(event) => match event {
+                toplevel::Event::Started(tx) => self.toplevel_tx = Some(tx),
+                toplevel::Event::Opened {
+                    handle,
+                    app_id,
+                    title,
+                } => {
+                    self.pending_launches
+                        .retain(|(request, _)| !request.matches(&app_id));
+                    self.toplevels.insert(handle, app_id);
+                    self.titles.insert(handle, title);
+                    self.update_running();
+                }
+                toplevel::Event::Closed { handle } => {
+                    self.toplevels.remove(&handle);
+                    self.titles.remove(&handle);
+                    self.update_running();
+                }
+                toplevel::Event::ActiveOutput { name, logical_size } => {
+                    self.output = Some(name);
+                    self.output_size = logical_size;
+                }
+            },
+            Message::AltTab => return self.apply(Input::AltTab),
             Message::AltRelease => {
-                if self.alt_tab || self.alt_grav {
-                    return self.update(Message::Activate(None));
+                // The previewed window is already focused, so there is nothing left to do.
+                if self.previewed.is_some() && self.previewed == self.focused_window() {
+                    return self.apply(Input::Hide);
                 }
+                return self.apply(Input::AltRelease);
             }
-            Message::AltGrav => {
-                if self.alt_grav {
-                    self.focus_next_current();
-                } else {
-                    self.alt_grav = true;
+            Message::AltGrav => return self.apply(Input::AltGrav),
+            Message::Confirm => return self.apply(Input::Confirm),
+            Message::CancelConfirmation => return self.apply(Input::CancelConfirmation),
+            Message::ControlKey(c) if self.modifiers.shift() && c.eq_ignore_ascii_case("p") => {
+                self.perf = match self.perf {
+                    Some(_) => None,
+                    None => Some(components::perf::Hud::default()),
+                };
+            }
+            Message::Gpus(defaults) => self.state.set_gpus(Gpus {
+                submenu: fl!("launch-using"),
+                names: gpu_names(&defaults),
+            }),
+            Message::Frame(at) => {
+                if let Some(perf) = self.perf.as_mut() {
+                    perf.frame(at);
                 }
-            },
+            }
+            Message::ControlKey(c) => {
+                let keys = &self.config.keybindings;
+                if keys.focus_previous.contains(&c) {
+                    return self.move_focus(Input::FocusPrevious);
+                }
+                if keys.focus_next.contains(&c) {
+                    return self.move_focus(Input::FocusNext);
+                }
+                if keys.pin.contains(&c) {
+                    return Command::batch([self.apply(Input::Pin), self.scroll_to_focused()]);
+                }
+                // Ctrl + 1 activates the first result, and Ctrl + 0 the tenth.
+                if let Ok(n) = c.parse::<usize>() {
+                    return self.apply(Input::Activate(Some((n + 9) % 10)));
+                }
+            }
+            Message::ConfigUpdated(config) => {
+                let output = self.output.as_deref();
+                let resized = config.max_width(output) != self.config.max_width(output);
+                self.config = config;
+                self.apply_config();
+                if resized && self.state.is_active() && !self.state.is_waiting_for_result() {
+                    return self.resize_surface();
+                }
+            }
         }
         Command::none()
     }
@@ -567,43 +1860,26 @@ impl cosmic::Application for CosmicLauncher {
         msg: cosmic::app::DbusActivationMessage,
     ) -> iced::Command<cosmic::app::Message<Self::Message>> {
         match msg.msg {
-            DbusActivationDetails::Activate => {
-                if self.active_surface || self.wait_for_result {
-                    return self.hide();
-                } else if self.last_hide.elapsed().as_millis() > 100 {
-                    if let Some(tx) = &self.tx {
-                        let _res = tx.blocking_send(launcher::Request::Search(String::new()));
-                    } else {
-                        tracing::info!("NOT FOUND");
-                    }
-
-                    self.input_value = String::new();
-                    self.active_surface = true;
-                    self.wait_for_result = true;
-                    return Command::none();
-                }
-            }
+            DbusActivationDetails::Activate => return self.apply(Input::Toggle),
             DbusActivationDetails::ActivateAction { action, .. } => {
-                if LauncherCommands::from_str(&action).is_err() {
-                    return Command::none();
-                }
-
-                if let Some(tx) = &self.tx {
-                    let _res = tx.blocking_send(launcher::Request::Search(String::new()));
-                } else {
-                    tracing::info!("NOT FOUND");
-                }
-                if self.active_surface {
-                    if self.launcher_items.is_empty() {
-                        return cosmic::command::message(cosmic::app::message::app(Message::Hide));
+                match LauncherCommands::from_str(&action) {
+                    Ok(LauncherCommands::AltTab) => return self.apply(Input::Action(action)),
+                    Ok(LauncherCommands::Profile { name }) => {
+                        tracing::info!("switching to profile {name:?}");
+                        self.profile = name;
+                        self.remember_profile();
+                        self.apply_config();
+                    }
+                    Ok(LauncherCommands::Search { query }) => {
+                        let show = if self.state.is_active() {
+                            Command::none()
+                        } else {
+                            self.apply(Input::Toggle)
+                        };
+                        return Command::batch([show, self.apply(Input::InputChanged(query))]);
                     }
-                    return cosmic::command::message(cosmic::app::message::app(Message::AltTab));
+                    Ok(LauncherCommands::Doctor) | Err(_) => {}
                 }
-
-                self.input_value = action;
-                self.active_surface = true;
-                self.wait_for_result = true;
-                return cosmic::command::message(cosmic::app::message::app(Message::AltTab));
             }
             DbusActivationDetails::Open { .. } => {}
         }
@@ -617,25 +1893,32 @@ impl cosmic::Application for CosmicLauncher {
     #[allow(clippy::too_many_lines)]
     fn view_window(&self, id: SurfaceId) -> Element<Self::Message> {
         if id == *WINDOW_ID {
-            let launcher_entry = text_input::search_input(
-                "Type to search apps or type “?” for more options...",
-                &self.input_value,
-            )
-            .on_input(Message::InputChanged)
-            .on_paste(Message::InputChanged)
-            .on_submit(Message::Activate(None))
-            .style(cosmic::theme::TextInput::Custom {
-                active: Box::new(|theme| theme.focused(&cosmic::theme::TextInput::Search)),
-                error: Box::new(|theme| theme.focused(&cosmic::theme::TextInput::Search)),
-                hovered: Box::new(|theme| theme.focused(&cosmic::theme::TextInput::Search)),
-                focused: Box::new(|theme| theme.focused(&cosmic::theme::TextInput::Search)),
-                disabled: Box::new(|theme| theme.disabled(&cosmic::theme::TextInput::Search)),
-            })
-            .id(INPUT_ID.clone())
-            .always_active();
+            let placeholder = match self.state.mode() {
+                Mode::Insert => fl!("insert-mode"),
+                Mode::Normal => fl!("normal-mode"),
+            };
+            let launcher_entry = text_input::search_input(&placeholder, self.state.input())
+                .on_input(Message::InputChanged)
+                .on_paste(Message::InputChanged)
+                .on_submit(Message::Activate(None))
+                .style(cosmic::theme::TextInput::Custom {
+                    active: Box::new(|theme| theme.focused(&cosmic::theme::TextInput::Search)),
+                    error: Box::new(|theme| theme.focused(&cosmic::theme::TextInput::Search)),
+                    hovered: Box::new(|theme| theme.focused(&cosmic::theme::TextInput::Search)),
+                    focused: Box::new(|theme| theme.focused(&cosmic::theme::TextInput::Search)),
+                    disabled: Box::new(|theme| theme.disabled(&cosmic::theme::TextInput::Search)),
+                })
+                .id(INPUT_ID.clone())
+                .always_active();
 
+            let style = self.config.style;
+            let compact = self.config.density == Density::Compact;
+            // Compact rows show the first line of the name and the description only.
+            let lines = if compact { 1 } else { usize::MAX };
+            let hint_alpha = self.hint_alpha();
             let buttons: Vec<_> = self
-                .launcher_items
+                .state
+                .items()
                 .iter()
                 .enumerate()
                 .flat_map(|(i, item)| {
@@ -645,24 +1928,30 @@ impl cosmic::Application for CosmicLauncher {
                         (&item.name, &item.description)
                     };
 
-                    let name = Column::with_children(name.lines().map(|line| {
-                        text(if line.width() > 45 {
-                            format!("{}...", line.unicode_truncate(45).0)
-                        } else {
-                            line.to_string()
-                        })
-                        .horizontal_alignment(Horizontal::Left)
-                        .vertical_alignment(Vertical::Center)
-                        .size(14)
-                        .style(cosmic::theme::Text::Custom(|t| {
-                            cosmic::iced::widget::text::Appearance {
-                                color: Some(t.cosmic().on_bg_color().into()),
-                            }
-                        }))
-                        .into()
-                    }));
+                    let (overflow, scroll) = match self.config.title_overflow {
+                        TitleOverflow::Wrap if compact => (TitleOverflow::Truncate, None),
+                        overflow if item.window.is_some() => {
+                            let focused = i == self.state.focused();
+                            (overflow, focused.then_some(self.marquee))
+                        }
+                        _ => (TitleOverflow::Truncate, None),
+                    };
 
-                    let desc = Column::with_children(desc.lines().map(|line| {
+                    let name = Column::with_children(
+                        name.lines()
+                            .flat_map(|line| title::fit(line, TITLE_WIDTH, overflow, scroll))
+                            .take(lines)
+                            .map(|line| {
+                                text(line)
+                                    .horizontal_alignment(Horizontal::Left)
+                                    .vertical_alignment(Vertical::Center)
+                                    .size(style.result.name_size)
+                                    .style(style.result.text())
+                                    .into()
+                            }),
+                    );
+
+                    let desc = Column::with_children(desc.lines().take(lines).map(|line| {
                         text(if line.width() > 60 {
                             format!("{}...", line.unicode_truncate(60).0)
                         } else {
@@ -670,17 +1959,17 @@ impl cosmic::Application for CosmicLauncher {
                         })
                         .horizontal_alignment(Horizontal::Left)
                         .vertical_alignment(Vertical::Center)
-                        .size(10)
-                        .style(theme::Text::Custom(|t| {
-                            cosmic::iced::widget::text::Appearance {
-                                color: Some(t.cosmic().on_bg_color().into()),
-                            }
-                        }))
+                        .size(style.result.description_size)
+                        .style(if compact {
+                            style.result.dimmed()
+                        } else {
+                            style.result.text()
+                        })
                         .into()
                     }));
 
                     let mut button_content = Vec::new();
-                    if !self.alt_tab {
+                    if !self.state.is_alt_tab() {
                         if let Some(source) = item.category_icon.as_ref() {
                             let name = match source {
                                 IconSource::Name(name) | IconSource::Mime(name) => name,
@@ -698,48 +1987,84 @@ impl cosmic::Application for CosmicLauncher {
                             );
                         }
                     }
-                    if let Some(source) = item.icon.as_ref() {
-                        let name = match source {
-                            IconSource::Name(name) | IconSource::Mime(name) => name,
-                        };
+                    let icon_name = match item.icon.as_ref() {
+                        Some(IconSource::Name(name) | IconSource::Mime(name)) => {
+                            Some(name.as_ref())
+                        }
+                        // Windows without an icon still get the generic application icon.
+                        None if item.window.is_some() => Some("application-default"),
+                        None => None,
+                    };
+                    if let Some(name) = icon_name {
+                        let size = if compact { 16.0 } else { 32.0 };
                         button_content.push(
-                            icon(
-                                from_name(name.clone())
-                                    .size(64)
-                                    .fallback(Some(IconFallback::Names(vec![
-                                        "application-default".into(),
-                                        "application-x-executable".into(),
-                                    ])))
-                                    .into(),
+                            icon(self.icons.handle(name, 64))
+                                .width(Length::Fixed(size))
+                                .height(Length::Fixed(size))
+                                .into(),
+                        );
+                    }
+
+                    button_content.push(if compact {
+                        row![name, desc]
+                            .spacing(8)
+                            .align_items(Alignment::Center)
+                            .width(Length::FillPortion(4))
+                            .into()
+                    } else {
+                        column![name, desc].width(Length::FillPortion(4)).into()
+                    });
+                    if self.running_window(item).is_some() {
+                        button_content.push(components::badge::running());
+                    }
+                    // The shortcut hint gives way to the state of an activation of the row, and
+                    // on the focused row to what Shift + Enter does.
+                    let secondary = (i == self.state.focused()
+                        && self.config.keyboard_hints != KeyboardHints::Never)
+                        .then(|| self.secondary(item))
+                        .flatten();
+                    let hint = match (self.state.activation(), secondary) {
+                        (Some(activation), _) if activation.row == i && activation.timed_out => {
+                            Some((fl!("not-responding"), 1.0))
+                        }
+                        (Some(activation), _) if activation.row == i => {
+                            Some((fl!("starting"), 1.0))
+                        }
+                        (_, Some(secondary)) => {
+                            Some((fl!("secondary-hint", action = secondary.name()), 1.0))
+                        }
+                        _ => hint_alpha.map(|alpha| (format!("Ctrl + {}", (i + 1) % 10), alpha)),
+                    };
+                    if let Some((hint, alpha)) = hint {
+                        button_content.push(
+                            container(
+                                text(hint)
+                                    .size(style.result.hint_size)
+                                    .vertical_alignment(Vertical::Center)
+                                    .horizontal_alignment(Horizontal::Right)
+                                    .style(style.result.faded(alpha)),
                             )
-                            .width(Length::Fixed(32.0))
-                            .height(Length::Fixed(32.0))
+                            .width(Length::FillPortion(1))
+                            .center_y()
+                            .align_y(Vertical::Center)
+                            .align_x(Horizontal::Right)
+                            .padding(if compact { [0, 16] } else { [8, 16] })
                             .into(),
                         );
                     }
-
-                    button_content.push(column![name, desc].width(Length::FillPortion(4)).into());
-                    button_content.push(
-                        container(
-                            text(format!("Ctrl + {}", (i + 1) % 10))
-                                .size(14)
-                                .vertical_alignment(Vertical::Center)
-                                .horizontal_alignment(Horizontal::Right)
-                                .style(theme::Text::Custom(|t| {
-                                    cosmic::iced::widget::text::Appearance {
-                                        color: Some(t.cosmic().on_bg_color().into()),
-                                    }
-                                })),
-                        )
-                        .width(Length::FillPortion(1))
-                        .center_y()
-                        .align_y(Vertical::Center)
-                        .align_x(Horizontal::Right)
-                        .padding([8, 16])
-                        .into(),
-                    );
-                    let is_focused = i == self.focused;
-                    let btn = mouse_area(
+                    let is_focused = i == self.state.focused();
+                    if style.result.highlight == Highlight::Bar {
+                        button_content.insert(
+                            0,
+                            container(vertical_space(Length::Fixed(24.0)))
+                                .width(Length::Fixed(BAR_WIDTH))
+                                .style(Container::Custom(Box::new(move |theme| {
+                                    style.result.bar(theme, is_focused)
+                                })))
+                                .into(),
+                        );
+                    }
+                    let mut btn = mouse_area(
                         cosmic::widget::button(
                             row(button_content)
                                 .spacing(8)
@@ -748,154 +2073,200 @@ impl cosmic::Application for CosmicLauncher {
                         .id(RESULT_IDS[i].clone())
                         .width(Length::Fill)
                         .on_press(Message::Activate(Some(i)))
-                        .padding([8, 16])
+                        .padding(if compact {
+                            [0, style.result.padding[1]]
+                        } else {
+                            style.result.padding
+                        })
+                        .height(if compact {
+                            Length::Fixed(COMPACT_ROW_HEIGHT)
+                        } else {
+                            Length::Shrink
+                        })
                         .style(Button::Custom {
                             active: Box::new(move |focused, theme| {
-                                let focused = is_focused || focused;
-                                let rad_s = theme.cosmic().corner_radii.radius_s;
-                                let a = if focused {
-                                    button::StyleSheet::hovered(
-                                        theme,
-                                        focused,
-                                        focused,
-                                        &Button::Text,
-                                    )
-                                } else {
-                                    button::StyleSheet::active(
-                                        theme,
-                                        focused,
-                                        focused,
-                                        &Button::Text,
-                                    )
-                                };
-                                button::Appearance {
-                                    border_radius: rad_s.into(),
-                                    outline_width: 0.0,
-                                    ..a
-                                }
+                                style.result.button(
+                                    theme,
+                                    Interaction::Active,
+                                    is_focused || focused,
+                                )
                             }),
                             hovered: Box::new(move |focused, theme| {
-                                let focused = is_focused || focused;
-                                let rad_s = theme.cosmic().corner_radii.radius_s;
-
-                                let text = button::StyleSheet::hovered(
+                                style.result.button(
                                     theme,
-                                    focused,
-                                    focused,
-                                    &Button::Text,
-                                );
-                                button::Appearance {
-                                    border_radius: rad_s.into(),
-                                    outline_width: 0.0,
-                                    ..text
-                                }
+                                    Interaction::Hovered,
+                                    is_focused || focused,
+                                )
                             }),
-                            disabled: Box::new(|theme| {
-                                let rad_s = theme.cosmic().corner_radii.radius_s;
-
-                                let text = button::StyleSheet::disabled(theme, &Button::Text);
-                                button::Appearance {
-                                    border_radius: rad_s.into(),
-                                    outline_width: 0.0,
-                                    ..text
-                                }
+                            disabled: Box::new(move |theme| {
+                                style.result.button(theme, Interaction::Disabled, false)
                             }),
                             pressed: Box::new(move |focused, theme| {
-                                let focused = is_focused || focused;
-                                let rad_s = theme.cosmic().corner_radii.radius_s;
-
-                                let text = button::StyleSheet::pressed(
+                                style.result.button(
                                     theme,
-                                    focused,
-                                    focused,
-                                    &Button::Text,
-                                );
-                                button::Appearance {
-                                    border_radius: rad_s.into(),
-                                    outline_width: 0.0,
-                                    ..text
-                                }
+                                    Interaction::Pressed,
+                                    is_focused || focused,
+                                )
                             }),
                         }),
                     )
                     .on_right_release(Message::Context(i));
-                    if i == self.launcher_items.len() - 1 {
-                        vec![btn.into()]
-                    } else {
-                        vec![btn.into(), divider::horizontal::light().into()]
+                    if self.config.focus_on_hover {
+                        btn = btn.on_enter(Message::Hovered(i));
+                    }
+                    let row = match self.state.confirmation() {
+                        Some(confirmation) if confirmation.row == i => {
+                            components::confirm::confirm_row(
+                                &confirmation.action,
+                                Message::Confirm,
+                                Message::CancelConfirmation,
+                            )
+                        }
+                        _ => btn.into(),
+                    };
+                    let row: Element<_> = container(row).id(RESULT_ROW_IDS[i].clone()).into();
+                    // Pins are set apart from the results below them by a heavier divider.
+                    let pins = self.state.pins().len();
+                    let mut rows: Vec<Element<_>> = Vec::new();
+                    if i == 0 && pins > 0 {
+                        rows.push(text::caption(fl!("pinned")).into());
                     }
+                    rows.push(row);
+                    if i + 1 == pins && i + 1 < self.state.items().len() {
+                        rows.push(divider::horizontal::default().into());
+                    } else if i + 1 < self.state.items().len() {
+                        rows.push(divider::horizontal::light().into());
+                    }
+                    rows
                 })
                 .collect();
 
-            let mut content = if self.alt_tab {
-                Column::new().max_width(600).spacing(16)
+            let mut content = if self.state.is_alt_tab() {
+                Column::new().max_width(self.max_width()).spacing(16)
             } else {
-                column![launcher_entry].max_width(600).spacing(16)
+                column![launcher_entry]
+                    .max_width(self.max_width())
+                    .spacing(16)
             };
+            if !self.state.is_alt_tab() && !self.state.scopes().is_empty() {
+                content = content.push(components::scopes::view(
+                    self.state.scopes(),
+                    self.state.scope_index(),
+                    |i| Message::Scope(Input::Scope(i)),
+                ));
+            }
 
-            if !buttons.is_empty() {
-                content = content.push(components::list::column(buttons));
+            if self.about {
+                content = content.push(components::about::view(
+                    &About::current(),
+                    Message::CopyAbout,
+                ));
+            } else if self.grid_shown() {
+                content = content.push(components::grid::view(
+                    &self.grid,
+                    &self.grid_apps,
+                    &self.icons,
+                    Message::GridActivate,
+                ));
+            } else if !buttons.is_empty() {
+                content = content
+                    .push(scrollable(components::list::column(buttons)).id(RESULTS_ID.clone()));
+                if self.state.items().len() > PAGE_SIZE {
+                    content = content.push(text::caption(fl!(
+                        "result-count",
+                        count = self.state.items().len()
+                    )));
+                }
+            } else if !self.state.is_alt_tab()
+                && !self.state.is_waiting_for_result()
+                && !self.state.input().trim().is_empty()
+            {
+                content = content.push(components::empty::view(self.state.input()));
+            }
+
+            if let Some(perf) = self.perf.as_ref() {
+                content =
+                    content.push(perf.view(self.spans.search_latency(), self.state.items().len()));
             }
 
             let window = container(content)
-                .style(Container::Custom(Box::new(|theme| container::Appearance {
-                    text_color: Some(theme.cosmic().on_bg_color().into()),
-                    icon_color: Some(theme.cosmic().on_bg_color().into()),
-                    background: Some(Color::from(theme.cosmic().background.base).into()),
-                    border: Border {
-                        radius: theme.cosmic().corner_radii.radius_m.into(),
-                        width: 1.0,
-                        color: theme.cosmic().bg_divider().into(),
-                    },
-                    shadow: Shadow::default(),
+                .style(Container::Custom(Box::new(move |theme| {
+                    style.window.appearance(theme)
                 })))
-                .padding([24, 32]);
+                .padding(style.window.padding);
+
+            // Menus are drawn over the results when the compositor cannot show popups.
+            let window: Element<_> = match self.menu_view().filter(|_| self.inline_menu) {
+                Some(menu) => popover(window)
+                    .popup(menu)
+                    .position(popover::Position::Center)
+                    .into(),
+                None => window.into(),
+            };
 
-            return if self.menu.is_some() {
+            return if self.state.menu().is_some() {
                 mouse_area(window)
                     .on_release(Message::CloseContextMenu)
                     .on_right_release(Message::CloseContextMenu)
                     .into()
+            } else if self.preferences {
+                mouse_area(window)
+                    .on_release(Message::ClosePreferences)
+                    .on_right_release(Message::ClosePreferences)
+                    .into()
             } else {
-                window.into()
+                // Results take their own right clicks, for their context options.
+                mouse_area(window)
+                    .on_right_release(Message::OpenPreferences)
+                    .into()
             };
         }
 
         if id == *MENU_ID {
-            let Some((i, options)) = self.menu.as_ref() else {
+            return self.menu_view().unwrap_or_else(|| {
+                container(horizontal_space(Length::Fixed(1.0)))
+                    .width(Length::Fixed(1.0))
+                    .height(Length::Fixed(1.0))
+                    .into()
+            });
+        }
+
+        if id == *QR_ID {
+            let Some((shared, handle)) = self.qr_code.as_ref() else {
                 return container(horizontal_space(Length::Fixed(1.0)))
                     .width(Length::Fixed(1.0))
                     .height(Length::Fixed(1.0))
                     .into();
             };
-            let list_column = Column::with_children(options.iter().map(|option| {
-                menu_button(text::body(&option.name))
-                    .on_press(Message::MenuButton(*i, option.id))
-                    .into()
-            }))
-            .padding([8, 0]);
-
-            return container(
-                container(scrollable(list_column)).style(theme::Container::custom(|theme| {
-                    let cosmic = theme.cosmic();
-                    let corners = cosmic.corner_radii;
-                    ContainerAppearance {
-                        text_color: Some(cosmic.background.on.into()),
-                        background: Some(Color::from(cosmic.background.base).into()),
-                        border: Border {
-                            radius: corners.radius_m.into(),
-                            width: 1.0,
-                            color: cosmic.background.divider.into(),
-                        },
-                        shadow: Shadow::default(),
-                        icon_color: Some(cosmic.background.on.into()),
-                    }
-                })),
-            )
-            .width(Length::Shrink)
-            .height(Length::Shrink)
-            .align_x(Horizontal::Center)
-            .align_y(Vertical::Top)
+            let caption = if shared.width() > 48 {
+                format!("{}...", shared.unicode_truncate(48).0)
+            } else {
+                shared.clone()
+            };
+            let content = column![
+                image(handle.clone()),
+                text::caption(caption).horizontal_alignment(Horizontal::Center),
+            ]
+            .spacing(8)
+            .align_items(Alignment::Center)
+            .padding(16);
+
+            // Clicking anywhere on the code dismisses it.
+            return mouse_area(container(content).style(theme::Container::custom(|theme| {
+                let cosmic = theme.cosmic();
+                ContainerAppearance {
+                    text_color: Some(cosmic.background.on.into()),
+                    background: Some(Color::from(cosmic.background.base).into()),
+                    border: Border {
+                        radius: cosmic.corner_radii.radius_m.into(),
+                        width: 1.0,
+                        color: cosmic.background.divider.into(),
+                    },
+                    shadow: Shadow::default(),
+                    icon_color: Some(cosmic.background.on.into()),
+                }
+            })))
+            .on_press(Message::CloseQrCode)
             .into();
         }
 
@@ -903,12 +2274,58 @@ impl cosmic::Application for CosmicLauncher {
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
-        Subscription::batch(vec![
+        #[allow(unused_mut)]
+        let mut subscriptions = vec![
+            if self.scrolls_title() {
+                iced::time::every(MARQUEE_INTERVAL).map(|_| Message::MarqueeTick)
+            } else {
+                Subscription::none()
+            },
+            if self.perf.is_some() || self.hints_fading() {
+                iced::window::frames().map(Message::Frame)
+            } else {
+                Subscription::none()
+            },
             launcher::subscription(0).map(Message::LauncherEvent),
+            toplevel::subscription(1).map(Message::Toplevel),
+            session::subscription(2).map(Message::Session),
+            cosmic_config::config_subscription::<_, Config>(
+                TypeId::of::<Config>(),
+                Self::APP_ID.into(),
+                Config::VERSION,
+            )
+            .map(|update| {
+                for why in update.errors {
+                    tracing::error!("failed to reload config: {why}");
+                }
+                Message::ConfigUpdated(update.config)
+            }),
             listen_raw(|e, status| match e {
                 cosmic::iced::Event::PlatformSpecific(PlatformSpecific::Wayland(
-                    wayland::Event::Layer(e, ..),
-                )) => Some(Message::Layer(e)),
+                    wayland::Event::Layer(e, surface, _),
+                )) => Some(Message::Layer(e, Some(surface))),
+                cosmic::iced::Event::PlatformSpecific(PlatformSpecific::Wayland(
+                    wayland::Event::Popup(e, _, id),
+                )) if id == *MENU_ID => match e {
+                    PopupEvent::Configured { .. } => Some(Message::MenuPopup(true)),
+                    PopupEvent::Done => Some(Message::MenuPopup(false)),
+                    _ => None,
+                },
+                #[cfg(feature = "window-fallback")]
+                cosmic::iced::Event::Window(id, iced::window::Event::Unfocused)
+                    if id == *WINDOW_ID =>
+                {
+                    Some(Message::Layer(LayerEvent::Unfocused, None))
+                }
+                #[cfg(feature = "window-fallback")]
+                cosmic::iced::Event::Window(id, iced::window::Event::Focused)
+                    if id == *WINDOW_ID =>
+                {
+                    Some(Message::Layer(LayerEvent::Focused, None))
+                }
+                cosmic::iced::Event::Keyboard(iced::keyboard::Event::ModifiersChanged(
+                    modifiers,
+                )) => Some(Message::Modifiers(modifiers)),
                 cosmic::iced::Event::Keyboard(iced::keyboard::Event::KeyReleased {
                     key, ..
                 }) => match key {
@@ -921,18 +2338,8 @@ impl cosmic::Application for CosmicLauncher {
                     modifiers,
                     ..
                 }) => match key {
-                    Key::Character(c) if modifiers.control() && (c == "p" || c == "k") => {
-                        Some(Message::KeyboardNav(keyboard_nav::Message::FocusPrevious))
-                    }
-                    Key::Character(c) if modifiers.control() && (c == "n" || c == "j") => {
-                        Some(Message::KeyboardNav(keyboard_nav::Message::FocusNext))
-                    }
                     Key::Character(c) if modifiers.control() => {
-                        let nums = (0..10)
-                            .map(|n| (n.to_string(), ((n + 10) % 10) - 1))
-                            .collect::<Vec<_>>();
-                        nums.iter()
-                            .find_map(|n| (n.0 == c).then(|| Message::Activate(Some(n.1))))
+                        Some(Message::ControlKey(c.to_string()))
                     }
                     Key::Named(Named::ArrowUp) => {
                         Some(Message::KeyboardNav(keyboard_nav::Message::FocusPrevious))
@@ -940,7 +2347,44 @@ impl cosmic::Application for CosmicLauncher {
                     Key::Named(Named::ArrowDown) => {
                         Some(Message::KeyboardNav(keyboard_nav::Message::FocusNext))
                     }
+                    Key::Named(Named::ArrowRight) if modifiers.control() => {
+                        Some(Message::Scope(Input::ScopeNext))
+                    }
+                    Key::Named(Named::ArrowLeft) if modifiers.control() => {
+                        Some(Message::Scope(Input::ScopePrevious))
+                    }
+                    // Unless they move the cursor of the search field, Left and Right close
+                    // and open submenus of the context menu, and Enter activates its entries.
+                    Key::Named(Named::ArrowRight) if matches!(status, Status::Ignored) => {
+                        Some(Message::ExpandSubmenu)
+                    }
+                    Key::Named(Named::ArrowLeft) if matches!(status, Status::Ignored) => {
+                        Some(Message::CollapseSubmenu)
+                    }
+                    // The search field is empty while the app grid is shown, so it has no
+                    // cursor to move.
+                    Key::Named(Named::ArrowRight) => Some(Message::Grid(GridMove::Right)),
+                    Key::Named(Named::ArrowLeft) => Some(Message::Grid(GridMove::Left)),
+                    Key::Named(Named::Enter) if matches!(status, Status::Ignored) => {
+                        Some(Message::Activate(None))
+                    }
+                    Key::Named(Named::ContextMenu) => Some(Message::ContextKey),
+                    Key::Named(Named::F10) if modifiers.shift() => Some(Message::ContextKey),
+                    Key::Named(Named::PageUp) => Some(Message::Page(Input::FocusPagePrevious)),
+                    Key::Named(Named::PageDown) => Some(Message::Page(Input::FocusPageNext)),
+                    // Without Ctrl, Home and End move the cursor of the search field first.
+                    Key::Named(Named::Home)
+                        if modifiers.control() || matches!(status, Status::Ignored) =>
+                    {
+                        Some(Message::Page(Input::FocusFirst))
+                    }
+                    Key::Named(Named::End)
+                        if modifiers.control() || matches!(status, Status::Ignored) =>
+                    {
+                        Some(Message::Page(Input::FocusLast))
+                    }
                     Key::Named(Named::Escape) => Some(Message::Hide),
+                    Key::Named(Named::Tab) if modifiers.shift() => Some(Message::ShiftTabPress),
                     Key::Named(Named::Tab) => Some(Message::TabPress),
                     Key::Named(Named::Backspace)
                         if matches!(status, Status::Ignored) && modifiers.is_empty() =>
@@ -954,6 +2398,13 @@ impl cosmic::Application for CosmicLauncher {
                 }
                 _ => None,
             }),
-        ])
+        ];
+
+        #[cfg(feature = "control-socket")]
+        if let Some(path) = self.control_socket.clone() {
+            subscriptions.push(control::subscription(3, path).map(Message::Control));
+        }
+
+        Subscription::batch(subscriptions)
     }
 }
@@ -0,0 +1,191 @@
+//! Switching between dark and light mode, and applying saved theme presets, for queries such
+//! as `dark`, `light`, or `theme`.
+//!
+//! Both are written to the configuration of COSMIC's theme, which the whole desktop follows.
+//! Presets are themes exported from the Appearance page of COSMIC Settings and saved in
+//! `cosmic/themes` of the data directories, such as `~/.local/share/cosmic/themes`.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use cosmic::cosmic_config::{self, ConfigGet, ConfigSet, CosmicConfigEntry};
+use cosmic::cosmic_theme::{Theme, ThemeBuilder};
+use futures::future::BoxFuture;
+
+use super::{Action, Item, Provider};
+use crate::fl;
+
+/// Shortest query which lists anything, so this does not show up for every other search.
+const MIN_QUERY: usize = 3;
+
+/// Configuration of whether COSMIC is dark.
+const MODE_CONFIG: &str = "com.system76.CosmicTheme.Mode";
+const MODE_CONFIG_VERSION: u64 = 1;
+
+/// Directory of presets in each data directory.
+const PRESETS: &str = "cosmic/themes";
+
+#[derive(Debug, Clone)]
+enum Appearance {
+    /// Switches to dark mode, or to light mode.
+    Mode {
+        dark: bool,
+    },
+    Preset {
+        name: String,
+        path: PathBuf,
+    },
+}
+
+impl Appearance {
+    fn keywords(&self) -> Vec<String> {
+        let mut keywords = vec!["theme".to_string(), "appearance".into()];
+        match self {
+            Self::Mode { dark } => {
+                keywords.push(if *dark { "dark" } else { "light" }.into());
+                keywords.push("mode".into());
+            }
+            Self::Preset { name, .. } => {
+                keywords.push("preset".into());
+                keywords.extend(name.split_whitespace().map(str::to_lowercase));
+            }
+        }
+        keywords
+    }
+
+    fn item(&self) -> Item {
+        match self {
+            Self::Mode { dark: true } => Item {
+                name: fl!("appearance-dark-mode"),
+                description: fl!("settings-appearance"),
+                icon: Some("weather-clear-night-symbolic".into()),
+                open: None,
+            },
+            Self::Mode { dark: false } => Item {
+                name: fl!("appearance-light-mode"),
+                description: fl!("settings-appearance"),
+                icon: Some("weather-clear-symbolic".into()),
+                open: None,
+            },
+            Self::Preset { name, .. } => Item {
+                name: name.clone(),
+                description: fl!("appearance-preset"),
+                icon: Some("preferences-desktop-appearance-symbolic".into()),
+                open: None,
+            },
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Appearances;
+
+impl Appearances {
+    async fn matching(&self, query: &str) -> Vec<Appearance> {
+        let query = query.trim().to_lowercase();
+        if query.chars().count() < MIN_QUERY {
+            return Vec::new();
+        }
+        let words = query.split_whitespace().collect::<Vec<_>>();
+        let matches = |appearance: &Appearance| {
+            let keywords = appearance.keywords();
+            words
+                .iter()
+                .all(|word| keywords.iter().any(|keyword| keyword.starts_with(word)))
+        };
+
+        // Only the mode which is not already active is offered.
+        let mut appearances = vec![Appearance::Mode { dark: !is_dark() }];
+        appearances.extend(
+            tokio::task::spawn_blocking(presets)
+                .await
+                .unwrap_or_default(),
+        );
+        appearances.into_iter().filter(matches).collect()
+    }
+}
+
+fn is_dark() -> bool {
+    cosmic_config::Config::new(MODE_CONFIG, MODE_CONFIG_VERSION)
+        .ok()
+        .and_then(|config| config.get::<bool>("is_dark").ok())
+        .unwrap_or(true)
+}
+
+fn set_dark(dark: bool) -> Result<(), cosmic_config::Error> {
+    cosmic_config::Config::new(MODE_CONFIG, MODE_CONFIG_VERSION)?.set("is_dark", dark)
+}
+
+/// The saved presets, named after their files.
+fn presets() -> Vec<Appearance> {
+    let Ok(dirs) = xdg::BaseDirectories::new() else {
+        return Vec::new();
+    };
+    let mut presets = std::iter::once(dirs.get_data_home())
+        .chain(dirs.get_data_dirs())
+        .filter_map(|dir| std::fs::read_dir(dir.join(PRESETS)).ok())
+        .flat_map(|entries| entries.flatten().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+        .map(|path| Appearance::Preset {
+            name: path
+                .file_stem()
+                .map(|name| name.to_string_lossy().replace(['-', '_'], " "))
+                .unwrap_or_default(),
+            path,
+        })
+        .collect::<Vec<_>>();
+    presets.sort_by_key(|preset| preset.item().name.to_lowercase());
+    // A preset of the user hides one of the same name installed for everyone.
+    presets.dedup_by_key(|preset| preset.item().name.to_lowercase());
+    presets
+}
+
+/// Applies a preset the way COSMIC Settings imports one: as the theme of its mode, which
+/// becomes the active mode.
+fn apply(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let builder: ThemeBuilder = ron::from_str(&std::fs::read_to_string(path)?)?;
+    let dark = builder.palette.is_dark();
+    let (builder_config, theme_config) = if dark {
+        (ThemeBuilder::dark_config()?, Theme::dark_config()?)
+    } else {
+        (ThemeBuilder::light_config()?, Theme::light_config()?)
+    };
+    builder.write_entry(&builder_config)?;
+    builder.build().write_entry(&theme_config)?;
+    set_dark(dark)?;
+    Ok(())
+}
+
+impl Provider for Appearances {
+    fn name(&self) -> &str {
+        "appearance"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .iter()
+                .map(Appearance::item)
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let appearance = self.matching(&query).await.into_iter().nth(index)?;
+            let result = match &appearance {
+                Appearance::Mode { dark } => set_dark(*dark).map_err(Into::into),
+                Appearance::Preset { path, .. } => apply(path),
+            };
+            if let Err(why) = result {
+                tracing::warn!("failed to apply {}: {why}", appearance.item().name);
+            }
+            None
+        })
+    }
+}
@@ -0,0 +1,398 @@
+//! AppImages in the configured directories, found by the name of their desktop entry.
+//!
+//! The desktop entry and icon embedded in each AppImage are extracted once, with the
+//! AppImage's own `--appimage-extract`, into a cache keyed by its path, size, and
+//! modification time. Only AppImages marked executable are listed. The context menu
+//! integrates an AppImage by installing its desktop entry, after which it is listed as an
+//! application instead.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use super::cache::{self, Cache};
+use super::{Action, Item, Provider};
+use crate::{fl, matcher};
+
+/// How long the list of AppImages is reused before the directories are read again.
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Time an AppImage has to extract its desktop entry and icon.
+const EXTRACT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Prefix of the desktop entries of integrated AppImages, as appimaged names them.
+const ENTRY_PREFIX: &str = "appimagekit_";
+
+/// Where AppImages are searched.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppImageConfig {
+    pub enabled: bool,
+    /// Directories searched for AppImages. A leading `~` is the home directory.
+    pub dirs: Vec<String>,
+}
+
+impl Default for AppImageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dirs: vec!["~/Applications".into(), "~/AppImages".into()],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct AppImage {
+    path: PathBuf,
+    name: String,
+    comment: String,
+    /// The extracted icon file.
+    icon: Option<PathBuf>,
+    /// The extracted desktop entry.
+    entry: Option<PathBuf>,
+    /// Name of the desktop entry installed when integrating the AppImage.
+    entry_id: String,
+}
+
+impl AppImage {
+    fn integrated(&self) -> bool {
+        applications_dir().is_some_and(|dir| dir.join(&self.entry_id).exists())
+    }
+}
+
+pub struct AppImages {
+    dirs: Vec<PathBuf>,
+    cache: Cache<Vec<AppImage>>,
+}
+
+impl AppImages {
+    pub fn new(config: &AppImageConfig) -> Self {
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let dirs = config
+            .dirs
+            .iter()
+            .filter_map(|dir| match dir.strip_prefix('~') {
+                Some(rest) => Some(home.as_ref()?.join(rest.trim_start_matches('/'))),
+                None => Some(PathBuf::from(dir)),
+            })
+            .collect();
+
+        Self {
+            dirs,
+            cache: Cache::new(CACHE_TTL),
+        }
+    }
+
+    async fn appimages(&self) -> Arc<Vec<AppImage>> {
+        let dirs = self.dirs.clone();
+        self.cache
+            .get(cache::blocking(move || {
+                let Some(cache) = cache_dir() else {
+                    return Vec::new();
+                };
+                dirs.iter()
+                    .filter_map(|dir| std::fs::read_dir(dir).ok())
+                    .flat_map(|entries| entries.filter_map(Result::ok))
+                    .map(|entry| entry.path())
+                    .filter(|path| {
+                        path.extension()
+                            .is_some_and(|extension| extension.eq_ignore_ascii_case("appimage"))
+                    })
+                    .filter_map(|path| read_appimage(path, &cache))
+                    .collect()
+            }))
+            .await
+    }
+
+    async fn matching(&self, query: &str) -> Vec<AppImage> {
+        let words = matcher::words(query);
+        if words.is_empty() {
+            return Vec::new();
+        }
+
+        self.appimages()
+            .await
+            .iter()
+            .filter(|appimage| !appimage.integrated())
+            .filter(|appimage| matcher::matches_all(&words, &appimage.name))
+            .take(MAX_RESULTS)
+            .cloned()
+            .collect()
+    }
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    xdg::BaseDirectories::with_prefix("cosmic-launcher")
+        .ok()
+        .map(|dirs| dirs.get_cache_home().join("appimages"))
+}
+
+fn applications_dir() -> Option<PathBuf> {
+    xdg::BaseDirectories::new()
+        .ok()
+        .map(|dirs| dirs.get_data_home().join("applications"))
+}
+
+/// Reads an AppImage's desktop entry and icon from the cache, extracting them first if
+/// the AppImage is new or changed.
+fn read_appimage(path: PathBuf, cache: &Path) -> Option<AppImage> {
+    let metadata = std::fs::metadata(&path).ok()?;
+    // AppImages which were not made executable yet could not be launched either.
+    if !metadata.is_file() || metadata.permissions().mode() & 0o111 == 0 {
+        return None;
+    }
+
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    metadata.len().hash(&mut hasher);
+    metadata.modified().ok().hash(&mut hasher);
+    let key = format!("{:016x}", hasher.finish());
+    let dir = cache.join(&key);
+
+    if !dir.exists() && std::fs::create_dir_all(&dir).is_ok() {
+        if let Err(why) = extract(&path, &dir) {
+            tracing::warn!("failed to extract {}: {why}", path.display());
+        }
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut appimage = AppImage {
+        name: stem.clone(),
+        comment: String::new(),
+        icon: ["icon.png", "icon.svg"]
+            .iter()
+            .map(|icon| dir.join(icon))
+            .find(|icon| icon.exists()),
+        entry: None,
+        entry_id: format!("{ENTRY_PREFIX}{key}-{stem}.desktop"),
+        path,
+    };
+
+    let entry = dir.join("entry.desktop");
+    if let Ok(contents) = std::fs::read_to_string(&entry) {
+        for (key, value) in main_group(&contents) {
+            match key {
+                "Name" => appimage.name = value.to_string(),
+                "Comment" => appimage.comment = value.to_string(),
+                _ => {}
+            }
+        }
+        appimage.entry = Some(entry);
+    }
+
+    Some(appimage)
+}
+
+/// Keys and values of the `[Desktop Entry]` group of a desktop entry.
+fn main_group(contents: &str) -> impl Iterator<Item = (&str, &str)> {
+    let mut in_main_group = false;
+    contents.lines().filter_map(move |line| {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_main_group = line == "[Desktop Entry]";
+            return None;
+        }
+        let (key, value) = line.split_once('=').filter(|_| in_main_group)?;
+        Some((key.trim(), value.trim()))
+    })
+}
+
+/// Extracts the desktop entry and icon at the root of an AppImage into `dir`.
+fn extract(appimage: &Path, dir: &Path) -> std::io::Result<()> {
+    let root = dir.join("squashfs-root");
+    let extract = |pattern: &str| -> std::io::Result<()> {
+        let mut child = Command::new(appimage)
+            .args(["--appimage-extract", pattern])
+            .current_dir(dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let started = Instant::now();
+        while child.try_wait()?.is_none() {
+            if started.elapsed() > EXTRACT_TIMEOUT {
+                let _res = child.kill();
+                let _res = child.wait();
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "extraction timed out",
+                ));
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        Ok(())
+    };
+
+    let result = (|| {
+        extract("*.desktop")?;
+        extract(".DirIcon")?;
+
+        // `.DirIcon` is usually a link to the icon file, which has to be extracted as well.
+        let mut icon = root.join(".DirIcon");
+        while let Ok(target) = std::fs::read_link(&icon) {
+            let target = target.to_string_lossy().trim_start_matches('/').to_string();
+            extract(&target)?;
+            icon = root.join(target);
+        }
+
+        if let Ok(contents) = std::fs::read(&icon) {
+            let svg = contents.iter().find(|byte| !byte.is_ascii_whitespace()) == Some(&b'<');
+            std::fs::write(
+                dir.join(if svg { "icon.svg" } else { "icon.png" }),
+                contents,
+            )?;
+        }
+
+        let entry = std::fs::read_dir(&root)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .find(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "desktop")
+            });
+        if let Some(entry) = entry {
+            std::fs::copy(entry, dir.join("entry.desktop"))?;
+        }
+        Ok(())
+    })();
+
+    let _res = std::fs::remove_dir_all(&root);
+    result
+}
+
+/// Installs a desktop entry launching the AppImage, based on its embedded one.
+fn integrate(appimage: &AppImage) -> std::io::Result<()> {
+    let dir = applications_dir()
+        .ok_or_else(|| std::io::Error::other("no data directory to install the entry in"))?;
+    std::fs::create_dir_all(&dir)?;
+
+    let exec = shlex::try_quote(&appimage.path.to_string_lossy())
+        .map_err(std::io::Error::other)?
+        .into_owned();
+    let icon = appimage
+        .icon
+        .as_ref()
+        .map(|icon| icon.display().to_string())
+        .unwrap_or_else(|| "application-x-executable".into());
+
+    let embedded = appimage
+        .entry
+        .as_ref()
+        .and_then(|entry| std::fs::read_to_string(entry).ok());
+    let mut entry = String::new();
+    match embedded {
+        Some(embedded) => {
+            let mut in_main_group = false;
+            for line in embedded.lines() {
+                if line.trim().starts_with('[') {
+                    in_main_group = line.trim() == "[Desktop Entry]";
+                }
+                let key = line.split_once('=').map(|(key, _)| key.trim());
+                let line = match key {
+                    // Actions run the AppImage with the arguments of their own `Exec`.
+                    Some("Exec") => match line.split_once(' ') {
+                        Some((_, args)) => format!("Exec={exec} {args}"),
+                        None => format!("Exec={exec}"),
+                    },
+                    Some("Icon") => format!("Icon={icon}"),
+                    Some("TryExec") if in_main_group => continue,
+                    _ => line.to_string(),
+                };
+                entry.push_str(&line);
+                entry.push('\n');
+            }
+        }
+        None => {
+            entry = format!(
+                "[Desktop Entry]\nType=Application\nName={}\nExec={exec}\nIcon={icon}\n",
+                appimage.name
+            );
+        }
+    }
+
+    std::fs::write(dir.join(&appimage.entry_id), entry)
+}
+
+impl Provider for AppImages {
+    fn name(&self) -> &str {
+        "appimages"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|appimage| Item {
+                    name: appimage.name,
+                    description: if appimage.comment.is_empty() {
+                        fl!(
+                            "appimage-description",
+                            path = appimage.path.display().to_string()
+                        )
+                    } else {
+                        appimage.comment
+                    },
+                    icon: Some(
+                        appimage
+                            .icon
+                            .map(|icon| icon.display().to_string())
+                            .unwrap_or_else(|| "application-x-executable".into()),
+                    ),
+                    open: None,
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let appimage = self.matching(&query).await.into_iter().nth(index)?;
+            Some(Action::Run(vec![appimage
+                .path
+                .to_string_lossy()
+                .into_owned()]))
+        })
+    }
+
+    fn context(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Vec<String>> {
+        Box::pin(async move {
+            match self.matching(&query).await.into_iter().nth(index) {
+                Some(_) => vec![fl!("appimage-integrate")],
+                None => Vec::new(),
+            }
+        })
+    }
+
+    fn activate_context(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+        _option: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let appimage = self.matching(&query).await.into_iter().nth(index)?;
+            let integrated = tokio::task::spawn_blocking(move || integrate(&appimage)).await;
+            if let Ok(Err(why)) = integrated {
+                tracing::warn!("failed to integrate an AppImage: {why}");
+            }
+            None
+        })
+    }
+}
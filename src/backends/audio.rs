@@ -0,0 +1,329 @@
+//! Audio outputs and inputs of PulseAudio, or of PipeWire through `pipewire-pulse`, for
+//! queries starting with `audio `, or only outputs for `out `.
+//!
+//! Activating a device makes it the default, and its context menu can also move the
+//! streams playing or recording elsewhere onto it.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+use libpulse_binding::callbacks::ListResult;
+use libpulse_binding::context::{self, Context};
+use libpulse_binding::mainloop::standard::{IterateResult, Mainloop};
+use libpulse_binding::operation::{Operation, State as OperationState};
+
+use super::{Action, Item, Provider};
+use crate::{fl, matcher};
+
+const PREFIXES: [(&str, bool); 2] = [("audio ", true), ("out ", false)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Output,
+    Input,
+}
+
+#[derive(Debug, Clone)]
+struct Device {
+    kind: Kind,
+    /// The name PulseAudio knows the device by.
+    name: String,
+    description: String,
+    default: bool,
+}
+
+impl Device {
+    fn icon(&self) -> &'static str {
+        match self.kind {
+            Kind::Output => "audio-speakers-symbolic",
+            Kind::Input => "audio-input-microphone-symbolic",
+        }
+    }
+
+    fn description(&self) -> String {
+        match (self.kind, self.default) {
+            (Kind::Output, true) => fl!("audio-output-default"),
+            (Kind::Output, false) => fl!("audio-output"),
+            (Kind::Input, true) => fl!("audio-input-default"),
+            (Kind::Input, false) => fl!("audio-input"),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Audio;
+
+impl Audio {
+    async fn matching(&self, query: &str) -> Vec<Device> {
+        let query = query.trim_start();
+        let Some((inputs, text)) = PREFIXES
+            .iter()
+            .find_map(|(prefix, inputs)| Some((*inputs, query.strip_prefix(prefix)?)))
+        else {
+            return Vec::new();
+        };
+        let words = matcher::words(text);
+
+        let devices = match tokio::task::spawn_blocking(devices).await {
+            Ok(Ok(devices)) => devices,
+            Ok(Err(why)) => {
+                tracing::debug!("failed to list audio devices: {why}");
+                return Vec::new();
+            }
+            Err(_) => return Vec::new(),
+        };
+
+        devices
+            .into_iter()
+            .filter(|device| inputs || device.kind == Kind::Output)
+            .filter(|device| matcher::matches_all(&words, &device.description))
+            .take(MAX_RESULTS)
+            .collect()
+    }
+
+    async fn run(&self, query: &str, index: usize, option: usize) -> Option<Action> {
+        let device = self.matching(query).await.into_iter().nth(index)?;
+        let move_streams = match option {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+
+        let description = device.description.clone();
+        let result = tokio::task::spawn_blocking(move || make_default(&device, move_streams)).await;
+        if let Ok(Err(why)) = result {
+            tracing::warn!("failed to switch to {description}: {why}");
+        }
+        None
+    }
+}
+
+/// A connection to the sound server, to be driven by iterating its main loop.
+struct Server {
+    // Dropped before the main loop it runs on.
+    context: Context,
+    mainloop: Mainloop,
+}
+
+impl Server {
+    fn connect() -> Result<Self, String> {
+        let mut mainloop = Mainloop::new().ok_or("no main loop")?;
+        let mut context =
+            Context::new(&mainloop, "cosmic-launcher").ok_or("no sound server context")?;
+        context
+            .connect(None, context::FlagSet::NOFLAGS, None)
+            .map_err(|why| format!("{why}"))?;
+
+        loop {
+            if let IterateResult::Quit(_) | IterateResult::Err(_) = mainloop.iterate(true) {
+                return Err("main loop stopped".into());
+            }
+            match context.get_state() {
+                context::State::Ready => break,
+                context::State::Failed | context::State::Terminated => {
+                    return Err("sound server unavailable".into());
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self { context, mainloop })
+    }
+
+    /// Iterates the main loop until the operation's callbacks have all been called.
+    fn wait<C: ?Sized>(&mut self, operation: Operation<C>) -> Result<(), String> {
+        while matches!(operation.get_state(), OperationState::Running) {
+            if let IterateResult::Quit(_) | IterateResult::Err(_) = self.mainloop.iterate(true) {
+                return Err("main loop stopped".into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Outputs and then inputs, leaving out the monitors of outputs, defaults first.
+fn devices() -> Result<Vec<Device>, String> {
+    let mut server = Server::connect()?;
+    let introspect = server.context.introspect();
+
+    let defaults = Rc::new(RefCell::new((String::new(), String::new())));
+    let operation = introspect.get_server_info({
+        let defaults = Rc::clone(&defaults);
+        move |info| {
+            let mut defaults = defaults.borrow_mut();
+            defaults.0 = info.default_sink_name.as_deref().unwrap_or("").to_string();
+            defaults.1 = info
+                .default_source_name
+                .as_deref()
+                .unwrap_or("")
+                .to_string();
+        }
+    });
+    server.wait(operation)?;
+
+    let devices = Rc::new(RefCell::new(Vec::new()));
+    let operation = introspect.get_sink_info_list({
+        let devices = Rc::clone(&devices);
+        move |result| {
+            if let ListResult::Item(sink) = result {
+                if let (Some(name), Some(description)) = (&sink.name, &sink.description) {
+                    devices.borrow_mut().push((
+                        Kind::Output,
+                        name.to_string(),
+                        description.to_string(),
+                    ));
+                }
+            }
+        }
+    });
+    server.wait(operation)?;
+
+    let operation = introspect.get_source_info_list({
+        let devices = Rc::clone(&devices);
+        move |result| {
+            if let ListResult::Item(source) = result {
+                if source.monitor_of_sink.is_some() {
+                    return;
+                }
+                if let (Some(name), Some(description)) = (&source.name, &source.description) {
+                    devices.borrow_mut().push((
+                        Kind::Input,
+                        name.to_string(),
+                        description.to_string(),
+                    ));
+                }
+            }
+        }
+    });
+    server.wait(operation)?;
+
+    let (default_sink, default_source) = defaults.take();
+    let mut devices = devices
+        .take()
+        .into_iter()
+        .map(|(kind, name, description)| Device {
+            default: match kind {
+                Kind::Output => name == default_sink,
+                Kind::Input => name == default_source,
+            },
+            kind,
+            name,
+            description,
+        })
+        .collect::<Vec<_>>();
+
+    devices.sort_by_key(|device| (device.kind == Kind::Input, !device.default));
+    Ok(devices)
+}
+
+/// Makes a device the default for new streams, and moves the existing ones onto it.
+fn make_default(device: &Device, move_streams: bool) -> Result<(), String> {
+    let mut server = Server::connect()?;
+
+    let done = Rc::new(RefCell::new(false));
+    let callback = {
+        let done = Rc::clone(&done);
+        move |success| *done.borrow_mut() = success
+    };
+    let operation = match device.kind {
+        Kind::Output => server.context.set_default_sink(&device.name, callback),
+        Kind::Input => server.context.set_default_source(&device.name, callback),
+    };
+    server.wait(operation)?;
+    if !done.take() {
+        return Err("the sound server refused".into());
+    }
+
+    if !move_streams {
+        return Ok(());
+    }
+
+    let mut introspect = server.context.introspect();
+    let streams = Rc::new(RefCell::new(Vec::new()));
+    match device.kind {
+        Kind::Output => {
+            let operation = introspect.get_sink_input_info_list({
+                let streams = Rc::clone(&streams);
+                move |result| {
+                    if let ListResult::Item(input) = result {
+                        streams.borrow_mut().push(input.index);
+                    }
+                }
+            });
+            server.wait(operation)?;
+        }
+        Kind::Input => {
+            let operation = introspect.get_source_output_info_list({
+                let streams = Rc::clone(&streams);
+                move |result| {
+                    if let ListResult::Item(output) = result {
+                        streams.borrow_mut().push(output.index);
+                    }
+                }
+            });
+            server.wait(operation)?;
+        }
+    }
+
+    for stream in streams.take() {
+        let operation = match device.kind {
+            Kind::Output => introspect.move_sink_input_by_name(stream, &device.name, None),
+            Kind::Input => introspect.move_source_output_by_name(stream, &device.name, None),
+        };
+        server.wait(operation)?;
+    }
+
+    Ok(())
+}
+
+impl Provider for Audio {
+    fn name(&self) -> &str {
+        "audio"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|device| Item {
+                    description: device.description(),
+                    icon: Some(device.icon().into()),
+                    name: device.description,
+                    open: None,
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move { self.run(&query, index, 0).await })
+    }
+
+    fn context(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Vec<String>> {
+        Box::pin(async move {
+            if self.matching(&query).await.len() > index {
+                // In the order of the options `run` takes.
+                vec![fl!("audio-make-default"), fl!("audio-make-default-move")]
+            } else {
+                Vec::new()
+            }
+        })
+    }
+
+    fn activate_context(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+        option: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move { self.run(&query, index, option).await })
+    }
+}
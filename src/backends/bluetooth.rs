@@ -0,0 +1,181 @@
+//! Paired Bluetooth devices for queries starting with `bt `, which activating connects or
+//! disconnects through BlueZ.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+use tokio::sync::OnceCell;
+use zbus::zvariant::{OwnedObjectPath, OwnedValue};
+
+use super::{Action, Item, Provider};
+use crate::{fl, matcher};
+
+const PREFIX: &str = "bt ";
+
+const BLUEZ_BUS: &str = "org.bluez";
+const DEVICE_INTERFACE: &str = "org.bluez.Device1";
+const BATTERY_INTERFACE: &str = "org.bluez.Battery1";
+
+type ManagedObjects = HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>>;
+
+#[derive(Debug, Clone)]
+struct Device {
+    path: OwnedObjectPath,
+    name: String,
+    icon: Option<String>,
+    connected: bool,
+    battery: Option<u8>,
+}
+
+impl Device {
+    fn description(&self) -> String {
+        match (self.connected, self.battery) {
+            (true, Some(battery)) => fl!("bluetooth-battery", battery = battery),
+            (true, None) => fl!("bluetooth-connected"),
+            (false, _) => fl!("bluetooth-disconnected"),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Bluetooth {
+    bus: OnceCell<zbus::Connection>,
+}
+
+impl Bluetooth {
+    async fn matching(&self, query: &str) -> Vec<Device> {
+        let Some(text) = query.trim_start().strip_prefix(PREFIX) else {
+            return Vec::new();
+        };
+        let words = matcher::words(text);
+
+        let devices = match self.devices().await {
+            Ok(devices) => devices,
+            Err(why) => {
+                tracing::debug!("failed to list Bluetooth devices: {why}");
+                return Vec::new();
+            }
+        };
+
+        devices
+            .into_iter()
+            .filter(|device| matcher::matches_all(&words, &device.name))
+            .take(MAX_RESULTS)
+            .collect()
+    }
+
+    async fn bus(&self) -> zbus::Result<&zbus::Connection> {
+        self.bus.get_or_try_init(zbus::Connection::system).await
+    }
+
+    /// Paired devices of every adapter, connected ones first.
+    async fn devices(&self) -> zbus::Result<Vec<Device>> {
+        let objects: ManagedObjects = self
+            .bus()
+            .await?
+            .call_method(
+                Some(BLUEZ_BUS),
+                "/",
+                Some("org.freedesktop.DBus.ObjectManager"),
+                "GetManagedObjects",
+                &(),
+            )
+            .await?
+            .body()
+            .deserialize()?;
+
+        let mut devices = objects
+            .into_iter()
+            .filter_map(|(path, interfaces)| {
+                let device = interfaces.get(DEVICE_INTERFACE)?;
+                let flag = |key: &str| {
+                    device
+                        .get(key)
+                        .and_then(|value| bool::try_from(&**value).ok())
+                        .unwrap_or_default()
+                };
+                let text = |key: &str| {
+                    device
+                        .get(key)
+                        .and_then(|value| <&str>::try_from(&**value).ok())
+                        .map(String::from)
+                };
+
+                if !flag("Paired") {
+                    return None;
+                }
+
+                Some(Device {
+                    name: text("Alias").or_else(|| text("Name"))?,
+                    icon: text("Icon"),
+                    connected: flag("Connected"),
+                    battery: interfaces
+                        .get(BATTERY_INTERFACE)
+                        .and_then(|battery| battery.get("Percentage"))
+                        .and_then(|value| u8::try_from(&**value).ok()),
+                    path,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        devices.sort_by(|a, b| b.connected.cmp(&a.connected).then(a.name.cmp(&b.name)));
+        Ok(devices)
+    }
+
+    async fn toggle(&self, device: &Device) -> zbus::Result<()> {
+        // Names of BlueZ methods, not text shown to the user.
+        let method = if device.connected {
+            "Disconnect"
+        } else {
+            "Connect"
+        };
+        self.bus()
+            .await?
+            .call_method(
+                Some(BLUEZ_BUS),
+                device.path.as_str(),
+                Some(DEVICE_INTERFACE),
+                method,
+                &(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+impl Provider for Bluetooth {
+    fn name(&self) -> &str {
+        "bluetooth"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|device| Item {
+                    description: device.description(),
+                    icon: Some(device.icon.unwrap_or_else(|| "bluetooth".into())),
+                    name: device.name,
+                    open: None,
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let device = self.matching(&query).await.into_iter().nth(index)?;
+            if let Err(why) = self.toggle(&device).await {
+                tracing::warn!("failed to toggle {}: {why}", device.name);
+            }
+            None
+        })
+    }
+}
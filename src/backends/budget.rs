@@ -0,0 +1,149 @@
+//! Time budgets of providers, so that a slow one cannot hold up the results of the others
+//! and make typing lag.
+//!
+//! Providers have [`HARD`] to answer a query, after which their results are dropped. One
+//! which takes longer than [`SOFT`] is demoted for [`DEMOTION`], during which its results
+//! are dropped once [`SOFT`] is over. Providers which ran out of time are written to
+//! `$XDG_STATE_HOME/cosmic-launcher/providers.json`, which `cosmic-launcher doctor` reports.
+//!
+//! Running out of time drops the query, which cancels whatever it awaits, but not the work
+//! a provider moved to a blocking thread with `spawn_blocking`. That work runs to its end
+//! in the background and only its answer is lost, so a provider which blocks for long
+//! still takes up a thread of the blocking pool each time it is queried. Anything a query
+//! should keep, such as a cache, is therefore filled on a task of its own, which dropping
+//! the query does not cancel, like [`super::cache::Cache`] does.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+pub const SOFT: Duration = Duration::from_millis(150);
+pub const HARD: Duration = Duration::from_secs(1);
+const DEMOTION: Duration = Duration::from_secs(60);
+
+const FILE: &str = "cosmic-launcher/providers.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Status {
+    pub name: String,
+    /// Milliseconds the last query took, or `None` if it ran out of time.
+    pub last_answer_ms: Option<u64>,
+    /// Queries which ran out of time since the launcher started.
+    pub timeouts: u32,
+    /// Seconds since the Unix epoch until which the provider is demoted.
+    pub demoted_until: Option<u64>,
+}
+
+impl Status {
+    pub fn is_demoted(&self) -> bool {
+        self.demoted_until.is_some_and(|until| until > now())
+    }
+}
+
+/// Budgets of the providers of a registry, by their position in it.
+#[derive(Clone, Default)]
+pub struct Budgets {
+    statuses: Arc<Mutex<Vec<Status>>>,
+}
+
+impl Budgets {
+    /// Budgets of providers named `names`.
+    pub fn new(names: impl IntoIterator<Item = String>) -> Self {
+        let statuses = names
+            .into_iter()
+            .map(|name| Status {
+                name,
+                ..Status::default()
+            })
+            .collect();
+        Self {
+            statuses: Arc::new(Mutex::new(statuses)),
+        }
+    }
+
+    /// Runs a query of the provider at `index`, with an empty answer if it runs out of time.
+    pub async fn run<T: Default>(self, index: usize, query: BoxFuture<'static, T>) -> T {
+        let demoted = self
+            .statuses
+            .lock()
+            .ok()
+            .and_then(|statuses| statuses.get(index).map(Status::is_demoted))
+            .unwrap_or_default();
+        let start = Instant::now();
+        let answer = tokio::time::timeout(if demoted { SOFT } else { HARD }, query).await;
+        if let Some(slow) = self.record(index, answer.is_ok().then(|| start.elapsed())) {
+            tokio::task::spawn_blocking(move || save(&slow));
+        }
+        answer.unwrap_or_default()
+    }
+
+    /// Records how long the provider at `index` took, returning the providers to save if
+    /// it was slow.
+    fn record(&self, index: usize, elapsed: Option<Duration>) -> Option<Vec<Status>> {
+        let mut statuses = self.statuses.lock().ok()?;
+        let status = statuses.get_mut(index)?;
+        status.last_answer_ms =
+            elapsed.map(|elapsed| u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX));
+        if elapsed.is_some_and(|elapsed| elapsed <= SOFT) {
+            return None;
+        }
+
+        if elapsed.is_none() {
+            status.timeouts += 1;
+        }
+        if !status.is_demoted() {
+            tracing::warn!("{} is slow to answer, demoting it", status.name);
+            status.demoted_until = Some(now() + DEMOTION.as_secs());
+        }
+        Some(
+            statuses
+                .iter()
+                .filter(|status| status.timeouts > 0 || status.demoted_until.is_some())
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+/// Forgets the providers which ran out of time in an earlier run of the launcher.
+pub fn forget() {
+    if let Some(path) = path() {
+        let _res = std::fs::remove_file(path);
+    }
+}
+
+/// The providers which ran out of time in the running launcher.
+pub fn load() -> Vec<Status> {
+    path()
+        .and_then(|path| std::fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save(slow: &[Status]) {
+    let Some(path) = path() else {
+        return;
+    };
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(&path, serde_json::to_vec(slow)?));
+    if let Err(why) = written {
+        tracing::warn!("failed to save {}: {why}", path.display());
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    xdg::BaseDirectories::new()
+        .ok()
+        .map(|dirs| dirs.get_state_home().join(FILE))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
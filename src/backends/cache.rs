@@ -0,0 +1,62 @@
+//! What providers read now and then and reuse for the queries in between.
+//!
+//! Reading happens on a task of its own rather than in the query, so that what a slow
+//! read returns is kept for the next query even when the budget of the query which
+//! started it ran out and dropped it.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex;
+
+pub struct Cache<T> {
+    ttl: Duration,
+    entry: Arc<Mutex<Option<(Instant, Arc<T>)>>>,
+}
+
+impl<T: Default + Send + Sync + 'static> Cache<T> {
+    /// A cache whose value is read again once it is older than `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entry: Arc::default(),
+        }
+    }
+
+    /// The cached value, or what `read` returns if there is none or it is too old.
+    pub async fn get(&self, read: impl Future<Output = T> + Send + 'static) -> Arc<T> {
+        let entry = Arc::clone(&self.entry);
+        let ttl = self.ttl;
+        tokio::spawn(async move {
+            let mut entry = entry.lock().await;
+            if let Some((at, value)) = entry.as_ref() {
+                if at.elapsed() < ttl {
+                    return Arc::clone(value);
+                }
+            }
+            let value = Arc::new(read.await);
+            *entry = Some((Instant::now(), Arc::clone(&value)));
+            value
+        })
+        .await
+        .unwrap_or_default()
+    }
+
+    /// Drops the cached value, so that the next query reads it again.
+    pub async fn clear(&self) {
+        *self.entry.lock().await = None;
+    }
+
+    /// Drops the cached value unless it is being read right now.
+    pub fn try_clear(&self) {
+        if let Ok(mut entry) = self.entry.try_lock() {
+            *entry = None;
+        }
+    }
+}
+
+/// Runs `read` on a blocking thread, with the default value if it panics.
+pub async fn blocking<T: Default + Send + 'static>(read: impl FnOnce() -> T + Send + 'static) -> T {
+    tokio::task::spawn_blocking(read).await.unwrap_or_default()
+}
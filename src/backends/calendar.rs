@@ -0,0 +1,334 @@
+//! Upcoming events from Evolution Data Server calendars, for queries starting with `cal `
+//! and for `today` or `tomorrow`.
+//!
+//! Calendars are found through the EDS source registry and queried over D-Bus. Where
+//! EDS is not running, the provider has no results. Recurring events are expanded by their
+//! frequency and interval, while finer rules such as `BYDAY` are not applied.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use jiff::{civil, tz::TimeZone, Span, Zoned};
+
+use super::cache::Cache;
+use super::eds::{self, CALENDARS};
+use super::{Action, Item, Provider};
+use crate::matcher;
+
+const PREFIX: &str = "cal ";
+
+/// How far ahead events are listed.
+const DAYS_AHEAD: i64 = 7;
+
+/// How long fetched events are reused before the calendars are queried again.
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Occurrences tried per recurring event before giving up on reaching the listed range.
+const MAX_OCCURRENCES: i64 = 5000;
+
+#[derive(Debug, Clone)]
+struct Event {
+    /// UID of the calendar the event belongs to.
+    source: String,
+    uid: String,
+    summary: String,
+    location: String,
+    start: Zoned,
+    all_day: bool,
+}
+
+pub struct Calendar {
+    cache: Cache<Vec<Event>>,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Self {
+            cache: Cache::new(CACHE_TTL),
+        }
+    }
+}
+
+impl Calendar {
+    /// Events from now until [`DAYS_AHEAD`] days ahead, ordered by start.
+    async fn events(&self) -> Vec<Event> {
+        let events = self.cache.get(async {
+            match fetch().await {
+                Ok(events) => events,
+                Err(why) => {
+                    tracing::debug!("calendar events are unavailable: {why}");
+                    Vec::new()
+                }
+            }
+        });
+        events.await.as_ref().clone()
+    }
+
+    /// The events a query asks for, or `None` if it is not a calendar query.
+    async fn matching(&self, query: &str) -> Option<Vec<Event>> {
+        let query = query.trim_start().to_lowercase();
+        let today = Zoned::now().date();
+
+        let filter: Box<dyn Fn(&Event) -> bool + Send> = match query.trim_end() {
+            "today" => Box::new(move |event| event.start.date() == today),
+            "tomorrow" => {
+                let tomorrow = today.tomorrow().ok()?;
+                Box::new(move |event| event.start.date() == tomorrow)
+            }
+            _ => {
+                let text = matcher::fold(query.strip_prefix(PREFIX)?.trim());
+                Box::new(move |event| {
+                    matcher::fold(&event.summary).contains(&text)
+                        || matcher::fold(&event.location).contains(&text)
+                })
+            }
+        };
+
+        Some(self.events().await.into_iter().filter(filter).collect())
+    }
+}
+
+async fn fetch() -> zbus::Result<Vec<Event>> {
+    let connection = zbus::Connection::session().await?;
+    let now = Zoned::now();
+    let until = now
+        .checked_add(Span::new().days(DAYS_AHEAD))
+        .map_err(|why| zbus::Error::Failure(why.to_string()))?;
+
+    let calendars = CALENDARS.sources(&connection).await?;
+
+    let range = format!(
+        "(occur-in-time-range? (make-time \"{}\") (make-time \"{}\"))",
+        now.timestamp().strftime("%Y%m%dT%H%M%SZ"),
+        until.timestamp().strftime("%Y%m%dT%H%M%SZ"),
+    );
+
+    let mut events = Vec::new();
+    for source in calendars {
+        match CALENDARS
+            .query(&connection, &source, "GetObjectList", &range)
+            .await
+        {
+            Ok(objects) => {
+                for object in objects {
+                    events.extend(parse(&object, &source, &now, &until));
+                }
+            }
+            Err(why) => tracing::debug!("failed to read calendar {source}: {why}"),
+        }
+    }
+
+    events.sort_by(|a, b| a.start.cmp(&b.start));
+    Ok(events)
+}
+
+/// The occurrences of the events in an iCalendar object between `from` and `until`.
+fn parse(ics: &str, source: &str, from: &Zoned, until: &Zoned) -> Vec<Event> {
+    let unfolded = eds::unfold(ics);
+
+    let mut events = Vec::new();
+    let mut event = None;
+    let mut rule = None;
+    let mut nested = 0;
+
+    for line in unfolded.lines() {
+        match line {
+            "BEGIN:VEVENT" => {
+                event = Some(Event {
+                    source: source.to_owned(),
+                    uid: String::new(),
+                    summary: String::new(),
+                    location: String::new(),
+                    start: from.clone(),
+                    all_day: false,
+                });
+                rule = None;
+            }
+            "END:VEVENT" => {
+                if let Some(event) = event.take() {
+                    events.extend(occurrences(event, rule.take(), from, until));
+                }
+            }
+            // Alarms and other components nested in the event have their own properties.
+            _ if event.is_some() && line.starts_with("BEGIN:") => nested += 1,
+            _ if event.is_some() && line.starts_with("END:") => nested -= 1,
+            _ if nested > 0 => {}
+            _ => {
+                let Some(event) = event.as_mut() else {
+                    continue;
+                };
+                let Some((name, params, value)) = eds::property(line) else {
+                    continue;
+                };
+
+                match name {
+                    "UID" => event.uid = value.to_owned(),
+                    "SUMMARY" => event.summary = eds::unescape(value),
+                    "LOCATION" => event.location = eds::unescape(value),
+                    "RRULE" => rule = Some(value.to_owned()),
+                    "DTSTART" => {
+                        if let Some((start, all_day)) = date_time(params, value) {
+                            event.start = start;
+                            event.all_day = all_day;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    events
+}
+
+/// Parses a `DATE` or `DATE-TIME` value, returning whether it is a date alone.
+fn date_time(params: &str, value: &str) -> Option<(Zoned, bool)> {
+    let tz = params
+        .split(';')
+        .find_map(|param| param.strip_prefix("TZID="))
+        .and_then(|tzid| jiff::tz::db().get(tzid.trim_matches('"')).ok())
+        .unwrap_or_else(TimeZone::system);
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let start = civil::DateTime::strptime("%Y%m%dT%H%M%S", utc).ok()?;
+        return Some((start.to_zoned(TimeZone::UTC).ok()?, false));
+    }
+
+    match civil::DateTime::strptime("%Y%m%dT%H%M%S", value) {
+        Ok(start) => Some((start.to_zoned(tz).ok()?, false)),
+        Err(_) => {
+            let date = civil::Date::strptime("%Y%m%d", value).ok()?;
+            Some((date.to_zoned(TimeZone::system()).ok()?, true))
+        }
+    }
+}
+
+/// The occurrences of `event` between `from` and `until`, following its `RRULE`.
+fn occurrences(event: Event, rule: Option<String>, from: &Zoned, until: &Zoned) -> Vec<Event> {
+    let Some(rule) = rule else {
+        let ends_today = event.all_day && event.start.date() == from.date();
+        return if (event.start >= *from || ends_today) && event.start < *until {
+            vec![event]
+        } else {
+            Vec::new()
+        };
+    };
+
+    let parts = rule
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .collect::<HashMap<_, _>>();
+    let interval = parts
+        .get("INTERVAL")
+        .and_then(|interval| interval.parse::<i64>().ok())
+        .unwrap_or(1);
+    let last = parts
+        .get("UNTIL")
+        .and_then(|value| date_time("", value))
+        .map(|(last, _)| last);
+    let count = parts
+        .get("COUNT")
+        .and_then(|count| count.parse::<i64>().ok())
+        .unwrap_or(MAX_OCCURRENCES);
+
+    let step = |n: i64| match parts.get("FREQ").copied() {
+        Some("DAILY") => Span::new().try_days(n * interval).ok(),
+        Some("WEEKLY") => Span::new().try_weeks(n * interval).ok(),
+        Some("MONTHLY") => Span::new().try_months(n * interval).ok(),
+        Some("YEARLY") => Span::new().try_years(n * interval).ok(),
+        _ => None,
+    };
+
+    (0..count.min(MAX_OCCURRENCES))
+        .map_while(|n| event.start.checked_add(step(n)?).ok())
+        .take_while(|start| {
+            start < until
+                && match &last {
+                    Some(last) => start <= last,
+                    None => true,
+                }
+        })
+        .filter(|start| *start >= *from || (event.all_day && start.date() == from.date()))
+        .map(|start| Event {
+            start,
+            ..event.clone()
+        })
+        .collect()
+}
+
+fn describe(event: &Event) -> String {
+    let when = if event.all_day {
+        event.start.strftime("%a, all day").to_string()
+    } else {
+        event
+            .start
+            .with_time_zone(TimeZone::system())
+            .strftime("%a %H:%M")
+            .to_string()
+    };
+
+    if event.location.is_empty() {
+        when
+    } else {
+        format!("{when} · {}", event.location)
+    }
+}
+
+/// Opens the default calendar application, showing the event where the application
+/// supports it.
+async fn open(event: Event) -> Option<Action> {
+    let output = tokio::process::Command::new("xdg-mime")
+        .args(["query", "default", "text/calendar"])
+        .output()
+        .await
+        .ok()?;
+    let app = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+
+    match app.as_str() {
+        "" => None,
+        "org.gnome.Calendar.desktop" => Some(Action::Run(vec![
+            "gnome-calendar".into(),
+            "--uuid".into(),
+            format!("{}:{}", event.source, event.uid),
+        ])),
+        app => Some(Action::Run(vec![
+            "gtk-launch".into(),
+            app.trim_end_matches(".desktop").into(),
+        ])),
+    }
+}
+
+impl Provider for Calendar {
+    fn name(&self) -> &str {
+        "calendar"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .unwrap_or_default()
+                .iter()
+                .map(|event| Item {
+                    name: event.summary.clone(),
+                    description: describe(event),
+                    icon: Some("x-office-calendar".into()),
+                    open: None,
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let event = self.matching(&query).await?.into_iter().nth(index)?;
+            open(event).await
+        })
+    }
+}
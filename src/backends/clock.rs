@@ -0,0 +1,186 @@
+//! Times in other time zones, for queries such as `time in tokyo` or `9am PST in CET`.
+//!
+//! Zones are named by their IANA name, the last part of it, or a common abbreviation,
+//! and looked up in the system time zone database. Activating a result copies it.
+
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use jiff::{tz::TimeZone, Zoned};
+
+use super::{Action, Item, Provider};
+use crate::fl;
+
+/// Abbreviations which are not zone names themselves, with a zone observing them.
+const ABBREVIATIONS: &[(&str, &str)] = &[
+    ("gmt", "UTC"),
+    ("pst", "America/Los_Angeles"),
+    ("pdt", "America/Los_Angeles"),
+    ("mst", "America/Denver"),
+    ("mdt", "America/Denver"),
+    ("cst", "America/Chicago"),
+    ("cdt", "America/Chicago"),
+    ("est", "America/New_York"),
+    ("edt", "America/New_York"),
+    ("bst", "Europe/London"),
+    ("cet", "Europe/Paris"),
+    ("cest", "Europe/Paris"),
+    ("eet", "Europe/Athens"),
+    ("eest", "Europe/Athens"),
+    ("ist", "Asia/Kolkata"),
+    ("jst", "Asia/Tokyo"),
+    ("aest", "Australia/Sydney"),
+    ("aedt", "Australia/Sydney"),
+];
+
+pub struct Clock;
+
+/// A zone found for a name in the query.
+struct Zone {
+    name: String,
+    tz: TimeZone,
+}
+
+impl Clock {
+    /// The converted times a query asks for, one per target zone.
+    fn convert(query: &str) -> Vec<Item> {
+        let query = query.trim().to_lowercase();
+
+        let (source, targets) = if let Some(targets) = query.strip_prefix("time in ") {
+            (Zoned::now(), targets)
+        } else if let Some((source, targets)) = query.rsplit_once(" in ") {
+            let Some(source) = source_time(source) else {
+                return Vec::new();
+            };
+            (source, targets)
+        } else {
+            return Vec::new();
+        };
+
+        targets
+            .split(',')
+            .filter_map(|target| zone(target.trim()))
+            .map(|zone| {
+                let converted = source.with_time_zone(zone.tz);
+                Item {
+                    name: fl!(
+                        "clock-in-zone",
+                        time = converted.strftime("%H:%M %a").to_string(),
+                        zone = zone.name
+                    ),
+                    description: fl!(
+                        "clock-source",
+                        time = source.strftime("%H:%M %a").to_string(),
+                        zone = source
+                            .time_zone()
+                            .iana_name()
+                            .map_or_else(|| fl!("clock-local-time"), String::from),
+                        offset = converted.offset().to_string()
+                    ),
+                    icon: Some("preferences-system-time".into()),
+                    open: None,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Parses a time of day followed by an optional zone, such as `9am`, `9:30 pm pst`, or
+/// `14:00 europe/berlin`, as today's date at that time. A missing zone is local time.
+fn source_time(text: &str) -> Option<Zoned> {
+    let words = text.split_whitespace().collect::<Vec<_>>();
+
+    (1..=words.len()).find_map(|split| {
+        let (hour, minute) = time_of_day(&words[..split].concat())?;
+        let tz = match words[split..].join(" ") {
+            zone_name if zone_name.is_empty() => TimeZone::system(),
+            zone_name => zone(&zone_name)?.tz,
+        };
+
+        Zoned::now()
+            .with_time_zone(tz.clone())
+            .date()
+            .at(hour, minute, 0, 0)
+            .to_zoned(tz)
+            .ok()
+    })
+}
+
+/// Parses `9`, `9am`, `9:30pm`, or `14:00` as an hour and minute.
+fn time_of_day(text: &str) -> Option<(i8, i8)> {
+    let (clock, meridiem) = match text.strip_suffix("am") {
+        Some(clock) => (clock, Some(0)),
+        None => match text.strip_suffix("pm") {
+            Some(clock) => (clock, Some(12)),
+            None => (text, None),
+        },
+    };
+
+    let (hour, minute) = clock.split_once(':').unwrap_or((clock, "0"));
+    let hour = hour.parse::<i8>().ok()?;
+    let minute = minute
+        .parse::<i8>()
+        .ok()
+        .filter(|minute| (0..60).contains(minute))?;
+
+    let hour = match meridiem {
+        Some(offset) if (1..=12).contains(&hour) => hour % 12 + offset,
+        Some(_) => return None,
+        None if (0..24).contains(&hour) => hour,
+        None => return None,
+    };
+
+    Some((hour, minute))
+}
+
+/// Finds a zone by IANA name, the last part of one, such as `tokyo` or `new york`, or an
+/// abbreviation.
+fn zone(name: &str) -> Option<Zone> {
+    let name = name.trim().replace(' ', "_");
+    if name.is_empty() {
+        return None;
+    }
+
+    let abbreviation = ABBREVIATIONS
+        .iter()
+        .find(|(abbreviation, _)| *abbreviation == name)
+        .map(|(_, zone)| (*zone).to_owned());
+
+    let found = abbreviation.or_else(|| {
+        jiff::tz::db().available().find_map(|available| {
+            let available = available.as_str();
+            let city = available.rsplit('/').next().unwrap_or(available);
+            (available.eq_ignore_ascii_case(&name) || city.eq_ignore_ascii_case(&name))
+                .then(|| available.to_owned())
+        })
+    })?;
+
+    let tz = jiff::tz::db().get(&found).ok()?;
+    Some(Zone {
+        name: found.replace('_', " "),
+        tz,
+    })
+}
+
+impl Provider for Clock {
+    fn name(&self) -> &str {
+        "clock"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move { Self::convert(&query) })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            Self::convert(&query)
+                .into_iter()
+                .nth(index)
+                .map(|item| Action::Copy(item.name))
+        })
+    }
+}
@@ -0,0 +1,316 @@
+//! Contacts matching queries starting with `@`, read from Evolution Data Server address
+//! books and from vCard files.
+//!
+//! Contacts are indexed asynchronously on the first contact query, and again once the
+//! index is older than [`REINDEX_AFTER`]. Activating a contact writes to its first email
+//! address, or calls its first phone number, and its context menu offers the rest.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use super::cache::Cache;
+use super::eds::{self, ADDRESS_BOOKS};
+use super::{Action, Item, Provider};
+use crate::{fl, matcher};
+
+const PREFIX: char = '@';
+
+/// Address book query matching every contact.
+const EVERY_CONTACT: &str = "(contains \"x-evolution-any-field\" \"\")";
+
+const REINDEX_AFTER: Duration = Duration::from_secs(5 * 60);
+
+/// Where contacts are read from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContactsConfig {
+    pub enabled: bool,
+    /// Directories searched for `.vcf` files, such as those synced by vdirsyncer. A leading
+    /// `~` is the home directory.
+    pub vcard_dirs: Vec<String>,
+}
+
+impl Default for ContactsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            vcard_dirs: vec!["~/.local/share/contacts".into()],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Contact {
+    name: String,
+    organization: String,
+    emails: Vec<String>,
+    phones: Vec<String>,
+}
+
+impl Contact {
+    fn matches(&self, text: &str) -> bool {
+        matcher::fold(&self.name).contains(text)
+            || self
+                .emails
+                .iter()
+                .any(|email| matcher::fold(email).contains(text))
+    }
+
+    /// What the contact's context menu offers, with the default action first.
+    fn actions(&self) -> Vec<(String, Action)> {
+        let emails = self.emails.iter().flat_map(|email| {
+            [
+                (
+                    fl!("contact-write", email = email.as_str()),
+                    Action::Open(format!("mailto:{email}")),
+                ),
+                (
+                    fl!("contact-copy", text = email.as_str()),
+                    Action::Copy(email.clone()),
+                ),
+            ]
+        });
+        let phones = self.phones.iter().flat_map(|phone| {
+            let number = phone
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>();
+            [
+                (
+                    fl!("contact-call", phone = phone.as_str()),
+                    Action::Open(format!("tel:{number}")),
+                ),
+                (
+                    fl!("contact-copy", text = phone.as_str()),
+                    Action::Copy(phone.clone()),
+                ),
+            ]
+        });
+
+        emails.chain(phones).collect()
+    }
+}
+
+pub struct Contacts {
+    vcard_dirs: Vec<PathBuf>,
+    index: Cache<Vec<Contact>>,
+}
+
+impl Contacts {
+    pub fn new(config: &ContactsConfig) -> Self {
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let vcard_dirs = config
+            .vcard_dirs
+            .iter()
+            .filter_map(|dir| match dir.strip_prefix('~') {
+                Some(rest) => Some(home.as_ref()?.join(rest.trim_start_matches('/'))),
+                None => Some(PathBuf::from(dir)),
+            })
+            .collect();
+
+        Self {
+            vcard_dirs,
+            index: Cache::new(REINDEX_AFTER),
+        }
+    }
+
+    /// The indexed contacts, waiting for the index to be built if it is missing or stale.
+    async fn index(&self) -> Arc<Vec<Contact>> {
+        let dirs = self.vcard_dirs.clone();
+        self.index
+            .get(async move {
+                let files = tokio::task::spawn_blocking(move || {
+                    let mut contacts = Vec::new();
+                    for dir in dirs {
+                        read_dir(&dir, &mut contacts);
+                    }
+                    contacts
+                });
+
+                let mut contacts = match address_books().await {
+                    Ok(contacts) => contacts,
+                    Err(why) => {
+                        tracing::debug!("address books are unavailable: {why}");
+                        Vec::new()
+                    }
+                };
+                contacts.extend(files.await.unwrap_or_default());
+                contacts.sort_by(|a, b| a.name.cmp(&b.name));
+                contacts.dedup_by(|a, b| a.name == b.name && a.emails == b.emails);
+                contacts
+            })
+            .await
+    }
+
+    /// The contacts a query asks for, or nothing if it is not a contact query.
+    async fn matching(&self, query: &str) -> Vec<Contact> {
+        let Some(text) = query.trim_start().strip_prefix(PREFIX) else {
+            return Vec::new();
+        };
+        let text = matcher::fold(text.trim());
+        if text.is_empty() {
+            return Vec::new();
+        }
+
+        self.index()
+            .await
+            .iter()
+            .filter(|contact| contact.matches(&text))
+            .take(MAX_RESULTS)
+            .cloned()
+            .collect()
+    }
+}
+
+async fn address_books() -> zbus::Result<Vec<Contact>> {
+    let connection = zbus::Connection::session().await?;
+    let mut contacts = Vec::new();
+
+    for source in ADDRESS_BOOKS.sources(&connection).await? {
+        match ADDRESS_BOOKS
+            .query(&connection, &source, "GetContactList", EVERY_CONTACT)
+            .await
+        {
+            Ok(cards) => {
+                for card in cards {
+                    contacts.extend(parse(&card));
+                }
+            }
+            Err(why) => tracing::debug!("failed to read address book {source}: {why}"),
+        }
+    }
+
+    Ok(contacts)
+}
+
+/// Reads the `.vcf` files in `dir` and its subdirectories.
+fn read_dir(dir: &Path, contacts: &mut Vec<Contact>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if path.is_dir() {
+            read_dir(&path, contacts);
+        } else if path.extension().is_some_and(|ext| ext == "vcf") {
+            if let Ok(text) = std::fs::read_to_string(&path) {
+                contacts.extend(parse(&text));
+            }
+        }
+    }
+}
+
+/// Parses the vCards in `text`, skipping those without a name.
+fn parse(text: &str) -> Vec<Contact> {
+    let mut contacts = Vec::new();
+    let mut contact = None;
+
+    for line in eds::unfold(text).lines() {
+        if line.eq_ignore_ascii_case("BEGIN:VCARD") {
+            contact = Some(Contact::default());
+        } else if line.eq_ignore_ascii_case("END:VCARD") {
+            contacts.extend(contact.take().filter(|contact| !contact.name.is_empty()));
+        } else if let (Some(contact), Some((name, _, value))) =
+            (contact.as_mut(), eds::property(line))
+        {
+            let value = eds::unescape(value);
+            match name.to_ascii_uppercase().as_str() {
+                "FN" => contact.name = value,
+                "ORG" => contact.organization = value.trim_end_matches(';').replace(';', ", "),
+                "EMAIL" if !value.is_empty() => contact.emails.push(value),
+                "TEL" if !value.is_empty() => {
+                    contact
+                        .phones
+                        .push(value.trim_start_matches("tel:").to_owned());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    contacts
+}
+
+impl Provider for Contacts {
+    fn name(&self) -> &str {
+        "contacts"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|contact| {
+                    let address = contact.emails.first().or(contact.phones.first());
+                    let description = match (address, contact.organization.is_empty()) {
+                        (Some(address), false) => format!("{address} · {}", contact.organization),
+                        (Some(address), true) => address.clone(),
+                        (None, _) => contact.organization.clone(),
+                    };
+
+                    Item {
+                        name: contact.name,
+                        description,
+                        icon: Some("avatar-default".into()),
+                        open: None,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let contact = self.matching(&query).await.into_iter().nth(index)?;
+            contact
+                .actions()
+                .into_iter()
+                .next()
+                .map(|(_, action)| action)
+        })
+    }
+
+    fn context(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Vec<String>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .nth(index)
+                .map(|contact| {
+                    contact
+                        .actions()
+                        .into_iter()
+                        .map(|(name, _)| name)
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    fn activate_context(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+        option: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let contact = self.matching(&query).await.into_iter().nth(index)?;
+            contact
+                .actions()
+                .into_iter()
+                .nth(option)
+                .map(|(_, action)| action)
+        })
+    }
+}
@@ -0,0 +1,386 @@
+//! Docker containers and images for queries starting with `docker `, and Podman's for
+//! queries starting with `pod `, listed through the engine's REST API socket.
+//!
+//! Activating a container shows its logs in a terminal, and its context menu starts or
+//! stops it. Every request to the socket is given up after a timeout, so that an engine
+//! which stopped answering cannot hold up searching.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+
+use super::{size, Action, Item, Provider};
+use crate::{fl, matcher};
+
+/// Time the engine has to list its containers or images.
+const LIST_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Time the engine has to start or stop a container, which it gives 10 seconds to exit.
+const CHANGE_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy)]
+enum Engine {
+    Docker,
+    Podman,
+}
+
+impl Engine {
+    const ALL: [(Self, &'static str); 2] = [(Self::Docker, "docker "), (Self::Podman, "pod ")];
+
+    /// The command line interface, which shows logs.
+    fn program(self) -> &'static str {
+        match self {
+            Self::Docker => "docker",
+            Self::Podman => "podman",
+        }
+    }
+
+    fn socket(self) -> Option<PathBuf> {
+        match self {
+            Self::Docker => match std::env::var("DOCKER_HOST") {
+                Ok(host) => host.strip_prefix("unix://").map(PathBuf::from),
+                Err(_) => Some(PathBuf::from("/var/run/docker.sock")),
+            },
+            Self::Podman => {
+                let user = std::env::var_os("XDG_RUNTIME_DIR")
+                    .map(|dir| PathBuf::from(dir).join("podman/podman.sock"))
+                    .filter(|socket| socket.exists());
+                Some(user.unwrap_or_else(|| PathBuf::from("/run/podman/podman.sock")))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ContainerInfo {
+    id: String,
+    #[serde(default)]
+    names: Vec<String>,
+    #[serde(default)]
+    image: String,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    status: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ImageInfo {
+    id: String,
+    #[serde(default)]
+    repo_tags: Option<Vec<String>>,
+    #[serde(default)]
+    size: u64,
+}
+
+#[derive(Debug, Clone)]
+enum Entry {
+    Container {
+        id: String,
+        name: String,
+        image: String,
+        running: bool,
+        status: String,
+    },
+    Image {
+        tag: String,
+        size: u64,
+    },
+}
+
+impl Entry {
+    fn name(&self) -> &str {
+        match self {
+            Self::Container { name, .. } => name,
+            Self::Image { tag, .. } => tag,
+        }
+    }
+}
+
+/// What a result's context menu offers, with the default action first.
+#[derive(Debug, Clone, Copy)]
+enum Operation {
+    Logs,
+    Start,
+    Stop,
+    CopyTag,
+}
+
+impl Operation {
+    fn name(self) -> String {
+        match self {
+            Self::Logs => fl!("container-logs"),
+            Self::Start => fl!("container-start"),
+            Self::Stop => fl!("container-stop"),
+            Self::CopyTag => fl!("container-copy-name"),
+        }
+    }
+
+    fn of(entry: &Entry) -> &'static [Self] {
+        match entry {
+            Entry::Container { running: true, .. } => &[Self::Logs, Self::Stop],
+            Entry::Container { running: false, .. } => &[Self::Logs, Self::Start],
+            Entry::Image { .. } => &[Self::CopyTag],
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Containers;
+
+impl Containers {
+    async fn matching(&self, query: &str) -> Option<(Engine, Vec<Entry>)> {
+        let query = query.trim_start();
+        let (engine, text) = Engine::ALL
+            .iter()
+            .find_map(|(engine, prefix)| Some((*engine, query.strip_prefix(prefix)?)))?;
+        let words = matcher::words(text);
+
+        let entries = list(engine)
+            .await
+            .into_iter()
+            .filter(|entry| matcher::matches_all(&words, &entry.name()))
+            .take(MAX_RESULTS)
+            .collect();
+        Some((engine, entries))
+    }
+
+    async fn run(&self, query: &str, index: usize, option: usize) -> Option<Action> {
+        let (engine, entries) = self.matching(query).await?;
+        let entry = entries.into_iter().nth(index)?;
+        let operation = *Operation::of(&entry).get(option)?;
+
+        let path = match (&entry, operation) {
+            (Entry::Container { name, .. }, Operation::Logs) => {
+                return Some(Action::Terminal {
+                    dir: None,
+                    args: vec![
+                        engine.program().into(),
+                        "logs".into(),
+                        "--follow".into(),
+                        name.clone(),
+                    ],
+                });
+            }
+            (Entry::Image { tag, .. }, Operation::CopyTag) => {
+                return Some(Action::Copy(tag.clone()))
+            }
+            (Entry::Container { id, .. }, Operation::Start) => format!("/containers/{id}/start"),
+            (Entry::Container { id, .. }, Operation::Stop) => format!("/containers/{id}/stop"),
+            _ => return None,
+        };
+
+        match request(engine, "POST", &path, CHANGE_TIMEOUT).await {
+            Ok((status, _)) if status < 300 || status == 304 => {}
+            Ok((status, body)) => tracing::warn!(
+                "{} {path} failed with {status}: {}",
+                engine.program(),
+                String::from_utf8_lossy(&body).trim()
+            ),
+            Err(why) => tracing::warn!("{} {path} failed: {why}", engine.program()),
+        }
+
+        None
+    }
+}
+
+/// The engine's containers followed by its images, or nothing if it is not running.
+async fn list(engine: Engine) -> Vec<Entry> {
+    let (containers, images) = futures::join!(
+        request(engine, "GET", "/containers/json?all=true", LIST_TIMEOUT),
+        request(engine, "GET", "/images/json", LIST_TIMEOUT),
+    );
+
+    let containers = parse::<ContainerInfo>(engine, containers)
+        .into_iter()
+        .map(|container| Entry::Container {
+            name: container
+                .names
+                .first()
+                .map(|name| name.trim_start_matches('/').to_string())
+                .unwrap_or_else(|| container.id.chars().take(12).collect()),
+            id: container.id,
+            image: container.image,
+            running: container.state == "running",
+            status: container.status,
+        });
+
+    let images = parse::<ImageInfo>(engine, images)
+        .into_iter()
+        .flat_map(|image| {
+            let tags = image
+                .repo_tags
+                .filter(|tags| !tags.is_empty())
+                .unwrap_or_else(|| {
+                    vec![image
+                        .id
+                        .trim_start_matches("sha256:")
+                        .chars()
+                        .take(12)
+                        .collect()]
+                });
+            tags.into_iter()
+                .filter(|tag| tag != "<none>:<none>")
+                .map(move |tag| Entry::Image {
+                    tag,
+                    size: image.size,
+                })
+        });
+
+    containers.chain(images).collect()
+}
+
+fn parse<T: serde::de::DeserializeOwned>(
+    engine: Engine,
+    response: std::io::Result<(u16, Vec<u8>)>,
+) -> Vec<T> {
+    match response {
+        Ok((200, body)) => serde_json::from_slice(&body).unwrap_or_else(|why| {
+            tracing::warn!("unexpected {} listing: {why}", engine.program());
+            Vec::new()
+        }),
+        Ok((status, _)) => {
+            tracing::debug!("{} answered {status}", engine.program());
+            Vec::new()
+        }
+        // Mostly an engine which is not installed or running.
+        Err(why) => {
+            tracing::debug!("failed to reach {}: {why}", engine.program());
+            Vec::new()
+        }
+    }
+}
+
+/// Sends an HTTP request without a body to the engine's socket, returning the status and
+/// body of its response.
+async fn request(
+    engine: Engine,
+    method: &str,
+    path: &str,
+    timeout: Duration,
+) -> std::io::Result<(u16, Vec<u8>)> {
+    let socket = engine
+        .socket()
+        .ok_or_else(|| std::io::Error::other("no socket configured"))?;
+
+    let exchange = async {
+        let mut stream = UnixStream::connect(&socket).await?;
+        // HTTP/1.0 has the engine close the connection after a body which is not chunked.
+        let request =
+            format!("{method} {path} HTTP/1.0\r\nHost: localhost\r\nContent-Length: 0\r\n\r\n");
+        stream.write_all(request.as_bytes()).await?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await?;
+        Ok::<_, std::io::Error>(response)
+    };
+    let response = tokio::time::timeout(timeout, exchange)
+        .await
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "no answer in time"))??;
+
+    let invalid = || std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid response");
+    let split = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(invalid)?;
+    let head = String::from_utf8_lossy(&response[..split]);
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(invalid)?;
+
+    Ok((status, response[split + 4..].to_vec()))
+}
+
+impl Provider for Containers {
+    fn name(&self) -> &str {
+        "containers"
+    }
+
+    fn addressed(&self, query: &str) -> bool {
+        let query = query.trim_start();
+        Engine::ALL
+            .iter()
+            .any(|(_, prefix)| query.starts_with(prefix))
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            let Some((_, entries)) = self.matching(&query).await else {
+                return Vec::new();
+            };
+
+            entries
+                .into_iter()
+                .map(|entry| match entry {
+                    Entry::Container {
+                        name,
+                        image,
+                        running,
+                        status,
+                        ..
+                    } => Item {
+                        name,
+                        description: format!("{image} · {status}"),
+                        icon: Some(
+                            if running {
+                                "media-playback-start"
+                            } else {
+                                "media-playback-stop"
+                            }
+                            .into(),
+                        ),
+                        open: None,
+                    },
+                    Entry::Image { tag, size: bytes } => Item {
+                        name: tag,
+                        description: fl!("container-image", size = size(bytes)),
+                        icon: Some("package-x-generic".into()),
+                        open: None,
+                    },
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move { self.run(&query, index, 0).await })
+    }
+
+    fn context(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Vec<String>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .and_then(|(_, entries)| entries.into_iter().nth(index))
+                .map(|entry| {
+                    Operation::of(&entry)
+                        .iter()
+                        .map(|operation| operation.name())
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    fn activate_context(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+        option: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move { self.run(&query, index, option).await })
+    }
+}
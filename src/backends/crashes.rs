@@ -0,0 +1,151 @@
+//! Crash reports of earlier runs, offered below the results of an empty query and for
+//! queries such as `report issue` or `crash`.
+//!
+//! Activating a report opens it, so it can be attached to an issue, and its context menu
+//! opens the issue tracker or dismisses the report by deleting it.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use jiff::{tz::TimeZone, Timestamp};
+
+use super::{Action, Item, Provider};
+use crate::{crash, fl};
+
+/// Characters a query needs before reports are matched.
+const MIN_QUERY: usize = 3;
+
+const KEYWORDS: [&str; 4] = ["report", "issue", "crash", "bug"];
+
+#[derive(Debug, Clone)]
+struct Report {
+    path: PathBuf,
+    /// Seconds since the Unix epoch, from the name of the report.
+    time: i64,
+}
+
+#[derive(Default)]
+pub struct Crashes;
+
+impl Crashes {
+    async fn matching(&self, query: &str) -> Vec<Report> {
+        let query = query.trim().to_lowercase();
+        let matches = query.is_empty()
+            || query.chars().count() >= MIN_QUERY
+                && query
+                    .split_whitespace()
+                    .all(|word| KEYWORDS.iter().any(|keyword| keyword.starts_with(word)));
+        if !matches {
+            return Vec::new();
+        }
+        tokio::task::spawn_blocking(reports)
+            .await
+            .unwrap_or_default()
+    }
+}
+
+/// The reports left by earlier crashes, newest first.
+fn reports() -> Vec<Report> {
+    let Some(entries) = crash::dir().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+        return Vec::new();
+    };
+    let mut reports = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let time = path
+                .file_name()?
+                .to_str()?
+                .strip_prefix("crash-")?
+                .strip_suffix(".txt")?
+                .parse()
+                .ok()?;
+            Some(Report { path, time })
+        })
+        .collect::<Vec<_>>();
+    reports.sort_by_key(|report| std::cmp::Reverse(report.time));
+    reports
+}
+
+fn item(report: &Report) -> Item {
+    let time = Timestamp::from_second(report.time)
+        .map(|time| {
+            time.to_zoned(TimeZone::system())
+                .strftime("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default();
+    Item {
+        name: fl!("crash-report"),
+        description: fl!(
+            "crash-description",
+            time = time,
+            path = report.path.display().to_string()
+        ),
+        icon: Some("dialog-warning-symbolic".into()),
+        open: None,
+    }
+}
+
+impl Provider for Crashes {
+    fn name(&self) -> &str {
+        "crashes"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            if query.trim().is_empty() {
+                return Vec::new();
+            }
+            self.matching(&query).await.iter().map(item).collect()
+        })
+    }
+
+    fn footer(self: Arc<Self>) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move { self.matching("").await.iter().take(1).map(item).collect() })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move { self.activate_context(query, index, 0).await })
+    }
+
+    fn context(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Vec<String>> {
+        Box::pin(async move {
+            if self.matching(&query).await.len() <= index {
+                return Vec::new();
+            }
+            vec![
+                fl!("crash-open-report"),
+                fl!("crash-open-issues"),
+                fl!("crash-dismiss"),
+            ]
+        })
+    }
+
+    fn activate_context(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+        option: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let report = self.matching(&query).await.into_iter().nth(index)?;
+            match option {
+                0 => Some(Action::Open(report.path.display().to_string())),
+                1 => Some(Action::Open(crash::ISSUES.into())),
+                2 => {
+                    if let Err(why) = tokio::fs::remove_file(&report.path).await {
+                        tracing::warn!("failed to dismiss {}: {why}", report.path.display());
+                    }
+                    Some(Action::Refresh)
+                }
+                _ => None,
+            }
+        })
+    }
+}
@@ -0,0 +1,208 @@
+//! Development environments set in the configuration, for queries starting with `dev `.
+//!
+//! Each is a Nix flake's dev shell, a toolbox or distrobox container, or a project with a
+//! devcontainer. Activating one opens the configured terminal inside the environment, in
+//! its directory.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use super::{Action, Item, Provider};
+use crate::{fl, matcher};
+
+const PREFIX: &str = "dev ";
+
+/// Starts a devcontainer, then opens bash in it, or sh where there is no bash.
+const DEVCONTAINER_SCRIPT: &str = concat!(
+    r#"devcontainer up --workspace-folder "$1" >/dev/null && "#,
+    r#"exec devcontainer exec --workspace-folder "$1" "#,
+    r#"sh -c 'exec "$(command -v bash || command -v sh)"'"#,
+);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DevKind {
+    Nix,
+    Toolbox,
+    Distrobox,
+    Devcontainer,
+}
+
+impl DevKind {
+    fn name(self) -> String {
+        match self {
+            Self::Nix => fl!("dev-nix"),
+            Self::Toolbox => fl!("dev-toolbox"),
+            Self::Distrobox => fl!("dev-distrobox"),
+            Self::Devcontainer => fl!("dev-devcontainer"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DevEnvironment {
+    pub name: String,
+    pub kind: DevKind,
+    /// The flake for `nix`, such as `~/src/app` or `github:owner/repo`, the container for
+    /// `toolbox` and `distrobox`, or the project directory for `devcontainer`.
+    pub target: String,
+    /// Directory the terminal opens in, by default the flake's or project's directory. A
+    /// leading `~` is the home directory.
+    #[serde(default)]
+    pub dir: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Shown below the name instead of the kind of environment.
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DevConfig {
+    pub enabled: bool,
+    pub environments: Vec<DevEnvironment>,
+}
+
+impl Default for DevConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            environments: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Environment {
+    name: String,
+    kind: DevKind,
+    /// The target, with a leading `~` expanded when it is a path.
+    target: String,
+    dir: Option<PathBuf>,
+    icon: String,
+    description: String,
+}
+
+impl Environment {
+    fn new(environment: &DevEnvironment) -> Self {
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let expand = |path: &str| match path.strip_prefix('~') {
+            Some(rest) => Some(home.as_ref()?.join(rest.trim_start_matches('/'))),
+            None => Some(PathBuf::from(path)),
+        };
+
+        // Flakes may also be references such as `github:owner/repo`, which have no directory.
+        let local = match environment.kind {
+            DevKind::Nix => {
+                let target = &environment.target;
+                (target.starts_with(['/', '.', '~']) || target.starts_with("path:"))
+                    .then(|| expand(target.trim_start_matches("path:")))
+                    .flatten()
+            }
+            DevKind::Devcontainer => expand(&environment.target),
+            DevKind::Toolbox | DevKind::Distrobox => None,
+        };
+
+        Self {
+            name: environment.name.clone(),
+            kind: environment.kind,
+            target: local.as_ref().map_or_else(
+                || environment.target.clone(),
+                |path| path.display().to_string(),
+            ),
+            dir: environment.dir.as_deref().and_then(expand).or(local),
+            icon: environment
+                .icon
+                .clone()
+                .unwrap_or_else(|| "utilities-terminal".into()),
+            description: environment.description.clone().unwrap_or_else(|| {
+                let kind = environment.kind.name();
+                format!("{kind} · {}", environment.target)
+            }),
+        }
+    }
+
+    /// The program run in the terminal.
+    fn command(&self) -> Vec<String> {
+        let target = self.target.clone();
+        match self.kind {
+            DevKind::Nix => vec!["nix".into(), "develop".into(), target],
+            DevKind::Toolbox => vec!["toolbox".into(), "enter".into(), target],
+            DevKind::Distrobox => vec!["distrobox".into(), "enter".into(), target],
+            DevKind::Devcontainer => vec![
+                "sh".into(),
+                "-c".into(),
+                DEVCONTAINER_SCRIPT.into(),
+                "sh".into(),
+                target,
+            ],
+        }
+    }
+}
+
+pub struct Dev {
+    environments: Vec<Environment>,
+}
+
+impl Dev {
+    pub fn new(config: &DevConfig) -> Self {
+        Self {
+            environments: config.environments.iter().map(Environment::new).collect(),
+        }
+    }
+
+    fn matching(&self, query: &str) -> Vec<&Environment> {
+        let Some(text) = query.trim_start().strip_prefix(PREFIX) else {
+            return Vec::new();
+        };
+        let words = matcher::words(text);
+
+        self.environments
+            .iter()
+            .filter(|environment| {
+                let text = format!("{} {}", environment.name, environment.description);
+                matcher::matches_all(&words, &text)
+            })
+            .take(MAX_RESULTS)
+            .collect()
+    }
+}
+
+impl Provider for Dev {
+    fn name(&self) -> &str {
+        "dev"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .into_iter()
+                .map(|environment| Item {
+                    name: environment.name.clone(),
+                    description: environment.description.clone(),
+                    icon: Some(environment.icon.clone()),
+                    open: None,
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let environment = self.matching(&query).into_iter().nth(index)?;
+            Some(Action::Terminal {
+                dir: environment.dir.clone(),
+                args: environment.command(),
+            })
+        })
+    }
+}
@@ -0,0 +1,440 @@
+//! Filesystems of drives and partitions known to udisks2, for queries starting with
+//! `mount `. System partitions are left out, as are MTP devices such as phones, which
+//! udisks2 does not manage.
+//!
+//! Activating a filesystem mounts it if needed and opens it. Its context menu can unmount it
+//! or eject its drive. Once every filesystem of a removable drive was unmounted, a result
+//! offering to safely remove it is listed first, which powers the drive off.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+use tokio::sync::OnceCell;
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+
+use super::{size, Action, Item, Provider};
+use crate::{fl, matcher};
+
+const PREFIX: &str = "mount ";
+
+const UDISKS_BUS: &str = "org.freedesktop.UDisks2";
+const UDISKS_PATH: &str = "/org/freedesktop/UDisks2";
+const BLOCK_INTERFACE: &str = "org.freedesktop.UDisks2.Block";
+const FILESYSTEM_INTERFACE: &str = "org.freedesktop.UDisks2.Filesystem";
+const DRIVE_INTERFACE: &str = "org.freedesktop.UDisks2.Drive";
+
+type ManagedObjects = HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>>;
+
+#[derive(Debug, Clone)]
+struct Drive {
+    path: OwnedObjectPath,
+    name: String,
+    removable: bool,
+    ejectable: bool,
+    can_power_off: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Volume {
+    path: OwnedObjectPath,
+    name: String,
+    /// Device file, such as `/dev/sdb1`.
+    device: String,
+    size: u64,
+    mount_points: Vec<String>,
+    drive: Option<Drive>,
+}
+
+impl Volume {
+    fn description(&self) -> String {
+        let state = match self.mount_points.first() {
+            Some(mount_point) => fl!("drive-mounted", path = mount_point.as_str()),
+            None => fl!("drive-not-mounted", device = self.device.as_str()),
+        };
+        match self.size {
+            0 => state,
+            bytes => format!("{state} · {}", size(bytes)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Entry {
+    SafelyRemove(Drive),
+    Volume(Volume),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Operation {
+    Open,
+    Unmount,
+    Eject,
+}
+
+impl Operation {
+    fn name(self, volume: &Volume) -> String {
+        match (self, volume.mount_points.is_empty()) {
+            (Self::Open, true) => fl!("drive-mount-and-open"),
+            (Self::Open, false) => fl!("drive-open"),
+            (Self::Unmount, _) => fl!("drive-unmount"),
+            (Self::Eject, _) => fl!("drive-eject"),
+        }
+    }
+
+    /// What can be done with `volume`, with activation first.
+    fn all(volume: &Volume) -> Vec<Self> {
+        let mut operations = vec![Self::Open];
+        if !volume.mount_points.is_empty() {
+            operations.push(Self::Unmount);
+        }
+        if volume.drive.as_ref().is_some_and(|drive| drive.ejectable) {
+            operations.push(Self::Eject);
+        }
+        operations
+    }
+}
+
+#[derive(Default)]
+pub struct Drives {
+    bus: OnceCell<zbus::Connection>,
+    /// A removable drive none of whose filesystems is mounted any more since the launcher
+    /// unmounted one.
+    unmounted: Mutex<Option<Drive>>,
+}
+
+impl Drives {
+    async fn matching(&self, query: &str) -> Vec<Entry> {
+        let Some(text) = query.trim_start().strip_prefix(PREFIX) else {
+            return Vec::new();
+        };
+        let words = matcher::words(text);
+
+        let volumes = match self.volumes().await {
+            Ok(volumes) => volumes,
+            Err(why) => {
+                tracing::debug!("failed to list drives: {why}");
+                return Vec::new();
+            }
+        };
+
+        let mut entries = Vec::new();
+        let unmounted = self.unmounted.lock().ok().and_then(|drive| drive.clone());
+        if let Some(drive) = unmounted {
+            // The drive is still connected, and nothing else mounted it since.
+            let connected = volumes
+                .iter()
+                .filter(|volume| volume.drive.as_ref().map(|d| &d.path) == Some(&drive.path))
+                .all(|volume| volume.mount_points.is_empty());
+            if connected {
+                entries.push(Entry::SafelyRemove(drive));
+            }
+        }
+
+        entries.extend(
+            volumes
+                .into_iter()
+                .filter(|volume| matcher::matches_all(&words, &volume.name))
+                .map(Entry::Volume),
+        );
+        entries.truncate(MAX_RESULTS);
+        entries
+    }
+
+    async fn bus(&self) -> zbus::Result<&zbus::Connection> {
+        self.bus.get_or_try_init(zbus::Connection::system).await
+    }
+
+    async fn call(
+        &self,
+        path: &OwnedObjectPath,
+        interface: &str,
+        method: &str,
+    ) -> zbus::Result<zbus::Message> {
+        let options = HashMap::<&str, Value>::new();
+        self.bus()
+            .await?
+            .call_method(
+                Some(UDISKS_BUS),
+                path.as_str(),
+                Some(interface),
+                method,
+                &(options,),
+            )
+            .await
+    }
+
+    /// Filesystems which are not part of the system, removable drives' first.
+    async fn volumes(&self) -> zbus::Result<Vec<Volume>> {
+        let objects: ManagedObjects = self
+            .bus()
+            .await?
+            .call_method(
+                Some(UDISKS_BUS),
+                UDISKS_PATH,
+                Some("org.freedesktop.DBus.ObjectManager"),
+                "GetManagedObjects",
+                &(),
+            )
+            .await?
+            .body()
+            .deserialize()?;
+
+        let drives = objects
+            .iter()
+            .filter_map(|(path, interfaces)| {
+                let drive = interfaces.get(DRIVE_INTERFACE)?;
+                let flag = |key: &str| {
+                    drive
+                        .get(key)
+                        .and_then(|value| bool::try_from(&**value).ok())
+                        .unwrap_or_default()
+                };
+                let text = |key: &str| {
+                    drive
+                        .get(key)
+                        .and_then(|value| <&str>::try_from(&**value).ok())
+                        .unwrap_or_default()
+                };
+
+                let name = format!("{} {}", text("Vendor"), text("Model"));
+                Some(Drive {
+                    path: path.clone(),
+                    name: name.trim().to_string(),
+                    removable: flag("Removable") || flag("MediaRemovable"),
+                    ejectable: flag("Ejectable"),
+                    can_power_off: flag("CanPowerOff"),
+                })
+            })
+            .map(|drive| (drive.path.clone(), drive))
+            .collect::<HashMap<_, _>>();
+
+        let mut volumes = objects
+            .into_iter()
+            .filter_map(|(path, mut interfaces)| {
+                let filesystem = interfaces.remove(FILESYSTEM_INTERFACE)?;
+                let mut block = interfaces.remove(BLOCK_INTERFACE)?;
+                let flag = |key: &str| {
+                    block
+                        .get(key)
+                        .and_then(|value| bool::try_from(&**value).ok())
+                        .unwrap_or_default()
+                };
+                let text = |key: &str| {
+                    block
+                        .get(key)
+                        .and_then(|value| <&str>::try_from(&**value).ok())
+                        .filter(|text| !text.is_empty())
+                        .map(String::from)
+                };
+
+                if flag("HintIgnore") || flag("HintSystem") {
+                    return None;
+                }
+
+                let drive = block
+                    .get("Drive")
+                    .and_then(|value| OwnedObjectPath::try_from(value.try_clone().ok()?).ok())
+                    .and_then(|path| drives.get(&path).cloned());
+                let name = text("IdLabel")
+                    .or_else(|| text("HintName"))
+                    .or_else(|| drive.as_ref().map(|drive| drive.name.clone()))
+                    .filter(|name| !name.is_empty());
+                let size = block
+                    .get("Size")
+                    .and_then(|value| u64::try_from(&**value).ok())
+                    .unwrap_or_default();
+                let device = block
+                    .remove("Device")
+                    .and_then(|value| Vec::<u8>::try_from(value).ok())
+                    .map(|bytes| c_string(&bytes))
+                    .unwrap_or_default();
+                let mount_points = filesystem
+                    .get("MountPoints")
+                    .and_then(|value| Vec::<Vec<u8>>::try_from(value.try_clone().ok()?).ok())
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|bytes| c_string(bytes))
+                    .collect();
+
+                Some(Volume {
+                    path,
+                    name: name.unwrap_or_else(|| device.clone()),
+                    device,
+                    size,
+                    mount_points,
+                    drive,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        volumes.sort_by(|a, b| {
+            let removable = |volume: &Volume| volume.drive.as_ref().is_some_and(|d| d.removable);
+            removable(b)
+                .cmp(&removable(a))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(volumes)
+    }
+
+    /// Mounts the volume unless it is mounted, returning where it is mounted.
+    async fn mount(&self, volume: &Volume) -> zbus::Result<String> {
+        if let Some(mount_point) = volume.mount_points.first() {
+            return Ok(mount_point.clone());
+        }
+        self.call(&volume.path, FILESYSTEM_INTERFACE, "Mount")
+            .await?
+            .body()
+            .deserialize()
+    }
+
+    async fn unmount(&self, volume: &Volume) -> zbus::Result<()> {
+        self.call(&volume.path, FILESYSTEM_INTERFACE, "Unmount")
+            .await?;
+
+        // Offer to safely remove the drive once none of its filesystems is mounted.
+        let Some(drive) = volume.drive.clone().filter(|drive| drive.removable) else {
+            return Ok(());
+        };
+        let mounted = self.volumes().await?.into_iter().any(|other| {
+            other.drive.as_ref().map(|d| &d.path) == Some(&drive.path)
+                && !other.mount_points.is_empty()
+        });
+        if !mounted {
+            if let Ok(mut unmounted) = self.unmounted.lock() {
+                *unmounted = Some(drive);
+            }
+        }
+        Ok(())
+    }
+
+    /// Unmounts every filesystem of the volume's drive, then ejects it.
+    async fn eject(&self, volume: &Volume) -> zbus::Result<()> {
+        let Some(drive) = volume.drive.as_ref() else {
+            return Ok(());
+        };
+        for other in self.volumes().await? {
+            if other.drive.as_ref().map(|d| &d.path) == Some(&drive.path)
+                && !other.mount_points.is_empty()
+            {
+                self.call(&other.path, FILESYSTEM_INTERFACE, "Unmount")
+                    .await?;
+            }
+        }
+        self.call(&drive.path, DRIVE_INTERFACE, "Eject").await?;
+        Ok(())
+    }
+
+    /// Powers the drive off, or ejects its media if it cannot be.
+    async fn safely_remove(&self, drive: &Drive) -> zbus::Result<()> {
+        if let Ok(mut unmounted) = self.unmounted.lock() {
+            unmounted.take();
+        }
+        let method = if drive.can_power_off {
+            "PowerOff"
+        } else {
+            "Eject"
+        };
+        self.call(&drive.path, DRIVE_INTERFACE, method).await?;
+        Ok(())
+    }
+
+    async fn run(&self, query: &str, index: usize, option: usize) -> Option<Action> {
+        let entry = self.matching(query).await.into_iter().nth(index)?;
+        let volume = match entry {
+            Entry::SafelyRemove(drive) => {
+                if let Err(why) = self.safely_remove(&drive).await {
+                    tracing::warn!("failed to power off {}: {why}", drive.name);
+                }
+                return None;
+            }
+            Entry::Volume(volume) => volume,
+        };
+
+        let result = match Operation::all(&volume).get(option)? {
+            Operation::Open => match self.mount(&volume).await {
+                Ok(mount_point) => return Some(Action::Open(mount_point)),
+                Err(why) => Err(why),
+            },
+            Operation::Unmount => match self.unmount(&volume).await {
+                // The launcher stays open to offer safely removing the drive.
+                Ok(()) => return Some(Action::Refresh),
+                Err(why) => Err(why),
+            },
+            Operation::Eject => self.eject(&volume).await,
+        };
+        if let Err(why) = result {
+            tracing::warn!("failed to change {}: {why}", volume.name);
+        }
+        None
+    }
+}
+
+/// A path from udisks2, which ends them with a NUL byte.
+fn c_string(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+impl Provider for Drives {
+    fn name(&self) -> &str {
+        "drives"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|entry| match entry {
+                    Entry::SafelyRemove(drive) => Item {
+                        name: fl!("drive-safely-remove", name = drive.name),
+                        description: fl!("drive-safely-remove-description"),
+                        icon: Some("media-eject-symbolic".into()),
+                        open: None,
+                    },
+                    Entry::Volume(volume) => Item {
+                        description: volume.description(),
+                        icon: Some(if volume.drive.as_ref().is_some_and(|d| d.removable) {
+                            "drive-removable-media-symbolic".into()
+                        } else {
+                            "drive-harddisk-symbolic".into()
+                        }),
+                        name: volume.name,
+                        open: None,
+                    },
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move { self.run(&query, index, 0).await })
+    }
+
+    fn context(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Vec<String>> {
+        Box::pin(async move {
+            match self.matching(&query).await.into_iter().nth(index) {
+                Some(Entry::Volume(volume)) => Operation::all(&volume)
+                    .into_iter()
+                    .map(|operation| operation.name(&volume))
+                    .collect(),
+                _ => Vec::new(),
+            }
+        })
+    }
+
+    fn activate_context(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+        option: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move { self.run(&query, index, option).await })
+    }
+}
@@ -0,0 +1,214 @@
+//! Evolution Data Server, which stores the calendars, task lists, and address books of
+//! GNOME Calendar, GNOME Contacts, Endeavour, and Evolution.
+
+use std::collections::HashMap;
+
+use zbus::zvariant::{OwnedObjectPath, OwnedValue};
+
+const SOURCES_BUS: &str = "org.gnome.evolution.dataserver.Sources5";
+const SOURCES_PATH: &str = "/org/gnome/evolution/dataserver/SourceManager";
+const SOURCE_INTERFACE: &str = "org.gnome.evolution.dataserver.Source";
+
+type ManagedObjects = HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>>;
+
+/// A service which opens one kind of source.
+pub struct Factory {
+    bus: &'static str,
+    path: &'static str,
+    interface: &'static str,
+    method: &'static str,
+    /// Interface of the opened sources.
+    opened: &'static str,
+    /// Section of the source data marking sources of this kind.
+    section: &'static str,
+}
+
+pub const CALENDARS: Factory = Factory {
+    bus: "org.gnome.evolution.dataserver.Calendar8",
+    path: "/org/gnome/evolution/dataserver/CalendarFactory",
+    interface: "org.gnome.evolution.dataserver.CalendarFactory",
+    method: "OpenCalendar",
+    opened: "org.gnome.evolution.dataserver.Calendar",
+    section: "[Calendar]",
+};
+
+pub const TASK_LISTS: Factory = Factory {
+    bus: "org.gnome.evolution.dataserver.Calendar8",
+    path: "/org/gnome/evolution/dataserver/CalendarFactory",
+    interface: "org.gnome.evolution.dataserver.CalendarFactory",
+    method: "OpenTaskList",
+    opened: "org.gnome.evolution.dataserver.Calendar",
+    section: "[Task List]",
+};
+
+pub const ADDRESS_BOOKS: Factory = Factory {
+    bus: "org.gnome.evolution.dataserver.AddressBook10",
+    path: "/org/gnome/evolution/dataserver/AddressBookFactory",
+    interface: "org.gnome.evolution.dataserver.AddressBookFactory",
+    method: "OpenAddressBook",
+    opened: "org.gnome.evolution.dataserver.AddressBook",
+    section: "[Address Book]",
+};
+
+impl Factory {
+    /// UIDs of the enabled sources of this kind.
+    pub async fn sources(&self, connection: &zbus::Connection) -> zbus::Result<Vec<String>> {
+        Ok(self
+            .named_sources(connection)
+            .await?
+            .into_iter()
+            .map(|(uid, _)| uid)
+            .collect())
+    }
+
+    /// UIDs and display names of the enabled sources of this kind.
+    pub async fn named_sources(
+        &self,
+        connection: &zbus::Connection,
+    ) -> zbus::Result<Vec<(String, String)>> {
+        let objects: ManagedObjects = connection
+            .call_method(
+                Some(SOURCES_BUS),
+                SOURCES_PATH,
+                Some("org.freedesktop.DBus.ObjectManager"),
+                "GetManagedObjects",
+                &(),
+            )
+            .await?
+            .body()
+            .deserialize()?;
+
+        Ok(objects
+            .values()
+            .filter_map(|interfaces| interfaces.get(SOURCE_INTERFACE))
+            .filter_map(|properties| {
+                let uid = <&str>::try_from(&**properties.get("UID")?).ok()?;
+                let data = <&str>::try_from(&**properties.get("Data")?).ok()?;
+                let enabled = !data.lines().any(|line| line.trim() == "Enabled=false");
+                if !enabled || !data.lines().any(|line| line.trim() == self.section) {
+                    return None;
+                }
+
+                let name = data
+                    .lines()
+                    .find_map(|line| line.trim().strip_prefix("DisplayName="))
+                    .unwrap_or(uid);
+                Some((uid.to_owned(), name.to_owned()))
+            })
+            .collect())
+    }
+
+    /// Opens a source, returning the bus name and path of the opened source.
+    pub async fn open(
+        &self,
+        connection: &zbus::Connection,
+        source: &str,
+    ) -> zbus::Result<(String, String)> {
+        let (path, bus): (String, String) = connection
+            .call_method(
+                Some(self.bus),
+                self.path,
+                Some(self.interface),
+                self.method,
+                &(source,),
+            )
+            .await?
+            .body()
+            .deserialize()?;
+
+        connection
+            .call_method(
+                Some(bus.as_str()),
+                path.as_str(),
+                Some(self.opened),
+                "Open",
+                &(),
+            )
+            .await?;
+
+        Ok((bus, path))
+    }
+
+    /// Opens a source and calls `method`, which takes an S-expression and returns a list
+    /// of iCalendar or vCard objects.
+    pub async fn query(
+        &self,
+        connection: &zbus::Connection,
+        source: &str,
+        method: &str,
+        sexp: &str,
+    ) -> zbus::Result<Vec<String>> {
+        let (bus, path) = self.open(connection, source).await?;
+
+        let (objects,): (Vec<String>,) = connection
+            .call_method(
+                Some(bus.as_str()),
+                path.as_str(),
+                Some(self.opened),
+                method,
+                &(sexp,),
+            )
+            .await?
+            .body()
+            .deserialize()?;
+
+        Ok(objects)
+    }
+
+    /// Opens a source and calls `method` of the opened source with `body`.
+    pub async fn call<B>(
+        &self,
+        connection: &zbus::Connection,
+        source: &str,
+        method: &str,
+        body: &B,
+    ) -> zbus::Result<()>
+    where
+        B: serde::Serialize + zbus::zvariant::DynamicType,
+    {
+        let (bus, path) = self.open(connection, source).await?;
+        connection
+            .call_method(
+                Some(bus.as_str()),
+                path.as_str(),
+                Some(self.opened),
+                method,
+                body,
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// Joins lines which iCalendar and vCard fold by starting them with whitespace.
+pub fn unfold(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .replace("\n ", "")
+        .replace("\n\t", "")
+}
+
+/// Splits a content line into its property name, without any group, its parameters, and
+/// its value.
+pub fn property(line: &str) -> Option<(&str, &str, &str)> {
+    let (name, value) = line.split_once(':')?;
+    let (name, params) = name.split_once(';').unwrap_or((name, ""));
+    let name = name.rsplit('.').next().unwrap_or(name);
+    Some((name, params, value))
+}
+
+/// Escapes text to be the value of a property.
+pub fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Unescapes a text value, joining its lines with spaces.
+pub fn unescape(text: &str) -> String {
+    text.replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
@@ -0,0 +1,299 @@
+//! Installed Steam and Lutris games, for queries starting with `game ` and for searches
+//! matching their name.
+//!
+//! Steam games are read from the app manifests in each of its library folders, and Lutris
+//! games from its database. Games are launched through their store's client, on the
+//! discrete GPU. Steam only passes its environment on to the game when it was not running
+//! yet.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+
+use super::cache::{self, Cache};
+use super::{Action, Item, Provider};
+use crate::{fl, matcher};
+
+const PREFIX: &str = "game ";
+
+/// How long the list of games is reused before the libraries are read again.
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Steam apps which are tools installed alongside games.
+const STEAM_TOOLS: &[&str] = &["Proton", "Steam Linux Runtime", "Steamworks Common"];
+
+/// Flag of an app manifest's `StateFlags` set once the app is fully installed.
+const STEAM_INSTALLED: u32 = 4;
+
+#[derive(Debug, Clone)]
+struct Game {
+    name: String,
+    store: &'static str,
+    icon: String,
+    command: Vec<String>,
+}
+
+pub struct Games {
+    cache: Cache<Vec<Game>>,
+}
+
+impl Default for Games {
+    fn default() -> Self {
+        Self {
+            cache: Cache::new(CACHE_TTL),
+        }
+    }
+}
+
+impl Games {
+    async fn games(&self) -> Arc<Vec<Game>> {
+        self.cache.get(cache::blocking(read_games)).await
+    }
+
+    async fn matching(&self, query: &str) -> Vec<Game> {
+        let query = query.trim_start();
+        let text = query.strip_prefix(PREFIX);
+        let words = matcher::words(text.unwrap_or(query));
+
+        // Other searches list games only once they say enough to tell them apart.
+        if text.is_none() && words.concat().chars().count() < 2 {
+            return Vec::new();
+        }
+
+        self.games()
+            .await
+            .iter()
+            .filter(|game| matcher::matches_all(&words, &game.name))
+            .take(MAX_RESULTS)
+            .cloned()
+            .collect()
+    }
+}
+
+fn read_games() -> Vec<Game> {
+    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else {
+        return Vec::new();
+    };
+
+    let mut games = steam_games(&home);
+    games.extend(lutris_games(&home));
+    games.sort_by_cached_key(|game| game.name.to_lowercase());
+    games
+}
+
+/// Games of the native Steam client, or of its Flatpak if it is not installed.
+fn steam_games(home: &Path) -> Vec<Game> {
+    let installations = [
+        (home.join(".local/share/Steam"), vec!["steam"]),
+        (home.join(".steam/steam"), vec!["steam"]),
+        (
+            home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"),
+            vec!["flatpak", "run", "com.valvesoftware.Steam"],
+        ),
+    ];
+    let Some((root, steam)) = installations
+        .iter()
+        .find(|(root, _)| root.join("steamapps").is_dir())
+    else {
+        return Vec::new();
+    };
+
+    let mut libraries = vec![root.clone()];
+    if let Ok(folders) = std::fs::read_to_string(root.join("steamapps/libraryfolders.vdf")) {
+        libraries.extend(
+            folders
+                .lines()
+                .filter_map(vdf_pair)
+                .filter(|(key, _)| key == "path")
+                .map(|(_, path)| PathBuf::from(path)),
+        );
+    }
+    libraries.sort();
+    libraries.dedup();
+
+    let mut games = Vec::new();
+    for library in libraries {
+        let Ok(entries) = std::fs::read_dir(library.join("steamapps")) else {
+            continue;
+        };
+
+        for entry in entries.filter_map(Result::ok) {
+            let is_manifest = entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.starts_with("appmanifest_") && name.ends_with(".acf"));
+            if !is_manifest {
+                continue;
+            }
+            let Ok(manifest) = std::fs::read_to_string(entry.path()) else {
+                continue;
+            };
+
+            let (mut id, mut name, mut flags) = (None, None, 0);
+            for (key, value) in manifest.lines().filter_map(vdf_pair) {
+                match key.as_str() {
+                    "appid" => id = Some(value),
+                    "name" => name = Some(value),
+                    "StateFlags" => flags = value.parse().unwrap_or_default(),
+                    _ => {}
+                }
+            }
+
+            let (Some(id), Some(name)) = (id, name) else {
+                continue;
+            };
+            if flags & STEAM_INSTALLED == 0 || STEAM_TOOLS.iter().any(|tool| name.starts_with(tool))
+            {
+                continue;
+            }
+
+            let mut command = steam.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+            command.extend(["-applaunch".to_string(), id.clone()]);
+            games.push(Game {
+                name,
+                store: "Steam",
+                icon: icon_or(home, &format!("steam_icon_{id}"), "steam"),
+                command,
+            });
+        }
+    }
+
+    games
+}
+
+/// Reads a line of a Valve data file holding a key and its value, such as
+/// `"name"    "Portal 2"`.
+fn vdf_pair(line: &str) -> Option<(String, String)> {
+    let mut strings = Vec::new();
+    let mut chars = line.trim().chars();
+    while let Some(c) = chars.next() {
+        if c != '"' {
+            continue;
+        }
+
+        let mut string = String::new();
+        loop {
+            match chars.next()? {
+                '"' => break,
+                '\\' => string.push(chars.next()?),
+                c => string.push(c),
+            }
+        }
+        strings.push(string);
+    }
+
+    let mut strings = strings.into_iter();
+    match (strings.next(), strings.next(), strings.next()) {
+        (Some(key), Some(value), None) => Some((key, value)),
+        _ => None,
+    }
+}
+
+/// Installed games of Lutris, or of its Flatpak if it is not installed.
+fn lutris_games(home: &Path) -> Vec<Game> {
+    let installations = [
+        (home.join(".local/share/lutris/pga.db"), vec!["lutris"]),
+        (
+            home.join(".var/app/net.lutris.Lutris/data/lutris/pga.db"),
+            vec!["flatpak", "run", "net.lutris.Lutris"],
+        ),
+    ];
+    let Some((database, lutris)) = installations
+        .iter()
+        .find(|(database, _)| database.is_file())
+    else {
+        return Vec::new();
+    };
+
+    let games =
+        rusqlite::Connection::open_with_flags(database, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .and_then(|connection| {
+                let mut statement = connection.prepare(
+                    "SELECT name, slug FROM games WHERE installed = 1 AND slug IS NOT NULL",
+                )?;
+                let games = statement
+                    .query_map([], |row| {
+                        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                    })?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                Ok(games)
+            });
+
+    match games {
+        Ok(games) => games
+            .into_iter()
+            .map(|(name, slug)| {
+                let mut command = lutris.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+                command.push(format!("lutris:rungame/{slug}"));
+                Game {
+                    name,
+                    store: "Lutris",
+                    icon: icon_or(home, &format!("lutris_{slug}"), "lutris"),
+                    command,
+                }
+            })
+            .collect(),
+        Err(why) => {
+            tracing::warn!("failed to read {}: {why}", database.display());
+            Vec::new()
+        }
+    }
+}
+
+/// The icon the store installed for a game, or the store's own icon.
+fn icon_or(home: &Path, icon: &str, fallback: &str) -> String {
+    let icons = home.join(".local/share/icons/hicolor");
+    let installed = std::fs::read_dir(icons).is_ok_and(|sizes| {
+        sizes.filter_map(Result::ok).any(|size| {
+            size.path()
+                .join("apps")
+                .join(format!("{icon}.png"))
+                .exists()
+        })
+    });
+
+    if installed {
+        icon.to_string()
+    } else {
+        fallback.to_string()
+    }
+}
+
+impl Provider for Games {
+    fn name(&self) -> &str {
+        "games"
+    }
+
+    fn addressed(&self, query: &str) -> bool {
+        query.trim_start().starts_with(PREFIX)
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|game| Item {
+                    name: game.name,
+                    description: fl!("game-description", store = game.store),
+                    icon: Some(game.icon),
+                    open: None,
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let game = self.matching(&query).await.into_iter().nth(index)?;
+            Some(Action::RunOnDiscreteGpu(game.command))
+        })
+    }
+}
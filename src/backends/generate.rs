@@ -0,0 +1,129 @@
+//! Developer utilities for queries starting with `gen ` or `hash `: a new UUID, and the
+//! SHA-256, MD5, and base64 encoding of the text that follows, or its decoding if it is
+//! base64 already. Activating a result copies it to the clipboard.
+
+use std::sync::{Arc, Mutex};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use futures::future::BoxFuture;
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::{Action, Item, Provider};
+
+const PREFIXES: [&str; 2] = ["gen ", "hash "];
+
+#[derive(Debug, Clone, Copy)]
+enum Output {
+    Uuid,
+    Sha256,
+    Md5,
+    Base64,
+    Decoded,
+}
+
+impl Output {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Uuid => "UUID v4",
+            Self::Sha256 => "SHA-256",
+            Self::Md5 => "MD5",
+            Self::Base64 => "Base64",
+            Self::Decoded => "Decoded from base64",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Generate {
+    /// The UUID shown for the last query, so that activating it copies the same one.
+    uuid: Mutex<(String, String)>,
+}
+
+impl Generate {
+    /// What is generated for `query`, with its value.
+    fn outputs(&self, query: &str) -> Vec<(Output, String)> {
+        let query = query.trim_start();
+        let Some((prefix, text)) = PREFIXES
+            .iter()
+            .find_map(|prefix| Some((*prefix, query.strip_prefix(prefix)?.trim())))
+        else {
+            return Vec::new();
+        };
+
+        let mut outputs = Vec::new();
+        if prefix == "gen " && (text.is_empty() || text.eq_ignore_ascii_case("uuid")) {
+            outputs.push((Output::Uuid, self.uuid(query)));
+        }
+        if text.is_empty() {
+            return outputs;
+        }
+
+        outputs.push((Output::Sha256, hex(&Sha256::digest(text))));
+        outputs.push((Output::Md5, hex(&Md5::digest(text))));
+        outputs.push((Output::Base64, STANDARD.encode(text)));
+        if let Some(decoded) = STANDARD
+            .decode(text)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+        {
+            outputs.push((Output::Decoded, decoded));
+        }
+        outputs
+    }
+
+    /// A new UUID, unless one was already shown for this query.
+    fn uuid(&self, query: &str) -> String {
+        let Ok(mut last) = self.uuid.lock() else {
+            return Uuid::new_v4().to_string();
+        };
+        if last.0 != query {
+            *last = (query.to_string(), Uuid::new_v4().to_string());
+        }
+        last.1.clone()
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+impl Provider for Generate {
+    fn name(&self) -> &str {
+        "generate"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.outputs(&query)
+                .into_iter()
+                .map(|(output, value)| Item {
+                    name: value,
+                    description: output.name().to_string(),
+                    icon: Some("edit-copy-symbolic".into()),
+                    open: None,
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let (_, value) = self.outputs(&query).into_iter().nth(index)?;
+            Some(Action::Copy(value))
+        })
+    }
+
+    fn share(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Option<String>> {
+        Box::pin(async move {
+            let (_, value) = self.outputs(&query).into_iter().nth(index)?;
+            Some(value)
+        })
+    }
+}
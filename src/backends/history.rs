@@ -0,0 +1,247 @@
+//! Pages from Firefox and Chromium-based browsers' history, for queries starting with `h `
+//! and, optionally, web searches.
+//!
+//! Browsers keep their history databases locked while running, so each database is
+//! copied to a temporary file before it is read. The index is rebuilt once it is older
+//! than [`REINDEX_AFTER`].
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+
+use super::cache::{self, Cache};
+use super::{Action, Item, Provider};
+use crate::config::HistoryConfig;
+use crate::matcher;
+
+const PREFIX: &str = "h ";
+
+const REINDEX_AFTER: Duration = Duration::from_secs(10 * 60);
+
+/// Pages read from each database, most visited first.
+const PAGES_PER_DATABASE: u32 = 5000;
+
+const FIREFOX_PAGES: &str = "SELECT url, title, visit_count FROM moz_places
+    WHERE hidden = 0 AND title IS NOT NULL ORDER BY frecency DESC LIMIT ?1";
+
+const CHROMIUM_PAGES: &str = "SELECT url, title, visit_count FROM urls
+    WHERE hidden = 0 ORDER BY visit_count DESC, last_visit_time DESC LIMIT ?1";
+
+/// Profile directories of Chromium-based browsers, relative to the config directory.
+const CHROMIUM_DIRS: &[&str] = &[
+    "chromium",
+    "google-chrome",
+    "BraveSoftware/Brave-Browser",
+    "microsoft-edge",
+    "vivaldi",
+];
+
+#[derive(Debug, Clone)]
+struct Page {
+    url: String,
+    title: String,
+    visits: i64,
+}
+
+pub struct History {
+    web_prefixes: Vec<String>,
+    index: Cache<Vec<Page>>,
+}
+
+impl History {
+    pub fn new(config: &HistoryConfig) -> Self {
+        Self {
+            web_prefixes: config.web_prefixes.clone(),
+            index: Cache::new(REINDEX_AFTER),
+        }
+    }
+
+    async fn index(&self) -> Arc<Vec<Page>> {
+        self.index.get(cache::blocking(read_history)).await
+    }
+
+    /// The pages a query asks for, or nothing if it is not a history or web query.
+    async fn matching(&self, query: &str) -> Vec<Page> {
+        let query = query.trim_start();
+        let Some(text) = std::iter::once(PREFIX)
+            .chain(self.web_prefixes.iter().map(String::as_str))
+            .find_map(|prefix| query.strip_prefix(prefix))
+        else {
+            return Vec::new();
+        };
+
+        let words = matcher::words(text);
+        if words.is_empty() {
+            return Vec::new();
+        }
+
+        self.index()
+            .await
+            .iter()
+            .filter(|page| matcher::matches_all(&words, &format!("{} {}", page.title, page.url)))
+            .take(MAX_RESULTS)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Reads every browser profile's history, merging pages visited in several profiles.
+fn read_history() -> Vec<Page> {
+    let mut pages = Vec::new();
+    for (database, query) in databases() {
+        match read_database(&database, query) {
+            Ok(read) => pages.extend(read),
+            Err(why) => tracing::warn!("failed to read {}: {why}", database.display()),
+        }
+    }
+
+    pages.sort_by(|a, b| a.url.cmp(&b.url).then(b.visits.cmp(&a.visits)));
+    pages.dedup_by(|a, b| a.url == b.url);
+    pages.sort_by_key(|page| std::cmp::Reverse(page.visits));
+    pages
+}
+
+/// History databases of the installed browser profiles, with the query listing their
+/// pages.
+fn databases() -> Vec<(PathBuf, &'static str)> {
+    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else {
+        return Vec::new();
+    };
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".config"));
+
+    let firefox = [
+        home.join(".mozilla/firefox"),
+        home.join(".var/app/org.mozilla.firefox/.mozilla/firefox"),
+    ];
+    let firefox = firefox
+        .iter()
+        .flat_map(|dir| subdirs(dir))
+        .map(|profile| (profile.join("places.sqlite"), FIREFOX_PAGES));
+
+    let chromium = CHROMIUM_DIRS
+        .iter()
+        .flat_map(|dir| subdirs(&config.join(dir)))
+        .map(|profile| (profile.join("History"), CHROMIUM_PAGES));
+
+    firefox
+        .chain(chromium)
+        .filter(|(database, _)| database.is_file())
+        .collect()
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Reads a copy of `database`, along with its write-ahead log, which holds the most
+/// recent visits while the browser runs.
+fn read_database(database: &Path, query: &str) -> Result<Vec<Page>, Box<dyn std::error::Error>> {
+    let copy = std::env::temp_dir().join(format!(
+        "cosmic-launcher-history-{}.sqlite",
+        std::process::id()
+    ));
+    let remove_copy = || {
+        for suffix in ["", "-wal", "-shm"] {
+            let _res = std::fs::remove_file(with_suffix(&copy, suffix));
+        }
+    };
+
+    // A copy left behind by a crash must not be mixed with another database's log.
+    remove_copy();
+    let pages = copy_database(database, &copy).and_then(|()| {
+        let connection = rusqlite::Connection::open(&copy)?;
+        let mut statement = connection.prepare(query)?;
+        let pages = statement
+            .query_map([PAGES_PER_DATABASE], |row| {
+                let url: String = row.get(0)?;
+                let title: Option<String> = row.get(1)?;
+                Ok(Page {
+                    title: title
+                        .filter(|title| !title.is_empty())
+                        .unwrap_or_else(|| url.clone()),
+                    url,
+                    visits: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(pages)
+    });
+
+    remove_copy();
+
+    pages
+}
+
+fn copy_database(database: &Path, copy: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::copy(database, copy)?;
+    let wal = with_suffix(database, "-wal");
+    if wal.exists() {
+        std::fs::copy(wal, with_suffix(copy, "-wal"))?;
+    }
+    Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+impl Provider for History {
+    fn name(&self) -> &str {
+        "history"
+    }
+
+    fn addressed(&self, query: &str) -> bool {
+        let query = query.trim_start();
+        std::iter::once(PREFIX)
+            .chain(self.web_prefixes.iter().map(String::as_str))
+            .any(|prefix| query.starts_with(prefix))
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|page| Item {
+                    name: page.title,
+                    description: page.url,
+                    icon: Some("web-browser".into()),
+                    open: None,
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let page = self.matching(&query).await.into_iter().nth(index)?;
+            Some(Action::Open(page.url))
+        })
+    }
+
+    fn share(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Option<String>> {
+        Box::pin(async move {
+            let page = self.matching(&query).await.into_iter().nth(index)?;
+            Some(page.url)
+        })
+    }
+}
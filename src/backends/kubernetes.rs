@@ -0,0 +1,243 @@
+//! Contexts of the kubeconfig, and namespaces of the current context's cluster, for queries
+//! starting with `kube `.
+//!
+//! The current context and namespace are marked as active. Activating a context makes it the
+//! current one, and activating a namespace makes it the current context's namespace, both
+//! through `kubectl config`, which writes the kubeconfig. Namespaces are asked from the
+//! cluster, and kept for [`NAMESPACES_FOR`].
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+use tokio::sync::Mutex;
+
+use super::{Action, Item, Provider};
+use crate::doctor::find_in_path;
+use crate::{fl, matcher};
+
+const PREFIX: &str = "kube ";
+
+const PROGRAM: &str = "kubectl";
+
+/// Time `kubectl` has to read the kubeconfig or change it.
+const CONFIG_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Time the cluster has to list its namespaces.
+const NAMESPACES_TIMEOUT: Duration = Duration::from_secs(2);
+
+const NAMESPACES_FOR: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+struct Context {
+    name: String,
+    cluster: String,
+    /// The namespace commands use, unless they name one.
+    namespace: String,
+    current: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Entry {
+    Context(Context),
+    Namespace { name: String, current: bool },
+}
+
+pub struct Kubernetes {
+    installed: bool,
+    /// Namespaces by context, and when they were listed.
+    namespaces: Mutex<Option<(String, Instant, Vec<String>)>>,
+}
+
+impl Default for Kubernetes {
+    fn default() -> Self {
+        Self {
+            installed: find_in_path(PROGRAM).is_some(),
+            namespaces: Mutex::new(None),
+        }
+    }
+}
+
+impl Kubernetes {
+    async fn matching(&self, query: &str) -> Vec<Entry> {
+        let Some(text) = query.trim_start().strip_prefix(PREFIX) else {
+            return Vec::new();
+        };
+        if !self.installed {
+            return Vec::new();
+        }
+        let words = matcher::words(text);
+        let matches = |name: &str| matcher::matches_all(&words, name);
+
+        let contexts = match contexts().await {
+            Ok(contexts) => contexts,
+            Err(why) => {
+                tracing::debug!("failed to read the kubeconfig: {why}");
+                return Vec::new();
+            }
+        };
+
+        let mut entries = Vec::new();
+        if let Some(current) = contexts.iter().find(|context| context.current) {
+            entries.extend(
+                self.namespaces(&current.name)
+                    .await
+                    .into_iter()
+                    .filter(|name| matches(name))
+                    .map(|name| Entry::Namespace {
+                        current: name == current.namespace,
+                        name,
+                    }),
+            );
+        }
+        // Contexts come first, as there are fewer of them.
+        entries.splice(
+            0..0,
+            contexts
+                .into_iter()
+                .filter(|context| matches(&context.name))
+                .map(Entry::Context),
+        );
+        entries.truncate(MAX_RESULTS);
+        entries
+    }
+
+    async fn namespaces(&self, context: &str) -> Vec<String> {
+        let mut namespaces = self.namespaces.lock().await;
+        if let Some((listed_for, at, names)) = namespaces.as_ref() {
+            if listed_for == context && at.elapsed() < NAMESPACES_FOR {
+                return names.clone();
+            }
+        }
+
+        let request_timeout = format!("--request-timeout={}s", NAMESPACES_TIMEOUT.as_secs());
+        let args = [
+            "--context",
+            context,
+            &request_timeout,
+            "get",
+            "namespaces",
+            "--output=jsonpath={.items[*].metadata.name}",
+        ];
+        let names = match kubectl(&args, NAMESPACES_TIMEOUT).await {
+            Ok(names) => names.split_whitespace().map(String::from).collect(),
+            Err(why) => {
+                tracing::debug!("failed to list the namespaces of {context}: {why}");
+                Vec::new()
+            }
+        };
+        *namespaces = Some((context.to_string(), Instant::now(), names.clone()));
+        names
+    }
+}
+
+/// Runs `kubectl`, returning what it printed.
+async fn kubectl(args: &[&str], timeout: Duration) -> Result<String, String> {
+    let output = tokio::process::Command::new(PROGRAM)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .output();
+
+    match tokio::time::timeout(timeout, output).await {
+        Ok(Ok(output)) if output.status.success() => {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        }
+        Ok(Ok(output)) => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+        Ok(Err(why)) => Err(why.to_string()),
+        Err(_) => Err(format!("no answer within {timeout:?}")),
+    }
+}
+
+/// The contexts of the kubeconfig, merged from every file of `KUBECONFIG` as `kubectl` does.
+async fn contexts() -> Result<Vec<Context>, String> {
+    let config = kubectl(&["config", "view", "--output=json"], CONFIG_TIMEOUT).await?;
+    let config =
+        serde_json::from_str::<serde_json::Value>(&config).map_err(|why| why.to_string())?;
+    let current = config["current-context"].as_str().unwrap_or_default();
+    let text = |value: &serde_json::Value| value.as_str().unwrap_or_default().to_string();
+
+    Ok(config["contexts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|context| {
+            let name = text(&context["name"]);
+            let namespace = match text(&context["context"]["namespace"]) {
+                namespace if namespace.is_empty() => "default".into(),
+                namespace => namespace,
+            };
+            Context {
+                current: name == current,
+                cluster: text(&context["context"]["cluster"]),
+                namespace,
+                name,
+            }
+        })
+        .filter(|context| !context.name.is_empty())
+        .collect())
+}
+
+impl Provider for Kubernetes {
+    fn name(&self) -> &str {
+        "kubernetes"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|entry| match entry {
+                    Entry::Context(context) => Item {
+                        description: if context.current {
+                            fl!("kube-context-active", cluster = context.cluster.as_str())
+                        } else {
+                            fl!("kube-context", cluster = context.cluster.as_str())
+                        },
+                        name: context.name,
+                        icon: Some("network-server-symbolic".into()),
+                        open: None,
+                    },
+                    Entry::Namespace { name, current } => Item {
+                        name,
+                        description: if current {
+                            fl!("kube-namespace-active")
+                        } else {
+                            fl!("kube-namespace")
+                        },
+                        icon: Some("folder-symbolic".into()),
+                        open: None,
+                    },
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let args: Vec<String> = match self.matching(&query).await.into_iter().nth(index)? {
+                Entry::Context(context) => {
+                    vec!["config".into(), "use-context".into(), context.name]
+                }
+                Entry::Namespace { name, .. } => vec![
+                    "config".into(),
+                    "set-context".into(),
+                    "--current".into(),
+                    format!("--namespace={name}"),
+                ],
+            };
+
+            let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+            if let Err(why) = kubectl(&args, CONFIG_TIMEOUT).await {
+                tracing::warn!("failed to switch the kubeconfig: {why}");
+            }
+            None
+        })
+    }
+}
@@ -0,0 +1,423 @@
+//! Media players which implement MPRIS, for queries starting with `play ` or `music `, and
+//! their controls for queries such as `pause` or `next`.
+//!
+//! Players are listed with what they are playing, playing ones first. Activating one plays
+//! or pauses it, and its context menu can also skip to the next or previous track. While
+//! the query is empty, what is playing is shown below the other results.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+use tokio::sync::OnceCell;
+use zbus::zvariant::OwnedValue;
+
+use super::{Action, Item, Provider};
+use crate::{fl, matcher};
+
+const PREFIXES: [&str; 2] = ["play ", "music "];
+
+const BUS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const PATH: &str = "/org/mpris/MediaPlayer2";
+const ROOT_INTERFACE: &str = "org.mpris.MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+/// Characters a query needs before controls are matched.
+const MIN_QUERY: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Control {
+    PlayPause,
+    Play,
+    Pause,
+    Next,
+    Previous,
+}
+
+/// Controls found by their name alone.
+const KEYWORDS: [(&str, Control); 6] = [
+    ("play", Control::Play),
+    ("resume", Control::Play),
+    ("pause", Control::Pause),
+    ("next", Control::Next),
+    ("skip", Control::Next),
+    ("previous", Control::Previous),
+];
+
+impl Control {
+    fn method(self) -> &'static str {
+        match self {
+            Self::PlayPause => "PlayPause",
+            Self::Play => "Play",
+            Self::Pause => "Pause",
+            Self::Next => "Next",
+            Self::Previous => "Previous",
+        }
+    }
+
+    fn name(self, player: &Player) -> String {
+        match self {
+            Self::PlayPause if player.playing => fl!("media-pause"),
+            Self::PlayPause | Self::Play => fl!("media-play"),
+            Self::Pause => fl!("media-pause"),
+            Self::Next => fl!("media-next"),
+            Self::Previous => fl!("media-previous"),
+        }
+    }
+
+    fn icon(self, player: &Player) -> &'static str {
+        match self {
+            Self::PlayPause if player.playing => "media-playback-pause-symbolic",
+            Self::PlayPause | Self::Play => "media-playback-start-symbolic",
+            Self::Pause => "media-playback-pause-symbolic",
+            Self::Next => "media-skip-forward-symbolic",
+            Self::Previous => "media-skip-backward-symbolic",
+        }
+    }
+
+    /// Whether the player can be controlled this way now.
+    fn applies_to(self, player: &Player) -> bool {
+        match self {
+            Self::PlayPause => player.can_play || player.can_pause,
+            Self::Play => player.can_play && !player.playing,
+            Self::Pause => player.can_pause && player.playing,
+            Self::Next => player.can_go_next,
+            Self::Previous => player.can_go_previous,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Player {
+    bus_name: String,
+    /// The name of the app, such as `Spotify`.
+    identity: String,
+    desktop_entry: Option<String>,
+    title: Option<String>,
+    artists: Vec<String>,
+    playing: bool,
+    /// Whether a track is loaded, playing or paused.
+    active: bool,
+    can_play: bool,
+    can_pause: bool,
+    can_go_next: bool,
+    can_go_previous: bool,
+}
+
+impl Player {
+    /// The track and its artists, or the app when nothing is loaded.
+    fn track(&self) -> String {
+        match (&self.title, self.artists.is_empty()) {
+            (Some(title), false) => format!("{title} — {}", self.artists.join(", ")),
+            (Some(title), true) => title.clone(),
+            (None, _) => self.identity.clone(),
+        }
+    }
+
+    fn state(&self) -> String {
+        match (self.playing, self.active) {
+            (true, _) => fl!("media-playing"),
+            (false, true) => fl!("media-paused"),
+            (false, false) => fl!("media-stopped"),
+        }
+    }
+
+    fn icon(&self) -> String {
+        self.desktop_entry
+            .clone()
+            .unwrap_or_else(|| "multimedia-player-symbolic".into())
+    }
+
+    /// The controls offered in the player's context menu, with activation first.
+    fn controls(&self) -> Vec<Control> {
+        [Control::PlayPause, Control::Next, Control::Previous]
+            .into_iter()
+            .filter(|control| control.applies_to(self))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Entry {
+    Player(Player),
+    Control(Control, Player),
+}
+
+#[derive(Default)]
+pub struct Media {
+    bus: OnceCell<zbus::Connection>,
+}
+
+impl Media {
+    async fn matching(&self, query: &str) -> Vec<Entry> {
+        let query = query.trim_start();
+        let text = PREFIXES
+            .iter()
+            .find_map(|prefix| query.strip_prefix(prefix));
+        let keyword = query.trim().to_lowercase();
+        let controls = KEYWORDS
+            .iter()
+            .filter(|(name, _)| keyword.chars().count() >= MIN_QUERY && name.starts_with(&keyword))
+            .map(|(_, control)| *control)
+            .collect::<Vec<_>>();
+        if text.is_none() && controls.is_empty() && !query.is_empty() {
+            return Vec::new();
+        }
+
+        let players = match self.players().await {
+            Ok(players) => players,
+            Err(why) => {
+                tracing::debug!("failed to list media players: {why}");
+                return Vec::new();
+            }
+        };
+
+        if let Some(text) = text {
+            let words = matcher::words(text);
+            return players
+                .into_iter()
+                .filter(|player| {
+                    matcher::matches_all(&words, &format!("{} {}", player.identity, player.track()))
+                })
+                .take(MAX_RESULTS)
+                .map(Entry::Player)
+                .collect();
+        }
+
+        // Nothing typed asks for what is playing, or was last.
+        if query.is_empty() {
+            return players
+                .into_iter()
+                .find(|player| player.active)
+                .map(Entry::Player)
+                .into_iter()
+                .collect();
+        }
+
+        // Each control applies to the player it is most likely meant for, which is the one
+        // playing, or else the first one paused.
+        controls
+            .into_iter()
+            .filter_map(|control| {
+                let player = players
+                    .iter()
+                    .filter(|player| player.active || control == Control::Play)
+                    .find(|player| control.applies_to(player))?;
+                Some(Entry::Control(control, player.clone()))
+            })
+            .collect()
+    }
+
+    async fn bus(&self) -> zbus::Result<&zbus::Connection> {
+        self.bus.get_or_try_init(zbus::Connection::session).await
+    }
+
+    /// Every player on the session bus, playing ones first, then paused ones.
+    async fn players(&self) -> zbus::Result<Vec<Player>> {
+        let bus = self.bus().await?;
+        let names: Vec<String> = bus
+            .call_method(
+                Some("org.freedesktop.DBus"),
+                "/org/freedesktop/DBus",
+                Some("org.freedesktop.DBus"),
+                "ListNames",
+                &(),
+            )
+            .await?
+            .body()
+            .deserialize()?;
+
+        let players = names
+            .into_iter()
+            .filter(|name| name.starts_with(BUS_PREFIX))
+            .map(|name| async move {
+                match self.player(bus, &name).await {
+                    Ok(player) => Some(player),
+                    Err(why) => {
+                        tracing::debug!("failed to read the state of {name}: {why}");
+                        None
+                    }
+                }
+            });
+        let mut players = futures::future::join_all(players)
+            .await
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        players.sort_by_key(|player| (!player.playing, !player.active));
+        Ok(players)
+    }
+
+    async fn player(&self, bus: &zbus::Connection, bus_name: &str) -> zbus::Result<Player> {
+        let properties = |interface: &'static str| async move {
+            bus.call_method(
+                Some(bus_name),
+                PATH,
+                Some("org.freedesktop.DBus.Properties"),
+                "GetAll",
+                &(interface,),
+            )
+            .await?
+            .body()
+            .deserialize::<HashMap<String, OwnedValue>>()
+        };
+        let root = properties(ROOT_INTERFACE).await?;
+        let player = properties(PLAYER_INTERFACE).await?;
+
+        let text = |properties: &HashMap<String, OwnedValue>, key: &str| {
+            properties
+                .get(key)
+                .and_then(|value| <&str>::try_from(&**value).ok())
+                .filter(|text| !text.is_empty())
+                .map(String::from)
+        };
+        let flag = |key: &str| {
+            player
+                .get(key)
+                .and_then(|value| bool::try_from(&**value).ok())
+                .unwrap_or_default()
+        };
+
+        let metadata = player
+            .get("Metadata")
+            .and_then(|value| HashMap::<String, OwnedValue>::try_from(value.try_clone().ok()?).ok())
+            .unwrap_or_default();
+        let artists = metadata
+            .get("xesam:artist")
+            .and_then(|value| Vec::<String>::try_from(value.try_clone().ok()?).ok())
+            .unwrap_or_default();
+        let status = text(&player, "PlaybackStatus").unwrap_or_default();
+
+        Ok(Player {
+            bus_name: bus_name.to_string(),
+            identity: text(&root, "Identity").unwrap_or_else(|| {
+                bus_name
+                    .trim_start_matches(BUS_PREFIX)
+                    .split('.')
+                    .next()
+                    .unwrap_or_default()
+                    .to_string()
+            }),
+            desktop_entry: text(&root, "DesktopEntry"),
+            title: text(&metadata, "xesam:title"),
+            artists: artists.into_iter().filter(|a| !a.is_empty()).collect(),
+            playing: status == "Playing",
+            active: status == "Playing" || status == "Paused",
+            can_play: flag("CanPlay"),
+            can_pause: flag("CanPause"),
+            can_go_next: flag("CanGoNext"),
+            can_go_previous: flag("CanGoPrevious"),
+        })
+    }
+
+    async fn control(&self, player: &Player, control: Control) {
+        let result = match self.bus().await {
+            Ok(bus) => bus
+                .call_method(
+                    Some(player.bus_name.as_str()),
+                    PATH,
+                    Some(PLAYER_INTERFACE),
+                    control.method(),
+                    &(),
+                )
+                .await
+                .map(drop),
+            Err(why) => Err(why),
+        };
+        if let Err(why) = result {
+            tracing::warn!("failed to {} {}: {why}", control.method(), player.identity);
+        }
+    }
+
+    async fn run(&self, query: &str, index: usize, option: usize) -> Option<Action> {
+        match self.matching(query).await.into_iter().nth(index)? {
+            Entry::Player(player) => {
+                let control = *player.controls().get(option)?;
+                self.control(&player, control).await;
+            }
+            Entry::Control(control, player) => self.control(&player, control).await,
+        }
+        None
+    }
+}
+
+fn item(entry: Entry, empty_query: bool) -> Item {
+    match entry {
+        Entry::Player(player) => Item {
+            name: player.track(),
+            description: if empty_query && player.playing {
+                fl!("media-now-playing", player = player.identity.as_str())
+            } else {
+                format!("{} · {}", player.state(), player.identity)
+            },
+            icon: Some(player.icon()),
+            open: None,
+        },
+        Entry::Control(control, player) => Item {
+            name: control.name(&player),
+            description: format!("{} · {}", player.track(), player.identity),
+            icon: Some(control.icon(&player).into()),
+            open: None,
+        },
+    }
+}
+
+impl Provider for Media {
+    fn name(&self) -> &str {
+        "media"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            if query.trim().is_empty() {
+                return Vec::new();
+            }
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|entry| item(entry, false))
+                .collect()
+        })
+    }
+
+    fn footer(self: Arc<Self>) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching("")
+                .await
+                .into_iter()
+                .map(|entry| item(entry, true))
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move { self.run(&query, index, 0).await })
+    }
+
+    fn context(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Vec<String>> {
+        Box::pin(async move {
+            match self.matching(&query).await.into_iter().nth(index) {
+                Some(Entry::Player(player)) => player
+                    .controls()
+                    .into_iter()
+                    .map(|control| control.name(&player))
+                    .collect(),
+                _ => Vec::new(),
+            }
+        })
+    }
+
+    fn activate_context(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+        option: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move { self.run(&query, index, option).await })
+    }
+}
@@ -0,0 +1,560 @@
+//! Result providers which run inside the launcher rather than as pop-launcher plugins.
+//!
+//! Every provider's results are merged into the list pop-launcher returns, using IDs from
+//! [`LOCAL_ID_BASE`] upward, so that activating them is routed back to the provider.
+
+pub mod appearance;
+pub mod appimages;
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod bluetooth;
+pub mod budget;
+mod cache;
+pub mod calendar;
+pub mod clock;
+pub mod contacts;
+#[cfg(feature = "containers")]
+pub mod containers;
+pub mod crashes;
+pub mod dev;
+pub mod drives;
+mod eds;
+#[cfg(feature = "games")]
+pub mod games;
+pub mod generate;
+#[cfg(feature = "browser-history")]
+pub mod history;
+#[cfg(feature = "kubernetes")]
+pub mod kubernetes;
+pub mod media;
+pub mod network;
+pub mod notes;
+#[cfg(feature = "ocr")]
+pub mod ocr;
+pub mod passwords;
+pub mod projects;
+pub mod remote;
+pub mod repos;
+pub mod script;
+pub mod sessions;
+pub mod settings;
+pub mod shell_history;
+pub mod snippets;
+#[cfg(feature = "browser-tabs")]
+pub mod tabs;
+pub mod todo;
+pub mod toggles;
+pub mod trash;
+pub mod unicode;
+pub mod vms;
+pub mod wallpapers;
+#[cfg(feature = "wasm-plugins")]
+pub mod wasm;
+pub mod weather;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use cosmic_launcher_core::scope::Scope;
+use cosmic_launcher_core::LOCAL_ID_BASE;
+use futures::future::BoxFuture;
+use pop_launcher::{ContextOption, IconSource, SearchResult};
+
+use self::budget::Budgets;
+use crate::config::Config;
+use crate::fl;
+
+/// Custom commands use the first block of local IDs, and each provider the next ones.
+const IDS_PER_PROVIDER: u32 = 1 << 16;
+
+/// A result produced by a provider.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct Item {
+    pub name: String,
+    pub description: String,
+    pub icon: Option<String>,
+    /// URI or path opened on activation, for providers which do not handle it themselves.
+    pub open: Option<String>,
+}
+
+/// What to do after a provider's result was activated.
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// Open a URI or path with the user's preferred application.
+    Open(String),
+    /// Put text on the clipboard.
+    Copy(String),
+    /// Put a secret on the clipboard, and clear it after `clear_after` unless it was
+    /// replaced since.
+    CopySecret { text: String, clear_after: Duration },
+    /// Type text into the window focused once the launcher is hidden.
+    Type(String),
+    /// Show text as a QR code, keeping the launcher open.
+    QrCode(String),
+    /// Query the providers again, keeping the launcher open.
+    Refresh,
+    /// Replace the query, keeping the launcher open.
+    Query(String),
+    /// Run a program, given as its arguments.
+    Run(Vec<String>),
+    /// Run a program, such as a game, on the GPU which is not the default one.
+    RunOnDiscreteGpu(Vec<String>),
+    /// Open the configured terminal in `dir`, running `args` if there are any.
+    Terminal {
+        dir: Option<PathBuf>,
+        args: Vec<String>,
+    },
+}
+
+pub trait Provider: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Whether `query` starts with a prefix of the provider, such as `game `, which runs
+    /// it however short the query is.
+    fn addressed(&self, _query: &str) -> bool {
+        false
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>>;
+
+    /// Activates the result at `index` in the list returned for `query`.
+    fn activate(self: Arc<Self>, query: String, index: usize)
+        -> BoxFuture<'static, Option<Action>>;
+
+    /// Names of the context options of the result at `index`.
+    fn context(self: Arc<Self>, _query: String, _index: usize) -> BoxFuture<'static, Vec<String>> {
+        Box::pin(async { Vec::new() })
+    }
+
+    /// Activates the context option at `option` of the result at `index`.
+    fn activate_context(
+        self: Arc<Self>,
+        _query: String,
+        _index: usize,
+        _option: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async { None })
+    }
+
+    /// Results listed below the others while the query is empty, such as what is playing.
+    /// They are activated as the results of an empty query.
+    fn footer(self: Arc<Self>) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async { Vec::new() })
+    }
+
+    /// The URL or text of the result at `index`, offered to be shown as a QR code.
+    fn share(self: Arc<Self>, _query: String, _index: usize) -> BoxFuture<'static, Option<String>> {
+        Box::pin(async { None })
+    }
+
+    /// Drops what the provider keeps in memory which should not stay there while the
+    /// session is locked, such as a generated password.
+    fn forget(&self) {}
+}
+
+/// Every provider discovered at startup.
+#[derive(Clone, Default)]
+pub struct Registry {
+    providers: Vec<Arc<dyn Provider>>,
+    /// Characters a query needs before each provider is run, unless it addresses it.
+    min_lengths: Vec<usize>,
+    budgets: Budgets,
+}
+
+impl Registry {
+    /// Sets up the built-in providers enabled in `config`, then discovers plugins.
+    pub fn load(config: &Config) -> Self {
+        let mut registry = Self::default();
+
+        if config.calendar {
+            registry
+                .providers
+                .push(Arc::new(calendar::Calendar::default()));
+        }
+
+        if config.contacts.enabled {
+            registry
+                .providers
+                .push(Arc::new(contacts::Contacts::new(&config.contacts)));
+        }
+
+        #[cfg(feature = "browser-history")]
+        if config.history.enabled {
+            registry
+                .providers
+                .push(Arc::new(history::History::new(&config.history)));
+        }
+
+        #[cfg(feature = "browser-tabs")]
+        registry.providers.push(Arc::new(tabs::Tabs::default()));
+
+        if config.appimages.enabled {
+            registry
+                .providers
+                .push(Arc::new(appimages::AppImages::new(&config.appimages)));
+        }
+
+        #[cfg(feature = "games")]
+        if config.games {
+            registry.providers.push(Arc::new(games::Games::default()));
+        }
+
+        if config.projects {
+            registry
+                .providers
+                .push(Arc::new(projects::Projects::default()));
+        }
+
+        if config.repositories.enabled {
+            registry
+                .providers
+                .push(Arc::new(repos::Repositories::new(&config.repositories)));
+        }
+
+        if config.sessions {
+            registry.providers.push(Arc::new(sessions::Sessions));
+        }
+
+        #[cfg(feature = "containers")]
+        if config.containers {
+            registry.providers.push(Arc::new(containers::Containers));
+        }
+
+        if config.dev.enabled {
+            registry
+                .providers
+                .push(Arc::new(dev::Dev::new(&config.dev)));
+        }
+
+        if config.network {
+            registry
+                .providers
+                .push(Arc::new(network::Network::default()));
+        }
+
+        #[cfg(feature = "audio")]
+        registry.providers.push(Arc::new(audio::Audio));
+
+        if config.media {
+            registry.providers.push(Arc::new(media::Media::default()));
+        }
+
+        if config.bluetooth {
+            registry
+                .providers
+                .push(Arc::new(bluetooth::Bluetooth::default()));
+        }
+
+        if config.virtual_machines {
+            registry.providers.push(Arc::new(vms::VirtualMachines));
+        }
+
+        #[cfg(feature = "kubernetes")]
+        registry
+            .providers
+            .push(Arc::new(kubernetes::Kubernetes::default()));
+
+        if config.remote.enabled {
+            registry
+                .providers
+                .push(Arc::new(remote::Remote::new(&config.remote)));
+        }
+
+        if config.toggles {
+            registry
+                .providers
+                .push(Arc::new(toggles::Toggles::default()));
+        }
+
+        if config.appearance {
+            registry.providers.push(Arc::new(appearance::Appearances));
+        }
+
+        #[cfg(feature = "ocr")]
+        registry.providers.push(Arc::new(ocr::Ocr::default()));
+
+        if config.trash {
+            registry.providers.push(Arc::new(trash::Trash::default()));
+        }
+
+        if config.drives {
+            registry.providers.push(Arc::new(drives::Drives::default()));
+        }
+
+        if config.settings {
+            registry
+                .providers
+                .push(Arc::new(settings::Settings::default()));
+        }
+
+        if config.wallpapers.enabled {
+            registry
+                .providers
+                .push(Arc::new(wallpapers::Wallpapers::new(&config.wallpapers)));
+        }
+
+        if config.snippets.enabled {
+            registry
+                .providers
+                .push(Arc::new(snippets::Snippets::new(&config.snippets)));
+        }
+
+        if config.notes.enabled {
+            registry
+                .providers
+                .push(Arc::new(notes::Notes::new(&config.notes)));
+        }
+
+        if config.todo.enabled {
+            registry
+                .providers
+                .push(Arc::new(todo::Todo::new(&config.todo)));
+        }
+
+        if config.shell_history.enabled {
+            registry
+                .providers
+                .push(Arc::new(shell_history::ShellHistory::new(
+                    &config.shell_history,
+                )));
+        }
+
+        if config.passwords.enabled {
+            registry
+                .providers
+                .push(Arc::new(passwords::Passwords::new(&config.passwords)));
+        }
+
+        if config.generate {
+            registry
+                .providers
+                .push(Arc::new(generate::Generate::default()));
+        }
+
+        if config.unicode {
+            registry
+                .providers
+                .push(Arc::new(unicode::Unicode::default()));
+        }
+
+        if config.world_clock {
+            registry.providers.push(Arc::new(clock::Clock));
+        }
+
+        if config.weather.enabled {
+            registry
+                .providers
+                .push(Arc::new(weather::Weather::new(config.weather.clone())));
+        }
+
+        registry.providers.push(Arc::new(crashes::Crashes));
+
+        for dir in plugin_dirs() {
+            #[cfg(feature = "wasm-plugins")]
+            registry.providers.extend(wasm::discover(&dir));
+
+            registry.providers.extend(script::discover(&dir, |name| {
+                config
+                    .script_timeouts
+                    .get(name)
+                    .map_or(script::DEFAULT_TIMEOUT, |ms| Duration::from_millis(*ms))
+            }));
+        }
+
+        for provider in &registry.providers {
+            tracing::info!("loaded provider {}", provider.name());
+        }
+        registry.min_lengths = registry
+            .providers
+            .iter()
+            .map(|provider| {
+                config
+                    .min_query_length
+                    .get(provider.name())
+                    .copied()
+                    .unwrap_or_default()
+            })
+            .collect();
+        registry.budgets = Budgets::new(
+            registry
+                .providers
+                .iter()
+                .map(|provider| provider.name().to_string()),
+        );
+
+        registry
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// Queries of every provider in `scope` the query is long enough for, to be run
+    /// concurrently so that each one's results are shown as soon as it answers.
+    pub fn query(
+        &self,
+        query: String,
+        scope: Option<&Scope>,
+    ) -> Vec<BoxFuture<'static, Vec<SearchResult>>> {
+        let length = query.trim().chars().count();
+        self.providers
+            .iter()
+            .enumerate()
+            .filter(|(_, provider)| scope.map_or(true, |scope| scope.queries(provider.name())))
+            .filter(|(i, provider)| {
+                length >= self.min_lengths.get(*i).copied().unwrap_or_default()
+                    || provider.addressed(&query)
+            })
+            .map(|(i, provider)| -> BoxFuture<'static, Vec<SearchResult>> {
+                let query = Arc::clone(provider).query(query.clone());
+                let results = self.budgets.clone().run(i, query);
+                Box::pin(async move { search_results(i as u32 + 1, results.await) })
+            })
+            .collect()
+    }
+
+    /// Has every provider drop its sensitive data, for the session being locked.
+    pub fn forget(&self) {
+        for provider in &self.providers {
+            provider.forget();
+        }
+    }
+
+    /// The footer results of every provider, shown while the query is empty.
+    pub fn footer(&self) -> BoxFuture<'static, Vec<SearchResult>> {
+        let footers = self
+            .providers
+            .iter()
+            .enumerate()
+            .map(|(i, provider)| self.budgets.clone().run(i, Arc::clone(provider).footer()))
+            .collect::<Vec<_>>();
+
+        Box::pin(async move {
+            futures::future::join_all(footers)
+                .await
+                .into_iter()
+                .zip(1..)
+                .flat_map(|(items, provider)| search_results(provider, items))
+                .collect()
+        })
+    }
+
+    /// Activates a result returned by [`Registry::query`], or returns `None` if the ID
+    /// does not belong to a provider.
+    pub fn activate(&self, id: u32, query: String) -> Option<BoxFuture<'static, Option<Action>>> {
+        let (provider, index) = self.provider(id)?;
+        Some(provider.activate(query, index))
+    }
+
+    /// The context options of a result returned by [`Registry::query`], followed by one
+    /// showing it as a QR code if it has something to share.
+    pub fn context(
+        &self,
+        id: u32,
+        query: String,
+    ) -> Option<BoxFuture<'static, Vec<ContextOption>>> {
+        let (provider, index) = self.provider(id)?;
+        Some(Box::pin(async move {
+            let mut options = Arc::clone(&provider)
+                .context(query.clone(), index)
+                .await
+                .into_iter()
+                .zip(0..)
+                .map(|(name, id)| ContextOption { id, name })
+                .collect::<Vec<_>>();
+            if provider.share(query, index).await.is_some() {
+                options.push(ContextOption {
+                    id: options.len() as u32,
+                    name: fl!("show-qr-code"),
+                });
+            }
+            options
+        }))
+    }
+
+    /// Activates a context option returned by [`Registry::context`].
+    pub fn activate_context(
+        &self,
+        id: u32,
+        context: u32,
+        query: String,
+    ) -> Option<BoxFuture<'static, Option<Action>>> {
+        let (provider, index) = self.provider(id)?;
+        Some(Box::pin(async move {
+            let options = Arc::clone(&provider).context(query.clone(), index).await;
+            if context as usize == options.len() {
+                return provider.share(query, index).await.map(Action::QrCode);
+            }
+            provider
+                .activate_context(query, index, context as usize)
+                .await
+        }))
+    }
+
+    /// The provider a result ID belongs to, with the result's index in its list.
+    fn provider(&self, id: u32) -> Option<(Arc<dyn Provider>, usize)> {
+        let offset = id.checked_sub(LOCAL_ID_BASE)?;
+        let provider = (offset / IDS_PER_PROVIDER).checked_sub(1)?;
+        let provider = self.providers.get(provider as usize)?;
+        Some((Arc::clone(provider), (offset % IDS_PER_PROVIDER) as usize))
+    }
+}
+
+/// Numbers the items of the provider counted `provider` from one with the IDs which route
+/// them back to it.
+fn search_results(provider: u32, items: Vec<Item>) -> Vec<SearchResult> {
+    items
+        .into_iter()
+        .zip(0..IDS_PER_PROVIDER)
+        .map(|(item, i)| SearchResult {
+            id: LOCAL_ID_BASE + provider * IDS_PER_PROVIDER + i,
+            name: item.name,
+            description: item.description,
+            icon: item.icon.map(|icon| IconSource::Name(icon.into())),
+            category_icon: None,
+            window: None,
+        })
+        .collect()
+}
+
+/// Formats a size in bytes with a binary unit.
+pub(crate) fn size(bytes: u64) -> String {
+    let mut size = bytes as f64;
+    for unit in ["B", "KiB", "MiB", "GiB"] {
+        if size < 1024.0 {
+            return format!("{size:.0} {unit}");
+        }
+        size /= 1024.0;
+    }
+    format!("{size:.1} TiB")
+}
+
+/// Decodes a `file://` URI. URIs of other schemes, such as remote folders, are skipped.
+pub(crate) fn file_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?.as_bytes();
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        let byte = match encoded.get(i..i + 3) {
+            Some([b'%', high, low]) => {
+                let hex = std::str::from_utf8(&[*high, *low]).ok()?.to_owned();
+                i += 2;
+                u8::from_str_radix(&hex, 16).ok()?
+            }
+            _ => encoded[i],
+        };
+        decoded.push(byte);
+        i += 1;
+    }
+
+    Some(PathBuf::from(String::from_utf8(decoded).ok()?))
+}
+
+/// Directories providers are discovered in.
+pub fn plugin_dirs() -> Vec<PathBuf> {
+    xdg::BaseDirectories::with_prefix("cosmic-launcher")
+        .map(|base| vec![base.get_data_home().join("plugins")])
+        .unwrap_or_default()
+}
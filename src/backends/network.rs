@@ -0,0 +1,280 @@
+//! Saved NetworkManager connections, Wi-Fi for queries starting with `wifi ` and VPNs for
+//! queries starting with `vpn `, which activating connects or disconnects.
+//!
+//! Connections needing a password ask for it through NetworkManager's secret agent, as
+//! when connecting from the panel.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+use tokio::sync::OnceCell;
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue};
+
+use super::{Action, Item, Provider};
+use crate::{fl, matcher};
+
+const NM_BUS: &str = "org.freedesktop.NetworkManager";
+const NM_PATH: &str = "/org/freedesktop/NetworkManager";
+const SETTINGS_PATH: &str = "/org/freedesktop/NetworkManager/Settings";
+const ACTIVE_INTERFACE: &str = "org.freedesktop.NetworkManager.Connection.Active";
+
+/// `NM_ACTIVE_CONNECTION_STATE_ACTIVATED`; lower states are still connecting.
+const ACTIVATED: u32 = 2;
+
+type Settings = HashMap<String, HashMap<String, OwnedValue>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Wifi,
+    Vpn,
+}
+
+impl Kind {
+    const PREFIXES: [(Self, &'static str); 2] = [(Self::Wifi, "wifi "), (Self::Vpn, "vpn ")];
+
+    fn of(connection_type: &str) -> Option<Self> {
+        match connection_type {
+            "802-11-wireless" => Some(Self::Wifi),
+            "vpn" | "wireguard" => Some(Self::Vpn),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Connection {
+    kind: Kind,
+    name: String,
+    path: OwnedObjectPath,
+    /// The active connection and its state, while connected or connecting.
+    active: Option<(OwnedObjectPath, u32)>,
+}
+
+impl Connection {
+    fn icon(&self) -> &'static str {
+        let connected = self
+            .active
+            .as_ref()
+            .is_some_and(|(_, state)| *state == ACTIVATED);
+        match (self.kind, connected) {
+            (Kind::Wifi, true) => "network-wireless-connected-symbolic",
+            (Kind::Wifi, false) => "network-wireless-offline-symbolic",
+            (Kind::Vpn, true) => "network-vpn-symbolic",
+            (Kind::Vpn, false) => "network-vpn-disconnected-symbolic",
+        }
+    }
+
+    fn description(&self) -> String {
+        match (self.kind, &self.active) {
+            (Kind::Wifi, Some((_, ACTIVATED))) => fl!("network-wifi-connected"),
+            (Kind::Wifi, Some(_)) => fl!("network-wifi-connecting"),
+            (Kind::Wifi, None) => fl!("network-wifi-disconnected"),
+            (Kind::Vpn, Some((_, ACTIVATED))) => fl!("network-vpn-connected"),
+            (Kind::Vpn, Some(_)) => fl!("network-vpn-connecting"),
+            (Kind::Vpn, None) => fl!("network-vpn-disconnected"),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Network {
+    bus: OnceCell<zbus::Connection>,
+}
+
+impl Network {
+    async fn matching(&self, query: &str) -> Vec<Connection> {
+        let query = query.trim_start();
+        let Some((kind, text)) = Kind::PREFIXES
+            .iter()
+            .find_map(|(kind, prefix)| Some((*kind, query.strip_prefix(prefix)?)))
+        else {
+            return Vec::new();
+        };
+        let words = matcher::words(text);
+
+        let connections = match self.connections().await {
+            Ok(connections) => connections,
+            Err(why) => {
+                tracing::debug!("failed to list network connections: {why}");
+                return Vec::new();
+            }
+        };
+
+        connections
+            .into_iter()
+            .filter(|connection| connection.kind == kind)
+            .filter(|connection| matcher::matches_all(&words, &connection.name))
+            .take(MAX_RESULTS)
+            .collect()
+    }
+
+    async fn bus(&self) -> zbus::Result<&zbus::Connection> {
+        self.bus.get_or_try_init(zbus::Connection::system).await
+    }
+
+    /// Saved Wi-Fi and VPN connections, active ones first.
+    async fn connections(&self) -> zbus::Result<Vec<Connection>> {
+        let bus = self.bus().await?;
+
+        let active_paths: Vec<OwnedObjectPath> =
+            property(bus, NM_PATH, NM_BUS, "ActiveConnections").await?;
+        let mut active = HashMap::new();
+        for path in active_paths {
+            let Ok(connection) =
+                property::<OwnedObjectPath>(bus, path.as_str(), ACTIVE_INTERFACE, "Connection")
+                    .await
+            else {
+                continue;
+            };
+            let state = property::<u32>(bus, path.as_str(), ACTIVE_INTERFACE, "State")
+                .await
+                .unwrap_or_default();
+            active.insert(connection, (path, state));
+        }
+
+        let (paths,): (Vec<OwnedObjectPath>,) = bus
+            .call_method(
+                Some(NM_BUS),
+                SETTINGS_PATH,
+                Some("org.freedesktop.NetworkManager.Settings"),
+                "ListConnections",
+                &(),
+            )
+            .await?
+            .body()
+            .deserialize()?;
+
+        let mut connections = Vec::new();
+        for path in paths {
+            let settings: Settings = match bus
+                .call_method(
+                    Some(NM_BUS),
+                    path.as_str(),
+                    Some("org.freedesktop.NetworkManager.Settings.Connection"),
+                    "GetSettings",
+                    &(),
+                )
+                .await
+            {
+                Ok(reply) => reply.body().deserialize()?,
+                Err(_) => continue,
+            };
+
+            let Some(connection) = settings.get("connection") else {
+                continue;
+            };
+            let text = |key: &str| {
+                connection
+                    .get(key)
+                    .and_then(|value| <&str>::try_from(&**value).ok())
+                    .map(String::from)
+            };
+            let (Some(kind), Some(name)) = (text("type").as_deref().and_then(Kind::of), text("id"))
+            else {
+                continue;
+            };
+
+            connections.push(Connection {
+                kind,
+                name,
+                active: active.remove(&path),
+                path,
+            });
+        }
+
+        connections.sort_by_key(|connection| connection.active.is_none());
+        Ok(connections)
+    }
+
+    /// Disconnects an active connection, or connects an inactive one on whichever device
+    /// NetworkManager picks.
+    async fn toggle(&self, connection: &Connection) -> zbus::Result<()> {
+        let bus = self.bus().await?;
+        match &connection.active {
+            Some((active, _)) => {
+                bus.call_method(
+                    Some(NM_BUS),
+                    NM_PATH,
+                    Some(NM_BUS),
+                    "DeactivateConnection",
+                    &(active,),
+                )
+                .await?;
+            }
+            None => {
+                let any = ObjectPath::from_static_str_unchecked("/");
+                bus.call_method(
+                    Some(NM_BUS),
+                    NM_PATH,
+                    Some(NM_BUS),
+                    "ActivateConnection",
+                    &(&connection.path, &any, &any),
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn property<T>(
+    bus: &zbus::Connection,
+    path: &str,
+    interface: &str,
+    name: &str,
+) -> zbus::Result<T>
+where
+    T: TryFrom<OwnedValue>,
+    T::Error: Into<zbus::zvariant::Error>,
+{
+    let value: OwnedValue = bus
+        .call_method(
+            Some(NM_BUS),
+            path,
+            Some("org.freedesktop.DBus.Properties"),
+            "Get",
+            &(interface, name),
+        )
+        .await?
+        .body()
+        .deserialize()?;
+
+    T::try_from(value).map_err(|why| zbus::Error::Variant(why.into()))
+}
+
+impl Provider for Network {
+    fn name(&self) -> &str {
+        "network"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|connection| Item {
+                    description: connection.description(),
+                    icon: Some(connection.icon().into()),
+                    name: connection.name,
+                    open: None,
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let connection = self.matching(&query).await.into_iter().nth(index)?;
+            if let Err(why) = self.toggle(&connection).await {
+                tracing::warn!("failed to toggle {}: {why}", connection.name);
+            }
+            None
+        })
+    }
+}
@@ -0,0 +1,186 @@
+//! Quick notes for queries starting with `note `, such as `note buy milk`.
+//!
+//! Activating the note appends it to the notes file as a timestamped line, or, when a notes
+//! directory is configured, writes it to a new file there. The context menu opens the notes
+//! file or directory.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use jiff::Zoned;
+use serde::{Deserialize, Serialize};
+
+use super::{Action, Item, Provider};
+use crate::fl;
+
+const PREFIX: &str = "note ";
+
+/// Where notes are written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotesConfig {
+    pub enabled: bool,
+    /// File notes are appended to. A leading `~` is the home directory.
+    pub file: String,
+    /// Directory each note is written to as a file of its own, instead of the notes file.
+    pub directory: Option<String>,
+}
+
+impl Default for NotesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            file: "~/Notes/notes.md".into(),
+            directory: None,
+        }
+    }
+}
+
+pub struct Notes {
+    /// The notes file, or the notes directory if there is one.
+    target: Option<PathBuf>,
+    directory: bool,
+}
+
+impl Notes {
+    pub fn new(config: &NotesConfig) -> Self {
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let expand = |path: &str| match path.strip_prefix('~') {
+            Some(rest) => Some(home.as_ref()?.join(rest.trim_start_matches('/'))),
+            None => Some(PathBuf::from(path)),
+        };
+
+        Self {
+            target: expand(config.directory.as_deref().unwrap_or(&config.file)),
+            directory: config.directory.is_some(),
+        }
+    }
+
+    /// The note typed after the prefix, if there is one.
+    fn note(query: &str) -> Option<&str> {
+        let note = query.trim_start().strip_prefix(PREFIX)?.trim();
+        (!note.is_empty()).then_some(note)
+    }
+
+    fn save(&self, note: &str) -> std::io::Result<PathBuf> {
+        let target = self
+            .target
+            .as_ref()
+            .ok_or_else(|| std::io::Error::other("no home directory"))?;
+        let now = Zoned::now();
+
+        if self.directory {
+            std::fs::create_dir_all(target)?;
+            let stem = now.strftime("%Y-%m-%d-%H%M%S").to_string();
+            // Notes saved within the same second are told apart by a number.
+            let mut path = target.join(format!("{stem}.md"));
+            let mut n = 1;
+            loop {
+                let file = std::fs::OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(&path);
+                match file {
+                    Ok(mut file) => {
+                        writeln!(file, "{note}")?;
+                        return Ok(path);
+                    }
+                    Err(why) if why.kind() == std::io::ErrorKind::AlreadyExists => {
+                        n += 1;
+                        path = target.join(format!("{stem}-{n}.md"));
+                    }
+                    Err(why) => return Err(why),
+                }
+            }
+        }
+
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(target)?;
+        writeln!(file, "- {} {note}", now.strftime("%Y-%m-%d %H:%M"))?;
+        Ok(target.clone())
+    }
+}
+
+/// The path as shown to the user, with the home directory as `~`.
+fn display(path: &Path) -> String {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    match home.and_then(|home| path.strip_prefix(home).ok().map(Path::to_path_buf)) {
+        Some(rest) => format!("~/{}", rest.display()),
+        None => path.display().to_string(),
+    }
+}
+
+impl Provider for Notes {
+    fn name(&self) -> &str {
+        "notes"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            let (Some(note), Some(target)) = (Self::note(&query), self.target.as_ref()) else {
+                return Vec::new();
+            };
+
+            let description = if self.directory {
+                fl!("note-save-in", path = display(target))
+            } else {
+                fl!("note-append-to", path = display(target))
+            };
+            vec![Item {
+                name: fl!("note-add", note = note),
+                description,
+                icon: Some("accessories-text-editor-symbolic".into()),
+                open: None,
+            }]
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        _index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let note = Self::note(&query)?.to_string();
+            match tokio::task::spawn_blocking(move || self.save(&note)).await {
+                Ok(Ok(path)) => tracing::debug!("saved a note to {}", path.display()),
+                Ok(Err(why)) => tracing::error!("failed to save the note: {why}"),
+                Err(_) => {}
+            }
+            None
+        })
+    }
+
+    fn context(self: Arc<Self>, query: String, _index: usize) -> BoxFuture<'static, Vec<String>> {
+        Box::pin(async move {
+            if Self::note(&query).is_some() && self.target.is_some() {
+                vec![if self.directory {
+                    fl!("note-open-folder")
+                } else {
+                    fl!("note-open-file")
+                }]
+            } else {
+                Vec::new()
+            }
+        })
+    }
+
+    fn activate_context(
+        self: Arc<Self>,
+        _query: String,
+        _index: usize,
+        option: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let target = self.target.as_ref().filter(|_| option == 0)?;
+            Some(Action::Open(target.to_string_lossy().into_owned()))
+        })
+    }
+}
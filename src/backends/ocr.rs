@@ -0,0 +1,181 @@
+//! Copying the text of a region of the screen, for queries such as `ocr` or `copy text`.
+//!
+//! Once the launcher is hidden, the region is chosen in the screenshot dialog of the
+//! desktop portal, and its text recognized by `tesseract` and copied to the clipboard. The
+//! screenshot is deleted afterwards.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use futures::StreamExt;
+use zbus::zvariant::{OwnedValue, Value};
+
+use super::{file_path, Action, Item, Provider};
+use crate::doctor::find_in_path;
+use crate::fl;
+
+const PROGRAM: &str = "tesseract";
+
+/// Characters a query needs before the result is matched.
+const MIN_QUERY: usize = 3;
+
+const KEYWORDS: [&str; 6] = ["ocr", "copy", "text", "from", "screen", "recognize"];
+
+/// Time the launcher has to hide before the screenshot is taken.
+const HIDE_DELAY: Duration = Duration::from_millis(300);
+
+const PORTAL_BUS: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+
+/// Makes the token of each screenshot request unique.
+static REQUESTS: AtomicU32 = AtomicU32::new(0);
+
+pub struct Ocr {
+    installed: bool,
+}
+
+impl Default for Ocr {
+    fn default() -> Self {
+        Self {
+            installed: find_in_path(PROGRAM).is_some(),
+        }
+    }
+}
+
+impl Ocr {
+    fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        self.installed
+            && query.chars().count() >= MIN_QUERY
+            && query
+                .split_whitespace()
+                .all(|word| KEYWORDS.iter().any(|keyword| keyword.starts_with(word)))
+    }
+}
+
+/// Asks the desktop portal for a screenshot of a region the user chooses, returning where
+/// it was saved, or nothing if it was cancelled.
+async fn screenshot() -> zbus::Result<Option<PathBuf>> {
+    let bus = zbus::Connection::session().await?;
+
+    // The portal answers on a request object named after the connection and a token, which
+    // is listened to before asking, so that the answer cannot be missed.
+    let sender = bus
+        .unique_name()
+        .map(|name| name.trim_start_matches(':').replace('.', "_"))
+        .unwrap_or_default();
+    let token = format!(
+        "cosmic_launcher_{}_{}",
+        std::process::id(),
+        REQUESTS.fetch_add(1, Ordering::Relaxed)
+    );
+    let request = zbus::Proxy::new(
+        &bus,
+        PORTAL_BUS,
+        format!("{PORTAL_PATH}/request/{sender}/{token}"),
+        "org.freedesktop.portal.Request",
+    )
+    .await?;
+    let mut responses = request.receive_signal("Response").await?;
+
+    let mut options = HashMap::<&str, Value>::new();
+    options.insert("handle_token", token.as_str().into());
+    options.insert("interactive", true.into());
+    bus.call_method(
+        Some(PORTAL_BUS),
+        PORTAL_PATH,
+        Some("org.freedesktop.portal.Screenshot"),
+        "Screenshot",
+        &("", options),
+    )
+    .await?;
+
+    let Some(response) = responses.next().await else {
+        return Ok(None);
+    };
+    let (code, results): (u32, HashMap<String, OwnedValue>) = response.body().deserialize()?;
+    // Any other code means the screenshot was cancelled or refused.
+    if code != 0 {
+        return Ok(None);
+    }
+    Ok(results
+        .get("uri")
+        .and_then(|uri| <&str>::try_from(&**uri).ok())
+        .and_then(file_path))
+}
+
+/// The text `tesseract` recognizes in an image.
+async fn recognize(image: &Path) -> std::io::Result<String> {
+    let output = tokio::process::Command::new(PROGRAM)
+        .arg(image)
+        .arg("stdout")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await?;
+    if !output.status.success() {
+        let why = format!("{PROGRAM} {}", output.status);
+        return Err(std::io::Error::other(why));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+async fn copy_text_from_screen() {
+    tokio::time::sleep(HIDE_DELAY).await;
+    let image = match screenshot().await {
+        Ok(Some(image)) => image,
+        Ok(None) => return,
+        Err(why) => {
+            tracing::warn!("failed to take a screenshot: {why}");
+            return;
+        }
+    };
+
+    let text = recognize(&image).await;
+    let _res = tokio::fs::remove_file(&image).await;
+    match text {
+        Ok(text) if text.is_empty() => tracing::info!("no text found in the screenshot"),
+        Ok(text) => crate::launch::copy(text).await,
+        Err(why) => tracing::warn!("failed to recognize text: {why}"),
+    }
+}
+
+impl Provider for Ocr {
+    fn name(&self) -> &str {
+        "ocr"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            if !self.matches(&query) {
+                return Vec::new();
+            }
+            vec![Item {
+                name: fl!("ocr-copy-text"),
+                description: fl!("ocr-copy-text-description"),
+                icon: Some("edit-copy-symbolic".into()),
+                open: None,
+            }]
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            if index == 0 && self.matches(&query) {
+                // The launcher is hidden once this returns, which the screenshot waits for.
+                tokio::spawn(copy_text_from_screen());
+            }
+            None
+        })
+    }
+}
@@ -0,0 +1,249 @@
+//! Random passwords for queries such as `pw` or `pw 24`, of the configured length unless
+//! one is given.
+//!
+//! The password is shown masked until its context menu reveals it, and stays the same while
+//! the query does. Activating it copies it to the clipboard, which is cleared again after
+//! the configured time unless something else was copied since.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use rand::rngs::OsRng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use super::{Action, Item, Provider};
+use crate::fl;
+
+const PREFIX: &str = "pw";
+
+/// Lengths a password may be asked for in.
+const MIN_LENGTH: usize = 4;
+const MAX_LENGTH: usize = 128;
+
+/// Characters which are easily mistaken for each other in some fonts.
+const AMBIGUOUS: &str = "0O1lI|";
+
+/// Which characters generated passwords are made of, and how long they are kept.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PasswordsConfig {
+    pub enabled: bool,
+    /// Length of passwords when the query does not give one.
+    pub length: usize,
+    pub lowercase: bool,
+    pub uppercase: bool,
+    pub digits: bool,
+    /// Symbols passwords may contain. Empty to leave symbols out.
+    pub symbols: String,
+    /// Leave out characters such as `l`, `1`, and `I`, which are easily mistaken.
+    pub avoid_ambiguous: bool,
+    /// Seconds after which a copied password is cleared from the clipboard. 0 keeps it.
+    pub clear_after: u64,
+}
+
+impl Default for PasswordsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            length: 20,
+            lowercase: true,
+            uppercase: true,
+            digits: true,
+            symbols: "!#$%&*+-=?@^_".into(),
+            avoid_ambiguous: false,
+            clear_after: 30,
+        }
+    }
+}
+
+/// The password shown for a query.
+struct Generated {
+    query: String,
+    password: String,
+    revealed: bool,
+}
+
+pub struct Passwords {
+    /// Characters of each class passwords contain at least one of.
+    classes: Vec<Vec<char>>,
+    length: usize,
+    clear_after: Duration,
+    generated: Mutex<Option<Generated>>,
+}
+
+impl Passwords {
+    pub fn new(config: &PasswordsConfig) -> Self {
+        let classes = [
+            (config.lowercase, "abcdefghijklmnopqrstuvwxyz"),
+            (config.uppercase, "ABCDEFGHIJKLMNOPQRSTUVWXYZ"),
+            (config.digits, "0123456789"),
+            (true, config.symbols.as_str()),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, chars)| {
+            chars
+                .chars()
+                .filter(|c| !(config.avoid_ambiguous && AMBIGUOUS.contains(*c)))
+                .collect::<Vec<_>>()
+        })
+        .filter(|class| !class.is_empty())
+        .collect();
+
+        Self {
+            classes,
+            length: config.length.clamp(MIN_LENGTH, MAX_LENGTH),
+            clear_after: Duration::from_secs(config.clear_after),
+            generated: Mutex::new(None),
+        }
+    }
+
+    /// The length asked for by `query`, if it asks for a password.
+    fn length(&self, query: &str) -> Option<usize> {
+        let rest = query.trim().strip_prefix(PREFIX)?;
+        if rest.is_empty() {
+            return Some(self.length);
+        }
+        if !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let length = rest.trim().parse::<usize>().ok()?;
+        Some(length.clamp(MIN_LENGTH, MAX_LENGTH))
+    }
+
+    fn generate(&self, length: usize) -> String {
+        let mut rng = OsRng;
+        let all = self.classes.concat();
+
+        // One character of each class, then any characters for the rest.
+        let mut password = self
+            .classes
+            .iter()
+            .filter_map(|class| class.choose(&mut rng))
+            .take(length)
+            .copied()
+            .collect::<Vec<_>>();
+        while password.len() < length {
+            password.extend(all.choose(&mut rng));
+        }
+        password.shuffle(&mut rng);
+        password.into_iter().collect()
+    }
+
+    /// The password shown for `query`, and whether it is revealed, after `change` was applied
+    /// to it. A new password is generated when the query changed.
+    fn password(
+        &self,
+        query: &str,
+        change: impl FnOnce(&mut Generated, &Self),
+    ) -> Option<(String, bool)> {
+        let length = self.length(query)?;
+        if self.classes.is_empty() {
+            return None;
+        }
+
+        let mut generated = self.generated.lock().ok()?;
+        let generated = match generated.as_mut() {
+            Some(generated) if generated.query == query => generated,
+            _ => generated.insert(Generated {
+                query: query.to_string(),
+                password: self.generate(length),
+                revealed: false,
+            }),
+        };
+        change(generated, self);
+        Some((generated.password.clone(), generated.revealed))
+    }
+}
+
+impl Provider for Passwords {
+    fn name(&self) -> &str {
+        "passwords"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            let Some((password, revealed)) = self.password(&query, |_, _| {}) else {
+                return Vec::new();
+            };
+
+            let length = password.chars().count();
+            let description = if self.clear_after.is_zero() {
+                fl!("password-description", length = length)
+            } else {
+                fl!(
+                    "password-description-cleared",
+                    length = length,
+                    seconds = self.clear_after.as_secs()
+                )
+            };
+            vec![Item {
+                name: if revealed {
+                    password
+                } else {
+                    "•".repeat(length)
+                },
+                description,
+                icon: Some("dialog-password-symbolic".into()),
+                open: None,
+            }]
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        _index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let (password, _) = self.password(&query, |_, _| {})?;
+            if self.clear_after.is_zero() {
+                Some(Action::Copy(password))
+            } else {
+                Some(Action::CopySecret {
+                    text: password,
+                    clear_after: self.clear_after,
+                })
+            }
+        })
+    }
+
+    fn context(self: Arc<Self>, query: String, _index: usize) -> BoxFuture<'static, Vec<String>> {
+        Box::pin(async move {
+            let Some((_, revealed)) = self.password(&query, |_, _| {}) else {
+                return Vec::new();
+            };
+            let toggle = if revealed {
+                fl!("password-hide")
+            } else {
+                fl!("password-show")
+            };
+            vec![toggle, fl!("password-generate-another")]
+        })
+    }
+
+    fn activate_context(
+        self: Arc<Self>,
+        query: String,
+        _index: usize,
+        option: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let length = self.length(&query)?;
+            self.password(&query, |generated, passwords| match option {
+                0 => generated.revealed = !generated.revealed,
+                1 => generated.password = passwords.generate(length),
+                _ => {}
+            })?;
+            Some(Action::Refresh)
+        })
+    }
+
+    fn forget(&self) {
+        if let Ok(mut generated) = self.generated.lock() {
+            *generated = None;
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! Recently opened projects of VS Code, VSCodium, and JetBrains IDEs, shown as results
+//! opening the project in its editor.
+//!
+//! VS Code lists recent folders and workspaces in `storage.json`, or in its state database
+//! since version 1.64. JetBrains IDEs list them in `options/recentProjects.xml` of each
+//! product's config directory, of which the newest version is read.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+use serde::Deserialize;
+
+use super::cache::{self, Cache};
+use super::{file_path, Action, Item, Provider};
+use crate::{fl, matcher};
+
+/// How long the list of projects is reused before the editors' files are read again.
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Config directories of VS Code and its builds, with their name and command.
+const VSCODE_EDITORS: &[(&str, &str, &str)] = &[
+    ("Code", "VS Code", "code"),
+    ("Code - OSS", "Code - OSS", "code-oss"),
+    ("VSCodium", "VSCodium", "codium"),
+];
+
+/// Prefixes of JetBrains config directories, with the IDE's name and command.
+const JETBRAINS_EDITORS: &[(&str, &str, &str)] = &[
+    ("IntelliJIdea", "IntelliJ IDEA", "idea"),
+    ("IdeaIC", "IntelliJ IDEA CE", "idea"),
+    ("PyCharmCE", "PyCharm CE", "pycharm"),
+    ("PyCharm", "PyCharm", "pycharm"),
+    ("CLion", "CLion", "clion"),
+    ("GoLand", "GoLand", "goland"),
+    ("WebStorm", "WebStorm", "webstorm"),
+    ("RustRover", "RustRover", "rustrover"),
+    ("PhpStorm", "PhpStorm", "phpstorm"),
+    ("Rider", "Rider", "rider"),
+];
+
+/// Key of the recent projects in VS Code's state database.
+const VSCODE_RECENTS_KEY: &str = "history.recentlyOpenedPathsList";
+
+#[derive(Debug, Clone)]
+struct Project {
+    path: PathBuf,
+    editor: &'static str,
+    command: &'static str,
+}
+
+impl Project {
+    fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.path.display().to_string())
+    }
+}
+
+#[derive(Deserialize)]
+struct Storage {
+    #[serde(rename = "openedPathsList")]
+    opened: Option<Recents>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Recents {
+    entries: Vec<Recent>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Recent {
+    folder_uri: Option<String>,
+    workspace: Option<Workspace>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Workspace {
+    config_path: String,
+}
+
+pub struct Projects {
+    cache: Cache<Vec<Project>>,
+}
+
+impl Default for Projects {
+    fn default() -> Self {
+        Self {
+            cache: Cache::new(CACHE_TTL),
+        }
+    }
+}
+
+impl Projects {
+    async fn projects(&self) -> Arc<Vec<Project>> {
+        self.cache.get(cache::blocking(read_projects)).await
+    }
+
+    async fn matching(&self, query: &str) -> Vec<Project> {
+        let words = matcher::words(query);
+        if words.concat().chars().count() < 2 {
+            return Vec::new();
+        }
+
+        self.projects()
+            .await
+            .iter()
+            .filter(|project| {
+                let text = format!("{} {}", project.name(), project.editor);
+                matcher::matches_all(&words, &text)
+            })
+            .take(MAX_RESULTS)
+            .cloned()
+            .collect()
+    }
+}
+
+fn read_projects() -> Vec<Project> {
+    let Some(config) = xdg::BaseDirectories::new()
+        .ok()
+        .map(|base| base.get_config_home())
+    else {
+        return Vec::new();
+    };
+
+    let mut projects = Vec::new();
+    for (dir, editor, command) in VSCODE_EDITORS {
+        let user = config.join(dir).join("User");
+        let paths = vscode_state(&user.join("globalStorage/state.vscdb"))
+            .or_else(|| vscode_storage(&user.join("globalStorage/storage.json")))
+            .unwrap_or_default();
+        projects.extend(paths.into_iter().map(|path| Project {
+            path,
+            editor,
+            command,
+        }));
+    }
+
+    for (prefix, editor, command) in JETBRAINS_EDITORS {
+        let Some(dir) = newest_jetbrains_dir(&config.join("JetBrains"), prefix) else {
+            continue;
+        };
+        let paths = ["recentProjects.xml", "recentSolutions.xml"]
+            .iter()
+            .filter_map(|file| std::fs::read_to_string(dir.join("options").join(file)).ok())
+            .flat_map(|xml| jetbrains_recents(&xml));
+        projects.extend(paths.map(|path| Project {
+            path,
+            editor,
+            command,
+        }));
+    }
+
+    // Projects which were moved or deleted since are not offered.
+    projects.retain(|project| project.path.exists());
+    projects
+}
+
+#[cfg(feature = "sqlite")]
+fn vscode_state(database: &Path) -> Option<Vec<PathBuf>> {
+    if !database.is_file() {
+        return None;
+    }
+
+    let connection =
+        rusqlite::Connection::open_with_flags(database, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .ok()?;
+    let recents: String = connection
+        .query_row(
+            "SELECT value FROM ItemTable WHERE key = ?1",
+            [VSCODE_RECENTS_KEY],
+            |row| row.get(0),
+        )
+        .ok()?;
+    let recents: Recents = serde_json::from_str(&recents).ok()?;
+    Some(vscode_paths(recents))
+}
+
+/// Without SQLite, only the `storage.json` of versions before 1.64 is read.
+#[cfg(not(feature = "sqlite"))]
+fn vscode_state(_database: &Path) -> Option<Vec<PathBuf>> {
+    None
+}
+
+fn vscode_storage(file: &Path) -> Option<Vec<PathBuf>> {
+    let storage: Storage = serde_json::from_str(&std::fs::read_to_string(file).ok()?).ok()?;
+    Some(vscode_paths(storage.opened.unwrap_or_default()))
+}
+
+fn vscode_paths(recents: Recents) -> Vec<PathBuf> {
+    recents
+        .entries
+        .into_iter()
+        .filter_map(|recent| {
+            recent
+                .folder_uri
+                .or_else(|| recent.workspace.map(|workspace| workspace.config_path))
+        })
+        // Remote folders, opened over SSH or in containers, are skipped.
+        .filter_map(|uri| file_path(&uri))
+        .collect()
+}
+
+/// The config directory of the newest installed version of a JetBrains product, such as
+/// `CLion2024.1` for `CLion`.
+fn newest_jetbrains_dir(dir: &Path, product: &str) -> Option<PathBuf> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry.file_name().to_str().is_some_and(|name| {
+                name.strip_prefix(product)
+                    .is_some_and(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+            })
+        })
+        .map(|entry| entry.path())
+        .max()
+}
+
+/// Project paths listed in the `additionalInfo` map of a JetBrains recent projects file.
+fn jetbrains_recents(xml: &str) -> Vec<PathBuf> {
+    let Some((_, info)) = xml.split_once("name=\"additionalInfo\"") else {
+        return Vec::new();
+    };
+    let home = std::env::var("HOME").unwrap_or_default();
+
+    info.split("<entry key=\"")
+        .skip(1)
+        .filter_map(|entry| entry.split_once('"'))
+        .map(|(path, _)| {
+            PathBuf::from(
+                path.replace("$USER_HOME$", &home)
+                    .replace("&amp;", "&")
+                    .replace("&apos;", "'")
+                    .replace("&quot;", "\"")
+                    .replace("&lt;", "<")
+                    .replace("&gt;", ">"),
+            )
+        })
+        .collect()
+}
+
+impl Provider for Projects {
+    fn name(&self) -> &str {
+        "projects"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            let home = std::env::var_os("HOME").map(PathBuf::from);
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|project| {
+                    let path = match home
+                        .as_ref()
+                        .and_then(|home| project.path.strip_prefix(home).ok())
+                    {
+                        Some(relative) => format!("~/{}", relative.display()),
+                        None => project.path.display().to_string(),
+                    };
+
+                    Item {
+                        name: fl!(
+                            "project-open",
+                            project = project.name(),
+                            editor = project.editor
+                        ),
+                        description: path,
+                        icon: Some(project.command.into()),
+                        open: None,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let project = self.matching(&query).await.into_iter().nth(index)?;
+            Some(Action::Run(vec![
+                project.command.into(),
+                project.path.to_string_lossy().into_owned(),
+            ]))
+        })
+    }
+}
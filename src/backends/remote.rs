@@ -0,0 +1,396 @@
+//! Remote desktop and file connections for queries starting with `remote `.
+//!
+//! Connections are set in the configuration, and imported from Remmina profiles and from
+//! GNOME Connections. Activating an RDP or VNC connection opens it in the client it came
+//! from, or else in Remmina, GNOME Connections, FreeRDP, or TigerVNC, whichever is
+//! installed. SFTP connections open in the file manager through their gvfs URI.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use super::{Action, Item, Provider};
+use crate::doctor::find_in_path;
+use crate::matcher;
+
+const PREFIX: &str = "remote ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    Rdp,
+    Vnc,
+    Sftp,
+}
+
+impl Protocol {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "rdp" => Some(Self::Rdp),
+            "vnc" => Some(Self::Vnc),
+            "sftp" => Some(Self::Sftp),
+            _ => None,
+        }
+    }
+
+    fn scheme(self) -> &'static str {
+        match self {
+            Self::Rdp => "rdp",
+            Self::Vnc => "vnc",
+            Self::Sftp => "sftp",
+        }
+    }
+}
+
+/// A connection set in the configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteConnection {
+    pub name: String,
+    pub protocol: Protocol,
+    pub host: String,
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Directory an SFTP connection opens in.
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+/// Connections set here, and whether those of Remmina and GNOME Connections are listed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteConfig {
+    pub enabled: bool,
+    pub import: bool,
+    pub connections: Vec<RemoteConnection>,
+}
+
+impl Default for RemoteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            import: true,
+            connections: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Source {
+    Config,
+    /// A Remmina profile file.
+    Remmina(PathBuf),
+    GnomeConnections,
+}
+
+impl Source {
+    fn name(&self) -> Option<&'static str> {
+        match self {
+            Self::Config => None,
+            Self::Remmina(_) => Some("Remmina"),
+            Self::GnomeConnections => Some("GNOME Connections"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Connection {
+    connection: RemoteConnection,
+    source: Source,
+}
+
+impl Connection {
+    /// `host:port`, or the host alone.
+    fn address(&self) -> String {
+        let RemoteConnection { host, port, .. } = &self.connection;
+        match port {
+            Some(port) => format!("{host}:{port}"),
+            None => host.clone(),
+        }
+    }
+
+    fn uri(&self) -> String {
+        let connection = &self.connection;
+        let user = match &connection.user {
+            Some(user) => format!("{user}@"),
+            None => String::new(),
+        };
+        let path = connection.path.as_deref().unwrap_or_default();
+        let path = if path.is_empty() || path.starts_with('/') {
+            path.to_string()
+        } else {
+            format!("/{path}")
+        };
+        format!(
+            "{}://{user}{}{path}",
+            connection.protocol.scheme(),
+            self.address()
+        )
+    }
+
+    /// The command opening an RDP or VNC connection, preferring the client it came from.
+    fn client(&self) -> Option<Vec<String>> {
+        let connection = &self.connection;
+        let args = match &self.source {
+            Source::Remmina(file) if find_in_path("remmina").is_some() => {
+                vec!["remmina".into(), "-c".into(), file.display().to_string()]
+            }
+            Source::GnomeConnections if find_in_path("gnome-connections").is_some() => {
+                vec!["gnome-connections".into(), self.uri()]
+            }
+            _ if find_in_path("remmina").is_some() => {
+                vec!["remmina".into(), "-c".into(), self.uri()]
+            }
+            _ if find_in_path("gnome-connections").is_some() => {
+                vec!["gnome-connections".into(), self.uri()]
+            }
+            _ if connection.protocol == Protocol::Rdp && find_in_path("xfreerdp").is_some() => {
+                let mut args = vec!["xfreerdp".into(), format!("/v:{}", self.address())];
+                args.extend(connection.user.as_ref().map(|user| format!("/u:{user}")));
+                args
+            }
+            _ if connection.protocol == Protocol::Vnc && find_in_path("vncviewer").is_some() => {
+                // A single colon would name a display rather than a port.
+                let address = match connection.port {
+                    Some(port) => format!("{}::{port}", connection.host),
+                    None => connection.host.clone(),
+                };
+                vec!["vncviewer".into(), address]
+            }
+            _ => return None,
+        };
+        Some(args)
+    }
+}
+
+pub struct Remote {
+    connections: Vec<RemoteConnection>,
+    import: bool,
+}
+
+impl Remote {
+    pub fn new(config: &RemoteConfig) -> Self {
+        Self {
+            connections: config.connections.clone(),
+            import: config.import,
+        }
+    }
+
+    async fn matching(&self, query: &str) -> Vec<Connection> {
+        let Some(text) = query.trim_start().strip_prefix(PREFIX) else {
+            return Vec::new();
+        };
+        let words = matcher::words(text);
+
+        let mut connections = self
+            .connections
+            .iter()
+            .map(|connection| Connection {
+                connection: connection.clone(),
+                source: Source::Config,
+            })
+            .collect::<Vec<_>>();
+        if self.import {
+            connections.extend(
+                tokio::task::spawn_blocking(|| {
+                    let mut imported = remmina();
+                    imported.extend(gnome_connections());
+                    imported
+                })
+                .await
+                .unwrap_or_default(),
+            );
+        }
+
+        connections
+            .into_iter()
+            .filter(|connection| {
+                let RemoteConnection { name, host, .. } = &connection.connection;
+                matcher::matches_all(&words, &format!("{name} {host}"))
+            })
+            .take(MAX_RESULTS)
+            .collect()
+    }
+}
+
+/// The groups of a key file, such as a Remmina profile, with their keys and values.
+fn groups(contents: &str) -> Vec<(&str, HashMap<&str, &str>)> {
+    let mut groups = Vec::<(&str, HashMap<&str, &str>)>::new();
+    for line in contents.lines().map(str::trim) {
+        if let Some(group) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            groups.push((group, HashMap::new()));
+        } else if let (Some((_, keys)), Some((key, value))) =
+            (groups.last_mut(), line.split_once('='))
+        {
+            keys.insert(key.trim(), value.trim());
+        }
+    }
+    groups
+}
+
+/// Splits a port off `host:port`.
+fn host_and_port(server: &str) -> (String, Option<u16>) {
+    match server.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => match port.parse() {
+            Ok(port) => (host.to_string(), Some(port)),
+            Err(_) => (server.to_string(), None),
+        },
+        _ => (server.to_string(), None),
+    }
+}
+
+fn non_empty(value: Option<&&str>) -> Option<String> {
+    value
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_string())
+}
+
+/// Profiles saved by Remmina, from `$XDG_DATA_HOME/remmina`.
+fn remmina() -> Vec<Connection> {
+    let Some(dir) = xdg::BaseDirectories::new()
+        .ok()
+        .map(|dirs| dirs.get_data_home().join("remmina"))
+    else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut connections = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "remmina"))
+        .filter_map(|file| {
+            let contents = std::fs::read_to_string(&file).ok()?;
+            let groups = groups(&contents);
+            let (_, keys) = groups.iter().find(|(group, _)| *group == "remmina")?;
+            let protocol = Protocol::parse(keys.get("protocol")?)?;
+            let (host, port) = host_and_port(&non_empty(keys.get("server"))?);
+            let connection = RemoteConnection {
+                name: non_empty(keys.get("name")).unwrap_or_else(|| host.clone()),
+                protocol,
+                host,
+                port,
+                user: non_empty(keys.get("username")),
+                path: None,
+            };
+            Some(Connection {
+                connection,
+                source: Source::Remmina(file),
+            })
+        })
+        .collect::<Vec<_>>();
+    connections.sort_by(|a, b| a.connection.name.cmp(&b.connection.name));
+    connections
+}
+
+/// Connections saved by GNOME Connections, installed natively or as a Flatpak.
+fn gnome_connections() -> Vec<Connection> {
+    let mut files = Vec::new();
+    if let Ok(dirs) = xdg::BaseDirectories::new() {
+        files.push(dirs.get_config_home().join("connections.db"));
+    }
+    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+        files.push(home.join(".var/app/org.gnome.Connections/config/connections.db"));
+    }
+
+    files
+        .iter()
+        .filter_map(|file| std::fs::read_to_string(file).ok())
+        .flat_map(|contents| {
+            groups(&contents)
+                .into_iter()
+                .filter_map(|(_, keys)| {
+                    let protocol = Protocol::parse(keys.get("protocol")?)?;
+                    let (host, port) = host_and_port(&non_empty(keys.get("host"))?);
+                    let port = port.or_else(|| keys.get("port")?.parse().ok());
+                    let connection = RemoteConnection {
+                        name: non_empty(keys.get("display-name")).unwrap_or_else(|| host.clone()),
+                        protocol,
+                        host,
+                        port,
+                        user: non_empty(keys.get("username")),
+                        path: None,
+                    };
+                    Some(Connection {
+                        connection,
+                        source: Source::GnomeConnections,
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+impl Provider for Remote {
+    fn name(&self) -> &str {
+        "remote"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|connection| {
+                    let protocol = connection.connection.protocol;
+                    let user = match &connection.connection.user {
+                        Some(user) => format!("{user}@"),
+                        None => String::new(),
+                    };
+                    let mut description = format!(
+                        "{} · {user}{}",
+                        protocol.scheme().to_uppercase(),
+                        connection.address()
+                    );
+                    if let Some(source) = connection.source.name() {
+                        description.push_str(" · ");
+                        description.push_str(source);
+                    }
+
+                    Item {
+                        name: connection.connection.name,
+                        description,
+                        icon: Some(match protocol {
+                            Protocol::Sftp => "folder-remote-symbolic".into(),
+                            Protocol::Rdp | Protocol::Vnc => {
+                                "preferences-desktop-remote-desktop-symbolic".into()
+                            }
+                        }),
+                        open: None,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let connection = self.matching(&query).await.into_iter().nth(index)?;
+            if connection.connection.protocol == Protocol::Sftp {
+                return Some(Action::Open(connection.uri()));
+            }
+
+            match connection.client() {
+                Some(args) => Some(Action::Run(args)),
+                None => {
+                    tracing::warn!(
+                        "no client installed for {}",
+                        connection.connection.protocol.scheme()
+                    );
+                    None
+                }
+            }
+        })
+    }
+}
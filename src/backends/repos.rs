@@ -0,0 +1,346 @@
+//! Git repositories below the configured root directories, for queries starting with
+//! `repo `.
+//!
+//! The roots are scanned on the first query, and each directory scanned is watched so that
+//! repositories created, moved, or deleted since are picked up by the next query.
+//! Activating a repository opens a terminal in it, and its context menu also offers the
+//! editor and the web page of its remote.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use super::{Action, Item, Provider};
+use crate::{fl, matcher};
+
+const PREFIX: &str = "repo ";
+
+/// Where repositories are searched, and what opens them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RepositoriesConfig {
+    pub enabled: bool,
+    /// Directories searched for repositories. A leading `~` is the home directory.
+    pub roots: Vec<String>,
+    /// How many directories deep repositories are searched below each root.
+    pub depth: usize,
+    /// Command opening a repository in an editor, which is passed its path.
+    pub editor: Vec<String>,
+}
+
+impl Default for RepositoriesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            roots: vec!["~/Projects".into(), "~/src".into()],
+            depth: 3,
+            editor: vec!["code".into()],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Repository {
+    name: String,
+    path: PathBuf,
+    /// Web page of the `origin` remote, or of the first remote if there is none.
+    remote: Option<String>,
+}
+
+impl Repository {
+    /// What the repository's context menu offers, with the default action first.
+    fn actions(&self, editor: &[String]) -> Vec<(String, Action)> {
+        let mut actions = vec![(
+            fl!("repo-open-terminal"),
+            Action::Terminal {
+                dir: Some(self.path.clone()),
+                args: Vec::new(),
+            },
+        )];
+
+        if !editor.is_empty() {
+            let mut args = editor.to_vec();
+            args.push(self.path.to_string_lossy().into_owned());
+            actions.push((fl!("repo-open-editor"), Action::Run(args)));
+        }
+
+        if let Some(remote) = self.remote.as_ref() {
+            actions.push((fl!("repo-open-remote"), Action::Open(remote.clone())));
+        }
+
+        actions
+    }
+}
+
+pub struct Repositories {
+    roots: Vec<PathBuf>,
+    depth: usize,
+    editor: Vec<String>,
+    /// Set when a watched directory changed since the last scan.
+    stale: Arc<AtomicBool>,
+    index: Arc<Mutex<(Arc<Vec<Repository>>, Option<RecommendedWatcher>)>>,
+}
+
+impl Repositories {
+    pub fn new(config: &RepositoriesConfig) -> Self {
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let roots = config
+            .roots
+            .iter()
+            .filter_map(|dir| match dir.strip_prefix('~') {
+                Some(rest) => Some(home.as_ref()?.join(rest.trim_start_matches('/'))),
+                None => Some(PathBuf::from(dir)),
+            })
+            .collect();
+
+        Self {
+            roots,
+            depth: config.depth,
+            editor: config.editor.clone(),
+            stale: Arc::new(AtomicBool::new(true)),
+            index: Arc::default(),
+        }
+    }
+
+    /// The repositories found, scanning the roots again if a watched directory changed.
+    ///
+    /// The scan runs on a task of its own, so that it completes and fills the index even
+    /// when the query which started it runs out of time.
+    async fn index(&self) -> Arc<Vec<Repository>> {
+        let index = Arc::clone(&self.index);
+        let stale = Arc::clone(&self.stale);
+        let roots = self.roots.clone();
+        let depth = self.depth;
+        tokio::spawn(async move {
+            let mut index = index.lock().await;
+            if !stale.load(Ordering::SeqCst) {
+                return Arc::clone(&index.0);
+            }
+
+            let (repositories, scanned) = tokio::task::spawn_blocking(move || {
+                let mut repositories = Vec::new();
+                let mut scanned = Vec::new();
+                for root in &roots {
+                    scan(root, depth, &mut repositories, &mut scanned);
+                }
+                repositories.sort_by(|a: &Repository, b| a.name.cmp(&b.name));
+                (repositories, scanned)
+            })
+            .await
+            .unwrap_or_default();
+            stale.store(false, Ordering::SeqCst);
+
+            // The previous watcher is dropped, removing its watches.
+            let watcher =
+                notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                    if event.is_ok_and(|event| !event.kind.is_access()) {
+                        stale.store(true, Ordering::SeqCst);
+                    }
+                });
+            let watcher = match watcher {
+                Ok(mut watcher) => {
+                    for dir in scanned {
+                        let _res = watcher.watch(&dir, RecursiveMode::NonRecursive);
+                    }
+                    Some(watcher)
+                }
+                Err(why) => {
+                    tracing::warn!("failed to watch for new repositories: {why}");
+                    None
+                }
+            };
+
+            *index = (Arc::new(repositories), watcher);
+            Arc::clone(&index.0)
+        })
+        .await
+        .unwrap_or_default()
+    }
+
+    async fn matching(&self, query: &str) -> Vec<Repository> {
+        let Some(text) = query.trim_start().strip_prefix(PREFIX) else {
+            return Vec::new();
+        };
+        let words = matcher::words(text);
+
+        self.index()
+            .await
+            .iter()
+            .filter(|repository| matcher::matches_all(&words, &repository.name))
+            .take(MAX_RESULTS)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Collects the repositories at most `depth` directories below `dir`, and the directories
+/// which were searched. Hidden directories and the insides of repositories are skipped.
+fn scan(dir: &Path, depth: usize, repositories: &mut Vec<Repository>, scanned: &mut Vec<PathBuf>) {
+    if dir.join(".git").exists() {
+        repositories.push(Repository {
+            name: dir
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            path: dir.to_path_buf(),
+            remote: remote(&dir.join(".git/config")),
+        });
+        return;
+    }
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    scanned.push(dir.to_path_buf());
+    if depth == 0 {
+        return;
+    }
+
+    for entry in entries.filter_map(Result::ok) {
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if !hidden && entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+            scan(&entry.path(), depth - 1, repositories, scanned);
+        }
+    }
+}
+
+/// Reads the web page of a repository's remote from its config file.
+fn remote(config: &Path) -> Option<String> {
+    let config = std::fs::read_to_string(config).ok()?;
+
+    let mut remotes = Vec::new();
+    let mut section = "";
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            section = line;
+        } else if let Some(url) = line
+            .strip_prefix("url")
+            .and_then(|rest| rest.trim_start().strip_prefix('='))
+        {
+            if section.starts_with("[remote ") {
+                remotes.push((section == "[remote \"origin\"]", url.trim()));
+            }
+        }
+    }
+
+    let (_, url) = remotes
+        .iter()
+        .find(|(origin, _)| *origin)
+        .or_else(|| remotes.first())?;
+    web_url(url)
+}
+
+/// Turns a remote such as `git@github.com:owner/repo.git` into `https://github.com/owner/repo`.
+fn web_url(url: &str) -> Option<String> {
+    let (host, path) = if let Some((_, rest)) = url.split_once("://") {
+        let (host, path) = rest.split_once('/')?;
+        let host = host.rsplit('@').next().unwrap_or(host);
+        // SSH ports are not those of the web server.
+        let host = if url.starts_with("http") {
+            host
+        } else {
+            host.split(':').next().unwrap_or(host)
+        };
+        (host, path)
+    } else {
+        let (host, path) = url.split_once(':')?;
+        (host.rsplit('@').next().unwrap_or(host), path)
+    };
+
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+
+    Some(format!("https://{host}/{}", path.trim_start_matches('/')))
+}
+
+impl Provider for Repositories {
+    fn name(&self) -> &str {
+        "repositories"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            let home = std::env::var_os("HOME").map(PathBuf::from);
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|repository| {
+                    let path = match home
+                        .as_ref()
+                        .and_then(|home| repository.path.strip_prefix(home).ok())
+                    {
+                        Some(relative) => format!("~/{}", relative.display()),
+                        None => repository.path.display().to_string(),
+                    };
+
+                    Item {
+                        name: repository.name,
+                        description: path,
+                        icon: Some("folder".into()),
+                        open: None,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let repository = self.matching(&query).await.into_iter().nth(index)?;
+            repository
+                .actions(&self.editor)
+                .into_iter()
+                .next()
+                .map(|(_, action)| action)
+        })
+    }
+
+    fn context(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Vec<String>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .nth(index)
+                .map(|repository| {
+                    repository
+                        .actions(&self.editor)
+                        .into_iter()
+                        .map(|(name, _)| name)
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    fn activate_context(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+        option: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let repository = self.matching(&query).await.into_iter().nth(index)?;
+            repository
+                .actions(&self.editor)
+                .into_iter()
+                .nth(option)
+                .map(|(_, action)| action)
+        })
+    }
+
+    fn share(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Option<String>> {
+        Box::pin(async move { self.matching(&query).await.into_iter().nth(index)?.remote })
+    }
+}
@@ -0,0 +1,152 @@
+//! Executables which receive the query on stdin and print their results as JSON.
+//!
+//! A script prints an array of `{ "name", "description", "icon", "open" }` objects, where
+//! `open` is a URI or path opened when the result is activated. Scripts which do not
+//! answer within their timeout are killed, and recent answers are cached.
+
+use std::collections::VecDeque;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::future::BoxFuture;
+use tokio::io::AsyncWriteExt;
+
+use super::{Action, Item, Provider};
+
+/// Time a script has to answer, unless configured otherwise.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long an answer is reused for repeated queries.
+const CACHE_TTL: Duration = Duration::from_secs(30);
+
+const CACHE_SIZE: usize = 32;
+
+pub struct Script {
+    name: String,
+    path: PathBuf,
+    timeout: Duration,
+    cache: Mutex<VecDeque<(String, Instant, Vec<Item>)>>,
+}
+
+/// Finds the executables in `dir`, using the timeout `timeout_for` returns for each name.
+pub fn discover(dir: &Path, timeout_for: impl Fn(&str) -> Duration) -> Vec<Arc<dyn Provider>> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().extension() != Some("wasm".as_ref()))
+        .filter(|entry| {
+            entry
+                .metadata()
+                .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        })
+        .map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            Arc::new(Script {
+                timeout: timeout_for(&name),
+                name,
+                path: entry.path(),
+                cache: Mutex::new(VecDeque::new()),
+            }) as Arc<dyn Provider>
+        })
+        .collect()
+}
+
+impl Script {
+    fn cached(&self, query: &str) -> Option<Vec<Item>> {
+        let cache = self.cache.lock().ok()?;
+        cache
+            .iter()
+            .find(|(cached, at, _)| cached == query && at.elapsed() < CACHE_TTL)
+            .map(|(_, _, items)| items.clone())
+    }
+
+    fn store(&self, query: String, items: Vec<Item>) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.retain(|(cached, ..)| *cached != query);
+            if cache.len() == CACHE_SIZE {
+                cache.pop_front();
+            }
+            cache.push_back((query, Instant::now(), items));
+        }
+    }
+
+    async fn run(&self, query: &str) -> Result<Vec<Item>, String> {
+        let mut child = tokio::process::Command::new(&self.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|why| why.to_string())?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(query.as_bytes())
+                .await
+                .map_err(|why| why.to_string())?;
+        }
+
+        let output = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .map_err(|_| format!("no answer within {} ms", self.timeout.as_millis()))?
+            .map_err(|why| why.to_string())?;
+
+        serde_json::from_slice(&output.stdout).map_err(|why| why.to_string())
+    }
+}
+
+impl Provider for Script {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            if let Some(items) = self.cached(&query) {
+                return items;
+            }
+
+            match self.run(&query).await {
+                Ok(items) => {
+                    self.store(query, items.clone());
+                    items
+                }
+                Err(why) => {
+                    tracing::warn!("script {} failed to answer a query: {why}", self.name);
+                    Vec::new()
+                }
+            }
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            self.cached(&query)?
+                .into_iter()
+                .nth(index)?
+                .open
+                .map(Action::Open)
+        })
+    }
+
+    fn share(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Option<String>> {
+        Box::pin(async move {
+            // Paths only open on this computer.
+            self.cached(&query)?
+                .into_iter()
+                .nth(index)?
+                .open
+                .filter(|open| open.contains("://"))
+        })
+    }
+}
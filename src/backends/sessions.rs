@@ -0,0 +1,260 @@
+//! Terminal sessions to return to: tmux sessions and windows for queries starting with
+//! `tmux `, and running toolbox and distrobox containers for queries starting with `box `.
+//!
+//! Activating one opens the configured terminal attached to the session, or entered into
+//! the container.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+
+use super::{Action, Item, Provider};
+use crate::{fl, matcher};
+
+const TMUX_PREFIX: &str = "tmux ";
+const BOX_PREFIX: &str = "box ";
+
+/// Time each listing command has to answer.
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone)]
+enum Session {
+    Tmux {
+        session: String,
+        windows: usize,
+        attached: bool,
+    },
+    TmuxWindow {
+        session: String,
+        index: String,
+        name: String,
+    },
+    Toolbox(String),
+    Distrobox(String),
+}
+
+impl Session {
+    fn name(&self) -> String {
+        match self {
+            Self::Tmux { session, .. } => session.clone(),
+            Self::TmuxWindow {
+                session,
+                index,
+                name,
+            } => format!("{session}:{index} {name}"),
+            Self::Toolbox(name) | Self::Distrobox(name) => name.clone(),
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            Self::Tmux {
+                windows, attached, ..
+            } if *attached => fl!("session-tmux-attached", windows = *windows),
+            Self::Tmux { windows, .. } => fl!("session-tmux", windows = *windows),
+            Self::TmuxWindow { .. } => fl!("session-tmux-window"),
+            Self::Toolbox(_) => fl!("session-toolbox"),
+            Self::Distrobox(_) => fl!("session-distrobox"),
+        }
+    }
+
+    /// The program run in the terminal.
+    fn command(&self) -> Vec<String> {
+        let args: &[&str] = match self {
+            Self::Tmux { session, .. } => &["tmux", "attach-session", "-t", session],
+            Self::TmuxWindow { session, index, .. } => {
+                let window = format!("{session}:{index}");
+                return [
+                    "tmux",
+                    "attach-session",
+                    "-t",
+                    session,
+                    ";",
+                    "select-window",
+                    "-t",
+                ]
+                .into_iter()
+                .map(String::from)
+                .chain([window])
+                .collect();
+            }
+            Self::Toolbox(name) => &["toolbox", "enter", name],
+            Self::Distrobox(name) => &["distrobox", "enter", name],
+        };
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+}
+
+#[derive(Default)]
+pub struct Sessions;
+
+impl Sessions {
+    async fn matching(&self, query: &str) -> Vec<Session> {
+        let query = query.trim_start();
+        let (sessions, text) = if let Some(text) = query.strip_prefix(TMUX_PREFIX) {
+            (tmux().await, text)
+        } else if let Some(text) = query.strip_prefix(BOX_PREFIX) {
+            (containers().await, text)
+        } else {
+            return Vec::new();
+        };
+
+        let words = matcher::words(text);
+        sessions
+            .into_iter()
+            .filter(|session| matcher::matches_all(&words, &session.name()))
+            .take(MAX_RESULTS)
+            .collect()
+    }
+}
+
+/// Lines printed by a command, or none if it is not installed or did not answer in time.
+async fn lines(program: &str, args: &[&str]) -> Vec<String> {
+    let output = tokio::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .output();
+
+    match tokio::time::timeout(TIMEOUT, output).await {
+        Ok(Ok(output)) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(String::from)
+            .collect(),
+        Ok(Ok(_)) => Vec::new(),
+        Ok(Err(why)) => {
+            tracing::debug!("failed to run {program}: {why}");
+            Vec::new()
+        }
+        Err(_) => {
+            tracing::warn!("{program} did not answer within {TIMEOUT:?}");
+            Vec::new()
+        }
+    }
+}
+
+/// Every tmux session, each followed by its windows.
+async fn tmux() -> Vec<Session> {
+    let (sessions, windows) = futures::join!(
+        lines(
+            "tmux",
+            &[
+                "list-sessions",
+                "-F",
+                "#{session_name}\t#{session_windows}\t#{session_attached}",
+            ],
+        ),
+        lines(
+            "tmux",
+            &[
+                "list-windows",
+                "-a",
+                "-F",
+                "#{session_name}\t#{window_index}\t#{window_name}",
+            ],
+        ),
+    );
+
+    let windows = windows
+        .iter()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            Some(Session::TmuxWindow {
+                session: fields.next()?.to_string(),
+                index: fields.next()?.to_string(),
+                name: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    sessions
+        .iter()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            Some(Session::Tmux {
+                session: fields.next()?.to_string(),
+                windows: fields.next()?.parse().unwrap_or_default(),
+                attached: fields.next().is_some_and(|clients| clients != "0"),
+            })
+        })
+        .flat_map(|session| {
+            let name = session.name();
+            let windows = windows.iter().filter(move |window| {
+                matches!(window, Session::TmuxWindow { session, .. } if *session == name)
+            });
+            std::iter::once(session.clone()).chain(windows.cloned())
+        })
+        .collect()
+}
+
+/// Running toolbox and distrobox containers.
+async fn containers() -> Vec<Session> {
+    let (toolboxes, distroboxes) = futures::join!(
+        lines(
+            "podman",
+            &[
+                "ps",
+                "--filter",
+                "label=com.github.containers.toolbox=true",
+                "--format",
+                "{{.Names}}",
+            ],
+        ),
+        lines("distrobox", &["list", "--no-color"]),
+    );
+
+    // Distrobox lists `ID | NAME | STATUS | IMAGE`, after a header.
+    let distroboxes = distroboxes.iter().skip(1).filter_map(|line| {
+        let mut columns = line.split('|').map(str::trim);
+        let name = columns.nth(1)?;
+        columns
+            .next()
+            .is_some_and(|status| status.starts_with("Up"))
+            .then(|| Session::Distrobox(name.to_string()))
+    });
+
+    toolboxes
+        .into_iter()
+        .filter(|name| !name.is_empty())
+        .map(Session::Toolbox)
+        .chain(distroboxes)
+        .collect()
+}
+
+impl Provider for Sessions {
+    fn name(&self) -> &str {
+        "sessions"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|session| Item {
+                    name: session.name(),
+                    description: session.description(),
+                    icon: Some("utilities-terminal".into()),
+                    open: None,
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let session = self.matching(&query).await.into_iter().nth(index)?;
+            Some(Action::Terminal {
+                dir: None,
+                args: session.command(),
+            })
+        })
+    }
+}
@@ -0,0 +1,239 @@
+//! Pages of COSMIC Settings matched by keywords, such as `bluetooth`, `wallpaper`, or
+//! `keyboard shortcuts`. Activating one opens COSMIC Settings on that page, through the
+//! page's subcommand of `cosmic-settings`, which a running instance handles itself.
+
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+
+use super::{Action, Item, Provider};
+use crate::fl;
+
+const PROGRAM: &str = "cosmic-settings";
+
+/// Characters a query needs before pages are matched.
+const MIN_QUERY: usize = 3;
+
+struct Page {
+    /// Subcommand of `cosmic-settings` opening the page.
+    command: &'static str,
+    icon: &'static str,
+    keywords: &'static [&'static str],
+}
+
+impl Page {
+    fn name(&self) -> String {
+        match self.command {
+            "wireless" => fl!("settings-wireless"),
+            "wired" => fl!("settings-wired"),
+            "vpn" => fl!("settings-vpn"),
+            "bluetooth" => fl!("settings-bluetooth"),
+            "appearance" => fl!("settings-appearance"),
+            "wallpaper" => fl!("settings-wallpaper"),
+            "panel" => fl!("settings-panel"),
+            "dock" => fl!("settings-dock"),
+            "window-management" => fl!("settings-window-management"),
+            "workspaces" => fl!("settings-workspaces"),
+            "displays" => fl!("settings-displays"),
+            "notifications" => fl!("settings-notifications"),
+            "power" => fl!("settings-power"),
+            "sound" => fl!("settings-sound"),
+            "keyboard" => fl!("settings-keyboard"),
+            "mouse" => fl!("settings-mouse"),
+            "touchpad" => fl!("settings-touchpad"),
+            "users" => fl!("settings-users"),
+            "date-time" => fl!("settings-date-time"),
+            "region-language" => fl!("settings-region-language"),
+            "default-apps" => fl!("settings-default-apps"),
+            "firmware" => fl!("settings-firmware"),
+            _ => fl!("settings-about"),
+        }
+    }
+}
+
+const PAGES: &[Page] = &[
+    Page {
+        command: "wireless",
+        icon: "network-wireless-symbolic",
+        keywords: &["wifi", "wireless", "network", "internet", "hotspot"],
+    },
+    Page {
+        command: "wired",
+        icon: "network-wired-symbolic",
+        keywords: &["ethernet", "wired", "network", "cable"],
+    },
+    Page {
+        command: "vpn",
+        icon: "network-vpn-symbolic",
+        keywords: &["vpn", "wireguard", "openvpn", "network"],
+    },
+    Page {
+        command: "bluetooth",
+        icon: "bluetooth-active-symbolic",
+        keywords: &["bluetooth", "devices", "pair", "headphones"],
+    },
+    Page {
+        command: "appearance",
+        icon: "preferences-desktop-appearance-symbolic",
+        keywords: &["appearance", "theme", "dark", "light", "accent", "colors"],
+    },
+    Page {
+        command: "wallpaper",
+        icon: "preferences-desktop-wallpaper-symbolic",
+        keywords: &["wallpaper", "background", "slideshow"],
+    },
+    Page {
+        command: "panel",
+        icon: "preferences-panel-symbolic",
+        keywords: &["panel", "top", "bar", "applets"],
+    },
+    Page {
+        command: "dock",
+        icon: "preferences-dock-symbolic",
+        keywords: &["dock", "applets", "favorites"],
+    },
+    Page {
+        command: "window-management",
+        icon: "preferences-window-management-symbolic",
+        keywords: &["window", "management", "tiling", "focus", "super"],
+    },
+    Page {
+        command: "workspaces",
+        icon: "preferences-workspaces-symbolic",
+        keywords: &["workspaces", "virtual", "desktops"],
+    },
+    Page {
+        command: "displays",
+        icon: "preferences-desktop-display-symbolic",
+        keywords: &["displays", "monitors", "screens", "resolution", "scale"],
+    },
+    Page {
+        command: "notifications",
+        icon: "preferences-system-notifications-symbolic",
+        keywords: &["notifications", "alerts", "banners"],
+    },
+    Page {
+        command: "power",
+        icon: "preferences-power-symbolic",
+        keywords: &["power", "battery", "suspend", "sleep", "energy"],
+    },
+    Page {
+        command: "sound",
+        icon: "preferences-sound-symbolic",
+        keywords: &["sound", "audio", "volume", "speakers", "microphone"],
+    },
+    Page {
+        command: "keyboard",
+        icon: "input-keyboard-symbolic",
+        keywords: &["keyboard", "shortcuts", "keybindings", "layout", "typing"],
+    },
+    Page {
+        command: "mouse",
+        icon: "input-mouse-symbolic",
+        keywords: &["mouse", "pointer", "cursor", "acceleration", "scrolling"],
+    },
+    Page {
+        command: "touchpad",
+        icon: "input-touchpad-symbolic",
+        keywords: &["touchpad", "trackpad", "gestures", "tap", "scrolling"],
+    },
+    Page {
+        command: "users",
+        icon: "system-users-symbolic",
+        keywords: &["users", "accounts", "password", "login"],
+    },
+    Page {
+        command: "date-time",
+        icon: "preferences-system-time-symbolic",
+        keywords: &["date", "time", "clock", "timezone"],
+    },
+    Page {
+        command: "region-language",
+        icon: "preferences-desktop-locale-symbolic",
+        keywords: &["region", "language", "locale", "formats"],
+    },
+    Page {
+        command: "default-apps",
+        icon: "preferences-desktop-default-applications-symbolic",
+        keywords: &["default", "applications", "apps", "browser", "mail"],
+    },
+    Page {
+        command: "firmware",
+        icon: "firmware-manager-symbolic",
+        keywords: &["firmware", "bios", "updates"],
+    },
+    Page {
+        command: "about",
+        icon: "help-about-symbolic",
+        keywords: &["about", "system", "hostname", "device", "hardware"],
+    },
+];
+
+pub struct Settings {
+    installed: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            installed: crate::doctor::find_in_path(PROGRAM).is_some(),
+        }
+    }
+}
+
+impl Settings {
+    fn matching(&self, query: &str) -> Vec<&'static Page> {
+        let query = query.trim().to_lowercase();
+        if !self.installed || query.chars().count() < MIN_QUERY {
+            return Vec::new();
+        }
+
+        // Queries such as `sound settings` name the settings app too.
+        let words = query
+            .split_whitespace()
+            .filter(|word| !"settings".starts_with(word))
+            .collect::<Vec<_>>();
+        if words.is_empty() {
+            return Vec::new();
+        }
+
+        PAGES
+            .iter()
+            .filter(|page| {
+                let matches = |word: &&str| page.keywords.iter().any(|k| k.starts_with(word));
+                words.iter().all(matches)
+            })
+            .collect()
+    }
+}
+
+impl Provider for Settings {
+    fn name(&self) -> &str {
+        "settings"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .into_iter()
+                .map(|page| Item {
+                    name: page.name(),
+                    description: fl!("settings-app"),
+                    icon: Some(page.icon.into()),
+                    open: None,
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let page = self.matching(&query).into_iter().nth(index)?;
+            Some(Action::Run(vec![PROGRAM.into(), page.command.into()]))
+        })
+    }
+}
@@ -0,0 +1,367 @@
+//! Commands from bash, zsh, and fish history, for queries starting with `hist `.
+//!
+//! Each command is listed once, ranked by how often and how recently it was run. Many
+//! history files have no timestamps, so recency is counted in the commands run since.
+//! Activating a command runs it again in a terminal, with the shell which ran it, and
+//! leaves that shell open afterwards. The index is rebuilt once it is older than
+//! [`REINDEX_AFTER`].
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use super::cache::{self, Cache};
+use super::{Action, Item, Provider};
+use crate::{fl, matcher};
+
+const PREFIX: &str = "hist ";
+
+const REINDEX_AFTER: Duration = Duration::from_secs(60);
+
+/// Commands run since a command after which it counts half as much as a recent one.
+const HALF_LIFE: f64 = 200.0;
+
+/// zsh escapes bytes of its own meaning in history files with this byte, followed by the
+/// escaped byte XORed with 32.
+const ZSH_META: u8 = 0x83;
+
+/// Which shells' history is searched.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShellHistoryConfig {
+    pub enabled: bool,
+    /// Commands left out, as patterns like those of bash's `HISTIGNORE`, where `*` matches
+    /// any text and `?` any character.
+    pub ignore: Vec<String>,
+}
+
+impl Default for ShellHistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ignore: ["ls", "cd", "cd *", "pwd", "clear", "exit", "history*"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    fn program(self) -> &'static str {
+        match self {
+            Self::Bash => "bash",
+            Self::Zsh => "zsh",
+            Self::Fish => "fish",
+        }
+    }
+
+    /// The commands of a history file, oldest first.
+    fn parse(self, bytes: &[u8]) -> Vec<String> {
+        match self {
+            Self::Bash => String::from_utf8_lossy(bytes)
+                .lines()
+                // Timestamps written when HISTTIMEFORMAT is set.
+                .filter(|line| {
+                    !line
+                        .strip_prefix('#')
+                        .is_some_and(|time| time.bytes().all(|b| b.is_ascii_digit()))
+                })
+                .map(String::from)
+                .collect(),
+            Self::Zsh => {
+                let mut unmetafied = Vec::with_capacity(bytes.len());
+                let mut bytes = bytes.iter();
+                while let Some(&byte) = bytes.next() {
+                    if byte == ZSH_META {
+                        unmetafied.extend(bytes.next().map(|byte| byte ^ 32));
+                    } else {
+                        unmetafied.push(byte);
+                    }
+                }
+
+                let mut commands = Vec::<String>::new();
+                let mut continued = false;
+                for line in String::from_utf8_lossy(&unmetafied).lines() {
+                    let (line, continues) = match line.strip_suffix('\\') {
+                        Some(line) => (line, true),
+                        None => (line, false),
+                    };
+                    match commands.last_mut().filter(|_| continued) {
+                        Some(command) => {
+                            command.push('\n');
+                            command.push_str(line);
+                        }
+                        None => {
+                            // `: <start>:<duration>;<command>` with EXTENDED_HISTORY.
+                            let command = line
+                                .strip_prefix(": ")
+                                .and_then(|rest| rest.split_once(';'))
+                                .map_or(line, |(_, command)| command);
+                            commands.push(command.to_string());
+                        }
+                    }
+                    continued = continues;
+                }
+                commands
+            }
+            Self::Fish => String::from_utf8_lossy(bytes)
+                .lines()
+                .filter_map(|line| line.strip_prefix("- cmd: "))
+                .map(|command| {
+                    let mut unescaped = String::with_capacity(command.len());
+                    let mut chars = command.chars();
+                    while let Some(c) = chars.next() {
+                        if c != '\\' {
+                            unescaped.push(c);
+                            continue;
+                        }
+                        match chars.next() {
+                            Some('n') => unescaped.push('\n'),
+                            Some(c) => unescaped.push(c),
+                            None => unescaped.push('\\'),
+                        }
+                    }
+                    unescaped
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Command {
+    line: String,
+    /// The shell which ran the command most recently.
+    shell: Shell,
+    score: f64,
+}
+
+impl Command {
+    /// Runs the command, then stays in the shell.
+    fn terminal_args(&self) -> Vec<String> {
+        let shell = self.shell.program();
+        vec![
+            shell.into(),
+            "-ic".into(),
+            format!("{}\nexec {shell}", self.line),
+        ]
+    }
+}
+
+pub struct ShellHistory {
+    ignore: Arc<Vec<String>>,
+    index: Cache<Vec<Command>>,
+}
+
+impl ShellHistory {
+    pub fn new(config: &ShellHistoryConfig) -> Self {
+        Self {
+            ignore: Arc::new(config.ignore.clone()),
+            index: Cache::new(REINDEX_AFTER),
+        }
+    }
+
+    async fn index(&self) -> Arc<Vec<Command>> {
+        let ignore = Arc::clone(&self.ignore);
+        self.index
+            .get(cache::blocking(move || read_history(&ignore)))
+            .await
+    }
+
+    async fn matching(&self, query: &str) -> Vec<Command> {
+        let Some(text) = query.trim_start().strip_prefix(PREFIX) else {
+            return Vec::new();
+        };
+        let words = matcher::words(text);
+
+        self.index()
+            .await
+            .iter()
+            .filter(|command| matcher::matches_all(&words, &command.line))
+            .take(MAX_RESULTS)
+            .cloned()
+            .collect()
+    }
+}
+
+/// History files of each shell, where they are kept unless a shell was told otherwise.
+fn history_files() -> Vec<(Shell, PathBuf)> {
+    let mut files = Vec::new();
+    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+        files.push((Shell::Bash, home.join(".bash_history")));
+        let zdotdir = std::env::var_os("ZDOTDIR").map_or_else(|| home.clone(), PathBuf::from);
+        files.push((Shell::Zsh, zdotdir.join(".zsh_history")));
+        files.push((Shell::Zsh, home.join(".histfile")));
+    }
+    if let Ok(dirs) = xdg::BaseDirectories::new() {
+        files.push((Shell::Fish, dirs.get_data_home().join("fish/fish_history")));
+    }
+    files
+}
+
+/// Reads every history file, merging repeated commands, best ranked first.
+fn read_history(ignore: &[String]) -> Vec<Command> {
+    let mut commands = HashMap::<String, (Command, f64)>::new();
+    for (shell, path) in history_files() {
+        let Ok(bytes) = std::fs::read(&path) else {
+            continue;
+        };
+
+        let lines = shell.parse(&bytes);
+        let count = lines.len();
+        for (i, line) in lines.into_iter().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || ignore.iter().any(|pattern| glob(pattern, line)) {
+                continue;
+            }
+
+            #[allow(clippy::cast_precision_loss)]
+            let weight = 0.5_f64.powf((count - i - 1) as f64 / HALF_LIFE);
+            let (command, best) = commands.entry(line.to_string()).or_insert_with(|| {
+                let command = Command {
+                    line: line.to_string(),
+                    shell,
+                    score: 0.0,
+                };
+                (command, 0.0)
+            });
+            command.score += weight;
+            if weight > *best {
+                *best = weight;
+                command.shell = shell;
+            }
+        }
+    }
+
+    let mut commands = commands
+        .into_values()
+        .map(|(command, _)| command)
+        .collect::<Vec<_>>();
+    commands.sort_by(|a, b| b.score.total_cmp(&a.score));
+    commands
+}
+
+/// Whether the whole of `text` matches `pattern`, where `*` matches any text and `?` any
+/// character.
+fn glob(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut p, mut t) = (0, 0);
+    // Where the last `*` was, and the text it matched up to.
+    let mut star = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+impl Provider for ShellHistory {
+    fn name(&self) -> &str {
+        "shell-history"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|command| {
+                    let mut lines = command.line.lines();
+                    let mut name = lines.next().unwrap_or_default().to_string();
+                    if lines.next().is_some() {
+                        name.push_str(" …");
+                    }
+                    Item {
+                        name,
+                        description: fl!(
+                            "shell-history-run-again",
+                            shell = command.shell.program()
+                        ),
+                        icon: Some("utilities-terminal".into()),
+                        open: None,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let command = self.matching(&query).await.into_iter().nth(index)?;
+            Some(Action::Terminal {
+                dir: None,
+                args: command.terminal_args(),
+            })
+        })
+    }
+
+    fn context(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Vec<String>> {
+        Box::pin(async move {
+            if self.matching(&query).await.len() > index {
+                vec![fl!("shell-history-run"), fl!("shell-history-copy")]
+            } else {
+                Vec::new()
+            }
+        })
+    }
+
+    fn activate_context(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+        option: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let command = self.matching(&query).await.into_iter().nth(index)?;
+            match option {
+                0 => Some(Action::Terminal {
+                    dir: None,
+                    args: command.terminal_args(),
+                }),
+                1 => Some(Action::Copy(command.line)),
+                _ => None,
+            }
+        })
+    }
+
+    /// Commands may hold secrets typed on the command line. They are read again from the
+    /// history files on the next search.
+    fn forget(&self) {
+        self.index.try_clear();
+    }
+}
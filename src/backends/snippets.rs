@@ -0,0 +1,194 @@
+//! Snippets of text defined in the configuration, found by their name and listed by
+//! `snip `. Activating one copies it to the clipboard, or types it into the focused window,
+//! with its placeholders filled in at that moment.
+//!
+//! Placeholders are `{date}`, `{time}`, and `{clipboard}`. Any other text between braces is
+//! kept as it is.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+use jiff::Zoned;
+use serde::{Deserialize, Serialize};
+
+use super::{Action, Item, Provider};
+use crate::{fl, matcher};
+
+const PREFIX: &str = "snip ";
+
+/// How much of a snippet is shown below its name.
+const PREVIEW_CHARS: usize = 60;
+
+/// Snippets by name, and how they are inserted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnippetsConfig {
+    pub enabled: bool,
+    /// Type snippets into the focused window on activation, instead of copying them.
+    pub type_text: bool,
+    pub snippets: BTreeMap<String, String>,
+}
+
+impl Default for SnippetsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            type_text: false,
+            snippets: BTreeMap::new(),
+        }
+    }
+}
+
+pub struct Snippets {
+    snippets: Vec<(String, String)>,
+    type_text: bool,
+}
+
+impl Snippets {
+    pub fn new(config: &SnippetsConfig) -> Self {
+        Self {
+            snippets: config
+                .snippets
+                .iter()
+                .map(|(name, text)| (name.clone(), text.clone()))
+                .collect(),
+            type_text: config.type_text,
+        }
+    }
+
+    fn matching(&self, query: &str) -> Vec<&(String, String)> {
+        let query = query.trim_start();
+        let text = query.strip_prefix(PREFIX);
+        let words = matcher::words(text.unwrap_or(query));
+
+        // Other searches list snippets only once they say enough to tell them apart.
+        if text.is_none() && words.concat().chars().count() < 2 {
+            return Vec::new();
+        }
+
+        self.snippets
+            .iter()
+            .filter(|(name, _)| matcher::matches_all(&words, name))
+            .take(MAX_RESULTS)
+            .collect()
+    }
+
+    /// Ways of inserting a snippet, with the default one first.
+    fn options(&self) -> [(String, bool); 2] {
+        let copy = (fl!("snippet-copy"), false);
+        let type_text = (fl!("snippet-type"), true);
+        if self.type_text {
+            [type_text, copy]
+        } else {
+            [copy, type_text]
+        }
+    }
+
+    async fn insert(&self, query: &str, index: usize, option: usize) -> Option<Action> {
+        let (_, text) = self.matching(query).into_iter().nth(index)?;
+        let type_text = self.options().get(option)?.1;
+        let text = expand(text).await;
+        if type_text {
+            Some(Action::Type(text))
+        } else {
+            Some(Action::Copy(text))
+        }
+    }
+}
+
+/// The first line of a snippet, shortened to fit below its name.
+fn preview(text: &str) -> String {
+    let mut lines = text.trim().lines();
+    let line = lines.next().unwrap_or("").trim();
+    let mut preview = line.chars().take(PREVIEW_CHARS).collect::<String>();
+    if line.chars().count() > PREVIEW_CHARS || lines.next().is_some() {
+        preview.push('…');
+    }
+    preview
+}
+
+/// Fills in the placeholders of a snippet.
+async fn expand(text: &str) -> String {
+    let now = Zoned::now();
+    let clipboard = if text.contains("{clipboard}") {
+        crate::launch::paste(false).await
+    } else {
+        String::new()
+    };
+
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        match &rest[1..end] {
+            "date" => expanded.push_str(&now.strftime("%Y-%m-%d").to_string()),
+            "time" => expanded.push_str(&now.strftime("%H:%M").to_string()),
+            "clipboard" => expanded.push_str(&clipboard),
+            _ => expanded.push_str(&rest[..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+impl Provider for Snippets {
+    fn name(&self) -> &str {
+        "snippets"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .into_iter()
+                .map(|(name, text)| Item {
+                    name: name.clone(),
+                    description: preview(text),
+                    icon: Some("edit-paste-symbolic".into()),
+                    open: None,
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move { self.insert(&query, index, 0).await })
+    }
+
+    fn context(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Vec<String>> {
+        Box::pin(async move {
+            if self.matching(&query).len() > index {
+                self.options().into_iter().map(|(name, _)| name).collect()
+            } else {
+                Vec::new()
+            }
+        })
+    }
+
+    fn activate_context(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+        option: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move { self.insert(&query, index, option).await })
+    }
+
+    fn share(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Option<String>> {
+        Box::pin(async move {
+            let (_, text) = self.matching(&query).into_iter().nth(index)?;
+            Some(expand(text).await)
+        })
+    }
+}
@@ -0,0 +1,162 @@
+//! Open browser tabs, reported by the browser extension through its native messaging
+//! host, for queries starting with `tab:` or `tab `.
+//!
+//! Every running host is asked for its tabs on each query. Activating a tab asks the host
+//! that reported it to have the extension focus it.
+
+// The host uses the messages to and from the extension.
+#[allow(dead_code)]
+pub mod protocol;
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+use self::protocol::{Request, Tab};
+use super::{Action, Item, Provider};
+use crate::matcher;
+
+const PREFIXES: [&str; 2] = ["tab:", "tab "];
+
+/// Time a host has to list its tabs.
+const TIMEOUT: Duration = Duration::from_millis(200);
+
+#[derive(Default)]
+pub struct Tabs {
+    /// The tabs listed for the last query, with the socket of the host reporting each.
+    listed: Mutex<(String, Vec<(PathBuf, Tab)>)>,
+}
+
+impl Tabs {
+    async fn list(&self, query: &str) -> Vec<(PathBuf, Tab)> {
+        let query = query.trim_start().to_lowercase();
+        let Some(text) = PREFIXES
+            .iter()
+            .find_map(|prefix| query.strip_prefix(prefix))
+        else {
+            return Vec::new();
+        };
+        let words = matcher::words(text);
+
+        let sockets = protocol::socket_dir()
+            .and_then(|dir| std::fs::read_dir(dir).ok())
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .map(|entry| entry.path())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let listed = futures::future::join_all(sockets.into_iter().map(|socket| async move {
+            match tokio::time::timeout(TIMEOUT, list_tabs(&socket)).await {
+                Ok(Ok(tabs)) => tabs.into_iter().map(|tab| (socket.clone(), tab)).collect(),
+                Ok(Err(why)) => {
+                    // Hosts of browsers which crashed leave their socket behind.
+                    if why.kind() == std::io::ErrorKind::ConnectionRefused {
+                        let _res = std::fs::remove_file(&socket);
+                    }
+                    Vec::new()
+                }
+                Err(_) => Vec::new(),
+            }
+        }))
+        .await;
+
+        listed
+            .into_iter()
+            .flatten()
+            .filter(|(_, tab)| matcher::matches_all(&words, &format!("{} {}", tab.title, tab.url)))
+            .collect()
+    }
+}
+
+async fn list_tabs(socket: &Path) -> std::io::Result<Vec<Tab>> {
+    let mut stream = UnixStream::connect(socket).await?;
+    send(&mut stream, &Request::List).await?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).await?;
+    Ok(serde_json::from_str(&line)?)
+}
+
+async fn send(stream: &mut UnixStream, request: &Request) -> std::io::Result<()> {
+    let mut line = serde_json::to_vec(request)?;
+    line.push(b'\n');
+    stream.write_all(&line).await
+}
+
+impl Provider for Tabs {
+    fn name(&self) -> &str {
+        "tabs"
+    }
+
+    fn addressed(&self, query: &str) -> bool {
+        let query = query.trim_start().to_lowercase();
+        PREFIXES.iter().any(|prefix| query.starts_with(prefix))
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            let tabs = self.list(&query).await;
+            let items = tabs
+                .iter()
+                .map(|(_, tab)| Item {
+                    name: tab.title.clone(),
+                    description: tab.url.clone(),
+                    icon: Some("web-browser".into()),
+                    open: None,
+                })
+                .collect();
+
+            if let Ok(mut listed) = self.listed.lock() {
+                *listed = (query, tabs);
+            }
+            items
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let listed = self
+                .listed
+                .lock()
+                .ok()
+                .filter(|listed| listed.0 == query)
+                .and_then(|listed| listed.1.get(index).cloned());
+
+            if let Some((socket, tab)) = listed {
+                let request = Request::Activate {
+                    tab_id: tab.id,
+                    window_id: tab.window_id,
+                };
+                let activated = async {
+                    let mut stream = UnixStream::connect(&socket).await?;
+                    send(&mut stream, &request).await
+                };
+                if let Err(why) = activated.await {
+                    tracing::warn!("failed to activate a browser tab: {why}");
+                }
+            }
+
+            // The browser raises its own window.
+            None
+        })
+    }
+
+    fn share(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Option<String>> {
+        Box::pin(async move {
+            let listed = self.listed.lock().ok()?;
+            let (_, tab) = listed.1.get(index).filter(|_| listed.0 == query)?;
+            Some(tab.url.clone())
+        })
+    }
+}
@@ -0,0 +1,56 @@
+//! Messages between the browser extension, the native messaging host the browser starts
+//! for it, and the launcher.
+//!
+//! The extension and the host exchange JSON messages prefixed with their length, as native
+//! messaging requires. Each host listens on a socket in [`socket_dir`], where the launcher
+//! sends one JSON [`Request`] per line.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tab {
+    pub id: u64,
+    pub window_id: u64,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub url: String,
+}
+
+/// Sent by the extension whenever its tabs change.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum FromExtension {
+    Tabs { tabs: Vec<Tab> },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ToExtension {
+    #[serde(rename_all = "camelCase")]
+    Activate { tab_id: u64, window_id: u64 },
+}
+
+/// Sent by the launcher to a host. `List` is answered with a line holding the tabs.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Request {
+    List,
+    #[serde(rename_all = "camelCase")]
+    Activate {
+        tab_id: u64,
+        window_id: u64,
+    },
+}
+
+/// Directory holding the socket of every running host.
+pub fn socket_dir() -> Option<PathBuf> {
+    xdg::BaseDirectories::with_prefix("cosmic-launcher")
+        .ok()?
+        .get_runtime_directory()
+        .ok()
+        .map(|dir| dir.join("tabs"))
+}
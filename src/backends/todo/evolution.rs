@@ -0,0 +1,198 @@
+//! Task lists of Evolution Data Server, each holding its tasks as iCalendar `VTODO`s.
+
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures::future::BoxFuture;
+use jiff::Timestamp;
+
+use super::{Task, TaskStore};
+use crate::backends::eds::{self, TASK_LISTS};
+use crate::fl;
+
+/// Query of the open tasks of a list.
+const OPEN_TASKS: &str = "(not (is-completed?))";
+
+/// Properties of a task which are replaced when it is completed.
+const COMPLETION: [&str; 3] = ["STATUS", "PERCENT-COMPLETE", "COMPLETED"];
+
+pub struct Evolution {
+    connection: zbus::Connection,
+    /// UID of the list's source.
+    source: String,
+    name: String,
+}
+
+impl Evolution {
+    /// The enabled task lists, or none if EDS is not running.
+    pub async fn lists() -> Vec<Arc<dyn TaskStore>> {
+        let Ok(connection) = zbus::Connection::session().await else {
+            return Vec::new();
+        };
+
+        match TASK_LISTS.named_sources(&connection).await {
+            Ok(sources) => sources
+                .into_iter()
+                .map(|(source, name)| {
+                    Arc::new(Self {
+                        connection: connection.clone(),
+                        source,
+                        name,
+                    }) as Arc<dyn TaskStore>
+                })
+                .collect(),
+            Err(why) => {
+                tracing::debug!("task lists are unavailable: {why}");
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// Reads the UID, summary, and priority of a `VTODO`.
+fn parse(ics: &str) -> Option<Task> {
+    let unfolded = eds::unfold(ics);
+    let (mut uid, mut summary, mut priority) = (None, None, None);
+    let mut nested = 0;
+
+    for line in unfolded.lines() {
+        match line {
+            "BEGIN:VTODO" | "END:VTODO" => {}
+            // Alarms nested in the task have their own properties.
+            _ if line.starts_with("BEGIN:") => nested += 1,
+            _ if line.starts_with("END:") => nested -= 1,
+            _ if nested > 0 => {}
+            _ => match eds::property(line) {
+                Some(("UID", _, value)) => uid = Some(value.to_string()),
+                Some(("SUMMARY", _, value)) => summary = Some(eds::unescape(value)),
+                Some(("PRIORITY", _, value)) => priority = value.trim().parse::<u8>().ok(),
+                _ => {}
+            },
+        }
+    }
+
+    // iCalendar priorities run from 1, the highest, to 9, with 0 for none.
+    let details = match priority {
+        Some(1..=4) => Some(fl!("task-priority-high")),
+        Some(5) => Some(fl!("task-priority-medium")),
+        Some(6..=9) => Some(fl!("task-priority-low")),
+        _ => None,
+    };
+
+    Some(Task {
+        id: uid?,
+        summary: summary?,
+        details,
+    })
+}
+
+/// A UID for a new task, unique enough that lists never hold it already.
+fn new_uid() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    format!("{nanos:x}-{:x}@cosmic-launcher", std::process::id())
+}
+
+impl TaskStore for Evolution {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn tasks(&self) -> BoxFuture<'_, Result<Vec<Task>, String>> {
+        Box::pin(async move {
+            let objects = TASK_LISTS
+                .query(&self.connection, &self.source, "GetObjectList", OPEN_TASKS)
+                .await
+                .map_err(|why| why.to_string())?;
+            Ok(objects.iter().filter_map(|ics| parse(ics)).collect())
+        })
+    }
+
+    fn add<'a>(&'a self, summary: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let now = Timestamp::now().strftime("%Y%m%dT%H%M%SZ").to_string();
+            let ics = [
+                "BEGIN:VTODO".to_string(),
+                format!("UID:{}", new_uid()),
+                format!("DTSTAMP:{now}"),
+                format!("CREATED:{now}"),
+                format!("SUMMARY:{}", eds::escape(summary)),
+                "STATUS:NEEDS-ACTION".to_string(),
+                "END:VTODO".to_string(),
+            ]
+            .join("\r\n");
+
+            TASK_LISTS
+                .call(
+                    &self.connection,
+                    &self.source,
+                    "CreateObjects",
+                    &(vec![ics], 0u32),
+                )
+                .await
+                .map_err(|why| why.to_string())
+        })
+    }
+
+    fn complete<'a>(&'a self, task: &'a Task) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let objects = TASK_LISTS
+                .query(
+                    &self.connection,
+                    &self.source,
+                    "GetObjectList",
+                    &format!("(uid? \"{}\")", task.id.replace('"', "\\\"")),
+                )
+                .await
+                .map_err(|why| why.to_string())?;
+            let ics = objects.first().ok_or("the task no longer exists")?;
+
+            let now = Timestamp::now().strftime("%Y%m%dT%H%M%SZ").to_string();
+            let mut lines = eds::unfold(ics)
+                .lines()
+                .filter(|line| {
+                    !eds::property(line).is_some_and(|(name, ..)| COMPLETION.contains(&name))
+                })
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+            let end = lines
+                .iter()
+                .rposition(|line| line == "END:VTODO")
+                .ok_or("the task is not a VTODO")?;
+            lines.splice(
+                end..end,
+                [
+                    "STATUS:COMPLETED".to_string(),
+                    "PERCENT-COMPLETE:100".to_string(),
+                    format!("COMPLETED:{now}"),
+                ],
+            );
+
+            TASK_LISTS
+                .call(
+                    &self.connection,
+                    &self.source,
+                    "ModifyObjects",
+                    &(vec![lines.join("\r\n")], "this", 0u32),
+                )
+                .await
+                .map_err(|why| why.to_string())
+        })
+    }
+
+    fn delete<'a>(&'a self, task: &'a Task) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            TASK_LISTS
+                .call(
+                    &self.connection,
+                    &self.source,
+                    "RemoveObjects",
+                    &(vec![(task.id.as_str(), "")], "this", 0u32),
+                )
+                .await
+                .map_err(|why| why.to_string())
+        })
+    }
+}
@@ -0,0 +1,275 @@
+//! Tasks for queries starting with `todo `, from a todo.txt file and from the task lists of
+//! Evolution Data Server, which include the CalDAV lists of GNOME Tasks and Evolution.
+//!
+//! Open tasks containing the words after the prefix are listed, after a result adding
+//! them as a new task to each list. Activating a task completes it, and its context menu
+//! can also delete it. Each kind of list is a [`TaskStore`].
+
+mod evolution;
+mod todotxt;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use self::evolution::Evolution;
+use self::todotxt::TodoTxt;
+use super::cache::Cache;
+use super::{Action, Item, Provider};
+use crate::{fl, matcher};
+
+const PREFIX: &str = "todo ";
+
+/// How long listed tasks are reused before the lists are read again, unless a task was
+/// changed from the launcher since.
+const CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// Where tasks are kept.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TodoConfig {
+    pub enabled: bool,
+    /// A todo.txt file. A leading `~` is the home directory.
+    pub todo_txt: Option<String>,
+    /// List the task lists of Evolution Data Server.
+    pub evolution: bool,
+}
+
+impl Default for TodoConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            todo_txt: Some("~/todo.txt".into()),
+            evolution: true,
+        }
+    }
+}
+
+/// An open task of a list.
+#[derive(Debug, Clone)]
+pub struct Task {
+    /// What the store identifies the task by.
+    pub id: String,
+    pub summary: String,
+    /// Shown next to the list's name, such as the task's priority or due date.
+    pub details: Option<String>,
+}
+
+/// A list of tasks.
+pub trait TaskStore: Send + Sync {
+    /// Name of the list, shown below its tasks.
+    fn name(&self) -> &str;
+
+    /// The open tasks of the list.
+    fn tasks(&self) -> BoxFuture<'_, Result<Vec<Task>, String>>;
+
+    fn add<'a>(&'a self, summary: &'a str) -> BoxFuture<'a, Result<(), String>>;
+
+    fn complete<'a>(&'a self, task: &'a Task) -> BoxFuture<'a, Result<(), String>>;
+
+    fn delete<'a>(&'a self, task: &'a Task) -> BoxFuture<'a, Result<(), String>>;
+}
+
+/// A result of a query, indexing the stores it was listed from.
+#[derive(Debug, Clone)]
+enum Entry {
+    Add { store: usize, summary: String },
+    Task { store: usize, task: Task },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Operation {
+    Complete,
+    Delete,
+}
+
+impl Operation {
+    const ALL: [Self; 2] = [Self::Complete, Self::Delete];
+
+    fn name(self) -> String {
+        match self {
+            Self::Complete => fl!("task-complete"),
+            Self::Delete => fl!("task-delete"),
+        }
+    }
+}
+
+/// Every store, and the open tasks of each by the index of its store.
+#[derive(Default)]
+struct Lists {
+    stores: Vec<Arc<dyn TaskStore>>,
+    tasks: Vec<(usize, Task)>,
+}
+
+pub struct Todo {
+    todo_txt: Option<Arc<dyn TaskStore>>,
+    evolution: bool,
+    cache: Cache<Lists>,
+}
+
+impl Todo {
+    pub fn new(config: &TodoConfig) -> Self {
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let todo_txt = config
+            .todo_txt
+            .as_deref()
+            .and_then(|path| match path.strip_prefix('~') {
+                Some(rest) => Some(home.as_ref()?.join(rest.trim_start_matches('/'))),
+                None => Some(PathBuf::from(path)),
+            })
+            .map(|path| Arc::new(TodoTxt::new(path)) as Arc<dyn TaskStore>);
+
+        Self {
+            todo_txt,
+            evolution: config.evolution,
+            cache: Cache::new(CACHE_TTL),
+        }
+    }
+
+    async fn lists(&self) -> Arc<Lists> {
+        let todo_txt = self.todo_txt.clone();
+        let evolution = self.evolution;
+        self.cache
+            .get(async move {
+                let mut lists = Lists::default();
+                lists.stores.extend(todo_txt);
+                if evolution {
+                    lists.stores.extend(Evolution::lists().await);
+                }
+
+                for (index, store) in lists.stores.iter().enumerate() {
+                    match store.tasks().await {
+                        Ok(tasks) => lists
+                            .tasks
+                            .extend(tasks.into_iter().map(|task| (index, task))),
+                        Err(why) => {
+                            tracing::debug!("failed to read tasks of {}: {why}", store.name());
+                        }
+                    }
+                }
+                lists
+            })
+            .await
+    }
+
+    async fn matching(&self, query: &str) -> (Arc<Lists>, Vec<Entry>) {
+        let Some(text) = query.trim_start().strip_prefix(PREFIX) else {
+            return Default::default();
+        };
+        let text = text.trim();
+        let words = matcher::words(text);
+
+        let lists = self.lists().await;
+        let mut entries = Vec::new();
+        if !text.is_empty() {
+            entries.extend((0..lists.stores.len()).map(|store| Entry::Add {
+                store,
+                summary: text.to_string(),
+            }));
+        }
+        entries.extend(
+            lists
+                .tasks
+                .iter()
+                .filter(|(_, task)| matcher::matches_all(&words, &task.summary))
+                .map(|(store, task)| Entry::Task {
+                    store: *store,
+                    task: task.clone(),
+                }),
+        );
+        entries.truncate(MAX_RESULTS);
+        (lists, entries)
+    }
+
+    async fn run(&self, query: &str, index: usize, option: usize) -> Option<Action> {
+        let (lists, entries) = self.matching(query).await;
+        let entry = entries.into_iter().nth(index)?;
+
+        let (store, result) = match entry {
+            Entry::Add { store, summary } if option == 0 => {
+                let store = lists.stores.get(store)?;
+                (store, store.add(&summary).await)
+            }
+            Entry::Add { .. } => return None,
+            Entry::Task { store, task } => {
+                let store = lists.stores.get(store)?;
+                let result = match Operation::ALL.get(option)? {
+                    Operation::Complete => store.complete(&task).await,
+                    Operation::Delete => store.delete(&task).await,
+                };
+                (store, result)
+            }
+        };
+
+        if let Err(why) = result {
+            tracing::warn!("failed to change the tasks of {}: {why}", store.name());
+        }
+        self.cache.clear().await;
+        None
+    }
+}
+
+impl Provider for Todo {
+    fn name(&self) -> &str {
+        "todo"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            let (lists, entries) = self.matching(&query).await;
+            entries
+                .into_iter()
+                .map(|entry| match entry {
+                    Entry::Add { store, summary } => Item {
+                        name: fl!("task-add", summary = summary),
+                        description: fl!("task-add-to", list = lists.stores[store].name()),
+                        icon: Some("list-add-symbolic".into()),
+                        open: None,
+                    },
+                    Entry::Task { store, task } => Item {
+                        name: task.summary,
+                        description: match task.details {
+                            Some(details) => format!("{} · {details}", lists.stores[store].name()),
+                            None => lists.stores[store].name().to_string(),
+                        },
+                        icon: Some("checkbox-symbolic".into()),
+                        open: None,
+                    },
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move { self.run(&query, index, 0).await })
+    }
+
+    fn context(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Vec<String>> {
+        Box::pin(async move {
+            match self.matching(&query).await.1.get(index) {
+                Some(Entry::Task { .. }) => Operation::ALL
+                    .iter()
+                    .map(|operation| operation.name())
+                    .collect(),
+                _ => Vec::new(),
+            }
+        })
+    }
+
+    fn activate_context(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+        option: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move { self.run(&query, index, option).await })
+    }
+}
@@ -0,0 +1,140 @@
+//! Tasks kept one per line in a todo.txt file, as described at
+//! <https://github.com/todotxt/todo.txt>.
+//!
+//! Completed lines start with `x`. Open lines may start with a priority such as `(A)` and a
+//! creation date. Tasks are identified by their line, so a line changed by another program
+//! since it was listed is left alone.
+
+use std::path::PathBuf;
+
+use futures::future::BoxFuture;
+use jiff::Zoned;
+
+use super::{Task, TaskStore};
+
+pub struct TodoTxt {
+    path: PathBuf,
+    name: String,
+}
+
+impl TodoTxt {
+    pub fn new(path: PathBuf) -> Self {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "todo.txt".into());
+        Self { path, name }
+    }
+
+    async fn read(&self) -> Result<String, String> {
+        match tokio::fs::read_to_string(&self.path).await {
+            Ok(text) => Ok(text),
+            Err(why) if why.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+            Err(why) => Err(why.to_string()),
+        }
+    }
+
+    /// Replaces the first line equal to the task's with `line`, or removes it if there is
+    /// none.
+    async fn replace(&self, task: &Task, line: Option<String>) -> Result<(), String> {
+        let text = self.read().await?;
+        let mut lines = text.lines().map(str::to_string).collect::<Vec<_>>();
+        let index = lines
+            .iter()
+            .position(|existing| *existing == task.id)
+            .ok_or("the task was changed since it was listed")?;
+
+        match line {
+            Some(line) => lines[index] = line,
+            None => {
+                lines.remove(index);
+            }
+        }
+
+        let mut text = lines.join("\n");
+        text.push('\n');
+        tokio::fs::write(&self.path, text)
+            .await
+            .map_err(|why| why.to_string())
+    }
+}
+
+/// Splits an open task's line into its priority and its text, without its creation date.
+fn parse(line: &str) -> (Option<char>, &str) {
+    let mut rest = line.trim();
+    let mut priority = None;
+
+    let mut chars = rest.chars();
+    if let (Some('('), Some(letter @ 'A'..='Z'), Some(')'), Some(' ')) =
+        (chars.next(), chars.next(), chars.next(), chars.next())
+    {
+        priority = Some(letter);
+        rest = rest[4..].trim_start();
+    }
+
+    if let Some((date, text)) = rest.split_once(' ') {
+        if jiff::civil::Date::strptime("%Y-%m-%d", date).is_ok() {
+            rest = text.trim_start();
+        }
+    }
+
+    (priority, rest)
+}
+
+impl TaskStore for TodoTxt {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn tasks(&self) -> BoxFuture<'_, Result<Vec<Task>, String>> {
+        Box::pin(async move {
+            let text = self.read().await?;
+            Ok(text
+                .lines()
+                .filter(|line| !line.trim().is_empty() && !line.starts_with("x "))
+                .map(|line| {
+                    let (priority, summary) = parse(line);
+                    Task {
+                        id: line.to_string(),
+                        summary: summary.to_string(),
+                        details: priority.map(|priority| format!("({priority})")),
+                    }
+                })
+                .collect())
+        })
+    }
+
+    fn add<'a>(&'a self, summary: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let mut text = self.read().await?;
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push_str(&format!(
+                "{} {}\n",
+                Zoned::now().strftime("%Y-%m-%d"),
+                summary.replace('\n', " ")
+            ));
+            tokio::fs::write(&self.path, text)
+                .await
+                .map_err(|why| why.to_string())
+        })
+    }
+
+    fn complete<'a>(&'a self, task: &'a Task) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            // The priority of a completed task is kept as a tag, as todo.sh does.
+            let (priority, _) = parse(&task.id);
+            let mut line = task.id.trim().to_string();
+            if let Some(priority) = priority {
+                line = format!("{} pri:{priority}", line[4..].trim_start());
+            }
+            let line = format!("x {} {line}", Zoned::now().strftime("%Y-%m-%d"));
+            self.replace(task, Some(line)).await
+        })
+    }
+
+    fn delete<'a>(&'a self, task: &'a Task) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move { self.replace(task, None).await })
+    }
+}
@@ -0,0 +1,489 @@
+//! Quick settings matched by keywords, such as `power saver` or `dnd`: the profiles of
+//! power-profiles-daemon, night light of the COSMIC settings daemon, do not disturb of
+//! COSMIC's notifications, and keeping the screen awake. The active choice is marked with a
+//! checkmark, and activating one switches to it.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use cosmic::cosmic_config::{self, ConfigGet, ConfigSet};
+use futures::future::BoxFuture;
+use tokio::sync::OnceCell;
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+
+use super::{Action, Item, Provider};
+use crate::fl;
+
+/// Shortest query which lists toggles, so they do not show up for every other search.
+const MIN_QUERY: usize = 3;
+
+/// Names power-profiles-daemon is reachable by, the first since version 0.20.
+const POWER_PROFILES: [(&str, &str, &str); 2] = [
+    (
+        "org.freedesktop.UPower.PowerProfiles",
+        "/org/freedesktop/UPower/PowerProfiles",
+        "org.freedesktop.UPower.PowerProfiles",
+    ),
+    (
+        "net.hadess.PowerProfiles",
+        "/net/hadess/PowerProfiles",
+        "net.hadess.PowerProfiles",
+    ),
+];
+
+/// Profiles in the order they are listed.
+const PROFILES: [Profile; 3] = [
+    Profile {
+        id: "performance",
+        icon: "power-profile-performance-symbolic",
+        keywords: &["performance", "fast"],
+    },
+    Profile {
+        id: "balanced",
+        icon: "power-profile-balanced-symbolic",
+        keywords: &["balanced"],
+    },
+    Profile {
+        id: "power-saver",
+        icon: "power-profile-power-saver-symbolic",
+        keywords: &["saver", "battery", "saving"],
+    },
+];
+
+const SETTINGS_DAEMON: &str = "com.system76.CosmicSettingsDaemon";
+const SETTINGS_DAEMON_PATH: &str = "/com/system76/CosmicSettingsDaemon";
+
+/// Configuration of cosmic-notifications, which its panel applet toggles do not disturb in.
+const NOTIFICATIONS_CONFIG: &str = "com.system76.CosmicNotifications";
+const NOTIFICATIONS_CONFIG_VERSION: u64 = 1;
+
+const PORTAL_BUS: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+
+/// What the portal's inhibitor prevents: the session going idle, which locks the screen.
+const INHIBIT_IDLE: u32 = 8;
+
+const NOTIFICATIONS: &str = "com.system76.CosmicNotifications";
+const NOTIFICATIONS_PATH: &str = "/com/system76/CosmicNotifications";
+
+#[derive(Debug)]
+struct Profile {
+    /// The name power-profiles-daemon knows the profile by.
+    id: &'static str,
+    icon: &'static str,
+    /// Words finding the profile besides `power` and `profile`.
+    keywords: &'static [&'static str],
+}
+
+impl Profile {
+    fn name(&self) -> String {
+        match self.id {
+            "performance" => fl!("power-performance"),
+            "balanced" => fl!("power-balanced"),
+            _ => fl!("power-saver"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Toggle {
+    PowerProfile {
+        profile: &'static Profile,
+        active: bool,
+    },
+    NightLight {
+        enabled: bool,
+    },
+    DoNotDisturb {
+        enabled: bool,
+    },
+    /// Keeps the screen from locking or turning off while the launcher runs.
+    KeepAwake {
+        enabled: bool,
+    },
+    /// Dismisses every notification, of which there are `count`.
+    ClearNotifications {
+        count: u32,
+    },
+}
+
+impl Toggle {
+    fn keywords(&self) -> Vec<&'static str> {
+        match self {
+            Self::PowerProfile { profile, .. } => {
+                let mut keywords = vec!["power", "profile", "mode"];
+                keywords.extend(profile.keywords);
+                keywords
+            }
+            Self::NightLight { .. } => vec!["night", "light", "blue", "warm"],
+            Self::DoNotDisturb { .. } => {
+                vec![
+                    "dnd",
+                    "do",
+                    "not",
+                    "disturb",
+                    "notifications",
+                    "mute",
+                    "quiet",
+                ]
+            }
+            Self::KeepAwake { .. } => vec![
+                "keep",
+                "screen",
+                "awake",
+                "caffeine",
+                "inhibit",
+                "idle",
+                "sleep",
+                "presentation",
+            ],
+            Self::ClearNotifications { .. } => vec!["notifications", "clear", "dismiss"],
+        }
+    }
+
+    fn item(&self) -> Item {
+        match self {
+            Self::PowerProfile { profile, active } => Item {
+                name: profile.name(),
+                description: if *active {
+                    fl!("power-profile-active")
+                } else {
+                    fl!("power-profile")
+                },
+                icon: Some(profile.icon.into()),
+                open: None,
+            },
+            Self::NightLight { enabled } => Item {
+                name: fl!("night-light"),
+                description: if *enabled {
+                    fl!("toggle-on")
+                } else {
+                    fl!("toggle-off")
+                },
+                icon: Some("night-light-symbolic".into()),
+                open: None,
+            },
+            Self::DoNotDisturb { enabled } => Item {
+                name: fl!("do-not-disturb"),
+                description: if *enabled {
+                    fl!("toggle-on")
+                } else {
+                    fl!("toggle-off")
+                },
+                icon: Some(
+                    if *enabled {
+                        "notification-disabled-symbolic"
+                    } else {
+                        "notification-symbolic"
+                    }
+                    .into(),
+                ),
+                open: None,
+            },
+            Self::KeepAwake { enabled } => Item {
+                name: fl!("keep-awake"),
+                description: if *enabled {
+                    fl!("toggle-on")
+                } else {
+                    fl!("toggle-off")
+                },
+                icon: Some("preferences-desktop-screensaver-symbolic".into()),
+                open: None,
+            },
+            Self::ClearNotifications { count } => Item {
+                name: fl!("clear-notifications"),
+                description: fl!("notification-count", count = *count),
+                icon: Some("edit-clear-all-symbolic".into()),
+                open: None,
+            },
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Toggles {
+    system: OnceCell<zbus::Connection>,
+    session: OnceCell<zbus::Connection>,
+    /// The portal's request holding the screen awake, released by closing it. The portal
+    /// also releases it when the session connection closes.
+    inhibitor: Mutex<Option<OwnedObjectPath>>,
+}
+
+impl Toggles {
+    async fn matching(&self, query: &str) -> Vec<Toggle> {
+        let query = query.trim().to_lowercase();
+        if query.chars().count() < MIN_QUERY {
+            return Vec::new();
+        }
+        let words = query.split_whitespace().collect::<Vec<_>>();
+        let matches = |toggle: &Toggle| {
+            let keywords = toggle.keywords();
+            words
+                .iter()
+                .all(|word| keywords.iter().any(|keyword| keyword.starts_with(word)))
+        };
+
+        let mut toggles = self.power_profiles().await;
+        toggles.extend(self.night_light().await);
+        toggles.extend(do_not_disturb());
+        toggles.push(Toggle::KeepAwake {
+            enabled: self
+                .inhibitor
+                .lock()
+                .is_ok_and(|inhibitor| inhibitor.is_some()),
+        });
+        toggles.extend(self.clear_notifications().await);
+        toggles.into_iter().filter(matches).collect()
+    }
+
+    async fn system(&self) -> zbus::Result<&zbus::Connection> {
+        self.system.get_or_try_init(zbus::Connection::system).await
+    }
+
+    async fn session(&self) -> zbus::Result<&zbus::Connection> {
+        self.session
+            .get_or_try_init(zbus::Connection::session)
+            .await
+    }
+
+    /// The profiles the daemon offers on this machine, or none if it is not running.
+    async fn power_profiles(&self) -> Vec<Toggle> {
+        let Ok(bus) = self.system().await else {
+            return Vec::new();
+        };
+
+        for (destination, path, interface) in POWER_PROFILES {
+            let Ok(active) =
+                property::<String>(bus, destination, path, interface, "ActiveProfile").await
+            else {
+                continue;
+            };
+            let available: Vec<HashMap<String, OwnedValue>> =
+                property(bus, destination, path, interface, "Profiles")
+                    .await
+                    .unwrap_or_default();
+            let available = available
+                .iter()
+                .filter_map(|profile| <&str>::try_from(&**profile.get("Profile")?).ok())
+                .collect::<Vec<_>>();
+
+            return PROFILES
+                .iter()
+                .filter(|profile| available.is_empty() || available.contains(&profile.id))
+                .map(|profile| Toggle::PowerProfile {
+                    profile,
+                    active: profile.id == active,
+                })
+                .collect();
+        }
+
+        Vec::new()
+    }
+
+    /// Night light, if the settings daemon provides it.
+    async fn night_light(&self) -> Option<Toggle> {
+        let bus = self.session().await.ok()?;
+        let enabled = property::<bool>(
+            bus,
+            SETTINGS_DAEMON,
+            SETTINGS_DAEMON_PATH,
+            SETTINGS_DAEMON,
+            "NightLight",
+        )
+        .await
+        .ok()?;
+        Some(Toggle::NightLight { enabled })
+    }
+
+    /// Clearing notifications, if the notifications daemon reports how many there are.
+    async fn clear_notifications(&self) -> Option<Toggle> {
+        let bus = self.session().await.ok()?;
+        let count = property::<u32>(
+            bus,
+            NOTIFICATIONS,
+            NOTIFICATIONS_PATH,
+            NOTIFICATIONS,
+            "Count",
+        )
+        .await
+        .ok()?;
+        Some(Toggle::ClearNotifications { count })
+    }
+
+    async fn switch(&self, toggle: &Toggle) -> zbus::Result<()> {
+        match toggle {
+            Toggle::PowerProfile { profile, .. } => {
+                let bus = self.system().await?;
+                let mut result = Ok(());
+                for (destination, path, interface) in POWER_PROFILES {
+                    result = set_property(
+                        bus,
+                        destination,
+                        path,
+                        interface,
+                        "ActiveProfile",
+                        Value::from(profile.id),
+                    )
+                    .await;
+                    if result.is_ok() {
+                        break;
+                    }
+                }
+                result
+            }
+            Toggle::NightLight { enabled } => {
+                let bus = self.session().await?;
+                set_property(
+                    bus,
+                    SETTINGS_DAEMON,
+                    SETTINGS_DAEMON_PATH,
+                    SETTINGS_DAEMON,
+                    "NightLight",
+                    Value::from(!enabled),
+                )
+                .await
+            }
+            Toggle::DoNotDisturb { enabled } => {
+                let result =
+                    cosmic_config::Config::new(NOTIFICATIONS_CONFIG, NOTIFICATIONS_CONFIG_VERSION)
+                        .and_then(|config| config.set("do_not_disturb", !enabled));
+                if let Err(why) = result {
+                    tracing::warn!("failed to toggle do not disturb: {why}");
+                }
+                Ok(())
+            }
+            Toggle::KeepAwake { .. } => {
+                let bus = self.session().await?;
+                let inhibitor = self
+                    .inhibitor
+                    .lock()
+                    .ok()
+                    .and_then(|mut inhibitor| inhibitor.take());
+                if let Some(inhibitor) = inhibitor {
+                    bus.call_method(
+                        Some(PORTAL_BUS),
+                        &inhibitor,
+                        Some("org.freedesktop.portal.Request"),
+                        "Close",
+                        &(),
+                    )
+                    .await?;
+                    return Ok(());
+                }
+
+                let mut options = HashMap::<&str, Value>::new();
+                options.insert("reason", fl!("keep-awake").into());
+                let inhibitor: OwnedObjectPath = bus
+                    .call_method(
+                        Some(PORTAL_BUS),
+                        PORTAL_PATH,
+                        Some("org.freedesktop.portal.Inhibit"),
+                        "Inhibit",
+                        &("", INHIBIT_IDLE, options),
+                    )
+                    .await?
+                    .body()
+                    .deserialize()?;
+                if let Ok(mut held) = self.inhibitor.lock() {
+                    *held = Some(inhibitor);
+                }
+                Ok(())
+            }
+            Toggle::ClearNotifications { .. } => {
+                let bus = self.session().await?;
+                bus.call_method(
+                    Some(NOTIFICATIONS),
+                    NOTIFICATIONS_PATH,
+                    Some(NOTIFICATIONS),
+                    "ClearAll",
+                    &(),
+                )
+                .await?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Do not disturb, if cosmic-notifications is configured.
+fn do_not_disturb() -> Option<Toggle> {
+    let config =
+        cosmic_config::Config::new(NOTIFICATIONS_CONFIG, NOTIFICATIONS_CONFIG_VERSION).ok()?;
+    let enabled = config.get::<bool>("do_not_disturb").unwrap_or_default();
+    Some(Toggle::DoNotDisturb { enabled })
+}
+
+async fn property<T>(
+    bus: &zbus::Connection,
+    destination: &str,
+    path: &str,
+    interface: &str,
+    name: &str,
+) -> zbus::Result<T>
+where
+    T: TryFrom<OwnedValue>,
+    T::Error: Into<zbus::zvariant::Error>,
+{
+    let value: OwnedValue = bus
+        .call_method(
+            Some(destination),
+            path,
+            Some("org.freedesktop.DBus.Properties"),
+            "Get",
+            &(interface, name),
+        )
+        .await?
+        .body()
+        .deserialize()?;
+
+    T::try_from(value).map_err(|why| zbus::Error::Variant(why.into()))
+}
+
+async fn set_property(
+    bus: &zbus::Connection,
+    destination: &str,
+    path: &str,
+    interface: &str,
+    name: &str,
+    value: Value<'_>,
+) -> zbus::Result<()> {
+    bus.call_method(
+        Some(destination),
+        path,
+        Some("org.freedesktop.DBus.Properties"),
+        "Set",
+        &(interface, name, value),
+    )
+    .await?;
+    Ok(())
+}
+
+impl Provider for Toggles {
+    fn name(&self) -> &str {
+        "toggles"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .iter()
+                .map(Toggle::item)
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let toggle = self.matching(&query).await.into_iter().nth(index)?;
+            if let Err(why) = self.switch(&toggle).await {
+                tracing::warn!("failed to switch {}: {why}", toggle.item().name);
+            }
+            None
+        })
+    }
+}
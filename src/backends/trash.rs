@@ -0,0 +1,159 @@
+//! The trash of the home directory, as laid out by the XDG trash specification, for queries
+//! such as `trash` or `empty bin`: opening it in the file manager and emptying it.
+//!
+//! The number and size of the trashed files are counted in the background for each query.
+//! Emptying is destructive, so the result is named to need confirmation, see
+//! `destructive_actions` in the config.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+
+use super::{size, Action, Item, Provider};
+use crate::fl;
+
+/// Characters a query needs before the trash is matched.
+const MIN_QUERY: usize = 3;
+
+const KEYWORDS: [&str; 5] = ["trash", "bin", "recycle", "deleted", "empty"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Entry {
+    Open,
+    Empty { items: usize, bytes: u64 },
+}
+
+pub struct Trash {
+    /// `$XDG_DATA_HOME/Trash`.
+    dir: Option<PathBuf>,
+}
+
+impl Default for Trash {
+    fn default() -> Self {
+        Self {
+            dir: xdg::BaseDirectories::new()
+                .ok()
+                .map(|dirs| dirs.get_data_home().join("Trash")),
+        }
+    }
+}
+
+impl Trash {
+    async fn matching(&self, query: &str) -> Vec<Entry> {
+        let query = query.trim().to_lowercase();
+        let Some(dir) = self.dir.clone() else {
+            return Vec::new();
+        };
+        if query.chars().count() < MIN_QUERY
+            || !query
+                .split_whitespace()
+                .all(|word| KEYWORDS.iter().any(|keyword| keyword.starts_with(word)))
+        {
+            return Vec::new();
+        }
+
+        let mut entries = vec![Entry::Open];
+        let counted = tokio::task::spawn_blocking(move || count(&dir.join("files"))).await;
+        if let Ok((items, bytes)) = counted {
+            if items > 0 {
+                entries.push(Entry::Empty { items, bytes });
+            }
+        }
+        entries
+    }
+}
+
+/// The number of trashed files and directories, and their size on disk.
+fn count(files: &Path) -> (usize, u64) {
+    let Ok(entries) = std::fs::read_dir(files) else {
+        return (0, 0);
+    };
+    entries.flatten().fold((0, 0), |(items, bytes), entry| {
+        (items + 1, bytes + disk_usage(&entry.path()))
+    })
+}
+
+fn disk_usage(path: &Path) -> u64 {
+    let Ok(metadata) = path.symlink_metadata() else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| disk_usage(&entry.path()))
+                .sum()
+        })
+        .unwrap_or_default()
+}
+
+/// Deletes every trashed file along with its trash info, and the cached directory sizes.
+fn empty(dir: &Path) {
+    for subdir in ["files", "info"] {
+        let Ok(entries) = std::fs::read_dir(dir.join(subdir)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let removed = match entry.file_type() {
+                Ok(kind) if kind.is_dir() => std::fs::remove_dir_all(&path),
+                _ => std::fs::remove_file(&path),
+            };
+            if let Err(why) = removed {
+                tracing::warn!("failed to delete {} from the trash: {why}", path.display());
+            }
+        }
+    }
+
+    let _res = std::fs::remove_file(dir.join("directorysizes"));
+}
+
+impl Provider for Trash {
+    fn name(&self) -> &str {
+        "trash"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|entry| match entry {
+                    Entry::Open => Item {
+                        name: fl!("trash-open"),
+                        description: fl!("trash-open-description"),
+                        icon: Some("user-trash-symbolic".into()),
+                        open: None,
+                    },
+                    Entry::Empty { items, bytes } => Item {
+                        name: fl!("trash-empty", items = items, size = size(bytes)),
+                        description: fl!("trash-empty-description"),
+                        icon: Some("user-trash-full-symbolic".into()),
+                        open: None,
+                    },
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            match self.matching(&query).await.into_iter().nth(index)? {
+                Entry::Open => Some(Action::Open("trash:///".into())),
+                Entry::Empty { .. } => {
+                    let dir = self.dir.clone()?;
+                    let _res = tokio::task::spawn_blocking(move || empty(&dir)).await;
+                    None
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,192 @@
+//! Characters found by their Unicode name for queries starting with `char `, such as
+//! `char check mark` or `char checkmark`, or by their code point, such as `u+2713`.
+//!
+//! Characters pasted after `char ` are looked up the other way, listing their names.
+//! Activating a character copies it, and its context menu can copy its code point instead.
+//! Names come from the compact table bundled by `unicode_names2`, indexed on first use.
+
+use std::sync::{Arc, OnceLock};
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+
+use super::{Action, Item, Provider};
+use crate::fl;
+
+const PREFIX: &str = "char ";
+const CODE_POINT_PREFIX: &str = "u+";
+
+/// Prefixes of names which only number the characters of large scripts, so searching them
+/// by name finds nothing useful.
+const NUMBERED: [&str; 5] = [
+    "CJK UNIFIED IDEOGRAPH-",
+    "CJK COMPATIBILITY IDEOGRAPH-",
+    "TANGUT IDEOGRAPH-",
+    "KHITAN SMALL SCRIPT CHARACTER-",
+    "NUSHU CHARACTER-",
+];
+
+#[derive(Default)]
+pub struct Unicode {
+    /// Every named character, with its name in lowercase without spaces or hyphens.
+    index: OnceLock<Vec<(char, String)>>,
+}
+
+impl Unicode {
+    fn index(&self) -> &[(char, String)] {
+        self.index.get_or_init(|| {
+            (0..=char::MAX as u32)
+                .filter_map(char::from_u32)
+                .filter_map(|c| Some((c, unicode_names2::name(c)?.to_string())))
+                .filter(|(_, name)| !NUMBERED.iter().any(|prefix| name.starts_with(prefix)))
+                .map(|(c, name)| (c, squash(&name)))
+                .collect()
+        })
+    }
+
+    fn matching(&self, query: &str) -> Vec<char> {
+        let query = query.trim_start();
+        if let Some(c) = code_point(query) {
+            return vec![c];
+        }
+        let Some(text) = query.strip_prefix(PREFIX).map(str::trim) else {
+            return Vec::new();
+        };
+        if let Some(c) = code_point(text) {
+            return vec![c];
+        }
+
+        // Anything which cannot be part of a name is a character to look up.
+        if !text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '-')
+        {
+            let mut chars = Vec::new();
+            for c in text.chars().filter(|c| !c.is_whitespace()) {
+                if !chars.contains(&c) && chars.len() < MAX_RESULTS {
+                    chars.push(c);
+                }
+            }
+            return chars;
+        }
+
+        let words = text.split_whitespace().map(squash).collect::<Vec<_>>();
+        if words.concat().len() < 2 {
+            return Vec::new();
+        }
+
+        let mut found = self
+            .index()
+            .iter()
+            .filter(|(_, name)| words.iter().all(|word| name.contains(word.as_str())))
+            .collect::<Vec<_>>();
+        // The shortest names are the closest matches, such as CHECK MARK for `check`.
+        found.sort_by_key(|(_, name)| name.len());
+        found
+            .into_iter()
+            .take(MAX_RESULTS)
+            .map(|(c, _)| *c)
+            .collect()
+    }
+}
+
+/// A name or query word in lowercase without spaces or hyphens, so that `checkmark` finds
+/// CHECK MARK.
+fn squash(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != ' ' && *c != '-')
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// The character of a code point such as `U+2713`.
+fn code_point(text: &str) -> Option<char> {
+    let prefix = text.get(..CODE_POINT_PREFIX.len())?;
+    if !prefix.eq_ignore_ascii_case(CODE_POINT_PREFIX) {
+        return None;
+    }
+    let hex = text[CODE_POINT_PREFIX.len()..].trim();
+    char::from_u32(u32::from_str_radix(hex, 16).ok()?)
+}
+
+fn name(c: char) -> String {
+    unicode_names2::name(c).map_or_else(|| fl!("unicode-unnamed"), |name| name.to_string())
+}
+
+/// The character as it is shown, with combining marks placed on a dotted circle.
+fn glyph(c: char, name: &str) -> String {
+    if name.starts_with("COMBINING ") {
+        format!("◌{c}")
+    } else {
+        c.to_string()
+    }
+}
+
+fn code(c: char) -> String {
+    format!("U+{:04X}", u32::from(c))
+}
+
+impl Provider for Unicode {
+    fn name(&self) -> &str {
+        "unicode"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            let chars = tokio::task::spawn_blocking(move || self.matching(&query))
+                .await
+                .unwrap_or_default();
+            chars
+                .into_iter()
+                .map(|c| {
+                    let name = name(c);
+                    Item {
+                        name: format!("{}  {name}", glyph(c, &name)),
+                        description: code(c),
+                        icon: Some("accessories-character-map-symbolic".into()),
+                        open: None,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let c = self.matching(&query).into_iter().nth(index)?;
+            Some(Action::Copy(c.to_string()))
+        })
+    }
+
+    fn context(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Vec<String>> {
+        Box::pin(async move {
+            match self.matching(&query).into_iter().nth(index) {
+                Some(c) => vec![
+                    fl!("unicode-copy-character"),
+                    fl!("unicode-copy-code", code = code(c)),
+                ],
+                None => Vec::new(),
+            }
+        })
+    }
+
+    fn activate_context(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+        option: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let c = self.matching(&query).into_iter().nth(index)?;
+            match option {
+                0 => Some(Action::Copy(c.to_string())),
+                1 => Some(Action::Copy(code(c))),
+                _ => None,
+            }
+        })
+    }
+}
@@ -0,0 +1,241 @@
+//! Virtual machines defined in libvirt, such as those of GNOME Boxes and virt-manager, for
+//! queries starting with `vm `.
+//!
+//! Machines of the user's session and of the system are listed with `virsh`. Activating a
+//! stopped machine starts it and opens its display, in virt-manager, virt-viewer, or GNOME
+//! Boxes, whichever is installed. Activating a running one opens its display.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+
+use super::{Action, Item, Provider};
+use crate::doctor::find_in_path;
+use crate::{fl, matcher};
+
+const PREFIX: &str = "vm ";
+
+/// Connections searched for machines, GNOME Boxes using the first.
+const CONNECTIONS: [&str; 2] = ["qemu:///session", "qemu:///system"];
+
+/// Time `virsh` has to list the machines of a connection.
+const LIST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Time `virsh` has to start a machine or ask it to shut down.
+const CHANGE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+struct Machine {
+    connection: &'static str,
+    name: String,
+    /// As reported by `virsh`, such as `running` or `shut off`.
+    state: String,
+}
+
+impl Machine {
+    fn running(&self) -> bool {
+        matches!(
+            self.state.as_str(),
+            "running" | "paused" | "idle" | "in shutdown"
+        )
+    }
+
+    /// What the machine's context menu offers, with the default action first.
+    fn operations(&self) -> &'static [Operation] {
+        if self.running() {
+            &[Operation::OpenViewer, Operation::ShutDown]
+        } else {
+            &[Operation::Start, Operation::OpenViewer]
+        }
+    }
+
+    fn viewer(&self) -> Option<Vec<String>> {
+        let args: &[&str] = if find_in_path("virt-manager").is_some() {
+            &[
+                "virt-manager",
+                "--connect",
+                self.connection,
+                "--show-domain-console",
+            ]
+        } else if find_in_path("virt-viewer").is_some() {
+            &["virt-viewer", "--connect", self.connection, "--wait"]
+        } else if find_in_path("gnome-boxes").is_some() {
+            // Boxes opens on its list of machines.
+            return Some(vec!["gnome-boxes".into()]);
+        } else {
+            return None;
+        };
+
+        let mut args = args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        args.push(self.name.clone());
+        Some(args)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Operation {
+    Start,
+    ShutDown,
+    OpenViewer,
+}
+
+impl Operation {
+    fn name(self) -> String {
+        match self {
+            Self::Start => fl!("vm-start"),
+            Self::ShutDown => fl!("vm-shut-down"),
+            Self::OpenViewer => fl!("vm-open-viewer"),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct VirtualMachines;
+
+impl VirtualMachines {
+    async fn matching(&self, query: &str) -> Vec<Machine> {
+        let Some(text) = query.trim_start().strip_prefix(PREFIX) else {
+            return Vec::new();
+        };
+        let words = matcher::words(text);
+
+        futures::future::join_all(CONNECTIONS.map(machines))
+            .await
+            .into_iter()
+            .flatten()
+            .filter(|machine| matcher::matches_all(&words, &machine.name))
+            .take(MAX_RESULTS)
+            .collect()
+    }
+
+    async fn run(&self, query: &str, index: usize, option: usize) -> Option<Action> {
+        let machine = self.matching(query).await.into_iter().nth(index)?;
+        let command = match machine.operations().get(option)? {
+            Operation::OpenViewer => return machine.viewer().map(Action::Run),
+            Operation::Start => "start",
+            Operation::ShutDown => "shutdown",
+        };
+
+        let args = ["--connect", machine.connection, command, &machine.name];
+        match virsh(&args, CHANGE_TIMEOUT).await {
+            // A machine which was just started is shown right away.
+            Ok(_) if command == "start" => machine.viewer().map(Action::Run),
+            Ok(_) => None,
+            Err(why) => {
+                tracing::warn!("failed to {command} {}: {why}", machine.name);
+                None
+            }
+        }
+    }
+}
+
+/// Runs `virsh`, returning what it printed.
+async fn virsh(args: &[&str], timeout: Duration) -> Result<String, String> {
+    let output = tokio::process::Command::new("virsh")
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .output();
+
+    match tokio::time::timeout(timeout, output).await {
+        Ok(Ok(output)) if output.status.success() => {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        }
+        Ok(Ok(output)) => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+        Ok(Err(why)) => Err(why.to_string()),
+        Err(_) => Err(format!("no answer within {timeout:?}")),
+    }
+}
+
+/// The machines of a connection, or none if it cannot be reached.
+async fn machines(connection: &'static str) -> Vec<Machine> {
+    let listing = match virsh(&["--connect", connection, "list", "--all"], LIST_TIMEOUT).await {
+        Ok(listing) => listing,
+        Err(why) => {
+            tracing::debug!("failed to list the machines of {connection}: {why}");
+            return Vec::new();
+        }
+    };
+
+    // A table of `Id Name State` under a line of dashes, in which stopped machines have
+    // `-` as their ID.
+    listing
+        .lines()
+        .skip_while(|line| !line.starts_with("---"))
+        .skip(1)
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let _id = columns.next()?;
+            let name = columns.next()?.to_string();
+            let state = columns.collect::<Vec<_>>().join(" ");
+            Some(Machine {
+                connection,
+                name,
+                state,
+            })
+        })
+        .collect()
+}
+
+impl Provider for VirtualMachines {
+    fn name(&self) -> &str {
+        "virtual-machines"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|machine| {
+                    let scope = match machine.connection {
+                        "qemu:///system" => fl!("vm-system"),
+                        _ => fl!("vm-session"),
+                    };
+                    Item {
+                        description: format!("{} · {scope}", machine.state),
+                        name: machine.name,
+                        icon: Some("computer".into()),
+                        open: None,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move { self.run(&query, index, 0).await })
+    }
+
+    fn context(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Vec<String>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .nth(index)
+                .map(|machine| {
+                    machine
+                        .operations()
+                        .iter()
+                        .map(|operation| operation.name())
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    fn activate_context(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+        option: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move { self.run(&query, index, option).await })
+    }
+}
@@ -0,0 +1,369 @@
+//! Wallpapers in the configured directories, for queries starting with `wall `.
+//!
+//! Activating one makes it the wallpaper of every display, and its context menu can set it
+//! on a single display instead. Wallpapers are set in the configuration of cosmic-bg, which
+//! applies it as soon as it changes. Its entries are read and written through cosmic-config,
+//! as the types of cosmic-bg-config mirrored below.
+//!
+//! Thumbnails come from the shared thumbnail cache when a file manager made them, and the
+//! image itself is shown otherwise.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use cosmic::cosmic_config::{self, ConfigGet, ConfigSet};
+use cosmic_launcher_core::MAX_RESULTS;
+use futures::future::BoxFuture;
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+
+use super::{Action, Item, Provider};
+use crate::{fl, matcher};
+
+const PREFIX: &str = "wall ";
+
+const EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "webp", "jxl", "avif"];
+
+/// Levels of subdirectories searched for wallpapers.
+const MAX_DEPTH: usize = 2;
+
+const BACKGROUND_CONFIG: &str = "com.system76.CosmicBackground";
+const BACKGROUND_CONFIG_VERSION: u64 = 1;
+
+/// Key of the entry shared by every display without one of its own.
+const ALL: &str = "all";
+
+/// Where wallpapers are searched.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WallpapersConfig {
+    pub enabled: bool,
+    /// Directories searched with their subdirectories. A leading `~` is the home directory.
+    pub directories: Vec<String>,
+}
+
+impl Default for WallpapersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            directories: vec![
+                "~/Pictures/Wallpapers".into(),
+                "/usr/share/backgrounds".into(),
+            ],
+        }
+    }
+}
+
+/// The wallpaper of a display, or of all of them, as cosmic-bg-config defines it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    output: String,
+    source: Source,
+    #[serde(default)]
+    filter_by_theme: bool,
+    #[serde(default = "rotation_frequency")]
+    rotation_frequency: u64,
+    #[serde(default)]
+    filter_method: FilterMethod,
+    #[serde(default)]
+    scaling_mode: ScalingMode,
+    #[serde(default)]
+    sampling_method: SamplingMethod,
+}
+
+impl Entry {
+    /// The wallpaper cosmic-bg starts from when nothing was configured yet.
+    fn all() -> Self {
+        Self {
+            output: ALL.into(),
+            source: Source::Path(PathBuf::new()),
+            filter_by_theme: false,
+            rotation_frequency: rotation_frequency(),
+            filter_method: FilterMethod::default(),
+            scaling_mode: ScalingMode::default(),
+            sampling_method: SamplingMethod::default(),
+        }
+    }
+}
+
+fn rotation_frequency() -> u64 {
+    300
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum Source {
+    Path(PathBuf),
+    Color(Color),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum Color {
+    Single([f32; 3]),
+    Gradient(Gradient),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Gradient {
+    colors: Vec<[f32; 3]>,
+    radius: f32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+enum FilterMethod {
+    Nearest,
+    Linear,
+    #[default]
+    Lanczos,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+enum ScalingMode {
+    Fit([f32; 3]),
+    Stretch,
+    #[default]
+    Zoom,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+enum SamplingMethod {
+    #[default]
+    Alphanumeric,
+    Random,
+}
+
+#[derive(Debug, Clone)]
+struct Wallpaper {
+    path: PathBuf,
+    current: bool,
+}
+
+pub struct Wallpapers {
+    directories: Vec<PathBuf>,
+}
+
+impl Wallpapers {
+    pub fn new(config: &WallpapersConfig) -> Self {
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let expand = |path: &str| match path.strip_prefix('~') {
+            Some(rest) => Some(home.as_ref()?.join(rest.trim_start_matches('/'))),
+            None => Some(PathBuf::from(path)),
+        };
+
+        Self {
+            directories: config
+                .directories
+                .iter()
+                .filter_map(|dir| expand(dir))
+                .collect(),
+        }
+    }
+
+    async fn matching(&self, query: &str) -> Vec<Wallpaper> {
+        let Some(text) = query.trim_start().strip_prefix(PREFIX) else {
+            return Vec::new();
+        };
+        let words = matcher::words(text);
+
+        let directories = self.directories.clone();
+        tokio::task::spawn_blocking(move || {
+            let current = background()
+                .and_then(|config| config.get::<Entry>(ALL).ok())
+                .and_then(|entry| match entry.source {
+                    Source::Path(path) => Some(path),
+                    Source::Color(_) => None,
+                });
+
+            let mut paths = Vec::new();
+            for dir in &directories {
+                find_images(dir, 0, &mut paths);
+            }
+            paths.sort();
+            paths.dedup();
+
+            paths
+                .into_iter()
+                .filter(|path| matcher::matches_all(&words, &path.to_string_lossy()))
+                .take(MAX_RESULTS)
+                .map(|path| Wallpaper {
+                    current: current.as_ref() == Some(&path),
+                    path,
+                })
+                .collect()
+        })
+        .await
+        .unwrap_or_default()
+    }
+}
+
+/// Collects the images in `dir` and its subdirectories.
+fn find_images(dir: &Path, depth: usize, paths: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth < MAX_DEPTH {
+                find_images(&path, depth + 1, paths);
+            }
+        } else if path.extension().is_some_and(|ext| {
+            let ext = ext.to_string_lossy().to_lowercase();
+            EXTENSIONS.contains(&ext.as_str())
+        }) {
+            paths.push(path);
+        }
+    }
+}
+
+/// The configuration of cosmic-bg, opened on a blocking thread since it reads the disk.
+fn background() -> Option<cosmic_config::Config> {
+    cosmic_config::Config::new(BACKGROUND_CONFIG, BACKGROUND_CONFIG_VERSION)
+        .map_err(|why| tracing::warn!("failed to open the cosmic-bg config: {why}"))
+        .ok()
+}
+
+/// The displays cosmic-bg knows about.
+fn outputs() -> Vec<String> {
+    background()
+        .and_then(|config| config.get::<Vec<String>>("outputs").ok())
+        .unwrap_or_default()
+}
+
+/// Sets the wallpaper of `output`, or of every display.
+fn set(path: PathBuf, output: Option<String>) -> Result<(), cosmic_config::Error> {
+    let config = cosmic_config::Config::new(BACKGROUND_CONFIG, BACKGROUND_CONFIG_VERSION)?;
+    let mut all = config.get::<Entry>(ALL).unwrap_or_else(|_| Entry::all());
+    let source = Source::Path(path);
+
+    let Some(output) = output else {
+        all.source = source;
+        config.set(ALL, all)?;
+        return config.set("same-on-all", true);
+    };
+
+    // Every other display keeps the wallpaper it has, which is the shared one unless it
+    // was given its own.
+    for other in outputs() {
+        let key = format!("output.{other}");
+        if other != output && config.get::<Entry>(&key).is_err() {
+            config.set(
+                &key,
+                Entry {
+                    output: other,
+                    ..all.clone()
+                },
+            )?;
+        }
+    }
+    let key = format!("output.{output}");
+    let mut entry = config.get::<Entry>(&key).unwrap_or(Entry { output, ..all });
+    entry.source = source;
+    config.set(&key, entry)?;
+    config.set("same-on-all", false)
+}
+
+/// A thumbnail of the image made by a file manager, as the thumbnail specification names
+/// them after the MD5 of the image's URI.
+fn thumbnail(path: &Path) -> Option<PathBuf> {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            uri.push(char::from(byte));
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    let name = format!("{:x}.png", Md5::digest(uri.as_bytes()));
+
+    let cache = xdg::BaseDirectories::new().ok()?.get_cache_home();
+    ["normal", "large"]
+        .into_iter()
+        .map(|size| cache.join("thumbnails").join(size).join(&name))
+        .find(|thumbnail| thumbnail.is_file())
+}
+
+impl Provider for Wallpapers {
+    fn name(&self) -> &str {
+        "wallpapers"
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            self.matching(&query)
+                .await
+                .into_iter()
+                .map(|wallpaper| {
+                    let dir = wallpaper
+                        .path
+                        .parent()
+                        .map(|dir| dir.display().to_string())
+                        .unwrap_or_default();
+                    let icon = thumbnail(&wallpaper.path).unwrap_or_else(|| wallpaper.path.clone());
+                    Item {
+                        name: wallpaper
+                            .path
+                            .file_stem()
+                            .map(|name| name.to_string_lossy().into_owned())
+                            .unwrap_or_default(),
+                        description: if wallpaper.current {
+                            fl!("wallpaper-current", dir = dir)
+                        } else {
+                            dir
+                        },
+                        icon: Some(icon.display().to_string()),
+                        open: None,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move { self.activate_context(query, index, 0).await })
+    }
+
+    fn context(self: Arc<Self>, query: String, index: usize) -> BoxFuture<'static, Vec<String>> {
+        Box::pin(async move {
+            if self.matching(&query).await.len() <= index {
+                return Vec::new();
+            }
+            let outputs = tokio::task::spawn_blocking(outputs)
+                .await
+                .unwrap_or_default();
+            let mut options = vec![fl!("wallpaper-set-on-all")];
+            options.extend(
+                outputs
+                    .into_iter()
+                    .map(|output| fl!("wallpaper-set-on", output = output)),
+            );
+            options
+        })
+    }
+
+    fn activate_context(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+        option: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let wallpaper = self.matching(&query).await.into_iter().nth(index)?;
+            let result = tokio::task::spawn_blocking(move || {
+                let output = match option {
+                    0 => None,
+                    n => Some(outputs().into_iter().nth(n - 1)?),
+                };
+                Some(set(wallpaper.path, output))
+            })
+            .await;
+            if let Ok(Some(Err(why))) = result {
+                tracing::warn!("failed to set the wallpaper: {why}");
+            }
+            None
+        })
+    }
+}
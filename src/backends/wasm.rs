@@ -0,0 +1,181 @@
+//! Providers compiled to WebAssembly and run in a sandbox without any imports.
+//!
+//! A plugin is a `.wasm` module exporting:
+//!
+//! - `memory`
+//! - `alloc(len: i32) -> i32`, returning a buffer the host writes arguments into
+//! - `query(ptr: i32, len: i32) -> i64`, taking the UTF-8 query and returning a JSON array
+//!   of `{ "name", "description", "icon" }` objects
+//! - `activate(index: i32) -> i64`, taking an index into the last query's results and
+//!   returning a URI for the launcher to open, or an empty string
+//!
+//! Returned strings are packed as `ptr << 32 | len`.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use futures::future::BoxFuture;
+use wasmtime::{Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use super::{Action, Item, Provider};
+
+/// Instructions a plugin may execute per call before it is interrupted.
+const FUEL: u64 = 100_000_000;
+
+/// Largest linear memory a plugin may grow to.
+const MEMORY_LIMIT: usize = 64 << 20;
+
+pub struct Plugin {
+    name: String,
+    instance: Mutex<(Store<StoreLimits>, Instance)>,
+}
+
+/// Loads every `.wasm` module in `dir`, skipping those which fail to instantiate.
+pub fn discover(dir: &Path) -> Vec<Arc<dyn Provider>> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut config = wasmtime::Config::new();
+    config.consume_fuel(true);
+    let engine = match Engine::new(&config) {
+        Ok(engine) => engine,
+        Err(why) => {
+            tracing::error!("failed to start the wasm runtime: {why}");
+            return Vec::new();
+        }
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+        .filter_map(|path| match Plugin::load(&engine, &path) {
+            Ok(plugin) => Some(Arc::new(plugin) as Arc<dyn Provider>),
+            Err(why) => {
+                tracing::error!("failed to load plugin {}: {why}", path.display());
+                None
+            }
+        })
+        .collect()
+}
+
+impl Plugin {
+    fn load(engine: &Engine, path: &Path) -> wasmtime::Result<Self> {
+        let module = Module::from_file(engine, path)?;
+        let limits = StoreLimitsBuilder::new().memory_size(MEMORY_LIMIT).build();
+        let mut store = Store::new(engine, limits);
+        store.limiter(|limits| limits);
+
+        // No host functions are linked, so plugins cannot reach the filesystem or network.
+        let instance = Linker::new(engine).instantiate(&mut store, &module)?;
+
+        Ok(Self {
+            name: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            instance: Mutex::new((store, instance)),
+        })
+    }
+
+    fn call_query(&self, query: &str) -> wasmtime::Result<Vec<Item>> {
+        let mut guard = self
+            .instance
+            .lock()
+            .map_err(|_| wasmtime::Error::msg("plugin poisoned"))?;
+        let (store, instance) = &mut *guard;
+        store.set_fuel(FUEL)?;
+
+        let memory = memory(store, instance)?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut *store, "alloc")?;
+        let len = i32::try_from(query.len())?;
+        let ptr = alloc.call(&mut *store, len)?;
+        memory.write(&mut *store, usize::try_from(ptr)?, query.as_bytes())?;
+
+        let query = instance.get_typed_func::<(i32, i32), i64>(&mut *store, "query")?;
+        let packed = query.call(&mut *store, (ptr, len))?;
+        let json = read(store, &memory, packed)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    fn call_activate(&self, index: usize) -> wasmtime::Result<String> {
+        let mut guard = self
+            .instance
+            .lock()
+            .map_err(|_| wasmtime::Error::msg("plugin poisoned"))?;
+        let (store, instance) = &mut *guard;
+        store.set_fuel(FUEL)?;
+
+        let memory = memory(store, instance)?;
+        let activate = instance.get_typed_func::<i32, i64>(&mut *store, "activate")?;
+        let packed = activate.call(&mut *store, i32::try_from(index)?)?;
+        read(store, &memory, packed)
+    }
+}
+
+fn memory(store: &mut Store<StoreLimits>, instance: &Instance) -> wasmtime::Result<Memory> {
+    instance
+        .get_memory(store, "memory")
+        .ok_or_else(|| wasmtime::Error::msg("plugin does not export its memory"))
+}
+
+/// Reads a string returned by the plugin as `ptr << 32 | len`.
+#[allow(clippy::cast_sign_loss)]
+fn read(store: &Store<StoreLimits>, memory: &Memory, packed: i64) -> wasmtime::Result<String> {
+    let ptr = (packed as u64 >> 32) as usize;
+    let len = (packed as u64 & u64::from(u32::MAX)) as usize;
+    let bytes = memory
+        .data(store)
+        .get(ptr..ptr + len)
+        .ok_or_else(|| wasmtime::Error::msg("plugin returned a string out of bounds"))?;
+    Ok(String::from_utf8_lossy(bytes).into_owned())
+}
+
+impl Provider for Plugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            let result = tokio::task::spawn_blocking(move || {
+                self.call_query(&query)
+                    .map_err(|why| (self.name.clone(), why))
+            })
+            .await;
+
+            match result {
+                Ok(Ok(items)) => items,
+                Ok(Err((name, why))) => {
+                    tracing::warn!("plugin {name} failed to answer a query: {why}");
+                    Vec::new()
+                }
+                Err(_) => Vec::new(),
+            }
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        _query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let result = tokio::task::spawn_blocking(move || {
+                self.call_activate(index)
+                    .map_err(|why| (self.name.clone(), why))
+            })
+            .await;
+
+            match result {
+                Ok(Ok(uri)) if !uri.is_empty() => Some(Action::Open(uri)),
+                Ok(Err((name, why))) => {
+                    tracing::warn!("plugin {name} failed to activate a result: {why}");
+                    None
+                }
+                _ => None,
+            }
+        })
+    }
+}
@@ -0,0 +1,288 @@
+//! Current conditions and a short forecast for queries starting with `weather`.
+//!
+//! Places are looked up and forecasts fetched from Open-Meteo compatible endpoints, but
+//! only once the query is activated, so that the partial place names typed on the way are
+//! never sent. Until then the query shows a single result offering to fetch the forecast.
+//! Forecasts are cached per place, and shown right away while they are fresh.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use super::{Action, Item, Provider};
+use crate::fl;
+
+/// How long a forecast is reused before it is fetched again.
+const CACHE_TTL: Duration = Duration::from_secs(15 * 60);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Days summarized after the current conditions.
+const FORECAST_DAYS: usize = 3;
+
+const KEYWORD: &str = "weather";
+
+/// Where forecasts come from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeatherConfig {
+    pub enabled: bool,
+    /// Open-Meteo compatible forecast endpoint.
+    pub forecast_url: String,
+    /// Open-Meteo compatible geocoding endpoint, used to find a place's coordinates.
+    pub geocoding_url: String,
+    /// Place used when the query names none.
+    pub location: String,
+    /// Page opened on activation, in which `{latitude}`, `{longitude}`, and `{name}` are
+    /// substituted.
+    pub forecast_page: String,
+    pub fahrenheit: bool,
+}
+
+impl Default for WeatherConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            forecast_url: "https://api.open-meteo.com/v1/forecast".into(),
+            geocoding_url: "https://geocoding-api.open-meteo.com/v1/search".into(),
+            location: String::new(),
+            forecast_page: "https://www.yr.no/en/forecast/daily-table/{latitude},{longitude}"
+                .into(),
+            fahrenheit: false,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Places {
+    #[serde(default)]
+    results: Vec<Place>,
+}
+
+#[derive(Deserialize)]
+struct Place {
+    name: String,
+    #[serde(default)]
+    country: String,
+    latitude: f64,
+    longitude: f64,
+}
+
+#[derive(Deserialize)]
+struct Forecast {
+    current: Current,
+    daily: Daily,
+}
+
+#[derive(Deserialize)]
+struct Current {
+    temperature_2m: f64,
+    weather_code: u8,
+}
+
+#[derive(Deserialize)]
+struct Daily {
+    time: Vec<String>,
+    weather_code: Vec<u8>,
+    temperature_2m_max: Vec<f64>,
+    temperature_2m_min: Vec<f64>,
+}
+
+pub struct Weather {
+    config: WeatherConfig,
+    cache: Mutex<HashMap<String, (Instant, Vec<Item>)>>,
+}
+
+impl Weather {
+    pub fn new(config: WeatherConfig) -> Self {
+        Self {
+            config,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The place a query asks about, or `None` if it is not a weather query.
+    fn place<'a>(&'a self, query: &'a str) -> Option<&'a str> {
+        let rest = query.trim().strip_prefix(KEYWORD)?;
+        if !rest.is_empty() && !rest.starts_with(' ') {
+            return None;
+        }
+
+        let place = match rest.trim() {
+            "" => self.config.location.trim(),
+            place => place,
+        };
+        Some(place).filter(|place| !place.is_empty())
+    }
+
+    fn cached(&self, place: &str) -> Option<Vec<Item>> {
+        let cache = self.cache.lock().ok()?;
+        cache
+            .get(&place.to_lowercase())
+            .filter(|(at, _)| at.elapsed() < CACHE_TTL)
+            .map(|(_, items)| items.clone())
+    }
+
+    fn fetch(&self, place: &str) -> Result<Vec<Item>, String> {
+        let places: Places = get(ureq::get(&self.config.geocoding_url)
+            .query("name", place)
+            .query("count", "1"))?;
+        let Some(place) = places.results.into_iter().next() else {
+            return Ok(Vec::new());
+        };
+
+        let mut request = ureq::get(&self.config.forecast_url)
+            .query("latitude", &place.latitude.to_string())
+            .query("longitude", &place.longitude.to_string())
+            .query("current", "temperature_2m,weather_code")
+            .query(
+                "daily",
+                "weather_code,temperature_2m_max,temperature_2m_min",
+            )
+            .query("forecast_days", &FORECAST_DAYS.to_string())
+            .query("timezone", "auto");
+        if self.config.fahrenheit {
+            request = request.query("temperature_unit", "fahrenheit");
+        }
+        let forecast: Forecast = get(request)?;
+
+        let open = self
+            .config
+            .forecast_page
+            .replace("{latitude}", &place.latitude.to_string())
+            .replace("{longitude}", &place.longitude.to_string())
+            .replace("{name}", &place.name);
+        let location = if place.country.is_empty() {
+            place.name
+        } else {
+            format!("{}, {}", place.name, place.country)
+        };
+
+        let current = Item {
+            name: format!(
+                "{:.0}° {}",
+                forecast.current.temperature_2m,
+                condition(forecast.current.weather_code).0
+            ),
+            description: location,
+            icon: Some(condition(forecast.current.weather_code).1.into()),
+            open: Some(open.clone()),
+        };
+
+        let daily = &forecast.daily;
+        let days = daily
+            .time
+            .iter()
+            .zip(&daily.weather_code)
+            .zip(
+                daily
+                    .temperature_2m_min
+                    .iter()
+                    .zip(&daily.temperature_2m_max),
+            )
+            .enumerate()
+            .take(FORECAST_DAYS)
+            .map(|(i, ((date, code), (min, max)))| {
+                let day = match i {
+                    0 => fl!("weather-today"),
+                    1 => fl!("weather-tomorrow"),
+                    _ => date.clone(),
+                };
+                let (summary, icon) = condition(*code);
+                Item {
+                    name: fl!("weather-day", day = day, summary = summary),
+                    description: fl!(
+                        "weather-range",
+                        min = format!("{min:.0}"),
+                        max = format!("{max:.0}")
+                    ),
+                    icon: Some(icon.into()),
+                    open: Some(open.clone()),
+                }
+            });
+
+        Ok(std::iter::once(current).chain(days).collect())
+    }
+}
+
+fn get<T: serde::de::DeserializeOwned>(request: ureq::Request) -> Result<T, String> {
+    let body = request
+        .timeout(REQUEST_TIMEOUT)
+        .call()
+        .map_err(|why| why.to_string())?
+        .into_string()
+        .map_err(|why| why.to_string())?;
+    serde_json::from_str(&body).map_err(|why| why.to_string())
+}
+
+/// Summary and icon name of a WMO weather code.
+fn condition(code: u8) -> (String, &'static str) {
+    match code {
+        0 => (fl!("weather-clear"), "weather-clear"),
+        1 | 2 => (fl!("weather-partly-cloudy"), "weather-few-clouds"),
+        3 => (fl!("weather-overcast"), "weather-overcast"),
+        45 | 48 => (fl!("weather-fog"), "weather-fog"),
+        51..=57 => (fl!("weather-drizzle"), "weather-showers-scattered"),
+        61..=67 | 80..=82 => (fl!("weather-rain"), "weather-showers"),
+        71..=77 | 85 | 86 => (fl!("weather-snow"), "weather-snow"),
+        95..=99 => (fl!("weather-thunderstorm"), "weather-storm"),
+        _ => (fl!("weather-unknown"), "weather-severe-alert"),
+    }
+}
+
+impl Provider for Weather {
+    fn name(&self) -> &str {
+        KEYWORD
+    }
+
+    fn query(self: Arc<Self>, query: String) -> BoxFuture<'static, Vec<Item>> {
+        Box::pin(async move {
+            let Some(place) = self.place(&query).map(str::to_owned) else {
+                return Vec::new();
+            };
+
+            self.cached(&place).unwrap_or_else(|| {
+                vec![Item {
+                    name: fl!("weather-fetch", place = place),
+                    description: fl!("weather-fetch-hint"),
+                    icon: Some("weather-few-clouds".into()),
+                    open: None,
+                }]
+            })
+        })
+    }
+
+    fn activate(
+        self: Arc<Self>,
+        query: String,
+        index: usize,
+    ) -> BoxFuture<'static, Option<Action>> {
+        Box::pin(async move {
+            let place = self.place(&query)?.to_owned();
+            if let Some(items) = self.cached(&place) {
+                return items.into_iter().nth(index)?.open.map(Action::Open);
+            }
+
+            let this = Arc::clone(&self);
+            let fetched =
+                tokio::task::spawn_blocking(move || this.fetch(&place).map(|items| (place, items)))
+                    .await
+                    .ok()?;
+            match fetched {
+                Ok((place, items)) => {
+                    if let Ok(mut cache) = self.cache.lock() {
+                        cache.insert(place.to_lowercase(), (Instant::now(), items));
+                    }
+                    Some(Action::Refresh)
+                }
+                Err(why) => {
+                    tracing::warn!("failed to fetch the weather: {why}");
+                    None
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,115 @@
+//! Native messaging host started by the browser extension in `data/browser-extension`.
+//!
+//! It keeps the tabs the extension reports and serves them to the launcher over a Unix
+//! socket, passing activations back to the extension. The host exits, removing its
+//! socket, when the browser closes its standard input.
+
+#[allow(dead_code)]
+#[path = "../backends/tabs/protocol.rs"]
+mod protocol;
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::{Arc, Mutex};
+
+use protocol::{FromExtension, Request, Tab, ToExtension};
+
+fn main() {
+    let Some(dir) = protocol::socket_dir() else {
+        eprintln!("no runtime directory to place the socket in");
+        std::process::exit(1);
+    };
+    if let Err(why) = std::fs::create_dir_all(&dir) {
+        eprintln!("failed to create {}: {why}", dir.display());
+        std::process::exit(1);
+    }
+
+    let path = dir.join(format!("{}.sock", std::process::id()));
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(why) => {
+            eprintln!("failed to listen on {}: {why}", path.display());
+            std::process::exit(1);
+        }
+    };
+
+    let tabs = Arc::new(Mutex::new(Vec::new()));
+    let stdout = Arc::new(Mutex::new(io::stdout()));
+
+    let served = Arc::clone(&tabs);
+    std::thread::spawn(move || {
+        for stream in listener.incoming().filter_map(Result::ok) {
+            let tabs = Arc::clone(&served);
+            let stdout = Arc::clone(&stdout);
+            std::thread::spawn(move || {
+                if let Err(why) = serve(stream, &tabs, &stdout) {
+                    eprintln!("failed to answer the launcher: {why}");
+                }
+            });
+        }
+    });
+
+    let mut stdin = io::stdin().lock();
+    loop {
+        match read_message(&mut stdin) {
+            Ok(Some(FromExtension::Tabs { tabs: reported })) => {
+                if let Ok(mut tabs) = tabs.lock() {
+                    *tabs = reported;
+                }
+            }
+            Ok(None) => break,
+            Err(why) => {
+                eprintln!("failed to read a message from the extension: {why}");
+                break;
+            }
+        }
+    }
+
+    let _res = std::fs::remove_file(&path);
+}
+
+/// Reads a message prefixed with its length in native byte order, or `None` once the
+/// browser closed the pipe.
+fn read_message(input: &mut impl Read) -> io::Result<Option<FromExtension>> {
+    let mut len = [0; 4];
+    match input.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(why) if why.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(why) => return Err(why),
+    }
+
+    let mut message = vec![0; u32::from_ne_bytes(len) as usize];
+    input.read_exact(&mut message)?;
+    serde_json::from_slice(&message)
+        .map(Some)
+        .map_err(io::Error::from)
+}
+
+fn write_message(output: &mut impl Write, message: &ToExtension) -> io::Result<()> {
+    let message = serde_json::to_vec(message)?;
+    let len = u32::try_from(message.len()).map_err(io::Error::other)?;
+    output.write_all(&len.to_ne_bytes())?;
+    output.write_all(&message)?;
+    output.flush()
+}
+
+fn serve(stream: UnixStream, tabs: &Mutex<Vec<Tab>>, stdout: &Mutex<io::Stdout>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        match serde_json::from_str(&line?)? {
+            Request::List => {
+                let tabs = tabs.lock().map(|tabs| tabs.clone()).unwrap_or_default();
+                serde_json::to_writer(&mut writer, &tabs)?;
+                writer.write_all(b"\n")?;
+            }
+            Request::Activate { tab_id, window_id } => {
+                let mut stdout = stdout
+                    .lock()
+                    .map_err(|_| io::Error::other("stdout poisoned"))?;
+                write_message(&mut *stdout, &ToExtension::Activate { tab_id, window_id })?;
+            }
+        }
+    }
+
+    Ok(())
+}
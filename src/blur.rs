@@ -0,0 +1,114 @@
+//! Blur of what is behind the launcher surface, through the `ext-background-effect`
+//! protocol or, on compositors without it, the KDE blur protocol. Nothing is blurred when
+//! the compositor offers neither.
+//!
+//! The effect is attached to the `wl_surface` iced reports with the layer surface events,
+//! on a queue of our own on the connection of that surface. It is double-buffered state,
+//! applied with the next frame the launcher draws.
+
+use cosmic::cctk::wayland_client::{
+    delegate_noop,
+    globals::{registry_queue_init, GlobalListContents},
+    protocol::{
+        wl_compositor::WlCompositor, wl_region::WlRegion, wl_registry, wl_surface::WlSurface,
+    },
+    Connection, Dispatch, EventQueue, Proxy, QueueHandle,
+};
+use wayland_protocols::ext::background_effect::v1::client::{
+    ext_background_effect_manager_v1::ExtBackgroundEffectManagerV1,
+    ext_background_effect_surface_v1::ExtBackgroundEffectSurfaceV1,
+};
+use wayland_protocols_plasma::blur::client::{
+    org_kde_kwin_blur::OrgKdeKwinBlur, org_kde_kwin_blur_manager::OrgKdeKwinBlurManager,
+};
+
+struct State;
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for State {
+    fn event(
+        _state: &mut Self,
+        _proxy: &wl_registry::WlRegistry,
+        _event: wl_registry::Event,
+        _data: &GlobalListContents,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+delegate_noop!(State: ignore WlCompositor);
+delegate_noop!(State: ignore WlRegion);
+delegate_noop!(State: ignore ExtBackgroundEffectManagerV1);
+delegate_noop!(State: ignore ExtBackgroundEffectSurfaceV1);
+delegate_noop!(State: ignore OrgKdeKwinBlurManager);
+delegate_noop!(State: ignore OrgKdeKwinBlur);
+
+enum Effect {
+    Background(ExtBackgroundEffectManagerV1, ExtBackgroundEffectSurfaceV1),
+    Kde(OrgKdeKwinBlur),
+}
+
+/// The blur of one surface, removed when dropped.
+pub struct Blur {
+    surface: WlSurface,
+    effect: Effect,
+    conn: Connection,
+    _queue: EventQueue<State>,
+}
+
+impl Blur {
+    /// Blurs what is behind the whole of `surface`, or returns `None` if the compositor
+    /// cannot.
+    pub fn new(surface: &WlSurface) -> Option<Self> {
+        let conn = Connection::from_backend(surface.backend().upgrade()?);
+        let (globals, queue) = registry_queue_init::<State>(&conn).ok()?;
+        let qh = queue.handle();
+
+        let effect = if let Ok(manager) =
+            globals.bind::<ExtBackgroundEffectManagerV1, _, _>(&qh, 1..=1, ())
+        {
+            let compositor = globals.bind::<WlCompositor, _, _>(&qh, 1..=4, ()).ok()?;
+            let region = compositor.create_region(&qh, ());
+            region.add(0, 0, i32::MAX, i32::MAX);
+            let effect = manager.get_background_effect(surface, &qh, ());
+            effect.set_blur_region(Some(&region));
+            region.destroy();
+            Effect::Background(manager, effect)
+        } else {
+            let manager = globals
+                .bind::<OrgKdeKwinBlurManager, _, _>(&qh, 1..=1, ())
+                .ok()?;
+            // Without a region, the whole surface is blurred.
+            let blur = manager.create(surface, &qh, ());
+            blur.commit();
+            Effect::Kde(blur)
+        };
+        if let Err(why) = conn.flush() {
+            tracing::warn!("failed to blur the background: {why}");
+        }
+
+        Some(Self {
+            surface: surface.clone(),
+            effect,
+            conn,
+            _queue: queue,
+        })
+    }
+
+    pub fn surface(&self) -> &WlSurface {
+        &self.surface
+    }
+}
+
+impl Drop for Blur {
+    fn drop(&mut self) {
+        match &self.effect {
+            Effect::Background(manager, effect) => {
+                effect.destroy();
+                manager.destroy();
+            }
+            Effect::Kde(blur) => blur.release(),
+        }
+        let _res = self.conn.flush();
+    }
+}
@@ -0,0 +1,46 @@
+//! Calls into the running launcher over the session bus, through the
+//! `org.freedesktop.Application` interface it is activated with.
+
+use std::collections::HashMap;
+
+use zbus::zvariant::Value;
+
+use crate::commands::LauncherCommands;
+
+/// Bus name of the launcher.
+pub const APP_ID: &str = "com.system76.CosmicLauncher";
+const PATH: &str = "/com/system76/CosmicLauncher";
+const INTERFACE: &str = "org.freedesktop.Application";
+
+/// Shows the launcher, or hides it if it is open, like its keyboard shortcut.
+pub async fn toggle(connection: &zbus::Connection) -> zbus::Result<()> {
+    connection
+        .call_method(
+            Some(APP_ID),
+            PATH,
+            Some(INTERFACE),
+            "Activate",
+            &(HashMap::<&str, Value>::new(),),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Hands a command to the launcher, such as [`LauncherCommands::Search`] to show it with a
+/// query typed in.
+pub async fn send(connection: &zbus::Connection, command: &LauncherCommands) -> zbus::Result<()> {
+    connection
+        .call_method(
+            Some(APP_ID),
+            PATH,
+            Some(INTERFACE),
+            "ActivateAction",
+            &(
+                command.to_string(),
+                Vec::<Value>::new(),
+                HashMap::<&str, Value>::new(),
+            ),
+        )
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,32 @@
+//! Commands of the `cosmic-launcher` command line, which are handed to the running launcher
+//! as the actions it is activated with.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, clap::Subcommand)]
+pub enum LauncherCommands {
+    #[clap(about = "Toggle the launcher and switch to the alt-tab view")]
+    AltTab,
+    #[clap(about = "Check the launcher's dependencies and print a diagnostic report")]
+    Doctor,
+    #[clap(about = "Switch the running launcher to another configuration profile")]
+    Profile { name: Option<String> },
+    #[clap(about = "Open the launcher with a query typed in")]
+    Search { query: String },
+}
+
+impl ToString for LauncherCommands {
+    fn to_string(&self) -> String {
+        serde_json::ser::to_string(self).unwrap()
+    }
+}
+
+impl FromStr for LauncherCommands {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::de::from_str(s)
+    }
+}
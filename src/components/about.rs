@@ -0,0 +1,25 @@
+//! About view which takes the place of the results, opened from the preferences menu.
+
+use cosmic::iced::widget::Column;
+use cosmic::iced::Length;
+use cosmic::widget::{button, container, text};
+use cosmic::Element;
+
+use crate::about::About;
+use crate::fl;
+
+pub fn view<'a, Message: Clone + 'static>(about: &About, on_copy: Message) -> Element<'a, Message> {
+    let lines = about
+        .lines()
+        .into_iter()
+        .map(|line| text::body(line).into())
+        .collect::<Vec<_>>();
+    container(
+        Column::with_children(lines)
+            .push(button::text(fl!("copy-details")).on_press(on_copy))
+            .spacing(4),
+    )
+    .width(Length::Fill)
+    .padding([8, 16])
+    .into()
+}
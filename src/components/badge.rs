@@ -0,0 +1,28 @@
+//! Dot shown next to applications which already have a window open.
+
+use cosmic::iced::Length;
+use cosmic::iced_core::{Border, Color, Shadow};
+use cosmic::iced_style::container::Appearance;
+use cosmic::theme;
+use cosmic::widget::{container, horizontal_space};
+use cosmic::Element;
+
+pub fn running<'a, Message: 'static>() -> Element<'a, Message> {
+    container(horizontal_space(Length::Fixed(6.0)))
+        .width(Length::Fixed(6.0))
+        .height(Length::Fixed(6.0))
+        .style(theme::Container::custom(|theme| {
+            let cosmic = theme.cosmic();
+            Appearance {
+                background: Some(Color::from(cosmic.accent_color()).into()),
+                border: Border {
+                    radius: 3.0.into(),
+                    ..Border::default()
+                },
+                shadow: Shadow::default(),
+                text_color: None,
+                icon_color: None,
+            }
+        }))
+        .into()
+}
@@ -0,0 +1,31 @@
+//! Inline prompt which takes the place of a result row until an action is confirmed.
+
+use cosmic::iced::alignment::Vertical;
+use cosmic::iced::{Alignment, Length};
+use cosmic::iced_widget::row;
+use cosmic::widget::{button, container, horizontal_space, text};
+use cosmic::Element;
+
+use crate::fl;
+
+pub fn confirm_row<'a, Message: Clone + 'static>(
+    action: &str,
+    on_confirm: Message,
+    on_cancel: Message,
+) -> Element<'a, Message> {
+    container(
+        row![
+            text(fl!("confirm-action", action = action))
+                .size(14)
+                .vertical_alignment(Vertical::Center),
+            horizontal_space(Length::Fill),
+            button::text(fl!("cancel")).on_press(on_cancel),
+            button::destructive(fl!("confirm")).on_press(on_confirm),
+        ]
+        .spacing(8)
+        .align_items(Alignment::Center),
+    )
+    .width(Length::Fill)
+    .padding([8, 16])
+    .into()
+}
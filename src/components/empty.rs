@@ -0,0 +1,14 @@
+//! Row which takes the place of the results when a query matches nothing.
+
+use cosmic::iced::Length;
+use cosmic::widget::{container, text};
+use cosmic::Element;
+
+use crate::fl;
+
+pub fn view<'a, Message: 'static>(query: &str) -> Element<'a, Message> {
+    container(text::body(fl!("no-results", query = query.trim())))
+        .width(Length::Fill)
+        .padding([8, 16])
+        .into()
+}
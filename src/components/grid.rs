@@ -0,0 +1,82 @@
+//! Tiles of favorite and frequent applications, shown for an empty query in place of
+//! the results.
+
+use cosmic::iced::widget::{Column, Row};
+use cosmic::iced::{Alignment, Length};
+use cosmic::iced_core::alignment::Horizontal;
+use cosmic::theme::Button;
+use cosmic::widget::{button, icon, text};
+use cosmic::Element;
+use cosmic_launcher_core::grid::Grid;
+use unicode_truncate::UnicodeTruncateStr;
+use unicode_width::UnicodeWidthStr;
+
+use crate::icons::{App, IconResolver};
+
+/// Side of a tile, in logical pixels.
+const TILE_SIZE: f32 = 96.0;
+const ICON_SIZE: u16 = 48;
+/// Columns of an application's name before it is cut off.
+const NAME_WIDTH: usize = 12;
+
+/// The page of `apps` holding the focused tile, with its number if there are more.
+pub fn view<'a, Message: Clone + 'static>(
+    grid: &Grid,
+    apps: &'a [App],
+    icons: &IconResolver,
+    on_press: impl Fn(usize) -> Message,
+) -> Element<'a, Message> {
+    let range = grid.visible();
+    let tiles = apps
+        .get(range.clone())
+        .unwrap_or_default()
+        .iter()
+        .zip(range)
+        .map(|(app, i)| {
+            let name = if app.name.width() > NAME_WIDTH {
+                format!("{}…", app.name.unicode_truncate(NAME_WIDTH - 1).0)
+            } else {
+                app.name.clone()
+            };
+            let content = Column::with_children([
+                icon(icons.handle(&app.icon, ICON_SIZE))
+                    .width(Length::Fixed(ICON_SIZE.into()))
+                    .height(Length::Fixed(ICON_SIZE.into()))
+                    .into(),
+                text::caption(name)
+                    .horizontal_alignment(Horizontal::Center)
+                    .into(),
+            ])
+            .spacing(8)
+            .align_items(Alignment::Center);
+
+            button(content)
+                .style(Button::IconVertical)
+                .selected(i == grid.focused())
+                .width(Length::Fixed(TILE_SIZE))
+                .height(Length::Fixed(TILE_SIZE))
+                .on_press(on_press(i))
+                .into()
+        })
+        .collect::<Vec<Element<_>>>();
+
+    let mut rows = Vec::new();
+    let mut tiles = tiles.into_iter().peekable();
+    while tiles.peek().is_some() {
+        let row = tiles.by_ref().take(grid.columns).collect::<Vec<_>>();
+        rows.push(Row::with_children(row).spacing(8).into());
+    }
+
+    let mut column = Column::with_children(rows)
+        .spacing(8)
+        .align_items(Alignment::Center)
+        .width(Length::Fill);
+    if grid.pages() > 1 {
+        column = column.push(text::caption(format!(
+            "{} / {}",
+            grid.page() + 1,
+            grid.pages()
+        )));
+    }
+    column.into()
+}
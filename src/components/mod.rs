@@ -1 +1,9 @@
+pub mod about;
+pub mod badge;
+pub mod confirm;
+pub mod empty;
+pub mod grid;
 pub mod list;
+pub mod perf;
+pub mod scopes;
+pub mod title;
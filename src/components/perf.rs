@@ -0,0 +1,53 @@
+//! Overlay in the corner of the launcher with the latency of the last search, the time
+//! between frames, and the number of results, shown with `--perf` or Ctrl + Shift + P.
+
+use std::time::{Duration, Instant};
+
+use cosmic::iced::Length;
+use cosmic::iced_core::alignment::Horizontal;
+use cosmic::widget::{container, text};
+use cosmic::Element;
+
+/// Frames further apart than this are the first of a new redraw rather than part of one.
+const IDLE: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Default)]
+pub struct Hud {
+    last_frame: Option<Instant>,
+    frame_time: Option<Duration>,
+}
+
+impl Hud {
+    /// Records a frame drawn at `at`.
+    pub fn frame(&mut self, at: Instant) {
+        if let Some(last) = self.last_frame {
+            let elapsed = at.saturating_duration_since(last);
+            if elapsed < IDLE {
+                self.frame_time = Some(elapsed);
+            }
+        }
+        self.last_frame = Some(at);
+    }
+
+    pub fn view<'a, Message: 'static>(
+        &self,
+        search_latency: Option<Duration>,
+        results: usize,
+    ) -> Element<'a, Message> {
+        let millis = |duration: Option<Duration>| {
+            duration.map_or_else(
+                || "–".to_string(),
+                |duration| format!("{:.1} ms", duration.as_secs_f64() * 1000.0),
+            )
+        };
+        let line = format!(
+            "search {} · frame {} · {results} results",
+            millis(search_latency),
+            millis(self.frame_time),
+        );
+        container(text::caption(line))
+            .width(Length::Fill)
+            .align_x(Horizontal::Right)
+            .into()
+    }
+}
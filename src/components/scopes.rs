@@ -0,0 +1,44 @@
+//! Chips under the search field which narrow the search to a scope.
+
+use cosmic::iced::widget::Row;
+use cosmic::theme::Button;
+use cosmic::widget::{button, text};
+use cosmic::Element;
+use cosmic_launcher_core::scope::Scope;
+
+use crate::fl;
+
+/// A chip searching everything, then one per scope, with the one at `selected` highlighted.
+/// Chips are counted from 0 for everything, like [`Input::Scope`].
+///
+/// [`Input::Scope`]: cosmic_launcher_core::Input::Scope
+pub fn view<'a, Message: Clone + 'static>(
+    scopes: &'a [Scope],
+    selected: usize,
+    on_press: impl Fn(usize) -> Message,
+) -> Element<'a, Message> {
+    let names = std::iter::once(fl!("scope-all")).chain(scopes.iter().map(|s| name(&s.name)));
+    let chips = names
+        .enumerate()
+        .map(|(i, name)| {
+            button(text::body(name))
+                .style(Button::Text)
+                .selected(i == selected)
+                .padding([4, 12])
+                .on_press(on_press(i))
+                .into()
+        })
+        .collect::<Vec<Element<_>>>();
+    Row::with_children(chips).spacing(8).into()
+}
+
+/// The translated name of a default scope, or the name of one from the configuration.
+fn name(key: &str) -> String {
+    match key {
+        "apps" => fl!("scope-apps"),
+        "files" => fl!("scope-files"),
+        "windows" => fl!("scope-windows"),
+        "web" => fl!("scope-web"),
+        name => name.to_owned(),
+    }
+}
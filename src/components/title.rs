@@ -0,0 +1,80 @@
+//! Fitting window titles which are wider than a result row.
+
+use serde::{Deserialize, Serialize};
+use unicode_truncate::UnicodeTruncateStr;
+use unicode_width::UnicodeWidthStr;
+
+/// How titles wider than a row are shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TitleOverflow {
+    /// Cut the title off after the first line's width.
+    Truncate,
+    /// Wrap the title onto a second line, cutting off whatever does not fit.
+    #[default]
+    Wrap,
+    /// Scroll the title of the focused row through its line.
+    Marquee,
+}
+
+/// Gap between the end of a scrolling title and its start.
+const MARQUEE_GAP: &str = "   ";
+
+/// Whether `line` is wider than `width` columns.
+pub fn overflows(line: &str, width: usize) -> bool {
+    line.width() > width
+}
+
+/// Lays out `line` within `width` columns. `scroll` is the marquee position, given only
+/// for the focused row.
+pub fn fit(
+    line: &str,
+    width: usize,
+    overflow: TitleOverflow,
+    scroll: Option<usize>,
+) -> Vec<String> {
+    if !overflows(line, width) {
+        return vec![line.to_owned()];
+    }
+
+    match (overflow, scroll) {
+        (TitleOverflow::Wrap, _) => wrap(line, width),
+        (TitleOverflow::Marquee, Some(scroll)) => vec![marquee(line, width, scroll)],
+        (TitleOverflow::Truncate | TitleOverflow::Marquee, _) => vec![truncate(line, width)],
+    }
+}
+
+fn truncate(line: &str, width: usize) -> String {
+    format!("{}...", line.unicode_truncate(width).0)
+}
+
+/// Breaks `line` at the last space which fits on the first line, or mid-word if there is
+/// none, and truncates the rest to a second line.
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let (first, _) = line.unicode_truncate(width);
+    let first = match first.rfind(' ') {
+        Some(space) if space > 0 => &first[..space],
+        _ => first,
+    };
+
+    let rest = line[first.len()..].trim_start();
+    let second = if overflows(rest, width) {
+        truncate(rest, width)
+    } else {
+        rest.to_owned()
+    };
+
+    vec![first.to_owned(), second]
+}
+
+/// The `width` columns of `line` starting `scroll` characters in, wrapping around.
+fn marquee(line: &str, width: usize, scroll: usize) -> String {
+    let len = line.chars().count() + MARQUEE_GAP.len();
+    let looped = line
+        .chars()
+        .chain(MARQUEE_GAP.chars())
+        .cycle()
+        .skip(scroll % len)
+        .take(len)
+        .collect::<String>();
+    looped.unicode_truncate(width).0.to_owned()
+}
@@ -1,3 +1,35 @@
+use std::collections::BTreeMap;
+
+use cosmic::cosmic_config::{
+    self, cosmic_config_derive::CosmicConfigEntry, ConfigSet, CosmicConfigEntry,
+};
+use cosmic_launcher_core::kiosk::Kiosk;
+use cosmic_launcher_core::ranking::{Ranking, Weights};
+use cosmic_launcher_core::routing::Routes;
+use cosmic_launcher_core::scope::Scope;
+use cosmic_launcher_core::vi::Mode;
+use cosmic_launcher_core::visibility::Debounce;
+use cosmic_launcher_core::LOCAL_ID_BASE;
+use pop_launcher::{IconSource, SearchResult};
+use serde::{Deserialize, Serialize};
+
+use crate::backends::appimages::AppImageConfig;
+use crate::backends::contacts::ContactsConfig;
+use crate::backends::dev::DevConfig;
+use crate::backends::notes::NotesConfig;
+use crate::backends::passwords::PasswordsConfig;
+use crate::backends::remote::RemoteConfig;
+use crate::backends::repos::RepositoriesConfig;
+use crate::backends::shell_history::ShellHistoryConfig;
+use crate::backends::snippets::SnippetsConfig;
+use crate::backends::todo::TodoConfig;
+use crate::backends::wallpapers::WallpapersConfig;
+use crate::backends::weather::WeatherConfig;
+use crate::components::title::TitleOverflow;
+use crate::fallback::Fallback;
+use crate::matcher::{self, MatcherConfig};
+use crate::style::Style;
+
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub fn profile() -> &'static str {
@@ -6,3 +38,509 @@ pub fn profile() -> &'static str {
         .nth_back(3)
         .unwrap_or("unknown")
 }
+
+/// Characters which, pressed together with Ctrl, trigger an action.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub focus_next: Vec<String>,
+    pub focus_previous: Vec<String>,
+    /// Pin the focused result above the others until the launcher hides, or unpin it.
+    pub pin: Vec<String>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            focus_next: vec!["n".into(), "j".into()],
+            focus_previous: vec!["p".into(), "k".into()],
+            pin: vec!["d".into()],
+        }
+    }
+}
+
+/// A command shown as a result when its name matches the query.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomCommand {
+    pub name: String,
+    /// Command line, in which `%query%`, `%clipboard%`, and `%selection%` are substituted.
+    pub exec: String,
+    /// Directory to run the command in. A leading `~` is the home directory.
+    pub working_dir: Option<String>,
+    pub icon: Option<String>,
+}
+
+impl CustomCommand {
+    pub fn working_dir(&self) -> Option<std::path::PathBuf> {
+        let dir = self.working_dir.as_deref()?;
+        match dir.strip_prefix('~') {
+            Some(rest) => {
+                let home = std::env::var_os("HOME")?;
+                Some(std::path::PathBuf::from(home).join(rest.trim_start_matches('/')))
+            }
+            None => Some(dir.into()),
+        }
+    }
+}
+
+/// The terminal emulator which programs such as shells and `tmux` are run in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Terminal {
+    pub command: Vec<String>,
+    /// Arguments placed between the terminal's command and the program it runs.
+    pub exec_args: Vec<String>,
+}
+
+impl Default for Terminal {
+    fn default() -> Self {
+        Self {
+            command: vec!["cosmic-term".into()],
+            exec_args: vec!["-e".into()],
+        }
+    }
+}
+
+impl Terminal {
+    /// Command line opening the terminal, running `args` in it if there are any.
+    pub fn command_line(&self, args: Vec<String>) -> Vec<String> {
+        let mut line = self.command.clone();
+        if !args.is_empty() {
+            line.extend(self.exec_args.iter().cloned());
+            line.extend(args);
+        }
+        line
+    }
+}
+
+/// Geometry of the launcher on one output, in place of the one configured for all of them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputGeometry {
+    pub max_width: Option<f32>,
+    pub top_margin: Option<i32>,
+    pub anchor: Option<Anchor>,
+}
+
+/// Where on the output the launcher opens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Anchor {
+    /// Below the top edge, leaving the margin and any top panel clear.
+    #[default]
+    Top,
+    /// In the middle of the output, where the margin is left out.
+    Center,
+    /// Above the bottom edge, leaving the margin clear.
+    Bottom,
+}
+
+/// How much room each result takes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Density {
+    /// The name above the description, next to a large icon.
+    #[default]
+    Comfortable,
+    /// The name, description, and a small icon on one line, to fit more results.
+    Compact,
+}
+
+/// When the "Ctrl + N" hints of the results show.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyboardHints {
+    #[default]
+    Always,
+    /// Only while Ctrl is held, fading in.
+    WhileCtrl,
+    Never,
+}
+
+/// Which browser history is searched, kept without the `browser-history` feature so that
+/// the config reads the same.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Off by default, since it reads every page the user visited.
+    pub enabled: bool,
+    /// Prefixes of web searches, such as `ddg `, whose queries also match history.
+    pub web_prefixes: Vec<String>,
+}
+
+/// A named set of preferences, selected with `--profile <name>` or `cosmic-launcher profile <name>`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    /// Names of results ranked ahead of everything else.
+    pub favorites: Vec<String>,
+    /// Names of the pop-launcher plugins whose results are shown. Empty shows all.
+    pub plugins: Vec<String>,
+    /// Results shown at most from each of these plugins per query, by plugin name.
+    pub quotas: BTreeMap<String, usize>,
+    pub ranking: Weights,
+}
+
+impl Profile {
+    pub fn ranking(&self) -> Ranking {
+        Ranking {
+            weights: self.ranking,
+            favorites: self.favorites.clone(),
+            sources: self.plugins.clone(),
+            quotas: self.quotas.clone().into_iter().collect(),
+            ..Ranking::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, CosmicConfigEntry, PartialEq, Serialize, Deserialize)]
+#[version = 1]
+pub struct Config {
+    /// Maximum width of the launcher surface, in logical pixels.
+    pub max_width: f32,
+    /// Gap between the edge of the output the launcher is anchored to and the launcher.
+    pub top_margin: i32,
+    /// Where on the output the launcher opens.
+    pub anchor: Anchor,
+    /// Width, margin, and anchor by output name, such as `eDP-1`, for the launcher opened
+    /// on it.
+    pub outputs: BTreeMap<String, OutputGeometry>,
+    pub keybindings: KeyBindings,
+    /// Profile used when none is selected.
+    pub default_profile: Profile,
+    pub profiles: BTreeMap<String, Profile>,
+    /// Restricted mode for shared machines, also enabled by `--kiosk`.
+    pub kiosk: Kiosk,
+    /// Thresholds against panel buttons reopening the launcher, and against focus flickers
+    /// hiding it.
+    pub debounce: Debounce,
+    /// Results and context options starting with these names ask for confirmation.
+    pub destructive_actions: Vec<String>,
+    /// Extra environment variables for applications, keyed by desktop entry ID.
+    pub launch_env: BTreeMap<String, BTreeMap<String, String>>,
+    pub commands: Vec<CustomCommand>,
+    /// How the names of custom commands are matched.
+    pub matcher: MatcherConfig,
+    /// Query prefixes rewritten before searching, such as `"g "` to `"google "`.
+    pub routes: Routes,
+    /// Keep windows ahead of applications while searching, not only for an empty query.
+    pub windows_first: bool,
+    /// How window titles too long for their row are shown.
+    pub title_overflow: TitleOverflow,
+    pub density: Density,
+    /// Blur what is behind the launcher, where the compositor supports it.
+    pub blur: bool,
+    /// Show a grid of the favorite and most used applications for an empty query, in
+    /// place of the results.
+    pub app_grid: bool,
+    pub keyboard_hints: KeyboardHints,
+    /// Activate the result in row 1 to 9 by typing its number into the empty search
+    /// field, such as right after opening the launcher to switch windows.
+    pub number_keys: bool,
+    /// Navigate the results with vi keys after pressing Esc, opening in this mode each
+    /// time. Without it, Esc hides the launcher.
+    pub vi_mode: Option<Mode>,
+    /// Chips under the search field, after the one searching everything, which narrow the
+    /// search to one kind of result. Ctrl + Left and Ctrl + Right move between them.
+    pub scopes: Vec<Scope>,
+    /// Keep the picked scope when the launcher hides, rather than search everything again.
+    pub remember_scope: bool,
+    /// Focus the result under the pointer, so that Enter activates the highlighted row.
+    pub focus_on_hover: bool,
+    /// Raise the window focused while cycling through alt-tab, so that releasing Alt only
+    /// keeps it.
+    pub alt_tab_preview: bool,
+    /// Results listed below the others for any query, in this order, such as a web search.
+    pub fallbacks: Vec<Fallback>,
+    /// Colors, corner radii, paddings, and font sizes in place of those of the theme.
+    pub style: Style,
+    pub terminal: Terminal,
+    /// Time in milliseconds each script provider has to answer, by file name.
+    pub script_timeouts: BTreeMap<String, u64>,
+    /// Characters a query needs before a provider is run, by provider name, unless the
+    /// query starts with a prefix of the provider.
+    pub min_query_length: BTreeMap<String, usize>,
+    /// List upcoming calendar events for `cal `, `today`, and `tomorrow`.
+    pub calendar: bool,
+    /// Search contacts for queries starting with `@`.
+    pub contacts: ContactsConfig,
+    /// Search browser history for queries starting with `h `. Off unless enabled.
+    pub history: HistoryConfig,
+    /// Find AppImages by name.
+    pub appimages: AppImageConfig,
+    /// Find installed Steam and Lutris games, also for `game `.
+    pub games: bool,
+    /// Offer recently opened projects of VS Code, VSCodium, and JetBrains IDEs.
+    pub projects: bool,
+    /// Find git repositories for queries starting with `repo `.
+    pub repositories: RepositoriesConfig,
+    /// List tmux sessions for `tmux `, and toolbox and distrobox containers for `box `.
+    pub sessions: bool,
+    /// List Docker containers and images for `docker `, and Podman's for `pod `.
+    pub containers: bool,
+    /// Open a terminal in the development environments set here, for `dev `.
+    pub dev: DevConfig,
+    /// Connect saved Wi-Fi networks for `wifi ` and VPNs for `vpn `.
+    pub network: bool,
+    /// Connect paired Bluetooth devices for `bt `.
+    pub bluetooth: bool,
+    /// Control media players for `play ` and `music `, and for queries such as `pause`.
+    pub media: bool,
+    /// List libvirt virtual machines for `vm `.
+    pub virtual_machines: bool,
+    /// Open RDP, VNC, and SFTP connections for `remote `.
+    pub remote: RemoteConfig,
+    /// Switch power profiles, night light, and do not disturb for queries such as `dnd`.
+    pub toggles: bool,
+    /// Switch between dark and light mode and apply theme presets for `dark`, `light`, or `theme`.
+    pub appearance: bool,
+    /// Open pages of COSMIC Settings for queries such as `bluetooth` or `wallpaper`.
+    pub settings: bool,
+    /// Set wallpapers from the configured directories for `wall `.
+    pub wallpapers: WallpapersConfig,
+    /// Open and empty the trash for queries such as `trash`.
+    pub trash: bool,
+    /// Mount, unmount, and eject drives and partitions for `mount `.
+    pub drives: bool,
+    /// Named snippets of text, found by name and copied or typed on activation.
+    pub snippets: SnippetsConfig,
+    /// Save quick notes typed after `note `.
+    pub notes: NotesConfig,
+    /// List, add, and complete tasks for queries starting with `todo `.
+    pub todo: TodoConfig,
+    /// Generate UUIDs, hashes, and base64 for queries starting with `gen ` or `hash `.
+    pub generate: bool,
+    /// Run commands from shell history again for `hist `. Off unless enabled.
+    pub shell_history: ShellHistoryConfig,
+    /// Generate random passwords for `pw`, such as `pw 24`.
+    pub passwords: PasswordsConfig,
+    /// Look up characters by name for `char `, and by code point for `u+`.
+    pub unicode: bool,
+    /// Convert times between zones for queries such as `time in tokyo`.
+    pub world_clock: bool,
+    pub weather: WeatherConfig,
+}
+
+/// Scopes of applications, files, windows, and the web, by the pop-launcher plugins and
+/// the local providers of each. Their names are keys of the chips translated for display.
+fn default_scopes() -> Vec<Scope> {
+    let scope = |name: &str, plugins: &[&str], providers: &[&str], windows| Scope {
+        name: name.to_string(),
+        plugins: plugins.iter().map(|p| p.to_string()).collect(),
+        providers: providers.iter().map(|p| p.to_string()).collect(),
+        windows,
+    };
+    vec![
+        scope(
+            "apps",
+            &["desktop_entries"],
+            &["commands", "appimages", "games"],
+            false,
+        ),
+        scope(
+            "files",
+            &["files", "find", "recent"],
+            &["projects", "repositories", "trash", "drives"],
+            false,
+        ),
+        scope("windows", &[], &[], true),
+        scope("web", &["web"], &["history", "tabs"], false),
+    ]
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_width: 600.0,
+            top_margin: 16,
+            anchor: Anchor::default(),
+            outputs: BTreeMap::new(),
+            keybindings: KeyBindings::default(),
+            default_profile: Profile::default(),
+            profiles: BTreeMap::new(),
+            kiosk: Kiosk::default(),
+            debounce: Debounce::default(),
+            destructive_actions: [
+                "Shut Down",
+                "Power Off",
+                "Restart",
+                "Reboot",
+                "Log Out",
+                "Kill",
+                "Force Quit",
+                "Uninstall",
+                "Empty Trash",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+            launch_env: BTreeMap::new(),
+            commands: Vec::new(),
+            matcher: MatcherConfig::default(),
+            routes: Routes::default(),
+            windows_first: false,
+            title_overflow: TitleOverflow::default(),
+            density: Density::default(),
+            blur: true,
+            app_grid: false,
+            keyboard_hints: KeyboardHints::default(),
+            number_keys: false,
+            vi_mode: None,
+            scopes: default_scopes(),
+            remember_scope: false,
+            focus_on_hover: false,
+            alt_tab_preview: false,
+            fallbacks: Vec::new(),
+            style: Style::default(),
+            terminal: Terminal::default(),
+            script_timeouts: BTreeMap::new(),
+            min_query_length: BTreeMap::new(),
+            calendar: true,
+            contacts: ContactsConfig::default(),
+            history: HistoryConfig::default(),
+            appimages: AppImageConfig::default(),
+            games: true,
+            projects: true,
+            repositories: RepositoriesConfig::default(),
+            sessions: true,
+            containers: true,
+            dev: DevConfig::default(),
+            network: true,
+            bluetooth: true,
+            media: true,
+            virtual_machines: true,
+            remote: RemoteConfig::default(),
+            toggles: true,
+            appearance: true,
+            settings: true,
+            wallpapers: WallpapersConfig::default(),
+            trash: true,
+            drives: true,
+            snippets: SnippetsConfig::default(),
+            notes: NotesConfig::default(),
+            todo: TodoConfig::default(),
+            generate: true,
+            shell_history: ShellHistoryConfig::default(),
+            passwords: PasswordsConfig::default(),
+            unicode: true,
+            world_clock: true,
+            weather: WeatherConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Maximum width of the launcher on the output named `output`.
+    pub fn max_width(&self, output: Option<&str>) -> f32 {
+        self.output(output)
+            .and_then(|geometry| geometry.max_width)
+            .unwrap_or(self.max_width)
+    }
+
+    /// Gap between the anchored edge of the output named `output` and the launcher.
+    pub fn top_margin(&self, output: Option<&str>) -> i32 {
+        self.output(output)
+            .and_then(|geometry| geometry.top_margin)
+            .unwrap_or(self.top_margin)
+    }
+
+    /// Where the launcher opens on the output named `output`.
+    pub fn anchor(&self, output: Option<&str>) -> Anchor {
+        self.output(output)
+            .and_then(|geometry| geometry.anchor)
+            .unwrap_or(self.anchor)
+    }
+
+    fn output(&self, output: Option<&str>) -> Option<&OutputGeometry> {
+        self.outputs.get(output?)
+    }
+
+    /// Looks up a profile by name, falling back to the default profile.
+    pub fn profile(&self, name: Option<&str>) -> &Profile {
+        match name {
+            Some(name) => self.profiles.get(name).unwrap_or_else(|| {
+                tracing::warn!("unknown profile {name:?}, using the default profile");
+                &self.default_profile
+            }),
+            None => &self.default_profile,
+        }
+    }
+
+    /// Custom commands whose name matches the query, best matches first, as results with
+    /// local IDs.
+    pub fn command_results(&self, query: &str) -> Vec<SearchResult> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let commands = self.commands.iter().zip(LOCAL_ID_BASE..);
+        let by_name = self.matcher.matcher();
+        matcher::matching(&*by_name, query, commands, |(command, _)| &command.name)
+            .into_iter()
+            .map(|(command, id)| SearchResult {
+                id,
+                name: command.name.clone(),
+                description: command.exec.clone(),
+                icon: Some(IconSource::Name(
+                    command
+                        .icon
+                        .clone()
+                        .unwrap_or_else(|| "utilities-terminal".into())
+                        .into(),
+                )),
+                category_icon: None,
+                window: None,
+            })
+            .collect()
+    }
+
+    /// Extra environment variables configured for a desktop entry.
+    pub fn launch_env(&self, app_id: &str) -> Vec<(String, String)> {
+        self.launch_env
+            .get(app_id)
+            .or_else(|| self.launch_env.get(app_id.trim_end_matches(".desktop")))
+            .map(|env| env.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            .unwrap_or_default()
+    }
+
+    /// The directory the entries of this configuration are stored in.
+    pub fn dir(app_id: &str) -> Option<std::path::PathBuf> {
+        Some(
+            xdg::BaseDirectories::with_prefix("cosmic")
+                .ok()?
+                .get_config_home()
+                .join(app_id)
+                .join(format!("v{}", Self::VERSION)),
+        )
+    }
+
+    /// Path of the file backing the `launch_env` entry, created with the current value if
+    /// it has not been written yet.
+    pub fn launch_env_path(&self, app_id: &str) -> Option<std::path::PathBuf> {
+        let path = Self::dir(app_id)?.join("launch_env");
+
+        if !path.exists() {
+            let handler = cosmic_config::Config::new(app_id, Self::VERSION).ok()?;
+            if let Err(why) = handler.set("launch_env", &self.launch_env) {
+                tracing::error!("failed to write launch_env: {why}");
+            }
+        }
+
+        Some(path)
+    }
+
+    /// Loads the configuration, falling back to defaults for anything unreadable.
+    pub fn load(app_id: &str) -> Self {
+        match cosmic_config::Config::new(app_id, Self::VERSION) {
+            Ok(handler) => Self::get_entry(&handler).unwrap_or_else(|(errors, config)| {
+                for why in errors {
+                    tracing::error!("failed to load config: {why}");
+                }
+                config
+            }),
+            Err(why) => {
+                tracing::error!("failed to open config: {why}");
+                Self::default()
+            }
+        }
+    }
+}
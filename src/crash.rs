@@ -0,0 +1,134 @@
+//! Crash reports written when the launcher panics.
+//!
+//! The panic hook writes the panic with its backtrace, the kinds of the last messages the
+//! application handled, and the configuration to `$XDG_STATE_HOME/cosmic-launcher/crashes`,
+//! where the next run finds the report and offers to file it.
+//!
+//! Reports are meant to be attached to public issues, so they leave out what was typed,
+//! copied, or generated: messages are kept by the name of their variant only, and the
+//! values of environment variables and snippets are redacted from the configuration.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+
+/// Where new issues are filed, named at the top of each report.
+pub const ISSUES: &str = "https://github.com/pop-os/cosmic-launcher/issues/new";
+
+const DIR: &str = "cosmic-launcher/crashes";
+
+/// Messages kept for the next report.
+const MAX_MESSAGES: usize = 50;
+
+const REDACTED: &str = "<redacted>";
+
+static MESSAGES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+static CONFIG: Mutex<String> = Mutex::new(String::new());
+
+/// Installs the panic hook, which writes a report before the default hook runs.
+pub fn install() {
+    let default = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        match write_report(&info.to_string()) {
+            Ok(path) => tracing::error!("crash report written to {}", path.display()),
+            Err(why) => tracing::error!("failed to write a crash report: {why}"),
+        }
+        default(info);
+    }));
+}
+
+/// Remembers the kind of a message the application handled.
+pub fn record(message: &impl fmt::Debug) {
+    let mut name = VariantName(String::new());
+    // Fails on purpose once the payload begins.
+    let _res = write!(name, "{message:?}");
+    if let Ok(mut messages) = MESSAGES.lock() {
+        if messages.len() == MAX_MESSAGES {
+            messages.pop_front();
+        }
+        messages.push_back(name.0);
+    }
+}
+
+/// Forgets the messages handled so far, when the session locks.
+pub fn forget() {
+    if let Ok(mut messages) = MESSAGES.lock() {
+        messages.clear();
+    }
+}
+
+/// Remembers the configuration in effect.
+pub fn set_config(config: &Config) {
+    let mut config = config.clone();
+    for env in config.launch_env.values_mut() {
+        env.values_mut().for_each(redact);
+    }
+    config.snippets.snippets.values_mut().for_each(redact);
+    if let Ok(mut snapshot) = CONFIG.lock() {
+        *snapshot = format!("{config:#?}");
+    }
+}
+
+fn redact(value: &mut String) {
+    REDACTED.clone_into(value);
+}
+
+/// Collects the `Debug` output of an enum up to the end of the variant name.
+struct VariantName(String);
+
+impl Write for VariantName {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match s.find(|c: char| !(c.is_alphanumeric() || c == '_')) {
+            Some(end) => {
+                self.0.push_str(&s[..end]);
+                Err(fmt::Error)
+            }
+            None => {
+                self.0.push_str(s);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The directory reports are written to.
+pub fn dir() -> Option<PathBuf> {
+    xdg::BaseDirectories::new()
+        .ok()
+        .map(|dirs| dirs.get_state_home().join(DIR))
+}
+
+fn write_report(panic: &str) -> std::io::Result<PathBuf> {
+    let dir = dir().ok_or_else(|| std::io::Error::other("no state directory"))?;
+    std::fs::create_dir_all(&dir)?;
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let path = dir.join(format!("crash-{time}.txt"));
+
+    let mut report = String::new();
+    let _res = writeln!(report, "Please attach this file to a new issue at {ISSUES}");
+    let _res = writeln!(report, "\ncosmic-launcher {}", crate::config::VERSION);
+    let _res = writeln!(report, "\n{panic}");
+    let _res = writeln!(report, "\n{}", std::backtrace::Backtrace::force_capture());
+
+    // The panic may have happened while one of these was held, which must not block.
+    report.push_str("\nLast messages:\n");
+    if let Ok(messages) = MESSAGES.try_lock() {
+        for message in messages.iter() {
+            let _res = writeln!(report, "{message}");
+        }
+    }
+    report.push_str("\nConfiguration:\n");
+    if let Ok(config) = CONFIG.try_lock() {
+        report.push_str(&config);
+    }
+
+    std::fs::write(&path, report)?;
+    Ok(path)
+}
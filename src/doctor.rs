@@ -0,0 +1,354 @@
+//! `cosmic-launcher doctor`: checks the environment the launcher depends on and prints a
+//! report that can be attached to bug reports.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+
+use crate::backends::budget;
+use crate::plugins;
+use crate::surface::{self, BLUR, LAYER_SHELL, XDG_ACTIVATION};
+
+const BACKEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Status::Ok => "ok",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+        })
+    }
+}
+
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+}
+
+#[derive(Default)]
+pub struct Report {
+    checks: Vec<Check>,
+}
+
+impl Report {
+    pub fn push(&mut self, name: &'static str, status: Status, detail: impl Into<String>) {
+        self.checks.push(Check {
+            name,
+            status,
+            detail: detail.into(),
+        });
+    }
+
+    pub fn has_failures(&self) -> bool {
+        self.checks.iter().any(|check| check.status == Status::Fail)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .checks
+            .iter()
+            .map(|check| check.name.len())
+            .max()
+            .unwrap_or_default();
+
+        for check in &self.checks {
+            writeln!(
+                f,
+                "[{:>4}] {:width$}  {}",
+                check.status, check.name, check.detail
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs every check, prints the report to stdout, and returns the process exit code.
+pub fn run() -> i32 {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(why) => {
+            eprintln!("failed to start async runtime: {why}");
+            return 1;
+        }
+    };
+
+    let report = runtime.block_on(diagnose());
+    println!(
+        "cosmic-launcher {} ({})\n",
+        crate::config::VERSION,
+        crate::config::profile()
+    );
+    print!("{report}");
+
+    i32::from(report.has_failures())
+}
+
+async fn diagnose() -> Report {
+    let mut report = Report::default();
+
+    check_pop_launcher(&mut report);
+    check_plugins(&mut report);
+    check_routes(&mut report);
+    check_wayland(&mut report);
+    check_dbus(&mut report).await;
+    check_backend(&mut report).await;
+    check_providers(&mut report);
+
+    report
+}
+
+pub fn find_in_path(binary: &str) -> Option<PathBuf> {
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(binary))
+            .find(|path| path.is_file())
+    })
+}
+
+fn check_pop_launcher(report: &mut Report) {
+    match find_in_path("pop-launcher") {
+        Some(path) => report.push("pop-launcher", Status::Ok, path.display().to_string()),
+        None => report.push("pop-launcher", Status::Fail, "binary not found in PATH"),
+    }
+}
+
+fn check_plugins(report: &mut Report) {
+    let mut plugins = plugins::dirs()
+        .iter()
+        .flat_map(|dir| plugins::installed(dir))
+        .collect::<Vec<_>>();
+    plugins.sort_unstable();
+    plugins.dedup();
+
+    if plugins.is_empty() {
+        report.push("plugins", Status::Fail, "no pop-launcher plugins installed");
+    } else {
+        report.push(
+            "plugins",
+            Status::Ok,
+            format!("{} found: {}", plugins.len(), plugins.join(", ")),
+        );
+    }
+}
+
+/// Prefixes claimed by installed plugins, read from the `help` entry of each
+/// `plugin.ron`, paired with the plugin name.
+fn plugin_prefixes() -> Vec<(String, String)> {
+    plugins::dirs()
+        .iter()
+        .flat_map(|dir| {
+            plugins::installed(dir)
+                .into_iter()
+                .filter_map(move |plugin| {
+                    let ron = std::fs::read_to_string(dir.join(&plugin).join("plugin.ron")).ok()?;
+                    let help = ron.split_once("help:")?.1.trim_start().strip_prefix('"')?;
+                    let prefix = help.split_once('"')?.0;
+                    Some((prefix.to_owned(), plugin))
+                })
+        })
+        .filter(|(prefix, _)| !prefix.is_empty())
+        .collect()
+}
+
+fn check_routes(report: &mut Report) {
+    let app_id = <crate::app::CosmicLauncher as cosmic::Application>::APP_ID;
+    let routes = crate::config::Config::load(app_id).routes;
+    if routes.0.is_empty() {
+        report.push("prefix routes", Status::Ok, "none configured");
+        return;
+    }
+
+    let conflicts = routes.conflicts(&plugin_prefixes());
+    if conflicts.is_empty() {
+        report.push(
+            "prefix routes",
+            Status::Ok,
+            format!("{} configured", routes.0.len()),
+        );
+        return;
+    }
+
+    for conflict in conflicts {
+        report.push(
+            "prefix routes",
+            Status::Warn,
+            format!(
+                "{:?} overlaps {:?} from {}",
+                conflict.prefix, conflict.other, conflict.owner
+            ),
+        );
+    }
+}
+
+fn check_wayland(report: &mut Report) {
+    let Some(globals) = surface::advertised_globals() else {
+        report.push(
+            "wayland",
+            Status::Fail,
+            "could not connect to a Wayland compositor",
+        );
+        return;
+    };
+
+    report.push("wayland", Status::Ok, "connected");
+
+    let has = |interface: &str| globals.iter().any(|global| global == interface);
+
+    if has(LAYER_SHELL) {
+        report.push("layer shell", Status::Ok, LAYER_SHELL);
+    } else if cfg!(feature = "window-fallback") {
+        report.push(
+            "layer shell",
+            Status::Warn,
+            "missing, the launcher will open as a regular window",
+        );
+    } else {
+        report.push(
+            "layer shell",
+            Status::Fail,
+            "missing, the launcher cannot be shown",
+        );
+    }
+
+    if has(XDG_ACTIVATION) {
+        report.push("xdg activation", Status::Ok, XDG_ACTIVATION);
+    } else {
+        report.push(
+            "xdg activation",
+            Status::Warn,
+            "missing, launched apps may not receive focus",
+        );
+    }
+
+    let app_id = <crate::app::CosmicLauncher as cosmic::Application>::APP_ID;
+    if !crate::config::Config::load(app_id).blur {
+        report.push("blur", Status::Ok, "turned off in the config");
+        return;
+    }
+    match BLUR.into_iter().find(|interface| has(interface)) {
+        Some(interface) => report.push("blur", Status::Ok, interface),
+        None => report.push(
+            "blur",
+            Status::Ok,
+            "not offered by the compositor, the background is not blurred",
+        ),
+    }
+}
+
+async fn check_dbus(report: &mut Report) {
+    let app_id = <crate::app::CosmicLauncher as cosmic::Application>::APP_ID;
+
+    let owned = async {
+        let connection = zbus::Connection::session().await?;
+        let proxy = zbus::fdo::DBusProxy::new(&connection).await?;
+        let name = zbus::names::BusName::try_from(app_id)?;
+        proxy.name_has_owner(name).await.map_err(zbus::Error::from)
+    };
+
+    match owned.await {
+        Ok(true) => report.push("dbus", Status::Ok, format!("{app_id} is registered")),
+        Ok(false) => report.push(
+            "dbus",
+            Status::Warn,
+            format!("{app_id} is not registered, the launcher is not running"),
+        ),
+        Err(why) => report.push("dbus", Status::Fail, format!("session bus error: {why}")),
+    }
+}
+
+async fn check_backend(report: &mut Report) {
+    let (mut client, responses) = match pop_launcher_service::IpcClient::new() {
+        Ok(client) => client,
+        Err(why) => {
+            report.push("backend", Status::Fail, format!("failed to spawn: {why}"));
+            return;
+        }
+    };
+
+    let started = Instant::now();
+    let round_trip = async {
+        client
+            .send(pop_launcher::Request::Search(String::new()))
+            .await
+            .ok()?;
+
+        let mut responses = std::pin::pin!(responses);
+        while let Some(response) = responses.next().await {
+            if let pop_launcher::Response::Update(results) = response {
+                return Some(results.len());
+            }
+        }
+
+        None
+    };
+
+    match tokio::time::timeout(BACKEND_TIMEOUT, round_trip).await {
+        Ok(Some(results)) => report.push(
+            "backend",
+            Status::Ok,
+            format!(
+                "round trip in {} ms, {results} results for an empty query",
+                started.elapsed().as_millis()
+            ),
+        ),
+        Ok(None) => report.push("backend", Status::Fail, "exited without responding"),
+        Err(_) => report.push(
+            "backend",
+            Status::Fail,
+            format!("no response within {} s", BACKEND_TIMEOUT.as_secs()),
+        ),
+    }
+
+    let _res = client.child.kill().await;
+    let _res = client.child.wait().await;
+}
+
+/// Providers of the running launcher which took longer than their budget to answer.
+fn check_providers(report: &mut Report) {
+    let slow = budget::load();
+    if slow.is_empty() {
+        report.push(
+            "providers",
+            Status::Ok,
+            format!("all answered within {} ms", budget::SOFT.as_millis()),
+        );
+        return;
+    }
+
+    for provider in slow {
+        let last = match provider.last_answer_ms {
+            Some(ms) => format!("last answered in {ms} ms"),
+            None => format!("last ran out of {} ms", budget::HARD.as_millis()),
+        };
+        let demoted = if provider.is_demoted() {
+            ", demoted"
+        } else {
+            ""
+        };
+        report.push(
+            "providers",
+            Status::Warn,
+            format!(
+                "{}: {last}, {} timeouts{demoted}",
+                provider.name, provider.timeouts
+            ),
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! Results kept at the bottom of the list for any query, as set by `fallbacks` in the
+//! config, such as a web search for it. When nothing else matches they come first, so
+//! that Enter still does something useful.
+
+use pop_launcher::{IconSource, SearchResult};
+use serde::{Deserialize, Serialize};
+
+use crate::backends::Action;
+use crate::fl;
+
+/// Fallbacks use the last block of local IDs, which no provider reaches.
+pub const ID_BASE: u32 = 0xffff_0000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Fallback {
+    /// Open a web search named `name`, with `{}` in the URL replaced by the query.
+    Web { name: String, url: String },
+    /// Run the query as a command line.
+    Run,
+    /// Search for files named like the query.
+    Files,
+}
+
+impl Fallback {
+    /// What activating the fallback does for `query`, if it can handle it.
+    pub fn action(&self, query: &str) -> Option<Action> {
+        let query = query.trim();
+        if query.is_empty() {
+            return None;
+        }
+
+        match self {
+            Fallback::Web { url, .. } => Some(Action::Open(url.replace("{}", &encode(query)))),
+            Fallback::Run => shlex::split(query)
+                .filter(|args| !args.is_empty())
+                .map(Action::Run),
+            Fallback::Files => Some(Action::Query(format!("find {query}"))),
+        }
+    }
+
+    fn result(&self, id: u32, query: &str) -> SearchResult {
+        let (name, icon) = match self {
+            Fallback::Web { name, .. } => (
+                fl!("fallback-web", engine = name.as_str(), query = query),
+                "web-browser",
+            ),
+            Fallback::Run => (fl!("fallback-run", query = query), "utilities-terminal"),
+            Fallback::Files => (fl!("fallback-files", query = query), "system-file-manager"),
+        };
+        SearchResult {
+            id,
+            name,
+            description: String::new(),
+            icon: Some(IconSource::Name(icon.into())),
+            category_icon: None,
+            window: None,
+        }
+    }
+}
+
+/// The fallbacks which can handle `query`, as results.
+pub fn results(fallbacks: &[Fallback], query: &str) -> Vec<SearchResult> {
+    let query = query.trim();
+    fallbacks
+        .iter()
+        .zip(ID_BASE..)
+        .filter(|(fallback, _)| fallback.action(query).is_some())
+        .map(|(fallback, id)| fallback.result(id, query))
+        .collect()
+}
+
+/// What activating the fallback result `id` does for `query`, or `None` if `id` is not
+/// one.
+pub fn activate(fallbacks: &[Fallback], id: u32, query: &str) -> Option<Action> {
+    let fallback = fallbacks.get(id.checked_sub(ID_BASE)? as usize)?;
+    fallback.action(query)
+}
+
+/// Percent-encodes `text` for the query string of a URL.
+fn encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                char::from(byte).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
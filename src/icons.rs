@@ -0,0 +1,202 @@
+//! Icon lookup for results whose icon name is not in the icon theme.
+//!
+//! Window results are often given the window's app ID as their icon, which only works
+//! when an icon happens to share that name. The resolver falls back to the icon of the
+//! desktop entry with that ID, then to one whose `StartupWMClass` matches. The same index
+//! relates application results to the windows they have open, and finds the applications
+//! of the app grid.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use cosmic::widget::icon::{self, IconFallback};
+
+/// Icons tried when nothing else matches.
+const FALLBACK: [&str; 2] = ["application-default", "application-x-executable"];
+
+#[derive(Debug, Default)]
+struct DesktopEntry {
+    id: String,
+    name: String,
+    wm_class: Option<String>,
+    icon: String,
+    path: PathBuf,
+}
+
+/// An application of the app grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct App {
+    pub name: String,
+    pub icon: String,
+    /// The desktop entry launching it.
+    pub path: PathBuf,
+}
+
+/// Maps app IDs and window classes to icon names, caching every lookup.
+#[derive(Debug, Clone, Default)]
+pub struct IconResolver {
+    entries: Arc<Vec<DesktopEntry>>,
+    resolved: HashMap<String, String>,
+}
+
+impl IconResolver {
+    /// Indexes the desktop entries installed in the XDG data directories.
+    pub fn load() -> Self {
+        let mut entries = Vec::new();
+        if let Ok(base) = xdg::BaseDirectories::new() {
+            for dir in std::iter::once(base.get_data_home()).chain(base.get_data_dirs()) {
+                read_entries(&dir.join("applications"), "", &mut entries);
+            }
+        }
+
+        Self {
+            entries: Arc::new(entries),
+            resolved: HashMap::new(),
+        }
+    }
+
+    /// Resolves `name` if it was not looked up before.
+    pub fn resolve(&mut self, name: &str) {
+        if self.resolved.contains_key(name) {
+            return;
+        }
+
+        let resolved = if freedesktop_icons::lookup(name).find().is_some() {
+            name.to_owned()
+        } else {
+            let by_id = self
+                .entries
+                .iter()
+                .find(|entry| entry.id.eq_ignore_ascii_case(name));
+            let by_class = || {
+                self.entries.iter().find(|entry| {
+                    entry
+                        .wm_class
+                        .as_deref()
+                        .is_some_and(|class| class.eq_ignore_ascii_case(name))
+                })
+            };
+
+            by_id
+                .or_else(by_class)
+                .map_or_else(|| name.to_owned(), |entry| entry.icon.clone())
+        };
+
+        self.resolved.insert(name.to_owned(), resolved);
+    }
+
+    /// Whether a toplevel with `app_id` belongs to the application named `name`, matching
+    /// the desktop entry's ID or window class.
+    pub fn is_app(&self, name: &str, app_id: &str) -> bool {
+        name.eq_ignore_ascii_case(app_id)
+            || self.entries.iter().any(|entry| {
+                entry.name == name
+                    && (entry.id.eq_ignore_ascii_case(app_id)
+                        || entry
+                            .wm_class
+                            .as_deref()
+                            .is_some_and(|class| class.eq_ignore_ascii_case(app_id)))
+            })
+    }
+
+    /// Names of the applications a toplevel with `app_id` may belong to, which are those
+    /// [`IconResolver::is_app`] relates to it.
+    pub fn names(&self, app_id: &str) -> Vec<String> {
+        std::iter::once(app_id.to_owned())
+            .chain(
+                self.entries
+                    .iter()
+                    .filter(|entry| {
+                        entry.id.eq_ignore_ascii_case(app_id)
+                            || entry
+                                .wm_class
+                                .as_deref()
+                                .is_some_and(|class| class.eq_ignore_ascii_case(app_id))
+                    })
+                    .map(|entry| entry.name.clone()),
+            )
+            .collect()
+    }
+
+    /// The ID and name of every desktop entry.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.id.as_str(), entry.name.as_str()))
+    }
+
+    /// The application named `name`, as its desktop entry names it.
+    pub fn app(&self, name: &str) -> Option<App> {
+        self.entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .map(|entry| App {
+                name: entry.name.clone(),
+                icon: entry.icon.clone(),
+                path: entry.path.clone(),
+            })
+    }
+
+    /// A handle for the resolved icon, with the generic application icons as fallback.
+    pub fn handle(&self, name: &str, size: u16) -> icon::Handle {
+        // Providers may give the path of an icon file, such as one cached from an AppImage.
+        if name.starts_with('/') {
+            return icon::from_path(name.into());
+        }
+
+        let name = self.resolved.get(name).map_or(name, String::as_str);
+        icon::from_name(name.to_owned())
+            .size(size)
+            .fallback(Some(IconFallback::Names(
+                FALLBACK.into_iter().map(Into::into).collect(),
+            )))
+            .into()
+    }
+}
+
+/// Reads the desktop entries in `dir`, naming those in subdirectories with the
+/// subdirectory as prefix, as the desktop entry specification does.
+fn read_entries(dir: &Path, prefix: &str, entries: &mut Vec<DesktopEntry>) {
+    let Ok(dir_entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in dir_entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        if path.is_dir() {
+            read_entries(&path, &format!("{prefix}{file_name}-"), entries);
+        } else if let Some(id) = file_name.strip_suffix(".desktop") {
+            if let Some(entry) = parse(&path, format!("{prefix}{id}")) {
+                entries.push(entry);
+            }
+        }
+    }
+}
+
+fn parse(path: &Path, id: String) -> Option<DesktopEntry> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    let mut entry = DesktopEntry {
+        id,
+        path: path.to_owned(),
+        ..DesktopEntry::default()
+    };
+
+    let mut in_main_group = false;
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_main_group = line == "[Desktop Entry]";
+        } else if in_main_group {
+            match line.split_once('=') {
+                Some(("Name", name)) => entry.name = name.trim().to_owned(),
+                Some(("Icon", icon)) => entry.icon = icon.trim().to_owned(),
+                Some(("StartupWMClass", class)) => entry.wm_class = Some(class.trim().to_owned()),
+                _ => {}
+            }
+        }
+    }
+
+    Some(entry).filter(|entry| !entry.icon.is_empty())
+}
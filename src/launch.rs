@@ -0,0 +1,324 @@
+//! Spawning of desktop entries, and reporting of launches which never map a window.
+
+use std::collections::{HashMap, VecDeque};
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use pop_launcher::GpuPreference;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// How long a launched application has to map a window before the user is notified.
+pub const LAUNCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long the launcher's surface has to go away before text is typed into the window
+/// focused after it.
+const TYPE_DELAY: Duration = Duration::from_millis(150);
+
+/// Number of trailing stderr lines kept for the failure notification.
+const STDERR_LINES: usize = 10;
+
+/// A desktop entry about to be launched.
+#[derive(Debug, Clone)]
+pub struct LaunchRequest {
+    pub app_id: String,
+    pub name: String,
+    pub wm_class: Option<String>,
+    pub exec: String,
+    pub gpu: GpuPreference,
+    /// Variables from the `launch_env` config entry, applied last.
+    pub env: Vec<(String, String)>,
+    pub working_dir: Option<PathBuf>,
+    /// The query the command was launched with. When set, the command is a custom
+    /// command whose `%query%`, `%clipboard%`, and `%selection%` placeholders are expanded.
+    pub query: Option<String>,
+    /// Whether to notify the user if no window appears after launching.
+    pub watch: bool,
+}
+
+impl LaunchRequest {
+    /// Whether a toplevel with this app ID belongs to the launched entry.
+    pub fn matches(&self, app_id: &str) -> bool {
+        let id = self.app_id.trim_end_matches(".desktop");
+        app_id.eq_ignore_ascii_case(id)
+            || self
+                .wm_class
+                .as_deref()
+                .is_some_and(|class| app_id.eq_ignore_ascii_case(class))
+    }
+}
+
+/// The last lines a spawned process wrote to stderr.
+#[derive(Debug, Clone, Default)]
+pub struct StderrTail(Arc<Mutex<VecDeque<String>>>);
+
+impl StderrTail {
+    fn push(&self, line: String) {
+        if let Ok(mut lines) = self.0.lock() {
+            if lines.len() == STDERR_LINES {
+                lines.pop_front();
+            }
+            lines.push_back(line);
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.0
+            .lock()
+            .map(|lines| {
+                lines
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Spawns the entry's command, returning a handle to its stderr output.
+pub async fn launch(token: Option<String>, request: LaunchRequest) -> Option<StderrTail> {
+    let mut envs = Vec::new();
+    if let Some(token) = token {
+        envs.push(("XDG_ACTIVATION_TOKEN".to_string(), token.clone()));
+        envs.push(("DESKTOP_STARTUP_ID".to_string(), token));
+    }
+
+    if let Some(gpu_envs) = try_get_gpu_envs(request.gpu).await {
+        envs.extend(gpu_envs);
+    }
+
+    envs.extend(request.env.iter().cloned());
+
+    let args = match request.query.as_deref() {
+        Some(query) => expand(&request.exec, query).await,
+        // Field codes such as `%U` are dropped, since the launcher never passes files.
+        None => shlex::Shlex::new(&request.exec)
+            .filter(|arg| !arg.starts_with('%'))
+            .collect(),
+    };
+    let (program, args) = args.split_first()?;
+
+    let mut command = tokio::process::Command::new(program);
+    if let Some(dir) = request.working_dir.as_ref() {
+        command.current_dir(dir);
+    }
+
+    let mut child = match command
+        .args(args)
+        .envs(envs)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .process_group(0)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(why) => {
+            tracing::error!("failed to spawn {}: {why}", request.app_id);
+            let tail = StderrTail::default();
+            tail.push(why.to_string());
+            return Some(tail);
+        }
+    };
+
+    let tail = StderrTail::default();
+    let stderr = child.stderr.take();
+    let lines = tail.clone();
+    tokio::spawn(async move {
+        if let Some(stderr) = stderr {
+            let mut reader = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = reader.next_line().await {
+                lines.push(line);
+            }
+        }
+
+        let _res = child.wait().await;
+    });
+
+    Some(tail)
+}
+
+/// Splits a custom command into arguments and substitutes its placeholders. Each
+/// substitution stays within its argument, so clipboard contents need no quoting.
+async fn expand(exec: &str, query: &str) -> Vec<String> {
+    let clipboard = if exec.contains("%clipboard%") {
+        paste(false).await
+    } else {
+        String::new()
+    };
+    let selection = if exec.contains("%selection%") {
+        paste(true).await
+    } else {
+        String::new()
+    };
+
+    shlex::Shlex::new(exec)
+        .map(|arg| {
+            arg.replace("%query%", query)
+                .replace("%clipboard%", &clipboard)
+                .replace("%selection%", &selection)
+        })
+        .collect()
+}
+
+/// Reads the clipboard, or the primary selection, through `wl-paste`.
+pub async fn paste(primary: bool) -> String {
+    let mut command = tokio::process::Command::new("wl-paste");
+    command.arg("--no-newline");
+    if primary {
+        command.arg("--primary");
+    }
+
+    match command
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .await
+    {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).into_owned(),
+        Err(why) => {
+            tracing::warn!("failed to read the clipboard: {why}");
+            String::new()
+        }
+    }
+}
+
+/// Puts `text` on the clipboard through `wl-copy`.
+pub async fn copy(text: String) {
+    let result = async {
+        let mut child = tokio::process::Command::new("wl-copy")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes()).await?;
+        }
+        child.wait().await
+    }
+    .await;
+
+    if let Err(why) = result {
+        tracing::warn!("failed to write the clipboard: {why}");
+    }
+}
+
+/// Puts a secret such as a password on the clipboard, then clears the clipboard after
+/// `clear_after` unless something else was copied since.
+pub async fn copy_secret(text: String, clear_after: Duration) {
+    copy(text.clone()).await;
+    tokio::time::sleep(clear_after).await;
+    clear_secret(text).await;
+}
+
+/// Clears the clipboard if it still holds the secret `text`.
+pub async fn clear_secret(text: String) {
+    if paste(false).await != text {
+        return;
+    }
+
+    let result = tokio::process::Command::new("wl-copy")
+        .arg("--clear")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await;
+    if let Err(why) = result {
+        tracing::warn!("failed to clear the clipboard: {why}");
+    }
+}
+
+/// Types text into the focused window through `wtype`, which uses the virtual keyboard
+/// protocol, once the launcher has given focus back.
+pub async fn type_text(text: String) {
+    tokio::time::sleep(TYPE_DELAY).await;
+    let result = tokio::process::Command::new("wtype")
+        .args(["--", &text])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .status()
+        .await;
+
+    if let Err(why) = result {
+        tracing::warn!("failed to type text: {why}");
+    }
+}
+
+/// Whether each GPU known to switcheroo-control is the default one, in the order of
+/// [`GpuPreference::SpecificIdx`], or nothing if it does not run.
+pub async fn gpus() -> Vec<bool> {
+    let Ok(connection) = zbus::Connection::system().await else {
+        return Vec::new();
+    };
+    let Ok(proxy) = switcheroo_control::SwitcherooControlProxy::new(&connection).await else {
+        return Vec::new();
+    };
+    match proxy.get_gpus().await {
+        Ok(gpus) => gpus.into_iter().map(|gpu| gpu.default).collect(),
+        Err(why) => {
+            tracing::debug!("switcheroo-control does not list the GPUs: {why}");
+            Vec::new()
+        }
+    }
+}
+
+async fn try_get_gpu_envs(gpu: GpuPreference) -> Option<HashMap<String, String>> {
+    let connection = zbus::Connection::system().await.ok()?;
+    let proxy = switcheroo_control::SwitcherooControlProxy::new(&connection)
+        .await
+        .ok()?;
+    let gpus = proxy.get_gpus().await.ok()?;
+    match gpu {
+        GpuPreference::Default => gpus.into_iter().find(|gpu| gpu.default),
+        GpuPreference::NonDefault => gpus.into_iter().find(|gpu| !gpu.default),
+        GpuPreference::SpecificIdx(idx) => gpus.into_iter().nth(idx as usize),
+    }
+    .map(|gpu| gpu.environment)
+}
+
+/// Opens a file or URI with the user's preferred application.
+pub async fn open(target: OsString) {
+    let result = tokio::process::Command::new("xdg-open")
+        .arg(&target)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .status()
+        .await;
+
+    if let Err(why) = result {
+        tracing::error!("failed to open {}: {why}", target.to_string_lossy());
+    }
+}
+
+/// Sends a desktop notification through `org.freedesktop.Notifications`.
+pub async fn notify(summary: String, body: String) {
+    let result = async {
+        let connection = zbus::Connection::session().await?;
+        connection
+            .call_method(
+                Some("org.freedesktop.Notifications"),
+                "/org/freedesktop/Notifications",
+                Some("org.freedesktop.Notifications"),
+                "Notify",
+                &(
+                    "cosmic-launcher",
+                    0u32,
+                    "dialog-error",
+                    summary,
+                    body,
+                    Vec::<&str>::new(),
+                    HashMap::<&str, zbus::zvariant::Value<'_>>::new(),
+                    -1i32,
+                ),
+            )
+            .await
+    };
+
+    if let Err(why) = result.await {
+        tracing::warn!("failed to send notification: {why}");
+    }
+}
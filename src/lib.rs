@@ -0,0 +1,6 @@
+//! Parts of cosmic-launcher for programs which drive a running launcher, such as a
+//! cosmic-panel applet with a search field of its own. The launcher itself is the
+//! `cosmic-launcher` binary.
+
+pub mod client;
+pub mod commands;
@@ -0,0 +1,153 @@
+//! Log output configuration and request latency tracing.
+
+use std::io::IsTerminal;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::Span;
+use tracing_subscriber::{filter::LevelFilter, prelude::*, EnvFilter, Layer};
+
+use crate::subscriptions::launcher::Request;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, clap::ValueEnum)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// Installs the global subscriber.
+///
+/// Logs go to `file` when given, and to journald otherwise. Output is mirrored to stderr
+/// when it is a terminal. An explicit `level` takes precedence over `RUST_LOG`.
+pub fn init(level: Option<LogLevel>, file: Option<&Path>) {
+    let filter = || match level {
+        Some(level) => EnvFilter::default().add_directive(LevelFilter::from(level).into()),
+        None => EnvFilter::builder()
+            .with_default_directive(LevelFilter::WARN.into())
+            .from_env_lossy(),
+    };
+
+    let mut layers = Vec::new();
+
+    if let Some(path) = file {
+        match std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+        {
+            Ok(file) => layers.push(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(Arc::new(file))
+                    .with_filter(filter())
+                    .boxed(),
+            ),
+            Err(why) => eprintln!("failed to open log file {}: {why}", path.display()),
+        }
+    } else {
+        match tracing_journald::layer() {
+            Ok(journald) => layers.push(journald.with_filter(filter()).boxed()),
+            Err(why) => eprintln!("journald is unavailable: {why}"),
+        }
+    }
+
+    if layers.is_empty() || std::io::stderr().is_terminal() {
+        layers.push(
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_filter(filter())
+                .boxed(),
+        );
+    }
+
+    let _res = tracing_subscriber::registry().with(layers).try_init();
+}
+
+/// Spans of the backend requests currently in flight, used to report their latency
+/// once the matching response arrives.
+#[derive(Clone, Default)]
+pub struct RequestSpans {
+    search: Option<(Span, Instant)>,
+    activate: Option<(Span, Instant)>,
+    /// Time the last search took to be answered.
+    search_latency: Option<Duration>,
+}
+
+impl RequestSpans {
+    pub fn sent(&mut self, request: &Request) {
+        match request {
+            Request::Search(query) => {
+                let span = tracing::debug_span!("search", query = %query);
+                span.in_scope(|| tracing::debug!("search requested"));
+                self.search = Some((span, Instant::now()));
+            }
+            Request::Activate(id) | Request::ActivateContext(id, _) => {
+                let parent = self.search.as_ref().and_then(|(span, _)| span.id());
+                let span = tracing::debug_span!(parent: parent, "activate", id);
+                span.in_scope(|| tracing::debug!("activation requested"));
+                self.activate = Some((span, Instant::now()));
+            }
+            Request::Context(_) | Request::Complete(_) | Request::Quit(_) | Request::Close => {}
+        }
+    }
+
+    pub fn search_latency(&self) -> Option<Duration> {
+        self.search_latency
+    }
+
+    pub fn received(&mut self, response: &pop_launcher::Response) {
+        match response {
+            pop_launcher::Response::Update(list) => {
+                if let Some((span, started)) = self.search.as_ref() {
+                    self.search_latency = Some(started.elapsed());
+                    span.in_scope(|| {
+                        tracing::debug!(
+                            latency_ms = started.elapsed().as_millis(),
+                            results = list.len(),
+                            "search response"
+                        );
+                    });
+                }
+            }
+            pop_launcher::Response::DesktopEntry { path, .. } => {
+                if let Some((span, started)) = self.activate.take() {
+                    span.in_scope(|| {
+                        tracing::debug!(
+                            latency_ms = started.elapsed().as_millis(),
+                            path = %path.display(),
+                            "activation response"
+                        );
+                    });
+                }
+            }
+            pop_launcher::Response::Close => {
+                if let Some((span, started)) = self.activate.take() {
+                    span.in_scope(|| {
+                        tracing::debug!(
+                            latency_ms = started.elapsed().as_millis(),
+                            "activation handled by plugin"
+                        );
+                    });
+                }
+            }
+            pop_launcher::Response::Context { .. } | pop_launcher::Response::Fill(_) => {}
+        }
+    }
+}
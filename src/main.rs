@@ -1,9 +1,29 @@
+mod about;
+mod backends;
+mod blur;
 mod components;
 #[rustfmt::skip]
 mod config;
 mod app;
+mod crash;
+mod doctor;
+mod fallback;
+mod icons;
+mod launch;
 mod localize;
+mod logging;
+mod matcher;
+mod panel;
+mod pipe;
+mod plugins;
+mod qr;
+mod secondary;
+mod snapshot;
+mod style;
 mod subscriptions;
+mod surface;
+use clap::Parser;
+use cosmic_launcher::commands::LauncherCommands;
 use tracing::info;
 
 use localize::localize;
@@ -11,13 +31,24 @@ use localize::localize;
 use crate::config::VERSION;
 
 fn main() -> cosmic::iced::Result {
+    let args = app::Args::parse();
+    if args.version {
+        about::print(args.json);
+        return Ok(());
+    }
+
     // Initialize logger
     #[cfg(feature = "console")]
     if std::env::var("TOKIO_CONSOLE").as_deref() == Ok("1") {
         std::env::set_var("RUST_LOG", "trace");
         console_subscriber::init();
     }
-    pretty_env_logger::init();
+    logging::init(args.log_level, args.log_file.as_deref());
+    crash::install();
+
+    if let Some(LauncherCommands::Doctor) = args.subcommand {
+        std::process::exit(doctor::run());
+    }
 
     info!(
         "cosmic-launcher ({})",
@@ -28,5 +59,5 @@ fn main() -> cosmic::iced::Result {
     // Prepare i18n
     localize();
 
-    app::run()
+    app::run(args)
 }
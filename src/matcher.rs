@@ -0,0 +1,315 @@
+//! Matching of queries against the names of results the launcher lists itself, such as
+//! custom commands, as selected by `matcher` in the config.
+//!
+//! Names are matched by substring, by the fzf-style fuzzy scoring of skim, or by their
+//! prefix. Case is ignored and accents are folded, so that `cafe` finds `Café`, unless the
+//! config says otherwise. Providers which match names themselves look for the words of the
+//! query in them through [`words`] and [`matches_all`], under the same settings.
+
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+pub trait Matcher {
+    /// How well `text` matches `query`, higher for better matches, or `None` if it does not
+    /// match at all.
+    fn score(&self, query: &str, text: &str) -> Option<i64>;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatcherKind {
+    /// Names containing the query, earlier occurrences first.
+    #[default]
+    Substring,
+    /// Names containing the characters of the query in order, scored like skim and fzf.
+    Fuzzy,
+    /// Names starting with the query, shorter names first.
+    Prefix,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MatcherConfig {
+    pub kind: MatcherKind,
+    pub case_sensitive: bool,
+    /// Match letters with accents by their base letter, such as `é` by `e`, and ligatures
+    /// and other compatibility characters by the letters they stand for.
+    pub fold_diacritics: bool,
+}
+
+const DEFAULT: MatcherConfig = MatcherConfig {
+    kind: MatcherKind::Substring,
+    case_sensitive: false,
+    fold_diacritics: true,
+};
+
+/// The settings [`fold`] applies, those of the loaded config.
+static SETTINGS: RwLock<MatcherConfig> = RwLock::new(DEFAULT);
+
+impl Default for MatcherConfig {
+    fn default() -> Self {
+        DEFAULT
+    }
+}
+
+impl MatcherConfig {
+    pub fn matcher(self) -> Box<dyn Matcher> {
+        match self.kind {
+            MatcherKind::Substring => Box::new(Substring(self)),
+            MatcherKind::Fuzzy => Box::new(Fuzzy(self)),
+            MatcherKind::Prefix => Box::new(Prefix(self)),
+        }
+    }
+
+    /// The characters of `text`, with accents folded if configured. Case is kept, as the
+    /// fuzzy matcher scores the start of words in camel case.
+    fn chars(self, text: &str) -> Vec<char> {
+        if self.fold_diacritics {
+            text.nfkd().filter(|c| !is_combining_mark(*c)).collect()
+        } else {
+            text.chars().collect()
+        }
+    }
+
+    /// `text` as compared by the substring and prefix matchers.
+    fn normalize(self, text: &str) -> String {
+        let text = self.chars(text).into_iter().collect::<String>();
+        if self.case_sensitive {
+            text
+        } else {
+            text.to_lowercase()
+        }
+    }
+
+    fn eq(self, a: char, b: char) -> bool {
+        a == b || !self.case_sensitive && a.to_lowercase().eq(b.to_lowercase())
+    }
+}
+
+struct Substring(MatcherConfig);
+
+impl Matcher for Substring {
+    fn score(&self, query: &str, text: &str) -> Option<i64> {
+        let position = self.0.normalize(text).find(&self.0.normalize(query))?;
+        Some(-i64::try_from(position).unwrap_or(i64::MAX))
+    }
+}
+
+struct Prefix(MatcherConfig);
+
+impl Matcher for Prefix {
+    fn score(&self, query: &str, text: &str) -> Option<i64> {
+        let text = self.0.normalize(text);
+        let query = self.0.normalize(query);
+        text.starts_with(&query)
+            .then(|| -i64::try_from(text.len() - query.len()).unwrap_or(i64::MAX))
+    }
+}
+
+struct Fuzzy(MatcherConfig);
+
+/// Scores of the fuzzy matcher, the same as skim's.
+const SCORE_MATCH: i64 = 16;
+const PENALTY_GAP_START: i64 = -3;
+const PENALTY_GAP_EXTENSION: i64 = -1;
+/// Matches at the start of a word, after a space or separator.
+const BONUS_BOUNDARY: i64 = SCORE_MATCH / 2;
+/// Matches at a change from lower to upper case or from letters to digits.
+const BONUS_CAMEL: i64 = BONUS_BOUNDARY + PENALTY_GAP_EXTENSION;
+const BONUS_CONSECUTIVE: i64 = -(PENALTY_GAP_START + PENALTY_GAP_EXTENSION);
+/// The bonus of the first character of the query counts this many times.
+const BONUS_FIRST_CHAR_MULTIPLIER: i64 = 2;
+
+/// Lower than any score, without overflowing when penalties are added to it.
+const UNMATCHED: i64 = i64::MIN / 2;
+
+fn bonus(previous: Option<char>, current: char) -> i64 {
+    match previous {
+        None => BONUS_BOUNDARY,
+        Some(previous)
+            if previous.is_whitespace() || "/-_.:,;".contains(previous) && current != previous =>
+        {
+            BONUS_BOUNDARY
+        }
+        Some(previous)
+            if previous.is_lowercase() && current.is_uppercase()
+                || !previous.is_numeric() && current.is_numeric() =>
+        {
+            BONUS_CAMEL
+        }
+        Some(_) => 0,
+    }
+}
+
+impl Matcher for Fuzzy {
+    /// The best alignment of the query's characters in order within `text`, found by
+    /// dynamic programming over one row of the table per character of the query.
+    fn score(&self, query: &str, text: &str) -> Option<i64> {
+        let query = self.0.chars(query);
+        let text = self.0.chars(text);
+        if query.is_empty() {
+            return Some(0);
+        }
+        if query.len() > text.len() {
+            return None;
+        }
+
+        let bonuses = text
+            .iter()
+            .enumerate()
+            .map(|(j, c)| bonus(j.checked_sub(1).map(|p| text[p]), *c))
+            .collect::<Vec<_>>();
+
+        // `previous[j]` is the best score of the query so far ending with a match at `j`.
+        let mut previous = vec![UNMATCHED; text.len()];
+        for (i, q) in query.iter().enumerate() {
+            let mut row = vec![UNMATCHED; text.len()];
+            // Best score ending before `j - 1`, with the gap up to `j` already deducted.
+            let mut gapped = UNMATCHED;
+            for (j, t) in text.iter().enumerate() {
+                if j >= 2 {
+                    gapped =
+                        (gapped + PENALTY_GAP_EXTENSION).max(previous[j - 2] + PENALTY_GAP_START);
+                }
+                if !self.0.eq(*q, *t) {
+                    continue;
+                }
+                row[j] = if i == 0 {
+                    SCORE_MATCH + bonuses[j] * BONUS_FIRST_CHAR_MULTIPLIER
+                } else {
+                    let consecutive = match j.checked_sub(1) {
+                        Some(p) => previous[p] + BONUS_CONSECUTIVE.max(bonuses[j]),
+                        None => UNMATCHED,
+                    };
+                    consecutive.max(gapped + bonuses[j]) + SCORE_MATCH
+                };
+            }
+            previous = row;
+        }
+
+        previous
+            .into_iter()
+            .max()
+            .filter(|score| *score > UNMATCHED / 2)
+    }
+}
+
+/// Makes the settings of `config` the ones [`fold`] applies.
+pub fn set(config: MatcherConfig) {
+    if let Ok(mut settings) = SETTINGS.write() {
+        *settings = config;
+    }
+}
+
+/// `text` as providers compare names with queries: lowercase and with accents folded, so
+/// that `malmo` is found in `Malmö`, unless the config asks for strict matching.
+pub fn fold(text: &str) -> String {
+    SETTINGS
+        .read()
+        .map_or(DEFAULT, |settings| *settings)
+        .normalize(text)
+}
+
+/// The words of `query`, folded as [`fold`] does, which providers look for in names.
+pub fn words(query: &str) -> Vec<String> {
+    query.split_whitespace().map(fold).collect()
+}
+
+/// Whether `text` contains each of `words`, once folded as [`fold`] does.
+pub fn matches_all(words: &[String], text: &str) -> bool {
+    let text = fold(text);
+    words.iter().all(|word| text.contains(word.as_str()))
+}
+
+/// The items whose key matches `query`, best matches first, and otherwise in their order.
+pub fn matching<T>(
+    matcher: &dyn Matcher,
+    query: &str,
+    items: impl IntoIterator<Item = T>,
+    key: impl Fn(&T) -> &str,
+) -> Vec<T> {
+    let mut matches = items
+        .into_iter()
+        .filter_map(|item| Some((matcher.score(query, key(&item))?, item)))
+        .collect::<Vec<_>>();
+    matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    matches.into_iter().map(|(_, item)| item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fuzzy(query: &str, text: &str) -> Option<i64> {
+        MatcherConfig {
+            kind: MatcherKind::Fuzzy,
+            ..DEFAULT
+        }
+        .matcher()
+        .score(query, text)
+    }
+
+    #[test]
+    fn consecutive_matches_beat_gaps() {
+        let consecutive = fuzzy("abc", "abcxx").unwrap();
+        let gapped = fuzzy("abc", "axbxc").unwrap();
+        assert!(consecutive > gapped);
+        // A longer gap costs more than a short one.
+        assert!(gapped > fuzzy("abc", "axxbxxc").unwrap());
+    }
+
+    #[test]
+    fn word_starts_and_camel_case_earn_bonuses() {
+        let inside = fuzzy("b", "ab").unwrap();
+        assert_eq!(inside, SCORE_MATCH);
+        assert_eq!(
+            fuzzy("b", "a b"),
+            Some(SCORE_MATCH + BONUS_BOUNDARY * BONUS_FIRST_CHAR_MULTIPLIER)
+        );
+        assert_eq!(
+            fuzzy("b", "aB"),
+            Some(SCORE_MATCH + BONUS_CAMEL * BONUS_FIRST_CHAR_MULTIPLIER)
+        );
+        assert!(fuzzy("fb", "foo-bar").unwrap() > fuzzy("fb", "foobar").unwrap());
+    }
+
+    #[test]
+    fn queries_longer_than_the_text_do_not_match() {
+        assert_eq!(fuzzy("firefox", "fire"), None);
+        assert_eq!(fuzzy("xf", "firefox"), None);
+        assert_eq!(fuzzy("", "firefox"), Some(0));
+    }
+
+    #[test]
+    fn case_sensitive_matching_keeps_case() {
+        let config = MatcherConfig {
+            kind: MatcherKind::Fuzzy,
+            case_sensitive: true,
+            ..DEFAULT
+        };
+        assert_eq!(config.matcher().score("F", "firefox"), None);
+        assert!(config.matcher().score("f", "firefox").is_some());
+        assert!(fuzzy("F", "firefox").is_some());
+
+        let substring = MatcherConfig {
+            case_sensitive: true,
+            ..DEFAULT
+        };
+        assert_eq!(substring.matcher().score("Fox", "firefox"), None);
+    }
+
+    #[test]
+    fn accents_are_folded_unless_turned_off() {
+        assert!(fuzzy("cafe", "Café").is_some());
+        assert_eq!(DEFAULT.matcher().score("malmo", "Malmö"), Some(0));
+
+        let strict = MatcherConfig {
+            kind: MatcherKind::Fuzzy,
+            fold_diacritics: false,
+            ..DEFAULT
+        };
+        assert_eq!(strict.matcher().score("cafe", "Café"), None);
+    }
+}
@@ -0,0 +1,82 @@
+//! Placement of the launcher below cosmic-panel.
+//!
+//! The launcher asks the compositor to keep it clear of the space panels reserve, but a
+//! panel which does not reserve any, such as one set to autohide, would still cover it. The
+//! launcher moves down past those, reading their position and size from the panel config.
+
+use cosmic::cosmic_config::{self, ConfigGet};
+use serde::Deserialize;
+
+const PANELS_CONFIG: &str = "com.system76.CosmicPanel";
+const PANELS_CONFIG_VERSION: u64 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+enum Anchor {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+enum Size {
+    #[serde(rename = "XS")]
+    ExtraSmall,
+    #[serde(rename = "S")]
+    Small,
+    #[serde(rename = "M")]
+    Medium,
+    #[serde(rename = "L")]
+    Large,
+    #[serde(rename = "XL")]
+    ExtraLarge,
+    Custom(u32),
+}
+
+impl Size {
+    /// About how thick a panel of this size is, in logical pixels.
+    fn thickness(self) -> u32 {
+        match self {
+            Size::ExtraSmall => 32,
+            Size::Small => 40,
+            Size::Medium => 48,
+            Size::Large => 56,
+            Size::ExtraLarge => 64,
+            Size::Custom(size) => size,
+        }
+    }
+}
+
+/// Space to leave above the launcher for top panels which do not reserve their own.
+pub fn top_clearance() -> i32 {
+    let Ok(config) = cosmic_config::Config::new(PANELS_CONFIG, PANELS_CONFIG_VERSION) else {
+        return 0;
+    };
+    let entries = config.get::<Vec<String>>("entries").unwrap_or_default();
+
+    let clearance = entries
+        .iter()
+        .filter_map(|name| {
+            let panel = cosmic_config::Config::new(
+                &format!("{PANELS_CONFIG}.{name}"),
+                PANELS_CONFIG_VERSION,
+            )
+            .ok()?;
+            let anchor = panel.get::<Anchor>("anchor").ok()?;
+            let reserved = panel.get::<bool>("exclusive_zone").unwrap_or(true);
+            if anchor != Anchor::Top || reserved {
+                return None;
+            }
+
+            let size = panel.get::<Size>("size").map_or(0, Size::thickness);
+            let gap = if panel.get::<bool>("anchor_gap").unwrap_or_default() {
+                panel.get::<u32>("margin").unwrap_or_default()
+            } else {
+                0
+            };
+            Some(size + gap)
+        })
+        .max()
+        .unwrap_or_default();
+    i32::try_from(clearance).unwrap_or_default()
+}
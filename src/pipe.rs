@@ -0,0 +1,62 @@
+//! Sources of compound queries, which produce the payload passed to the final stage.
+
+use std::path::PathBuf;
+
+use cosmic_launcher_core::pipeline::{Pipeline, Stage};
+
+use crate::launch::paste;
+
+/// Runs every source stage in order, each receiving the previous stage's output.
+pub async fn resolve(pipeline: Pipeline) -> String {
+    let mut payload = String::new();
+    for stage in &pipeline.sources {
+        payload = run(stage, payload).await;
+    }
+    payload
+}
+
+async fn run(stage: &Stage, input: String) -> String {
+    match stage.name.as_str() {
+        "clip" => paste(false).await,
+        "sel" => paste(true).await,
+        "file" => {
+            let name = if stage.arg.is_empty() {
+                input.trim()
+            } else {
+                stage.arg.as_str()
+            };
+            let name = name.to_owned();
+            tokio::task::spawn_blocking(move || find_file(&name))
+                .await
+                .ok()
+                .flatten()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default()
+        }
+        // The parser only accepts the sources above.
+        _ => input,
+    }
+}
+
+/// Resolves a file name against the working directory, then the home directory and its
+/// usual subdirectories.
+fn find_file(name: &str) -> Option<PathBuf> {
+    if name.is_empty() {
+        return None;
+    }
+
+    let home = PathBuf::from(std::env::var_os("HOME")?);
+    if let Some(rest) = name.strip_prefix("~/") {
+        return Some(home.join(rest)).filter(|path| path.exists());
+    }
+
+    let path = PathBuf::from(name);
+    if path.is_absolute() {
+        return Some(path).filter(|path| path.exists());
+    }
+
+    ["", "Documents", "Desktop", "Downloads"]
+        .into_iter()
+        .map(|dir| home.join(dir).join(name))
+        .find(|path| path.exists())
+}
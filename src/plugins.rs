@@ -0,0 +1,73 @@
+//! The installed pop-launcher plugins, which the configuration refers to by the name of
+//! their directory, such as `desktop_entries`, `files`, or `web`.
+//!
+//! Results of pop-launcher do not say which plugin they came from, only the category icon
+//! the plugin declares in its `plugin.ron`, so names are turned into these icons before
+//! the results are filtered. Plugins sharing an icon cannot be told apart.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use pop_launcher::IconSource;
+use ron::extensions::Extensions;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct PluginConfig {
+    #[serde(default)]
+    icon: Option<IconSource>,
+}
+
+/// Directories pop-launcher loads plugins from, in order of precedence.
+pub fn dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(data) = xdg::BaseDirectories::new()
+        .ok()
+        .map(|base| base.get_data_home())
+    {
+        dirs.push(data.join("pop-launcher/plugins"));
+    }
+    dirs.push(PathBuf::from("/etc/pop-launcher/plugins"));
+    dirs.push(PathBuf::from("/usr/lib/pop-launcher/plugins"));
+    dirs
+}
+
+/// Names of the plugins in `dir`.
+pub fn installed(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().join("plugin.ron").is_file())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect()
+}
+
+/// Category icons of the installed plugins by name. A plugin installed in several
+/// directories has the icon of the one pop-launcher loads.
+pub fn icons() -> HashMap<String, String> {
+    let options = ron::Options::default().with_default_extension(Extensions::IMPLICIT_SOME);
+    let mut icons = HashMap::new();
+    for dir in dirs() {
+        for plugin in installed(&dir) {
+            if icons.contains_key(&plugin) {
+                continue;
+            }
+            let Ok(ron) = std::fs::read_to_string(dir.join(&plugin).join("plugin.ron")) else {
+                continue;
+            };
+            match options.from_str::<PluginConfig>(&ron) {
+                Ok(PluginConfig {
+                    icon: Some(IconSource::Name(icon) | IconSource::Mime(icon)),
+                }) => {
+                    icons.insert(plugin, icon.into_owned());
+                }
+                Ok(_) => {}
+                Err(why) => tracing::warn!("failed to read the {plugin} plugin: {why}"),
+            }
+        }
+    }
+    icons
+}
@@ -0,0 +1,39 @@
+//! QR codes of the URLs and text of results, shown in a popup to be scanned by a phone.
+
+use cosmic::iced::widget::image;
+use qrcode::{Color, QrCode};
+
+/// Width the code is scaled up to, including its quiet zone, if it has few modules.
+const SIZE: usize = 240;
+
+/// Modules of white around the code, which scanners need to find it.
+const QUIET_ZONE: usize = 4;
+
+/// Renders `text` as black modules on white, or returns `None` if it is too long to fit in
+/// a QR code.
+pub fn render(text: &str) -> Option<image::Handle> {
+    let code = QrCode::new(text).ok()?;
+    let modules = code.width() + 2 * QUIET_ZONE;
+    let scale = (SIZE / modules).max(1);
+    let width = modules * scale;
+    let colors = code.to_colors();
+
+    let mut pixels = Vec::with_capacity(width * width * 4);
+    for y in 0..width {
+        let row = (y / scale).checked_sub(QUIET_ZONE);
+        for x in 0..width {
+            let column = (x / scale).checked_sub(QUIET_ZONE);
+            let dark = match (row, column) {
+                (Some(row), Some(column)) if row < code.width() && column < code.width() => {
+                    colors[row * code.width() + column] == Color::Dark
+                }
+                _ => false,
+            };
+            let value = if dark { 0 } else { 255 };
+            pixels.extend_from_slice(&[value, value, value, 255]);
+        }
+    }
+
+    let width = u32::try_from(width).ok()?;
+    Some(image::Handle::from_pixels(width, width, pixels))
+}
@@ -0,0 +1,56 @@
+//! What Shift + Enter does to the focused result in place of activating it, named beside
+//! the result so that it can be found.
+
+use std::path::PathBuf;
+
+use cosmic_launcher_core::LOCAL_ID_BASE;
+use pop_launcher::SearchResult;
+
+use crate::fl;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Secondary {
+    /// Start another instance of an application with an open window, rather than raise it.
+    NewInstance,
+    CloseWindow,
+    /// Open the folder a file is in, or the folder itself.
+    OpenFolder(PathBuf),
+    CopyUrl(String),
+}
+
+impl Secondary {
+    /// The secondary action of `item`, whose application has an open window if `running`.
+    pub fn of(item: &SearchResult, running: bool) -> Option<Self> {
+        if item.window.is_some() && item.id < LOCAL_ID_BASE {
+            return Some(Self::CloseWindow);
+        }
+        if running {
+            return Some(Self::NewInstance);
+        }
+
+        let description = item.description.trim();
+        if description.starts_with("https://") || description.starts_with("http://") {
+            return Some(Self::CopyUrl(description.to_string()));
+        }
+        path(description).map(Self::OpenFolder)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::NewInstance => fl!("secondary-new-instance"),
+            Self::CloseWindow => fl!("secondary-close-window"),
+            Self::OpenFolder(_) => fl!("secondary-open-folder"),
+            Self::CopyUrl(_) => fl!("secondary-copy-url"),
+        }
+    }
+}
+
+/// The path a description such as `~/Documents/notes.txt` names, if it names one.
+fn path(description: &str) -> Option<PathBuf> {
+    if let Some(rest) = description.strip_prefix("~/") {
+        return std::env::var_os("HOME").map(|home| PathBuf::from(home).join(rest));
+    }
+    description
+        .starts_with('/')
+        .then(|| PathBuf::from(description))
+}
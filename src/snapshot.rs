@@ -0,0 +1,182 @@
+//! Runtime state kept across restarts, crashes, and updates: the last profile switched to,
+//! how often and how recently each result was used, and the results hidden with `dd`.
+//!
+//! The snapshot is written to `$XDG_STATE_HOME/cosmic-launcher/state.json` when the
+//! launcher hides after it changed, replacing the previous file at once so that a crash
+//! never leaves half of it.
+//! Favorites are not part of it, since they are read from the configuration.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Version of the snapshot format, raised whenever a field changes meaning.
+const VERSION: u32 = 1;
+
+const FILE: &str = "cosmic-launcher/state.json";
+
+/// Results remembered at most, dropping the least recently used.
+const MAX_USAGE: usize = 500;
+
+const DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Usage {
+    pub count: u32,
+    /// Seconds since the Unix epoch.
+    pub last_used: u64,
+}
+
+impl Usage {
+    /// Steps of the frequent bonus: one for results used now and then, two for results
+    /// used often lately.
+    fn frecency(&self, now: u64) -> i32 {
+        let recency = match now.saturating_sub(self.last_used) / DAY {
+            0 => 4,
+            1..=6 => 2,
+            7..=29 => 1,
+            _ => 0,
+        };
+        match self.count.min(20) * recency {
+            0 => 0,
+            1..=7 => 1,
+            _ => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Snapshot {
+    pub version: u32,
+    /// The profile last switched to, started with unless another is given.
+    pub profile: Option<String>,
+    /// Use of results by their name.
+    pub usage: HashMap<String, Usage>,
+    /// Names of the results hidden in normal mode, which are never shown again.
+    pub hidden: BTreeSet<String>,
+}
+
+impl Default for Snapshot {
+    fn default() -> Self {
+        Self {
+            version: VERSION,
+            profile: None,
+            usage: HashMap::new(),
+            hidden: BTreeSet::new(),
+        }
+    }
+}
+
+impl Snapshot {
+    /// Reads the snapshot of the last run, or starts a new one if there is none or it
+    /// cannot be read.
+    pub fn load() -> Self {
+        let Some(path) = path() else {
+            return Self::default();
+        };
+        let value = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice::<serde_json::Value>(&bytes),
+            Err(why) if why.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(why) => {
+                tracing::warn!("failed to read {}: {why}", path.display());
+                return Self::default();
+            }
+        };
+        match value.map_err(|why| why.to_string()).and_then(migrate) {
+            Ok(snapshot) => snapshot,
+            Err(why) => {
+                tracing::warn!("ignoring the state in {}: {why}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    /// Writes the snapshot, replacing the previous one.
+    pub fn save(&self) {
+        let Some(path) = path() else {
+            return;
+        };
+        // A snapshot of a newer launcher is left alone rather than downgraded.
+        if self.version > VERSION {
+            return;
+        }
+        if let Err(why) = self.write(&path) {
+            tracing::warn!("failed to save {}: {why}", path.display());
+        }
+    }
+
+    fn write(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let temporary = path.with_extension("json.tmp");
+        std::fs::write(&temporary, serde_json::to_vec(self)?)?;
+        std::fs::rename(&temporary, path)
+    }
+
+    /// Counts a use of the result named `name`.
+    pub fn used(&mut self, name: &str) {
+        let usage = self.usage.entry(name.to_string()).or_default();
+        usage.count = usage.count.saturating_add(1);
+        usage.last_used = now();
+
+        if self.usage.len() > MAX_USAGE {
+            let mut last_used = self
+                .usage
+                .values()
+                .map(|usage| usage.last_used)
+                .collect::<Vec<_>>();
+            last_used.sort_unstable();
+            let oldest = last_used[self.usage.len() - MAX_USAGE - 1];
+            self.usage.retain(|_, usage| usage.last_used > oldest);
+        }
+    }
+
+    /// Steps of the frequent bonus of the results which have one.
+    pub fn frecency(&self) -> HashMap<String, i32> {
+        let now = now();
+        self.usage
+            .iter()
+            .map(|(name, usage)| (name.clone(), usage.frecency(now)))
+            .filter(|(_, steps)| *steps > 0)
+            .collect()
+    }
+}
+
+/// Upgrades a snapshot written by an earlier version to the current format, one version at
+/// a time, so that what the launcher learned survives updates.
+fn migrate(mut value: serde_json::Value) -> Result<Snapshot, String> {
+    let version = value
+        .get("version")
+        .and_then(serde_json::Value::as_u64)
+        .ok_or("it has no version")?;
+    if version > u64::from(VERSION) {
+        // Kept as it is, so that the newer launcher which wrote it can still read it.
+        return Ok(Snapshot {
+            version: u32::try_from(version).unwrap_or(u32::MAX),
+            ..Snapshot::default()
+        });
+    }
+
+    // Each upgrade rewrites the fields which changed since the version before it, and new
+    // ones go here as `VERSION` is raised.
+    if let Some(object) = value.as_object_mut() {
+        object.insert("version".into(), VERSION.into());
+    }
+    serde_json::from_value(value).map_err(|why| why.to_string())
+}
+
+fn path() -> Option<PathBuf> {
+    xdg::BaseDirectories::new()
+        .ok()
+        .map(|dirs| dirs.get_state_home().join(FILE))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
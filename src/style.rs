@@ -0,0 +1,266 @@
+//! Look of the launcher as set in the `style` config entry, a RON file in the config
+//! directory which is applied again whenever it changes. Colors and corner radii left out
+//! follow the COSMIC theme.
+//!
+//! ```ron
+//! (
+//!     window: (background: Some("#1e1e2ef0"), radius: Some(4.0), padding: (16, 24)),
+//!     result: (name_size: 16, description_size: 12, highlight: Bar),
+//! )
+//! ```
+
+use cosmic::iced::widget::{container, text};
+use cosmic::iced_core::{Border, Color, Shadow};
+use cosmic::widget::button::{self, StyleSheet};
+use cosmic::Theme;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Style {
+    /// The surface holding the search field and the results.
+    pub window: Window,
+    /// Each result row.
+    pub result: Row,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Window {
+    pub background: Option<Rgba>,
+    pub border: Option<Rgba>,
+    pub radius: Option<f32>,
+    /// Vertical and horizontal padding, in logical pixels.
+    pub padding: [u16; 2],
+}
+
+impl Window {
+    pub fn appearance(&self, theme: &Theme) -> container::Appearance {
+        let cosmic = theme.cosmic();
+        container::Appearance {
+            text_color: Some(cosmic.on_bg_color().into()),
+            icon_color: Some(cosmic.on_bg_color().into()),
+            background: Some(
+                self.background
+                    .map_or(Color::from(cosmic.background.base), Color::from)
+                    .into(),
+            ),
+            border: Border {
+                radius: self
+                    .radius
+                    .map_or(cosmic.corner_radii.radius_m, |radius| [radius; 4])
+                    .into(),
+                width: 1.0,
+                color: self.border.map_or(cosmic.bg_divider().into(), Color::from),
+            },
+            shadow: Shadow::default(),
+        }
+    }
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Self {
+            background: None,
+            border: None,
+            radius: None,
+            padding: [24, 32],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Row {
+    /// Color of the names, descriptions, and shortcut hints.
+    pub text: Option<Rgba>,
+    pub radius: Option<f32>,
+    /// Vertical and horizontal padding, in logical pixels.
+    pub padding: [u16; 2],
+    pub name_size: u16,
+    pub description_size: u16,
+    /// Size of the shortcut hint, such as "Ctrl + 1".
+    pub hint_size: u16,
+    /// How the focused result stands out from the others.
+    pub highlight: Highlight,
+}
+
+/// Look of the focused result.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Highlight {
+    /// Drawn as if hovered.
+    #[default]
+    Hover,
+    /// Filled with the accent color.
+    Accent,
+    /// Outlined with the accent color.
+    Outline,
+    /// Marked by a bar of the accent color on its left.
+    Bar,
+}
+
+/// What the pointer is doing with a result, as the button style sheet tells it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interaction {
+    Active,
+    Hovered,
+    Pressed,
+    Disabled,
+}
+
+/// Opacity of dimmed text, relative to the text of names.
+const DIMMED_ALPHA: f32 = 0.6;
+
+/// Width of the bar marking the focused result with [`Highlight::Bar`].
+pub const BAR_WIDTH: f32 = 3.0;
+
+impl Row {
+    pub fn radius(&self, theme: &Theme) -> [f32; 4] {
+        self.radius
+            .map_or(theme.cosmic().corner_radii.radius_s, |radius| [radius; 4])
+    }
+
+    /// Appearance of a result's button, highlighted if it is `focused`.
+    pub fn button(
+        &self,
+        theme: &Theme,
+        interaction: Interaction,
+        focused: bool,
+    ) -> button::Appearance {
+        let style = &cosmic::theme::Button::Text;
+        let highlighted = focused && interaction != Interaction::Disabled;
+        let mut appearance = match interaction {
+            Interaction::Active if highlighted && self.highlight == Highlight::Hover => {
+                StyleSheet::hovered(theme, focused, focused, style)
+            }
+            Interaction::Active => StyleSheet::active(theme, focused, focused, style),
+            Interaction::Hovered => StyleSheet::hovered(theme, focused, focused, style),
+            Interaction::Pressed => StyleSheet::pressed(theme, focused, focused, style),
+            Interaction::Disabled => StyleSheet::disabled(theme, style),
+        };
+        appearance.border_radius = self.radius(theme).into();
+        appearance.outline_width = 0.0;
+
+        if !highlighted {
+            return appearance;
+        }
+        let cosmic = theme.cosmic();
+        match self.highlight {
+            Highlight::Hover | Highlight::Bar => {}
+            Highlight::Accent => {
+                appearance.background = Some(Color::from(cosmic.accent_color()).into());
+                appearance.text_color = Some(cosmic.on_accent_color().into());
+                appearance.icon_color = Some(cosmic.on_accent_color().into());
+            }
+            Highlight::Outline => {
+                appearance.border_width = 2.0;
+                appearance.border_color = cosmic.accent_color().into();
+            }
+        }
+        appearance
+    }
+
+    /// Appearance of the bar left of a result with [`Highlight::Bar`], which only shows on
+    /// the `focused` one.
+    pub fn bar(&self, theme: &Theme, focused: bool) -> container::Appearance {
+        let cosmic = theme.cosmic();
+        container::Appearance {
+            background: focused.then(|| Color::from(cosmic.accent_color()).into()),
+            border: Border {
+                radius: cosmic.corner_radii.radius_xs.into(),
+                ..Border::default()
+            },
+            ..container::Appearance::default()
+        }
+    }
+
+    /// Style of descriptions shown on the same line as the name, fainter than it.
+    pub fn dimmed(&self) -> cosmic::theme::Text {
+        match self.text {
+            Some(color) => cosmic::theme::Text::Color(Color {
+                a: f32::from(color.0[3]) / 255.0 * DIMMED_ALPHA,
+                ..color.into()
+            }),
+            None => cosmic::theme::Text::Custom(|theme| text::Appearance {
+                color: Some(Color {
+                    a: DIMMED_ALPHA,
+                    ..theme.cosmic().on_bg_color().into()
+                }),
+            }),
+        }
+    }
+
+    /// Style of text fading in, at `alpha` of its full opacity.
+    pub fn faded(&self, alpha: f32) -> cosmic::theme::Text {
+        if alpha >= 1.0 {
+            return self.text();
+        }
+
+        let color = self.text.map_or_else(
+            || cosmic::theme::active().cosmic().on_bg_color().into(),
+            Color::from,
+        );
+        cosmic::theme::Text::Color(Color {
+            a: color.a * alpha,
+            ..color
+        })
+    }
+
+    pub fn text(&self) -> cosmic::theme::Text {
+        match self.text {
+            Some(color) => cosmic::theme::Text::Color(color.into()),
+            None => cosmic::theme::Text::Custom(|theme| text::Appearance {
+                color: Some(theme.cosmic().on_bg_color().into()),
+            }),
+        }
+    }
+}
+
+impl Default for Row {
+    fn default() -> Self {
+        Self {
+            text: None,
+            radius: None,
+            padding: [8, 16],
+            name_size: 14,
+            description_size: 10,
+            hint_size: 14,
+            highlight: Highlight::default(),
+        }
+    }
+}
+
+/// A color written as `#rrggbb` or `#rrggbbaa`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Rgba(pub [u8; 4]);
+
+impl From<Rgba> for Color {
+    fn from(Rgba([r, g, b, a]): Rgba) -> Self {
+        Self::from_rgba8(r, g, b, f32::from(a) / 255.0)
+    }
+}
+
+impl TryFrom<String> for Rgba {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("{text:?} is not a color like \"#rrggbb\" or \"#rrggbbaa\"");
+        let hex = text.strip_prefix('#').ok_or_else(invalid)?;
+        if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+            return Err(invalid());
+        }
+
+        let mut rgba = [u8::MAX; 4];
+        for (channel, i) in rgba.iter_mut().zip((0..hex.len()).step_by(2)) {
+            *channel = u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid())?;
+        }
+        Ok(Self(rgba))
+    }
+}
+
+impl From<Rgba> for String {
+    fn from(Rgba([r, g, b, a]): Rgba) -> Self {
+        format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+    }
+}
@@ -0,0 +1,116 @@
+//! JSON API on a Unix socket for end-to-end tests and automation, served with
+//! `--control-socket <path>` by builds with the `control-socket` feature.
+//!
+//! Each line written to the socket is a request, answered with a line holding its response:
+//!
+//! ```text
+//! {"command": "show"}                  "ok"
+//! {"command": "type", "text": "fire"}  "ok"
+//! {"command": "results"}               {"results": {"visible": true, "focused": 0, "results": [...]}}
+//! {"command": "activate", "index": 0}  "ok"
+//! ```
+
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+
+use futures::SinkExt;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixListener;
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request {
+    /// Open the launcher, unless it is open already.
+    Show,
+    Hide,
+    /// Replace the query with `text`.
+    Type {
+        text: String,
+    },
+    /// List the results shown.
+    Results,
+    /// Activate the result in row `index`.
+    Activate {
+        index: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Response {
+    Ok,
+    Results {
+        /// Whether the launcher is open, or about to be once results arrive.
+        visible: bool,
+        focused: usize,
+        results: Vec<Row>,
+    },
+    Error(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Row {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// The socket is listening, and the response to each request is sent here.
+    Started(mpsc::Sender<Response>),
+    Request(Request),
+}
+
+pub fn subscription<I: 'static + Hash + Copy + Send + Sync>(
+    id: I,
+    path: PathBuf,
+) -> cosmic::iced::Subscription<Event> {
+    use cosmic::iced::subscription;
+
+    subscription::channel(id, 4, |mut output| async move {
+        if let Err(why) = serve(&path, &mut output).await {
+            tracing::error!("control socket {} failed: {why}", path.display());
+        }
+
+        futures::future::pending().await
+    })
+}
+
+async fn serve(
+    path: &Path,
+    output: &mut futures::channel::mpsc::Sender<Event>,
+) -> std::io::Result<()> {
+    // A socket left behind by an earlier run would keep it from binding.
+    let _res = std::fs::remove_file(path);
+    let listener = UnixListener::bind(path)?;
+    let (tx, mut responses) = mpsc::channel(1);
+    let _res = output.send(Event::Started(tx)).await;
+
+    // Clients are served one at a time, and each request waits for its response.
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+
+        while let Ok(Some(line)) = lines.next_line().await {
+            let response = match serde_json::from_str::<Request>(&line) {
+                Ok(request) => {
+                    let _res = output.send(Event::Request(request)).await;
+                    responses
+                        .recv()
+                        .await
+                        .unwrap_or_else(|| Response::Error("the launcher stopped".into()))
+                }
+                Err(why) => Response::Error(why.to_string()),
+            };
+
+            let mut line = serde_json::to_string(&response).unwrap_or_default();
+            line.push('\n');
+            if writer.write_all(line.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    }
+}
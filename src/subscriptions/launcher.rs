@@ -4,15 +4,7 @@ use pop_launcher_service::IpcClient;
 use std::hash::Hash;
 use tokio::sync::{mpsc, oneshot};
 
-#[derive(Debug, Clone)]
-pub enum Request {
-    Search(String),
-    Activate(u32),
-    Context(u32),
-    Complete(u32),
-    ActivateContext(u32, u32),
-    Close,
-}
+pub use cosmic_launcher_core::Request;
 
 #[derive(Debug, Clone)]
 pub enum Event {
@@ -133,6 +125,11 @@ pub fn service() -> impl Stream<Item = Event> + MaybeSend {
                         let _res = client.send(pop_launcher::Request::Complete(id)).await;
                     }
                 }
+                Request::Quit(id) => {
+                    if let Some((client, _)) = client_request(&responses_tx, client).await {
+                        let _res = client.send(pop_launcher::Request::Quit(id)).await;
+                    }
+                }
             }
         }
     };
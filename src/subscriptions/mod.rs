@@ -1 +1,5 @@
+#[cfg(feature = "control-socket")]
+pub mod control;
 pub mod launcher;
+pub mod session;
+pub mod toplevel;
@@ -0,0 +1,52 @@
+//! Watches whether the session is locked, through logind's session object, which the
+//! screen locker marks as locked and `loginctl lock-session` asks to lock.
+
+use std::hash::Hash;
+
+use futures::{SinkExt, StreamExt};
+
+const LOGIN_BUS: &str = "org.freedesktop.login1";
+const SESSION_PATH: &str = "/org/freedesktop/login1/session/auto";
+const SESSION_INTERFACE: &str = "org.freedesktop.login1.Session";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Locked(bool),
+}
+
+pub fn subscription<I: 'static + Hash + Copy + Send + Sync>(
+    id: I,
+) -> cosmic::iced::Subscription<Event> {
+    use cosmic::iced::subscription;
+
+    subscription::channel(id, 4, |mut output| async move {
+        if let Err(why) = watch(&mut output).await {
+            tracing::warn!("session lock state is unavailable: {why}");
+        }
+
+        futures::future::pending().await
+    })
+}
+
+async fn watch(output: &mut futures::channel::mpsc::Sender<Event>) -> zbus::Result<()> {
+    let bus = zbus::Connection::system().await?;
+    let session = zbus::Proxy::new(&bus, LOGIN_BUS, SESSION_PATH, SESSION_INTERFACE).await?;
+
+    let locks = session.receive_signal("Lock").await?.map(|_| true);
+    let unlocks = session.receive_signal("Unlock").await?.map(|_| false);
+    let hints = session
+        .receive_property_changed::<bool>("LockedHint")
+        .await
+        .filter_map(|change| async move { change.get().await.ok() });
+
+    if session.get_property::<bool>("LockedHint").await? {
+        let _res = output.send(Event::Locked(true)).await;
+    }
+
+    let changes = futures::stream::select(futures::stream::select(locks, unlocks), hints);
+    let mut changes = std::pin::pin!(changes);
+    while let Some(locked) = changes.next().await {
+        let _res = output.send(Event::Locked(locked)).await;
+    }
+    Ok(())
+}
@@ -0,0 +1,302 @@
+//! Tracks the app IDs and titles of open toplevels through the cosmic toplevel-info protocol,
+//! and the output of the focused one, and activates them through the toplevel-management
+//! protocol.
+
+use cosmic::cctk::{
+    self,
+    cosmic_protocols::{
+        toplevel_info::v1::client::zcosmic_toplevel_handle_v1::{self, ZcosmicToplevelHandleV1},
+        toplevel_management::v1::client::zcosmic_toplevel_manager_v1,
+    },
+    sctk::{
+        self,
+        output::{OutputHandler, OutputState},
+        reexports::{calloop, calloop_wayland_source::WaylandSource},
+        registry::{ProvidesRegistryState, RegistryState},
+        seat::{SeatHandler, SeatState},
+    },
+    toplevel_info::{ToplevelInfoHandler, ToplevelInfoState},
+    toplevel_management::{ToplevelManagerHandler, ToplevelManagerState},
+    wayland_client::{
+        globals::registry_queue_init,
+        protocol::{wl_output::WlOutput, wl_seat::WlSeat},
+        Connection, Proxy, QueueHandle, WEnum,
+    },
+};
+use futures::SinkExt;
+use std::hash::Hash;
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// The watcher is running and accepts requests.
+    Started(calloop::channel::Sender<Request>),
+    Opened {
+        handle: u32,
+        app_id: String,
+        title: String,
+    },
+    Closed {
+        handle: u32,
+    },
+    /// The focused toplevel is on another output, or the size of its output changed.
+    ActiveOutput {
+        name: String,
+        /// Size of the output in logical pixels, if the compositor reported it.
+        logical_size: Option<(i32, i32)>,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Request {
+    /// Raise and focus the toplevel with this handle.
+    Activate(u32),
+}
+
+pub fn subscription<I: 'static + Hash + Copy + Send + Sync>(
+    id: I,
+) -> cosmic::iced::Subscription<Event> {
+    use cosmic::iced::subscription;
+
+    subscription::channel(id, 16, |mut output| async move {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        let _res = std::thread::Builder::new()
+            .name("toplevel-info".into())
+            .spawn(move || {
+                if let Err(why) = watch(tx) {
+                    tracing::error!("toplevel info is unavailable: {why}");
+                }
+            });
+
+        while let Some(event) = rx.recv().await {
+            let _res = output.send(event).await;
+        }
+
+        futures::future::pending().await
+    })
+}
+
+struct State {
+    registry_state: RegistryState,
+    seat_state: SeatState,
+    output_state: OutputState,
+    /// Name and logical size of the output of the focused toplevel, as last reported.
+    active_output: Option<(String, Option<(i32, i32)>)>,
+    toplevel_info_state: ToplevelInfoState,
+    toplevel_manager_state: Option<ToplevelManagerState>,
+    tx: mpsc::UnboundedSender<Event>,
+    exit: bool,
+}
+
+fn watch(tx: mpsc::UnboundedSender<Event>) -> Result<(), Box<dyn std::error::Error>> {
+    let conn = Connection::connect_to_env()?;
+    let (globals, event_queue) = registry_queue_init::<State>(&conn)?;
+    let qh = event_queue.handle();
+
+    let mut event_loop = calloop::EventLoop::<State>::try_new()?;
+    WaylandSource::new(conn, event_queue).insert(event_loop.handle())?;
+
+    let (requests, request_rx) = calloop::channel::channel();
+    event_loop
+        .handle()
+        .insert_source(request_rx, |event, _, state| match event {
+            calloop::channel::Event::Msg(Request::Activate(handle)) => state.activate(handle),
+            calloop::channel::Event::Closed => state.exit = true,
+        })?;
+
+    let registry_state = RegistryState::new(&globals);
+    let mut state = State {
+        seat_state: SeatState::new(&globals, &qh),
+        output_state: OutputState::new(&globals, &qh),
+        active_output: None,
+        toplevel_info_state: ToplevelInfoState::new(&registry_state, &qh),
+        toplevel_manager_state: ToplevelManagerState::try_new(&registry_state, &qh),
+        registry_state,
+        tx,
+        exit: false,
+    };
+
+    let _res = state.tx.send(Event::Started(requests));
+
+    while !state.exit && !state.tx.is_closed() {
+        event_loop.dispatch(None, &mut state)?;
+    }
+
+    Ok(())
+}
+
+impl State {
+    /// Reports `output` as the active output, unless it already was with the same size.
+    fn report_output(&mut self, output: &WlOutput) {
+        let Some(info) = self.output_state.info(output) else {
+            return;
+        };
+        let Some(name) = info.name else {
+            return;
+        };
+
+        let active = (name, info.logical_size);
+        if self.active_output.as_ref() != Some(&active) {
+            let _res = self.tx.send(Event::ActiveOutput {
+                name: active.0.clone(),
+                logical_size: active.1,
+            });
+            self.active_output = Some(active);
+        }
+    }
+
+    fn activate(&mut self, handle: u32) {
+        let Some(manager) = self.toplevel_manager_state.as_ref() else {
+            tracing::warn!("the compositor does not support toplevel management");
+            return;
+        };
+
+        let toplevel = self
+            .toplevel_info_state
+            .toplevels()
+            .find(|(toplevel, _)| toplevel.id().protocol_id() == handle)
+            .map(|(toplevel, _)| toplevel.clone());
+
+        if let (Some(toplevel), Some(seat)) = (toplevel, self.seat_state.seats().next()) {
+            manager.manager.activate(&toplevel, &seat);
+        }
+    }
+}
+
+impl ProvidesRegistryState for State {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    sctk::registry_handlers!(SeatState, OutputState);
+}
+
+impl SeatHandler for State {
+    fn seat_state(&mut self) -> &mut SeatState {
+        &mut self.seat_state
+    }
+
+    fn new_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: WlSeat) {}
+
+    fn new_capability(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: WlSeat,
+        _: sctk::seat::Capability,
+    ) {
+    }
+
+    fn remove_capability(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: WlSeat,
+        _: sctk::seat::Capability,
+    ) {
+    }
+
+    fn remove_seat(&mut self, _: &Connection, _: &QueueHandle<Self>, _: WlSeat) {}
+}
+
+impl OutputHandler for State {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(&mut self, _: &Connection, _: &QueueHandle<Self>, _: WlOutput) {}
+
+    fn update_output(&mut self, _: &Connection, _: &QueueHandle<Self>, output: WlOutput) {
+        let name = self.output_state.info(&output).and_then(|info| info.name);
+        if name.is_some() && name.as_ref() == self.active_output.as_ref().map(|(name, _)| name) {
+            self.report_output(&output);
+        }
+    }
+
+    fn output_destroyed(&mut self, _: &Connection, _: &QueueHandle<Self>, _: WlOutput) {}
+}
+
+impl ToplevelManagerHandler for State {
+    fn toplevel_manager_state(&mut self) -> &mut ToplevelManagerState {
+        self.toplevel_manager_state
+            .as_mut()
+            .expect("manager events are only sent once it is bound")
+    }
+
+    fn capabilities(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: Vec<WEnum<zcosmic_toplevel_manager_v1::ZcosmicToplelevelManagementCapabilitiesV1>>,
+    ) {
+    }
+}
+
+impl ToplevelInfoHandler for State {
+    fn toplevel_info_state(&mut self) -> &mut ToplevelInfoState {
+        &mut self.toplevel_info_state
+    }
+
+    fn new_toplevel(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        toplevel: &ZcosmicToplevelHandleV1,
+    ) {
+        let Some(info) = self.toplevel_info_state.info(toplevel) else {
+            return;
+        };
+        let _res = self.tx.send(Event::Opened {
+            handle: toplevel.id().protocol_id(),
+            app_id: info.app_id.clone(),
+            title: info.title.clone(),
+        });
+
+        if !info
+            .state
+            .contains(&zcosmic_toplevel_handle_v1::State::Activated)
+        {
+            return;
+        }
+        let output = info
+            .output
+            .iter()
+            .find(|output| {
+                self.output_state
+                    .info(output)
+                    .is_some_and(|info| info.name.is_some())
+            })
+            .cloned();
+        if let Some(output) = output {
+            self.report_output(&output);
+        }
+    }
+
+    fn update_toplevel(
+        &mut self,
+        conn: &Connection,
+        qh: &QueueHandle<Self>,
+        toplevel: &ZcosmicToplevelHandleV1,
+    ) {
+        // The app ID may only be known after the first update, and the title changes.
+        self.new_toplevel(conn, qh, toplevel);
+    }
+
+    fn toplevel_closed(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        toplevel: &ZcosmicToplevelHandleV1,
+    ) {
+        let _res = self.tx.send(Event::Closed {
+            handle: toplevel.id().protocol_id(),
+        });
+    }
+}
+
+cctk::delegate_toplevel_info!(State);
+cctk::delegate_toplevel_manager!(State);
+sctk::delegate_output!(State);
+sctk::delegate_seat!(State);
+sctk::delegate_registry!(State);
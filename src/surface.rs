@@ -0,0 +1,69 @@
+//! Selection of the kind of surface the launcher maps itself onto.
+//!
+//! The launcher is normally a layer-shell surface. When a Wayland compositor does not
+//! advertise `zwlr_layer_shell_v1` and the `window-fallback` feature is enabled,
+//! it falls back to a regular undecorated xdg-shell toplevel instead. Both need a
+//! Wayland connection; X11 sessions are not supported.
+
+use cosmic::cctk::wayland_client::{
+    globals::{registry_queue_init, GlobalListContents},
+    protocol::wl_registry,
+    Connection, Dispatch, QueueHandle,
+};
+
+pub const LAYER_SHELL: &str = "zwlr_layer_shell_v1";
+pub const XDG_ACTIVATION: &str = "xdg_activation_v1";
+/// Protocols which blur what is behind a surface, the standard one first.
+pub const BLUR: [&str; 2] = [
+    "ext_background_effect_manager_v1",
+    "org_kde_kwin_blur_manager",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SurfaceMode {
+    LayerShell,
+    #[cfg(feature = "window-fallback")]
+    Window,
+}
+
+/// Picks the surface mode supported by the running compositor.
+pub fn detect() -> SurfaceMode {
+    #[cfg(feature = "window-fallback")]
+    match advertised_globals() {
+        Some(globals) if !globals.iter().any(|g| g == LAYER_SHELL) => {
+            tracing::warn!("layer shell is unavailable, falling back to a toplevel window");
+            return SurfaceMode::Window;
+        }
+        Some(_) => {}
+        None => tracing::error!("no Wayland compositor is reachable, and X11 is not supported"),
+    }
+
+    SurfaceMode::LayerShell
+}
+
+struct Probe;
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for Probe {
+    fn event(
+        _state: &mut Self,
+        _proxy: &wl_registry::WlRegistry,
+        _event: wl_registry::Event,
+        _data: &GlobalListContents,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+/// Opens a short-lived connection to the compositor and lists the interfaces of its
+/// globals, or returns `None` if no compositor is reachable.
+pub fn advertised_globals() -> Option<Vec<String>> {
+    let conn = Connection::connect_to_env().ok()?;
+    let (globals, _queue) = registry_queue_init::<Probe>(&conn).ok()?;
+
+    Some(
+        globals
+            .contents()
+            .with_list(|list| list.iter().map(|global| global.interface.clone()).collect()),
+    )
+}